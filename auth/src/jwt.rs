@@ -12,6 +12,9 @@ pub struct AccessTokenClaims {
     pub email: String,
     pub system_role: SystemRoleType,
     pub organizations: Vec<OrganizationRole>,
+    /// Session the token was issued for, `None` for tokens issued outside of a login
+    #[serde(default)]
+    pub sid: Option<uuid::Uuid>,
     pub exp: usize,
 }
 
@@ -20,6 +23,12 @@ pub struct RefreshTokenClaims {
     pub sub: uuid::Uuid, // Subject (user identifier)
     pub iat: usize,      // Issued At (timestamp)
     pub exp: usize,      // Expiration (timestamp)
+    // Session (user_session id), missing from tokens issued before sessions existed
+    #[serde(default)]
+    pub sid: Option<uuid::Uuid>,
+    // Unique token id, so rotated tokens never repeat within the same second
+    #[serde(default)]
+    pub jti: String,
 }
 
 pub fn create_token<S, E>(
@@ -48,6 +57,7 @@ where
             .unwrap_or_else(|| "user@example.com".to_string()),
         system_role: system_role.unwrap_or_default(),
         organizations: organizations.unwrap_or_default(),
+        sid: None,
         exp: expiration as usize,
     };
 
@@ -66,6 +76,7 @@ where
 pub fn create_access_token_for_user(
     user_record: User,
     organization_roles: Vec<OrganizationRole>,
+    session_id: Option<uuid::Uuid>,
 ) -> Result<String, Error> {
     let key = std::env::var("JWT_SECRET")?;
 
@@ -80,6 +91,7 @@ pub fn create_access_token_for_user(
         email: user_record.email,
         system_role: user_record.system_role,
         organizations: organization_roles,
+        sid: session_id,
         exp: expiration as usize,
     };
 
@@ -95,7 +107,10 @@ pub fn create_access_token_for_user(
     Ok(token)
 }
 
-pub fn create_refresh_token_for_user(user_record: User) -> Result<String, Error> {
+pub fn create_refresh_token_for_user(
+    user_record: User,
+    session_id: uuid::Uuid,
+) -> Result<String, Error> {
     let key = std::env::var("JWT_SECRET")?;
 
     let expiration = chrono::Utc::now()
@@ -107,6 +122,8 @@ pub fn create_refresh_token_for_user(user_record: User) -> Result<String, Error>
         sub: user_record.id,
         iat: chrono::Utc::now().timestamp() as usize,
         exp: expiration as usize,
+        sid: Some(session_id),
+        jti: create_random_token()?,
     };
 
     let token = match encode(
@@ -1,5 +1,9 @@
 mod errors;
+mod llm;
+mod rate_limits;
 pub use crate::errors::Error;
+pub use crate::llm::{LlmConfig, LlmTaskConfig};
+pub use crate::rate_limits::{RateLimitBudget, RateLimits};
 use serde::{Deserialize, Serialize};
 use std::{env, fmt, str::FromStr};
 use url::Url;
@@ -8,6 +12,8 @@ use url::Url;
 pub struct Config {
     pub environment: Environment,
     pub web_app_url: Url,
+    /// Public URL of this server, used in links back to it such as email tracking
+    pub api_url: Url,
     pub root_domain: String,
     pub same_site: String,
 }
@@ -21,6 +27,14 @@ impl Default for Config {
             Environment::Staging => Url::parse("https://staging.populist.us").unwrap(),
             _ => Url::parse("http://localhost:3030").unwrap(),
         };
+        let api_url = env::var("API_URL")
+            .ok()
+            .and_then(|url| Url::parse(&url).ok())
+            .unwrap_or_else(|| match environment {
+                Environment::Production => Url::parse("https://api.populist.us").unwrap(),
+                Environment::Staging => Url::parse("https://api.staging.populist.us").unwrap(),
+                _ => Url::parse("http://localhost:1234").unwrap(),
+            });
         let root_domain = match environment {
             Environment::Production => "populist.us".to_string(),
             Environment::Staging => "staging.populist.us".to_string(),
@@ -34,6 +48,7 @@ impl Default for Config {
         Config {
             environment,
             web_app_url,
+            api_url,
             root_domain,
             same_site,
         }
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, env, str::FromStr, time::Duration};
+
+/// Generation settings for one kind of LLM call. A model of `None` uses `LLM_MODEL`, or the
+/// provider's default when that isn't set either.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlmTaskConfig {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+}
+
+impl LlmTaskConfig {
+    pub const fn new(temperature: Option<f32>, max_tokens: u32) -> Self {
+        LlmTaskConfig {
+            model: None,
+            temperature,
+            max_tokens,
+        }
+    }
+
+    /// Applies overrides in the form `model=gpt-4o-mini,temperature=0.2,max_tokens=200`.
+    /// Keys that aren't given keep their current value and `none` clears a model or
+    /// temperature.
+    pub fn with_overrides(mut self, overrides: &str) -> Result<Self, String> {
+        for part in overrides.split(',').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, got '{}'", part))?;
+            let value = value.trim();
+            let is_none = value.is_empty() || value.eq_ignore_ascii_case("none");
+            match key.trim() {
+                "model" => self.model = (!is_none).then(|| value.to_string()),
+                "temperature" => {
+                    self.temperature = if is_none {
+                        None
+                    } else {
+                        let temperature = f32::from_str(value)
+                            .ok()
+                            .filter(|t| (0.0..=2.0).contains(t))
+                            .ok_or_else(|| format!("Invalid temperature '{}'", value))?;
+                        Some(temperature)
+                    }
+                }
+                "max_tokens" => {
+                    self.max_tokens = u32::from_str(value)
+                        .ok()
+                        .filter(|tokens| *tokens > 0)
+                        .ok_or_else(|| format!("Invalid max_tokens '{}'", value))?;
+                }
+                other => return Err(format!("Unknown LLM setting '{}'", other)),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Which LLM provider to call and how, with settings for each task keyed by name
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    /// `openai`, `anthropic`, `local` or `fixture`. When unset the provider is picked from
+    /// whichever API key is present.
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    /// Base URL of an OpenAI compatible server, e.g. `http://localhost:11434/v1`
+    pub base_url: Option<String>,
+    /// How long a single attempt may take
+    pub timeout: Duration,
+    /// Attempts after the first for rate limits, server errors, timeouts and invalid
+    /// structured output
+    pub max_retries: u32,
+    pub tasks: HashMap<String, LlmTaskConfig>,
+}
+
+impl LlmConfig {
+    const DEFAULT_TASK: LlmTaskConfig = LlmTaskConfig::new(None, 1024);
+
+    /// Settings for a task, falling back to the provider defaults with 1024 tokens
+    pub fn task(&self, task: &str) -> LlmTaskConfig {
+        self.tasks.get(task).cloned().unwrap_or(Self::DEFAULT_TASK)
+    }
+
+    fn defaults() -> HashMap<String, LlmTaskConfig> {
+        [
+            ("group_summary", LlmTaskConfig::new(Some(0.7), 200)),
+            ("opinion_summary", LlmTaskConfig::new(Some(0.7), 320)),
+            ("translation", LlmTaskConfig::new(Some(0.2), 2048)),
+            ("bill_categorization", LlmTaskConfig::new(Some(0.2), 100)),
+            ("bill_summary", LlmTaskConfig::new(None, 1024)),
+            ("bill_question", LlmTaskConfig::new(None, 1024)),
+            ("sentiment", LlmTaskConfig::new(Some(0.0), 40)),
+            ("statement_screening", LlmTaskConfig::new(Some(0.0), 200)),
+        ]
+        .into_iter()
+        .map(|(task, config)| (task.to_string(), config))
+        .collect()
+    }
+}
+
+impl Default for LlmConfig {
+    /// Reads `LLM_PROVIDER`, `LLM_MODEL`, `LLM_BASE_URL`, `LLM_TIMEOUT_SECONDS` and
+    /// `LLM_MAX_RETRIES`. The built in task settings are adjusted by `LLM_<TASK>` variables,
+    /// e.g. `LLM_GROUP_SUMMARY=model=gpt-4o,max_tokens=300`.
+    fn default() -> Self {
+        let var = |name: &str| env::var(name).ok().filter(|value| !value.trim().is_empty());
+        let timeout = var("LLM_TIMEOUT_SECONDS")
+            .and_then(|seconds| u64::from_str(&seconds).ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(60);
+        let max_retries = var("LLM_MAX_RETRIES")
+            .and_then(|retries| u32::from_str(&retries).ok())
+            .unwrap_or(2);
+        let mut tasks = LlmConfig::defaults();
+        for (task, config) in tasks.iter_mut() {
+            let var = format!("LLM_{}", task.to_uppercase());
+            if let Ok(overrides) = env::var(&var) {
+                match config.clone().with_overrides(&overrides) {
+                    Ok(updated) => *config = updated,
+                    Err(err) => tracing::warn!("Ignoring {}: {}", var, err),
+                }
+            }
+        }
+        LlmConfig {
+            provider: var("LLM_PROVIDER").map(|provider| provider.to_lowercase()),
+            model: var("LLM_MODEL"),
+            base_url: var("LLM_BASE_URL"),
+            timeout: Duration::from_secs(timeout),
+            max_retries,
+            tasks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_overrides() {
+        let config = LlmTaskConfig::new(Some(0.7), 40)
+            .with_overrides("model=gpt-4o-mini, max_tokens=200,temperature=none")
+            .unwrap();
+        assert_eq!(config.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(config.temperature, None);
+        assert_eq!(config.max_tokens, 200);
+
+        assert!(config.clone().with_overrides("model").is_err());
+        assert!(config.clone().with_overrides("max_tokens=0").is_err());
+        assert!(config.clone().with_overrides("temperature=3").is_err());
+        assert!(config.with_overrides("top_p=1").is_err());
+    }
+
+    #[test]
+    fn test_unknown_task_uses_default() {
+        let config = LlmConfig {
+            provider: None,
+            model: None,
+            base_url: None,
+            timeout: Duration::from_secs(60),
+            max_retries: 2,
+            tasks: LlmConfig::defaults(),
+        };
+        assert_eq!(config.task("group_summary").max_tokens, 200);
+        assert_eq!(config.task("haiku"), LlmConfig::DEFAULT_TASK);
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, env, str::FromStr, time::Duration};
+
+/// How many times a client may run an operation per window. Each scope is counted
+/// separately, so a request is refused as soon as any one of them is exhausted. A limit
+/// of `None` leaves that scope unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitBudget {
+    pub window: Duration,
+    /// Shared by everyone behind the same address, e.g. a newsroom, so usually the highest
+    pub per_ip: Option<u32>,
+    pub per_session: Option<u32>,
+    pub per_user: Option<u32>,
+}
+
+impl RateLimitBudget {
+    pub const fn new(window_seconds: u64, per_ip: u32, per_session: u32, per_user: u32) -> Self {
+        RateLimitBudget {
+            window: Duration::from_secs(window_seconds),
+            per_ip: Some(per_ip),
+            per_session: Some(per_session),
+            per_user: Some(per_user),
+        }
+    }
+
+    /// Applies overrides in the form `ip=300,session=60,user=60,window=60`. Keys that
+    /// aren't given keep their current value and `none` removes a limit.
+    pub fn with_overrides(mut self, overrides: &str) -> Result<Self, String> {
+        for part in overrides.split(',').filter(|part| !part.trim().is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, got '{}'", part))?;
+            let value = value.trim();
+            let limit = || -> Result<Option<u32>, String> {
+                if value.eq_ignore_ascii_case("none") {
+                    Ok(None)
+                } else {
+                    u32::from_str(value)
+                        .map(Some)
+                        .map_err(|_| format!("Invalid limit '{}'", value))
+                }
+            };
+            match key.trim() {
+                "ip" => self.per_ip = limit()?,
+                "session" => self.per_session = limit()?,
+                "user" => self.per_user = limit()?,
+                "window" => {
+                    let seconds = u64::from_str(value)
+                        .ok()
+                        .filter(|seconds| *seconds > 0)
+                        .ok_or_else(|| format!("Invalid window '{}'", value))?;
+                    self.window = Duration::from_secs(seconds);
+                }
+                other => return Err(format!("Unknown rate limit key '{}'", other)),
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Budgets for rate limited GraphQL operations, keyed by field name
+#[derive(Debug, Clone)]
+pub struct RateLimits {
+    pub enabled: bool,
+    pub budgets: HashMap<String, RateLimitBudget>,
+}
+
+impl RateLimits {
+    pub fn budget(&self, operation: &str) -> Option<&RateLimitBudget> {
+        if self.enabled {
+            self.budgets.get(operation)
+        } else {
+            None
+        }
+    }
+
+    fn defaults() -> HashMap<String, RateLimitBudget> {
+        [
+            // Participants vote through a whole conversation in one sitting
+            ("vote_on_statement", RateLimitBudget::new(60, 600, 120, 120)),
+            ("add_statement", RateLimitBudget::new(600, 100, 10, 20)),
+            (
+                "upsert_poll_submission",
+                RateLimitBudget::new(600, 200, 10, 10),
+            ),
+            (
+                "upsert_question_submission",
+                RateLimitBudget::new(600, 200, 10, 10),
+            ),
+            (
+                "upsert_bill_public_vote",
+                RateLimitBudget::new(600, 300, 30, 30),
+            ),
+            // Every page view of an embed pings, so only the address is limited
+            (
+                "ping_embed_origin",
+                RateLimitBudget {
+                    window: Duration::from_secs(60),
+                    per_ip: Some(600),
+                    per_session: None,
+                    per_user: None,
+                },
+            ),
+        ]
+        .into_iter()
+        .map(|(operation, budget)| (operation.to_string(), budget))
+        .collect()
+    }
+}
+
+impl Default for RateLimits {
+    /// The built in budgets, adjusted by `RATE_LIMIT_<OPERATION>` variables, e.g.
+    /// `RATE_LIMIT_VOTE_ON_STATEMENT=ip=1000,window=60`. `RATE_LIMITS_ENABLED=false`
+    /// turns limiting off.
+    fn default() -> Self {
+        let enabled = env::var("RATE_LIMITS_ENABLED")
+            .map(|value| !matches!(value.to_lowercase().as_str(), "false" | "0" | "off"))
+            .unwrap_or(true);
+        let mut budgets = RateLimits::defaults();
+        for (operation, budget) in budgets.iter_mut() {
+            let var = format!("RATE_LIMIT_{}", operation.to_uppercase());
+            if let Ok(overrides) = env::var(&var) {
+                match budget.with_overrides(&overrides) {
+                    Ok(updated) => *budget = updated,
+                    Err(err) => tracing::warn!("Ignoring {}: {}", var, err),
+                }
+            }
+        }
+        RateLimits { enabled, budgets }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_overrides() {
+        let budget = RateLimitBudget::new(60, 300, 30, 30)
+            .with_overrides("ip=1000, session=none,window=120")
+            .unwrap();
+        assert_eq!(budget.per_ip, Some(1000));
+        assert_eq!(budget.per_session, None);
+        assert_eq!(budget.per_user, Some(30));
+        assert_eq!(budget.window, Duration::from_secs(120));
+
+        assert!(budget.with_overrides("ip").is_err());
+        assert!(budget.with_overrides("window=0").is_err());
+        assert!(budget.with_overrides("device=5").is_err());
+    }
+
+    #[test]
+    fn test_disabled_limits_have_no_budget() {
+        let mut limits = RateLimits {
+            enabled: true,
+            budgets: RateLimits::defaults(),
+        };
+        assert!(limits.budget("vote_on_statement").is_some());
+        assert!(limits.budget("login").is_none());
+
+        limits.enabled = false;
+        assert!(limits.budget("vote_on_statement").is_none());
+    }
+}
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS race_results_update ON race;
+DROP TRIGGER IF EXISTS race_candidates_votes_update ON race_candidates;
+DROP FUNCTION IF EXISTS notify_race_results_updated();
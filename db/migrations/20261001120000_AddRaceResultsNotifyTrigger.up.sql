@@ -0,0 +1,42 @@
+-- Notify listeners whenever results for a race change so that GraphQL
+-- subscriptions can push updates instead of clients polling. Postgres
+-- collapses identical payloads within a transaction, so a merge that touches
+-- every candidate in a race only emits one notification per race.
+CREATE OR REPLACE FUNCTION notify_race_results_updated()
+RETURNS TRIGGER AS $$
+DECLARE
+  race_id UUID;
+  election_id UUID;
+BEGIN
+  IF TG_TABLE_NAME = 'race_candidates' THEN
+    race_id := NEW.race_id;
+    SELECT r.election_id INTO election_id FROM race r WHERE r.id = NEW.race_id;
+  ELSE
+    race_id := NEW.id;
+    election_id := NEW.election_id;
+  END IF;
+
+  PERFORM pg_notify(
+    'race_results_updated',
+    json_build_object('raceId', race_id, 'electionId', election_id)::text
+  );
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER race_candidates_votes_update
+AFTER UPDATE OF votes ON race_candidates
+FOR EACH ROW
+WHEN (OLD.votes IS DISTINCT FROM NEW.votes)
+EXECUTE FUNCTION notify_race_results_updated();
+
+CREATE TRIGGER race_results_update
+AFTER UPDATE OF total_votes, num_precincts_reporting, total_precincts, winner_ids ON race
+FOR EACH ROW
+WHEN (
+  OLD.total_votes IS DISTINCT FROM NEW.total_votes
+  OR OLD.num_precincts_reporting IS DISTINCT FROM NEW.num_precincts_reporting
+  OR OLD.total_precincts IS DISTINCT FROM NEW.total_precincts
+  OR OLD.winner_ids IS DISTINCT FROM NEW.winner_ids
+)
+EXECUTE FUNCTION notify_race_results_updated();
@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS statement_vote_notify ON statement_vote;
+DROP TRIGGER IF EXISTS statement_moderation_notify ON statement;
+DROP TRIGGER IF EXISTS statement_insert_notify ON statement;
+DROP FUNCTION IF EXISTS notify_statement_vote_updated();
+DROP FUNCTION IF EXISTS notify_statement_updated();
@@ -0,0 +1,58 @@
+-- Notify listeners when statements are added or moderated so conversation
+-- subscriptions stay in sync across every server instance.
+CREATE OR REPLACE FUNCTION notify_statement_updated()
+RETURNS TRIGGER AS $$
+BEGIN
+  PERFORM pg_notify(
+    'statement_updated',
+    json_build_object(
+      'statementId', NEW.id,
+      'conversationId', NEW.conversation_id,
+      'moderationStatus', NEW.moderation_status,
+      'previousModerationStatus', CASE WHEN TG_OP = 'UPDATE' THEN OLD.moderation_status ELSE NULL END
+    )::text
+  );
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER statement_insert_notify
+AFTER INSERT ON statement
+FOR EACH ROW
+EXECUTE FUNCTION notify_statement_updated();
+
+CREATE TRIGGER statement_moderation_notify
+AFTER UPDATE OF moderation_status ON statement
+FOR EACH ROW
+WHEN (OLD.moderation_status IS DISTINCT FROM NEW.moderation_status)
+EXECUTE FUNCTION notify_statement_updated();
+
+-- Notify listeners when the vote tally for a statement changes
+CREATE OR REPLACE FUNCTION notify_statement_vote_updated()
+RETURNS TRIGGER AS $$
+DECLARE
+  vote_statement_id UUID;
+BEGIN
+  IF TG_OP = 'DELETE' THEN
+    vote_statement_id := OLD.statement_id;
+  ELSE
+    vote_statement_id := NEW.statement_id;
+  END IF;
+
+  PERFORM pg_notify(
+    'statement_vote_updated',
+    json_build_object(
+      'statementId', s.id,
+      'conversationId', s.conversation_id
+    )::text
+  )
+  FROM statement s
+  WHERE s.id = vote_statement_id;
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER statement_vote_notify
+AFTER INSERT OR DELETE OR UPDATE OF vote_type ON statement_vote
+FOR EACH ROW
+EXECUTE FUNCTION notify_statement_vote_updated();
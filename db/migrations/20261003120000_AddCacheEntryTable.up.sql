@@ -0,0 +1,11 @@
+-- Shared resolver cache so every server instance sees the same entries
+CREATE UNLOGGED TABLE IF NOT EXISTS cache_entry (
+    key TEXT NOT NULL PRIMARY KEY,
+    value JSONB NOT NULL,
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_cache_entry_tags ON cache_entry USING GIN (tags);
+CREATE INDEX idx_cache_entry_expires_at ON cache_entry (expires_at);
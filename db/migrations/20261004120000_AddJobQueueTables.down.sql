@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS job_run;
+DROP TABLE IF EXISTS job;
+DROP TYPE IF EXISTS job_status;
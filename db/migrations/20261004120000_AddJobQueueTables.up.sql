@@ -0,0 +1,45 @@
+-- Add up migration script here
+
+CREATE TYPE job_status AS ENUM ('pending', 'running', 'succeeded', 'failed', 'dead', 'cancelled');
+
+CREATE TABLE IF NOT EXISTS job (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    name TEXT NOT NULL,
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    -- Cron expression (with seconds) for recurring jobs, NULL for one-off jobs
+    schedule TEXT,
+    status job_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    locked_at TIMESTAMPTZ,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON job
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE INDEX job_status_run_at_idx ON job (status, run_at);
+CREATE UNIQUE INDEX job_recurring_name_idx ON job (name) WHERE schedule IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS job_run (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    job_id UUID NOT NULL REFERENCES job (id) ON DELETE CASCADE,
+    attempt INTEGER NOT NULL,
+    status job_status NOT NULL DEFAULT 'running',
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE INDEX job_run_job_id_idx ON job_run (job_id, started_at DESC);
+
+-- Recurring jobs previously hardcoded in the server's cron scheduler
+INSERT INTO job (name, payload, schedule, run_at) VALUES
+    ('import_legiscan_dataset', '{"sessionId": 2151, "state": "MN", "year": 2025}', '0 0 8 * * *', now()),
+    ('update_legiscan_bill_data', '{"sessionIds": [2173, 2151]}', '0 0 1/4 * * *', now());
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS results_source;
+DROP TYPE IF EXISTS results_source_format;
@@ -0,0 +1,33 @@
+-- Add up migration script here
+
+CREATE TYPE results_source_format AS ENUM ('mn_sos', 'tx_clarity', 'tx_hart', 'tx_civix');
+
+CREATE TABLE IF NOT EXISTS results_source (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    state state NOT NULL,
+    election_id UUID NOT NULL REFERENCES election (id) ON DELETE CASCADE,
+    -- Human readable label, e.g. "School Board Races". MN SoS uses it to pick the file layout.
+    name TEXT NOT NULL,
+    url TEXT NOT NULL,
+    format results_source_format NOT NULL,
+    -- Staging table the source is loaded into before results are merged into races
+    table_name TEXT NOT NULL,
+    -- Format specific settings, e.g. county and party for TX county sources
+    options JSONB NOT NULL DEFAULT '{}'::jsonb,
+    poll_interval_seconds INTEGER NOT NULL DEFAULT 600 CHECK (poll_interval_seconds >= 30),
+    active_from TIMESTAMPTZ NOT NULL,
+    active_until TIMESTAMPTZ NOT NULL,
+    is_enabled BOOLEAN NOT NULL DEFAULT true,
+    last_polled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CHECK (active_until > active_from)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON results_source
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE INDEX results_source_active_idx ON results_source (active_from, active_until) WHERE is_enabled;
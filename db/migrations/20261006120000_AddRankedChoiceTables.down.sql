@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS ranked_choice_round_tally;
+DROP TABLE IF EXISTS ranked_choice_round;
+DROP TABLE IF EXISTS ranked_choice_ballot;
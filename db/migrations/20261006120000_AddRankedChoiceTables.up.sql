@@ -0,0 +1,44 @@
+-- Add up migration script here
+
+-- Ballot rankings for ranked choice races, aggregated so identical rankings share a row
+CREATE TABLE IF NOT EXISTS ranked_choice_ballot (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    -- Candidate ids in order of preference, first choice first
+    rankings UUID[] NOT NULL CHECK (cardinality(rankings) > 0),
+    count INTEGER NOT NULL DEFAULT 1 CHECK (count > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (race_id, rankings)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON ranked_choice_ballot
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+-- Output of the most recent tabulation of a ranked choice race, one row per round
+CREATE TABLE IF NOT EXISTS ranked_choice_round (
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    round INTEGER NOT NULL CHECK (round > 0),
+    -- Votes needed to be elected in this round
+    quota DOUBLE PRECISION NOT NULL,
+    exhausted_votes DOUBLE PRECISION NOT NULL DEFAULT 0,
+    elected_ids UUID[] NOT NULL DEFAULT '{}',
+    eliminated_ids UUID[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (race_id, round)
+);
+
+CREATE TABLE IF NOT EXISTS ranked_choice_round_tally (
+    race_id UUID NOT NULL,
+    round INTEGER NOT NULL,
+    candidate_id UUID NOT NULL REFERENCES politician (id) ON DELETE CASCADE,
+    -- Fractional once surplus votes of an elected candidate have been transferred
+    votes DOUBLE PRECISION NOT NULL,
+    -- Votes gained (or lost) since the previous round
+    transferred DOUBLE PRECISION NOT NULL DEFAULT 0,
+    PRIMARY KEY (race_id, round, candidate_id),
+    FOREIGN KEY (race_id, round) REFERENCES ranked_choice_round (race_id, round) ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS county_result;
+DROP TABLE IF EXISTS precinct_turnout;
+DROP TABLE IF EXISTS precinct_result;
+DROP TABLE IF EXISTS precinct;
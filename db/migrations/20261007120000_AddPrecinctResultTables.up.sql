@@ -0,0 +1,81 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS precinct (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    state state NOT NULL,
+    -- County identifier used by the state's results files, e.g. MN SoS county ID "27"
+    county_code TEXT NOT NULL,
+    county TEXT,
+    -- Precinct identifier within the county, e.g. MN SoS precinct ID "0005"
+    code TEXT NOT NULL,
+    name TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (state, county_code, code)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON precinct
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE TABLE IF NOT EXISTS precinct_result (
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    precinct_id UUID NOT NULL REFERENCES precinct (id) ON DELETE CASCADE,
+    candidate_id UUID NOT NULL REFERENCES politician (id) ON DELETE CASCADE,
+    votes INTEGER NOT NULL DEFAULT 0,
+    -- Total votes cast for the race in the precinct
+    total_votes INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (race_id, precinct_id, candidate_id)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON precinct_result
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE INDEX precinct_result_precinct_id_idx ON precinct_result (precinct_id);
+
+CREATE TABLE IF NOT EXISTS precinct_turnout (
+    election_id UUID NOT NULL REFERENCES election (id) ON DELETE CASCADE,
+    precinct_id UUID NOT NULL REFERENCES precinct (id) ON DELETE CASCADE,
+    -- Registered as of the morning of election day
+    registered_voters INTEGER,
+    election_day_registrations INTEGER,
+    ballots_cast INTEGER,
+    has_reported BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (election_id, precinct_id)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON precinct_turnout
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+-- County totals for sources that only publish results by county, e.g. TX county Clarity and Hart feeds.
+-- Counties of states with precinct results are aggregated from precinct_result instead.
+CREATE TABLE IF NOT EXISTS county_result (
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    county TEXT NOT NULL,
+    candidate_id UUID NOT NULL REFERENCES politician (id) ON DELETE CASCADE,
+    votes INTEGER NOT NULL DEFAULT 0,
+    total_votes INTEGER,
+    precincts_reporting INTEGER,
+    total_precincts INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (race_id, county, candidate_id)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON county_result
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS race_call;
+
+ALTER TABLE race
+DROP COLUMN IF EXISTS status,
+DROP COLUMN IF EXISTS status_updated_at,
+DROP COLUMN IF EXISTS status_updated_by;
+
+DROP TYPE IF EXISTS race_status;
@@ -0,0 +1,30 @@
+-- Add up migration script here
+
+CREATE TYPE race_status AS ENUM ('uncalled', 'projected', 'called', 'certified', 'recount');
+
+ALTER TABLE race
+ADD COLUMN status race_status NOT NULL DEFAULT 'uncalled',
+ADD COLUMN status_updated_at TIMESTAMPTZ,
+ADD COLUMN status_updated_by UUID REFERENCES populist_user (id) ON DELETE SET NULL;
+
+-- Races that already have winners were called by hand
+UPDATE race SET status = 'called', status_updated_at = updated_at
+WHERE winner_ids IS NOT NULL AND cardinality(winner_ids) > 0;
+
+-- Every status change of a race, whether projected from results or set by staff
+CREATE TABLE IF NOT EXISTS race_call (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    status race_status NOT NULL,
+    -- Winners, or the candidates advancing to a runoff
+    winner_ids UUID[] NOT NULL DEFAULT '{}',
+    is_runoff BOOLEAN NOT NULL DEFAULT false,
+    -- Candidates tied for the last winning (or advancing) place, left for staff to resolve
+    tied_candidate_ids UUID[] NOT NULL DEFAULT '{}',
+    note TEXT,
+    -- NULL when projected automatically from results
+    created_by UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX race_call_race_id_idx ON race_call (race_id, created_at DESC);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS scrape_run;
+DROP TYPE IF EXISTS scrape_stage;
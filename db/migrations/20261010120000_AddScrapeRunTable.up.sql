@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TYPE scrape_stage AS ENUM ('scrape', 'process', 'merge');
+
+-- Every run of a registered scraper, processor or merger, see `scrape list`
+CREATE TABLE IF NOT EXISTS scrape_run (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Registry id, e.g. mn.filings
+    source_id TEXT NOT NULL,
+    stage scrape_stage NOT NULL,
+    dry_run BOOLEAN NOT NULL DEFAULT false,
+    -- Options the run was started with
+    options JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status job_status NOT NULL DEFAULT 'running',
+    -- Named counts reported by the stage, e.g. {"rows": 120, "unmatched": 3}
+    counts JSONB NOT NULL DEFAULT '{}'::jsonb,
+    error TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ,
+    duration_ms BIGINT
+);
+
+CREATE INDEX scrape_run_source_id_idx ON scrape_run (source_id, stage, started_at DESC);
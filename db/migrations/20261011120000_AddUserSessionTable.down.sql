@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS user_session;
+DROP TYPE IF EXISTS session_revocation_reason;
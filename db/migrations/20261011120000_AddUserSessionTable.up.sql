@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TYPE session_revocation_reason AS ENUM ('logout', 'revoked', 'token_reuse');
+
+-- One row per signed in device. The refresh token is rotated on every use, so a session is
+-- the whole family of refresh tokens descended from one login.
+CREATE TABLE IF NOT EXISTS user_session (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES populist_user (id) ON DELETE CASCADE,
+    -- Hex SHA-256 of the current refresh token, the token itself is never stored
+    token_hash TEXT NOT NULL UNIQUE,
+    -- Hash of the token it replaced, accepted briefly so concurrent requests don't trip reuse detection
+    previous_token_hash TEXT,
+    user_agent TEXT,
+    ip_address TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    last_used_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    rotated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    revoked_at TIMESTAMPTZ,
+    revoked_reason session_revocation_reason
+);
+
+CREATE INDEX user_session_user_id_idx ON user_session (user_id) WHERE revoked_at IS NULL;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS organization_api_key;
+DROP TYPE IF EXISTS api_key_scope;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+CREATE TYPE api_key_scope AS ENUM ('read', 'write');
+
+-- Keys for partners calling the API from their own servers, sent as `Authorization: Bearer pop_...`
+CREATE TABLE IF NOT EXISTS organization_api_key (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    organization_id UUID NOT NULL REFERENCES organization (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    -- First characters of the key, shown so it can be recognized
+    prefix TEXT NOT NULL,
+    -- Hex SHA-256 of the key, the key itself is only shown once
+    key_hash TEXT NOT NULL UNIQUE,
+    scope api_key_scope NOT NULL DEFAULT 'read',
+    -- Embed types a write key may create, update and delete
+    embed_types embed_type[] NOT NULL DEFAULT '{}',
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    -- Calls made with the key act on behalf of this user
+    created_by UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX organization_api_key_organization_id_idx ON organization_api_key (organization_id);
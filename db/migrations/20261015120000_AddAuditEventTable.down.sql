@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS audit_event;
+DROP TYPE IF EXISTS audit_operation;
+DROP TYPE IF EXISTS audit_entity_type;
@@ -0,0 +1,42 @@
+-- Add up migration script here
+
+CREATE TYPE audit_entity_type AS ENUM (
+    'politician',
+    'race',
+    'office',
+    'bill',
+    'ballot_measure',
+    'election',
+    'issue_tag',
+    'organization',
+    'embed',
+    'candidate_guide',
+    'question',
+    'poll',
+    'conversation',
+    'statement',
+    'results_source'
+);
+
+CREATE TYPE audit_operation AS ENUM ('create', 'update', 'delete');
+
+-- One row per record changed by a staff or organization mutation
+CREATE TABLE IF NOT EXISTS audit_event (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_user_id UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+    actor_api_key_id UUID REFERENCES organization_api_key (id) ON DELETE SET NULL,
+    -- Organization that owns the record, if any, so org admins can see its history
+    organization_id UUID REFERENCES organization (id) ON DELETE SET NULL,
+    entity_type audit_entity_type NOT NULL,
+    entity_id UUID NOT NULL,
+    operation audit_operation NOT NULL,
+    -- GraphQL mutation that made the change, e.g. upsertRace
+    mutation TEXT NOT NULL,
+    -- Changed fields as {"field": {"old": ..., "new": ...}}
+    changes JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX audit_event_entity_idx ON audit_event (entity_type, entity_id, created_at DESC);
+CREATE INDEX audit_event_organization_id_idx ON audit_event (organization_id, created_at DESC);
+CREATE INDEX audit_event_actor_user_id_idx ON audit_event (actor_user_id, created_at DESC);
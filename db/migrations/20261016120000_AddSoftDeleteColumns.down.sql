@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+-- Records that were already deleted stay deleted
+DELETE FROM candidate_guide WHERE deleted_at IS NOT NULL;
+DELETE FROM embed WHERE deleted_at IS NOT NULL;
+DELETE FROM race WHERE deleted_at IS NOT NULL;
+DELETE FROM politician WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE candidate_guide DROP COLUMN deleted_at;
+ALTER TABLE embed DROP COLUMN deleted_at;
+ALTER TABLE race DROP COLUMN deleted_at;
+ALTER TABLE politician DROP COLUMN deleted_at;
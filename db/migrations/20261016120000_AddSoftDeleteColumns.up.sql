@@ -0,0 +1,17 @@
+-- Add up migration script here
+
+-- Deleted records are kept until the purge job removes them after the retention window
+ALTER TABLE politician ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE race ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE embed ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE candidate_guide ADD COLUMN deleted_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN politician.deleted_at IS 'Set when the politician is deleted, hidden from finders until restored or purged';
+COMMENT ON COLUMN race.deleted_at IS 'Set when the race is deleted, hidden from finders until restored or purged';
+COMMENT ON COLUMN embed.deleted_at IS 'Set when the embed is deleted, hidden from finders until restored or purged';
+COMMENT ON COLUMN candidate_guide.deleted_at IS 'Set when the guide is deleted, hidden from finders until restored or purged';
+
+CREATE INDEX politician_deleted_at_idx ON politician (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX race_deleted_at_idx ON race (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX embed_deleted_at_idx ON embed (deleted_at) WHERE deleted_at IS NOT NULL;
+CREATE INDEX candidate_guide_deleted_at_idx ON candidate_guide (deleted_at) WHERE deleted_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS email_outbox;
+DROP TYPE IF EXISTS email_status;
@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+CREATE TYPE email_status AS ENUM ('pending', 'sending', 'sent', 'dead');
+
+CREATE TABLE IF NOT EXISTS email_outbox (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    to_address TEXT NOT NULL,
+    template TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    html_body TEXT NOT NULL,
+    text_body TEXT NOT NULL,
+    -- Enqueuing an email with a key that's already in the outbox is a no-op
+    dedup_key TEXT UNIQUE,
+    status email_status NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 5,
+    send_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    locked_at TIMESTAMPTZ,
+    last_error TEXT,
+    sent_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON email_outbox
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE INDEX email_outbox_status_send_at_idx ON email_outbox (status, send_at);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS candidate_guide_outreach_recipient;
+DROP TABLE IF EXISTS candidate_guide_outreach;
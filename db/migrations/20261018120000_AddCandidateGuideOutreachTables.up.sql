@@ -0,0 +1,42 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS candidate_guide_outreach (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    candidate_guide_id UUID NOT NULL UNIQUE REFERENCES candidate_guide (id) ON DELETE CASCADE,
+    deadline TIMESTAMPTZ NOT NULL,
+    -- Days before the deadline to remind candidates who haven't responded
+    reminder_days INTEGER[] NOT NULL DEFAULT '{7,2}',
+    created_by UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON candidate_guide_outreach
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
+
+CREATE TABLE IF NOT EXISTS candidate_guide_outreach_recipient (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    outreach_id UUID NOT NULL REFERENCES candidate_guide_outreach (id) ON DELETE CASCADE,
+    race_id UUID NOT NULL REFERENCES race (id) ON DELETE CASCADE,
+    politician_id UUID NOT NULL REFERENCES politician (id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    -- Identifies the recipient in open and click tracking links
+    tracking_token TEXT NOT NULL UNIQUE DEFAULT encode(gen_random_bytes(24), 'hex'),
+    invite_email_id UUID REFERENCES email_outbox (id) ON DELETE SET NULL,
+    reminders_sent INTEGER NOT NULL DEFAULT 0,
+    last_reminded_at TIMESTAMPTZ,
+    opened_at TIMESTAMPTZ,
+    clicked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (outreach_id, race_id, politician_id)
+);
+
+CREATE TRIGGER set_updated_at
+BEFORE UPDATE
+ON candidate_guide_outreach_recipient
+FOR EACH ROW
+EXECUTE PROCEDURE set_updated_at();
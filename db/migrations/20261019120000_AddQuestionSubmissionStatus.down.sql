@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS question_submission_review;
+
+ALTER TABLE question_submission DROP COLUMN IF EXISTS status;
+
+DROP TYPE IF EXISTS submission_status;
@@ -0,0 +1,33 @@
+-- Add up migration script here
+
+CREATE TYPE submission_status AS ENUM (
+    'draft',
+    'submitted',
+    'in_review',
+    'approved',
+    'published',
+    'rejected'
+);
+
+-- Submissions made before review existed were already public
+ALTER TABLE question_submission
+ADD COLUMN status submission_status NOT NULL DEFAULT 'published';
+
+ALTER TABLE question_submission
+ALTER COLUMN status SET DEFAULT 'submitted';
+
+CREATE INDEX IF NOT EXISTS idx_question_submission_status ON question_submission (status);
+
+-- Every review action, so earlier editorial notes aren't lost when they're edited
+CREATE TABLE IF NOT EXISTS question_submission_review (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_submission_id UUID NOT NULL REFERENCES question_submission (id) ON DELETE CASCADE,
+    from_status submission_status NOT NULL,
+    to_status submission_status NOT NULL,
+    editorial TEXT,
+    reviewer_id UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_question_submission_review_submission_id
+ON question_submission_review (question_submission_id, created_at);
@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+DROP TRIGGER IF EXISTS record_revision ON question_submission;
+DROP TRIGGER IF EXISTS record_revision ON politician;
+DROP FUNCTION IF EXISTS record_revision();
+
+DROP TABLE IF EXISTS revision;
+DROP FUNCTION IF EXISTS prevent_revision_update();
+
+DROP TYPE IF EXISTS revision_entity_type;
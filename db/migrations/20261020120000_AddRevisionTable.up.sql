@@ -0,0 +1,116 @@
+-- Add up migration script here
+
+CREATE TYPE revision_entity_type AS ENUM ('question_submission', 'politician');
+
+-- Snapshots of a record's tracked columns after every write that changed them
+CREATE TABLE IF NOT EXISTS revision (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    entity_type revision_entity_type NOT NULL,
+    entity_id UUID NOT NULL,
+    revision_number INTEGER NOT NULL,
+    data JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (entity_type, entity_id, revision_number)
+);
+
+CREATE OR REPLACE FUNCTION prevent_revision_update()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Revisions can''t be changed';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER prevent_revision_update
+BEFORE UPDATE
+ON revision
+FOR EACH ROW
+EXECUTE PROCEDURE prevent_revision_update();
+
+-- Called with the entity type followed by the columns to keep, e.g.
+-- record_revision('question_submission', 'response', 'editorial')
+CREATE OR REPLACE FUNCTION record_revision()
+RETURNS TRIGGER AS $$
+DECLARE
+    tracked JSONB;
+    latest_data JSONB;
+    latest_number INTEGER;
+BEGIN
+    SELECT COALESCE(jsonb_object_agg(key, value), '{}') INTO tracked
+    FROM jsonb_each(to_jsonb(NEW))
+    WHERE key = ANY(TG_ARGV[1:]);
+
+    SELECT data, revision_number INTO latest_data, latest_number
+    FROM revision
+    WHERE entity_type = TG_ARGV[0]::revision_entity_type AND entity_id = NEW.id
+    ORDER BY revision_number DESC
+    LIMIT 1;
+
+    IF latest_data IS DISTINCT FROM tracked THEN
+        INSERT INTO revision (entity_type, entity_id, revision_number, data)
+        VALUES (TG_ARGV[0]::revision_entity_type, NEW.id, COALESCE(latest_number, 0) + 1, tracked);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Keep in sync with RevisionEntityType::columns
+CREATE TRIGGER record_revision
+AFTER INSERT OR UPDATE
+ON question_submission
+FOR EACH ROW
+EXECUTE PROCEDURE record_revision('question_submission', 'response', 'editorial', 'translations');
+
+CREATE TRIGGER record_revision
+AFTER INSERT OR UPDATE
+ON politician
+FOR EACH ROW
+EXECUTE PROCEDURE record_revision(
+    'politician',
+    'first_name',
+    'middle_name',
+    'last_name',
+    'suffix',
+    'preferred_name',
+    'biography',
+    'biography_source',
+    'home_state',
+    'party_id',
+    'date_of_birth',
+    'thumbnail_image_url',
+    'assets',
+    'official_website_url',
+    'ballotpedia_url',
+    'campaign_website_url',
+    'facebook_url',
+    'twitter_url',
+    'instagram_url',
+    'youtube_url',
+    'linkedin_url',
+    'tiktok_url',
+    'email',
+    'phone'
+);
+
+-- Existing records start from what they are now
+INSERT INTO revision (entity_type, entity_id, revision_number, data)
+SELECT 'question_submission', qs.id, 1, jsonb_build_object(
+    'response', qs.response,
+    'editorial', qs.editorial,
+    'translations', qs.translations
+)
+FROM question_submission qs;
+
+INSERT INTO revision (entity_type, entity_id, revision_number, data)
+SELECT 'politician', p.id, 1, (
+    SELECT jsonb_object_agg(key, value)
+    FROM jsonb_each(to_jsonb(p))
+    WHERE key = ANY(ARRAY[
+        'first_name', 'middle_name', 'last_name', 'suffix', 'preferred_name', 'biography',
+        'biography_source', 'home_state', 'party_id', 'date_of_birth', 'thumbnail_image_url',
+        'assets', 'official_website_url', 'ballotpedia_url', 'campaign_website_url',
+        'facebook_url', 'twitter_url', 'instagram_url', 'youtube_url', 'linkedin_url',
+        'tiktok_url', 'email', 'phone'
+    ])
+)
+FROM politician p;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+CREATE TABLE IF NOT EXISTS conversation_analysis_snapshot (
+    id UUID NOT NULL PRIMARY KEY DEFAULT gen_random_uuid(),
+    conversation_id UUID NOT NULL REFERENCES conversation (id) ON DELETE CASCADE,
+    vote_count INTEGER NOT NULL,
+    participant_count INTEGER NOT NULL,
+    opinion_analysis JSONB NOT NULL,
+    opinion_groups JSONB NOT NULL,
+    opinion_map JSONB NOT NULL,
+    -- Each participant's group, so groups keep their ids when the analysis is recomputed
+    group_labels JSONB NOT NULL DEFAULT '{}',
+    -- When the votes were read, so votes cast during the analysis count towards the next one
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS conversation_analysis_snapshot_conversation_id_created_at_idx
+ON conversation_analysis_snapshot (conversation_id, created_at DESC);
@@ -0,0 +1,15 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS statement_conversation_id_moderation_status_idx;
+
+ALTER TABLE statement
+DROP COLUMN IF EXISTS moderated_at,
+DROP COLUMN IF EXISTS moderated_by,
+DROP COLUMN IF EXISTS screening_flags;
+
+ALTER TABLE conversation
+DROP COLUMN IF EXISTS llm_screening,
+DROP COLUMN IF EXISTS blocked_words,
+DROP COLUMN IF EXISTS moderation_mode;
+
+DROP TYPE IF EXISTS conversation_moderation_mode;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+CREATE TYPE conversation_moderation_mode AS ENUM ('pre', 'post');
+
+-- Statements have always been held as unmoderated until approved, so existing
+-- conversations keep pre-moderation
+ALTER TABLE conversation
+ADD COLUMN moderation_mode conversation_moderation_mode NOT NULL DEFAULT 'pre',
+ADD COLUMN blocked_words TEXT[] NOT NULL DEFAULT '{}',
+ADD COLUMN llm_screening BOOLEAN NOT NULL DEFAULT false;
+
+-- Why the pre-screen held a statement for review, as [{"reason": ..., "detail": ...}]
+ALTER TABLE statement
+ADD COLUMN screening_flags JSONB NOT NULL DEFAULT '[]',
+ADD COLUMN moderated_by UUID REFERENCES populist_user (id) ON DELETE SET NULL,
+ADD COLUMN moderated_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS statement_conversation_id_moderation_status_idx
+ON statement (conversation_id, moderation_status, created_at);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+ALTER TABLE results_source ADD COLUMN IF NOT EXISTS table_name TEXT;
+
+UPDATE results_source SET table_name = CASE format
+    WHEN 'tx_clarity' THEN 'ingest_staging.stg_tx_results_clarity'
+    WHEN 'tx_hart' THEN 'ingest_staging.stg_tx_results_hart'
+    WHEN 'tx_civix' THEN 'ingest_staging.stg_tx_results_sos_civix'
+    ELSE 'p6t_state_mn.stg_results_' || replace(id::text, '-', '')
+END;
+
+ALTER TABLE results_source ALTER COLUMN table_name SET NOT NULL;
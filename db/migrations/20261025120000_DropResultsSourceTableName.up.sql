@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- Staging tables are derived from the source's format and id rather than set through the API
+ALTER TABLE results_source DROP COLUMN IF EXISTS table_name;
@@ -0,0 +1,14 @@
+-- Add down migration script here
+
+DELETE FROM audit_event
+WHERE entity_type IS NULL
+    OR entity_id IS NULL
+    OR operation IS NULL
+    OR entity_type IN ('question_submission', 'organization_api_key', 'user_session');
+
+ALTER TABLE audit_event
+    ALTER COLUMN entity_type SET NOT NULL,
+    ALTER COLUMN entity_id SET NOT NULL,
+    ALTER COLUMN operation SET NOT NULL;
+
+-- Postgres can't drop enum values, the new audit_entity_type values are left unused
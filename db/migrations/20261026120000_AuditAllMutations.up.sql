@@ -0,0 +1,12 @@
+-- Add up migration script here
+
+ALTER TYPE audit_entity_type ADD VALUE IF NOT EXISTS 'question_submission';
+ALTER TYPE audit_entity_type ADD VALUE IF NOT EXISTS 'organization_api_key';
+ALTER TYPE audit_entity_type ADD VALUE IF NOT EXISTS 'user_session';
+
+-- Every mutation is recorded, including ones without a single target record and ones
+-- that didn't change the record's columns
+ALTER TABLE audit_event
+    ALTER COLUMN entity_type DROP NOT NULL,
+    ALTER COLUMN entity_id DROP NOT NULL,
+    ALTER COLUMN operation DROP NOT NULL;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+UPDATE job SET payload = '{"sessionId": 2151, "state": "MN", "year": 2025}'
+WHERE name = 'import_legiscan_dataset' AND schedule IS NOT NULL;
+
+UPDATE job SET payload = '{"sessionIds": [2173, 2151]}'
+WHERE name = 'update_legiscan_bill_data' AND schedule IS NOT NULL;
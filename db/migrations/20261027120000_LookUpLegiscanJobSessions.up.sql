@@ -0,0 +1,7 @@
+-- Add up migration script here
+
+-- Look up the current Minnesota sessions when the Legiscan jobs run instead of pinning
+-- session ids that go stale when a new session starts
+UPDATE job SET payload = '{"state": "MN"}'
+WHERE name IN ('import_legiscan_dataset', 'update_legiscan_bill_data')
+AND schedule IS NOT NULL;
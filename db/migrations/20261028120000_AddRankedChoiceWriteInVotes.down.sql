@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE ranked_choice_round DROP COLUMN write_in_votes;
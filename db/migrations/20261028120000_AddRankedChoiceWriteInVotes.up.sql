@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Write-ins are tabulated together like a candidate, but aren't a politician to tally
+ALTER TABLE ranked_choice_round
+ADD COLUMN write_in_votes DOUBLE PRECISION NOT NULL DEFAULT 0;
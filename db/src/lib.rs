@@ -11,21 +11,37 @@ pub use errors::Error;
 
 pub use models::address::*;
 pub use models::argument::*;
+pub use models::audit_event::*;
 pub use models::ballot_measure::*;
 pub use models::bill::*;
+pub use models::candidate_guide_outreach::*;
 pub use models::conversation::*;
+pub use models::conversation_analysis_snapshot::*;
+pub use models::deleted_record::*;
 pub use models::election::*;
+pub use models::email_outbox::*;
 pub use models::embed::*;
 pub use models::enums::*;
 pub use models::issue_tag::*;
+pub use models::job::*;
 pub use models::office::*;
 pub use models::organization::*;
+pub use models::organization_api_key::*;
 pub use models::organization_politician_note::*;
 pub use models::party::*;
 pub use models::politician::*;
 pub use models::poll::*;
+pub use models::precinct::*;
 pub use models::question::*;
 pub use models::race::*;
+pub use models::race_advancement::*;
+pub use models::race_call::*;
+pub use models::ranked_choice::*;
 pub use models::respondent::*;
+pub use models::results_source::*;
+pub use models::revision::*;
+pub use models::scrape_run::*;
+pub use models::statement_moderation::*;
 pub use models::user::*;
+pub use models::user_session::*;
 pub use pool::*;
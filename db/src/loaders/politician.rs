@@ -32,7 +32,7 @@ impl Loader<PoliticianId> for PoliticianLoader {
         keys: &[PoliticianId],
     ) -> Result<HashMap<PoliticianId, Self::Value>, Self::Error> {
         let query = format!(
-            r#"SELECT * FROM politician WHERE id IN ({})"#,
+            r#"SELECT * FROM politician WHERE id IN ({}) AND deleted_at IS NULL"#,
             keys.iter().map(|k| format!("'{}'", k.0)).join(",")
         );
 
@@ -55,7 +55,7 @@ impl Loader<PoliticianSlug> for PoliticianLoader {
         keys: &[PoliticianSlug],
     ) -> Result<HashMap<PoliticianSlug, Self::Value>, Self::Error> {
         let query = format!(
-            r#"SELECT * FROM politician WHERE slug IN ({})"#,
+            r#"SELECT * FROM politician WHERE slug IN ({}) AND deleted_at IS NULL"#,
             keys.iter().map(|k| format!("'{}'", k.0)).join(",")
         );
 
@@ -75,7 +75,7 @@ impl Loader<OfficeId> for PoliticianLoader {
 
     async fn load(&self, keys: &[OfficeId]) -> Result<HashMap<OfficeId, Self::Value>, Self::Error> {
         let query = format!(
-            r#"SELECT * FROM politician WHERE office_id IN ({})"#,
+            r#"SELECT * FROM politician WHERE office_id IN ({}) AND deleted_at IS NULL"#,
             keys.iter().map(|k| format!("'{}'", k.0)).join(",")
         );
 
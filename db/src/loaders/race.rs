@@ -25,7 +25,7 @@ impl Loader<uuid::Uuid> for RaceLoader {
         keys: &[uuid::Uuid],
     ) -> Result<HashMap<uuid::Uuid, Self::Value>, Self::Error> {
         let query = format!(
-            r#"SELECT * FROM race WHERE id IN ({})"#,
+            r#"SELECT * FROM race WHERE id IN ({}) AND deleted_at IS NULL"#,
             keys.iter().map(|k| format!("'{}'", k)).join(",")
         );
 
@@ -0,0 +1,328 @@
+use async_graphql::InputObject;
+use serde_json::{json, Map, Value as JSON};
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::enums::{AuditEntityType, AuditOperation},
+    DateTime, Error,
+};
+
+/// Columns left out of `changes`, since they change on every write
+const IGNORED_COLUMNS: &[&str] = &["created_at", "updated_at"];
+
+/// Columns whose values are never copied into `changes`, only that they changed
+const REDACTED_COLUMNS: &[&str] = &[
+    "intake_token",
+    "key_hash",
+    "token_hash",
+    "previous_token_hash",
+    "password",
+];
+
+/// Most events a single audit log query returns
+pub const MAX_AUDIT_EVENTS_LIMIT: i64 = 500;
+
+#[derive(FromRow, Debug, Clone)]
+pub struct AuditEvent {
+    pub id: uuid::Uuid,
+    pub actor_user_id: Option<uuid::Uuid>,
+    pub actor_api_key_id: Option<uuid::Uuid>,
+    pub organization_id: Option<uuid::Uuid>,
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_id: Option<uuid::Uuid>,
+    pub operation: Option<AuditOperation>,
+    pub mutation: String,
+    pub changes: JSON,
+    pub created_at: DateTime,
+}
+
+/// A record as it was before and after a mutation, as captured by `snapshot`. Mutations
+/// without a single target record, or whose id couldn't be resolved, have no entity.
+#[derive(Debug, Clone)]
+pub struct NewAuditEvent<'a> {
+    pub actor_user_id: Option<uuid::Uuid>,
+    pub actor_api_key_id: Option<uuid::Uuid>,
+    /// Organization named in the mutation's arguments, used when the record has none
+    pub organization_id: Option<uuid::Uuid>,
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_id: Option<uuid::Uuid>,
+    pub mutation: &'a str,
+    pub before: Option<JSON>,
+    pub after: Option<JSON>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct AuditEventFilter {
+    pub entity_type: Option<AuditEntityType>,
+    pub entity_id: Option<uuid::Uuid>,
+    pub actor_user_id: Option<uuid::Uuid>,
+    pub organization_id: Option<uuid::Uuid>,
+    pub operation: Option<AuditOperation>,
+}
+
+impl AuditEntityType {
+    pub fn table(&self) -> &'static str {
+        match self {
+            AuditEntityType::Politician => "politician",
+            AuditEntityType::Race => "race",
+            AuditEntityType::Office => "office",
+            AuditEntityType::Bill => "bill",
+            AuditEntityType::BallotMeasure => "ballot_measure",
+            AuditEntityType::Election => "election",
+            AuditEntityType::IssueTag => "issue_tag",
+            AuditEntityType::Organization => "organization",
+            AuditEntityType::Embed => "embed",
+            AuditEntityType::CandidateGuide => "candidate_guide",
+            AuditEntityType::Question => "question",
+            AuditEntityType::Poll => "poll",
+            AuditEntityType::Conversation => "conversation",
+            AuditEntityType::Statement => "statement",
+            AuditEntityType::ResultsSource => "results_source",
+            AuditEntityType::QuestionSubmission => "question_submission",
+            AuditEntityType::OrganizationApiKey => "organization_api_key",
+            AuditEntityType::UserSession => "user_session",
+        }
+    }
+}
+
+fn column_value(column: &str, value: &JSON) -> JSON {
+    if REDACTED_COLUMNS.contains(&column) && !value.is_null() {
+        json!("[redacted]")
+    } else {
+        value.clone()
+    }
+}
+
+/// Compares two snapshots of a record, returning what happened to it and the changed
+/// columns as `{"column": {"old": ..., "new": ...}}`. Returns `None` when nothing changed.
+pub(crate) fn diff(before: Option<&JSON>, after: Option<&JSON>) -> Option<(AuditOperation, JSON)> {
+    let empty = Map::new();
+    let before_columns = before.and_then(JSON::as_object);
+    let after_columns = after.and_then(JSON::as_object);
+    let operation = match (before_columns, after_columns) {
+        (None, None) => return None,
+        (None, Some(_)) => AuditOperation::Create,
+        (Some(_), None) => AuditOperation::Delete,
+        // Soft deleted records are still there afterwards, with a tombstone
+        (Some(before), Some(after))
+            if before.get("deleted_at").is_none_or(JSON::is_null)
+                && after.get("deleted_at").is_some_and(|d| !d.is_null()) =>
+        {
+            AuditOperation::Delete
+        }
+        (Some(_), Some(_)) => AuditOperation::Update,
+    };
+    let before_columns = before_columns.unwrap_or(&empty);
+    let after_columns = after_columns.unwrap_or(&empty);
+
+    let mut changes = Map::new();
+    let columns = before_columns.keys().chain(
+        after_columns
+            .keys()
+            .filter(|column| !before_columns.contains_key(*column)),
+    );
+    for column in columns {
+        if IGNORED_COLUMNS.contains(&column.as_str()) {
+            continue;
+        }
+        let old = before_columns.get(column).unwrap_or(&JSON::Null);
+        let new = after_columns.get(column).unwrap_or(&JSON::Null);
+        if old == new {
+            continue;
+        }
+        let mut change = Map::new();
+        if before.is_some() {
+            change.insert("old".to_string(), column_value(column, old));
+        }
+        if after.is_some() {
+            change.insert("new".to_string(), column_value(column, new));
+        }
+        changes.insert(column.clone(), JSON::Object(change));
+    }
+
+    if operation == AuditOperation::Update && changes.is_empty() {
+        None
+    } else {
+        Some((operation, JSON::Object(changes)))
+    }
+}
+
+impl AuditEvent {
+    /// The record's current columns, or `None` if it doesn't exist
+    pub async fn snapshot(
+        db_pool: &PgPool,
+        entity_type: AuditEntityType,
+        entity_id: uuid::Uuid,
+    ) -> Result<Option<JSON>, Error> {
+        // The table name comes from the enum, never from input
+        let query = format!(
+            "SELECT to_jsonb(t) FROM {} t WHERE t.id = $1",
+            entity_type.table()
+        );
+        let snapshot = sqlx::query_scalar::<_, JSON>(&query)
+            .bind(entity_id)
+            .fetch_optional(db_pool)
+            .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Stores the event with the changes between the snapshots. Events without an entity,
+    /// or whose record didn't change, are stored without an operation and with no changes.
+    pub async fn record(db_pool: &PgPool, event: NewAuditEvent<'_>) -> Result<Self, Error> {
+        let (operation, changes) = match diff(event.before.as_ref(), event.after.as_ref()) {
+            Some((operation, changes)) => (Some(operation), changes),
+            None => (None, json!({})),
+        };
+
+        let snapshot = event.after.as_ref().or(event.before.as_ref());
+        let column = |name: &str| {
+            snapshot
+                .and_then(|snapshot| snapshot.get(name))
+                .and_then(JSON::as_str)
+                .and_then(|id| uuid::Uuid::parse_str(id).ok())
+        };
+        let organization_id = match event.entity_type {
+            // A deleted organization can't be referenced anymore
+            Some(AuditEntityType::Organization)
+                if snapshot.is_none() || operation == Some(AuditOperation::Delete) =>
+            {
+                None
+            }
+            Some(AuditEntityType::Organization) => event.entity_id,
+            Some(AuditEntityType::Statement) => match column("conversation_id") {
+                Some(conversation_id) => sqlx::query_scalar!(
+                    "SELECT organization_id FROM conversation WHERE id = $1",
+                    conversation_id
+                )
+                .fetch_optional(db_pool)
+                .await?
+                .flatten(),
+                None => None,
+            },
+            _ => column("organization_id"),
+        }
+        .or(event.organization_id);
+
+        let audit_event = sqlx::query_as!(
+            AuditEvent,
+            r#"
+            INSERT INTO audit_event
+                (actor_user_id, actor_api_key_id, organization_id, entity_type, entity_id,
+                 operation, mutation, changes)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, actor_user_id, actor_api_key_id, organization_id,
+                entity_type AS "entity_type:AuditEntityType", entity_id,
+                operation AS "operation:AuditOperation", mutation, changes, created_at
+            "#,
+            event.actor_user_id,
+            event.actor_api_key_id,
+            organization_id,
+            event.entity_type as Option<AuditEntityType>,
+            event.entity_id,
+            operation as Option<AuditOperation>,
+            event.mutation,
+            changes,
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(audit_event)
+    }
+
+    /// Matching events, newest first, at most `MAX_AUDIT_EVENTS_LIMIT` of them
+    pub async fn filter(
+        db_pool: &PgPool,
+        filter: &AuditEventFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let audit_events = sqlx::query_as!(
+            AuditEvent,
+            r#"
+            SELECT id, actor_user_id, actor_api_key_id, organization_id,
+                entity_type AS "entity_type:AuditEntityType", entity_id,
+                operation AS "operation:AuditOperation", mutation, changes, created_at
+            FROM audit_event
+            WHERE ($1::audit_entity_type IS NULL OR entity_type = $1)
+                AND ($2::uuid IS NULL OR entity_id = $2)
+                AND ($3::uuid IS NULL OR actor_user_id = $3)
+                AND ($4::uuid IS NULL OR organization_id = $4)
+                AND ($5::audit_operation IS NULL OR operation = $5)
+            ORDER BY created_at DESC
+            LIMIT $6
+            "#,
+            filter.entity_type as Option<AuditEntityType>,
+            filter.entity_id,
+            filter.actor_user_id,
+            filter.organization_id,
+            filter.operation as Option<AuditOperation>,
+            limit.clamp(1, MAX_AUDIT_EVENTS_LIMIT),
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(audit_events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let before = json!({
+            "id": "1",
+            "title": "Mayor",
+            "intake_token": "abc",
+            "updated_at": "2026-01-01"
+        });
+        let after = json!({
+            "id": "1",
+            "title": "Mayor of Minneapolis",
+            "intake_token": "def",
+            "updated_at": "2026-01-02"
+        });
+
+        let (operation, changes) = diff(Some(&before), Some(&after)).unwrap();
+        assert_eq!(operation, AuditOperation::Update);
+        assert_eq!(
+            changes,
+            json!({
+                "title": { "old": "Mayor", "new": "Mayor of Minneapolis" },
+                "intake_token": { "old": "[redacted]", "new": "[redacted]" }
+            })
+        );
+
+        let deleted = json!({ "id": "1", "title": "Mayor", "deleted_at": "2026-01-03" });
+        let (operation, changes) = diff(Some(&before), Some(&deleted)).unwrap();
+        assert_eq!(operation, AuditOperation::Delete);
+        assert_eq!(
+            changes["deleted_at"],
+            json!({ "old": null, "new": "2026-01-03" })
+        );
+
+        // Saving without changes isn't an event
+        assert!(diff(Some(&before), Some(&before)).is_none());
+        assert!(diff(None, None).is_none());
+    }
+
+    #[test]
+    fn test_diff_create_and_delete() {
+        let record = json!({ "id": "1", "title": "Mayor", "description": null });
+
+        let (operation, changes) = diff(None, Some(&record)).unwrap();
+        assert_eq!(operation, AuditOperation::Create);
+        assert_eq!(
+            changes,
+            json!({ "id": { "new": "1" }, "title": { "new": "Mayor" } })
+        );
+
+        let (operation, changes) = diff(Some(&record), None).unwrap();
+        assert_eq!(operation, AuditOperation::Delete);
+        assert_eq!(
+            changes,
+            json!({ "id": { "old": "1" }, "title": { "old": "Mayor" } })
+        );
+    }
+}
@@ -478,7 +478,7 @@ impl Bill {
                         p.created_at,
                         p.updated_at FROM politician p 
                 JOIN bill_sponsors bs ON bs.politician_id = p.id 
-                WHERE bs.bill_id = $1
+                WHERE bs.bill_id = $1 AND p.deleted_at IS NULL
             "#,
             bill_id
         )
@@ -27,7 +27,69 @@ pub struct UpsertCandidateGuideInput {
     pub submissions_close_at: Option<DateTime>,
 }
 
+/// Guides with no open or close date accept submissions at any time
+const GUIDE_IS_OPEN: &str = r#"
+    cg.deleted_at IS NULL
+    AND (cg.submissions_open_at IS NULL OR cg.submissions_open_at <= now())
+    AND (cg.submissions_close_at IS NULL OR cg.submissions_close_at > now())
+"#;
+
 impl CandidateGuide {
+    /// Whether candidates can submit or edit answers at `now`
+    pub fn is_accepting_submissions(&self, now: DateTime) -> bool {
+        self.submissions_open_at
+            .is_none_or(|open_at| open_at <= now)
+            && self
+                .submissions_close_at
+                .is_none_or(|close_at| close_at > now)
+    }
+
+    /// Whether a guide asking `question_id` is accepting submissions
+    pub async fn is_question_open(
+        db_pool: &PgPool,
+        question_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM candidate_guide cg
+                JOIN candidate_guide_questions cgq ON cgq.candidate_guide_id = cg.id
+                WHERE cgq.question_id = $1 AND {}
+            )
+            "#,
+            GUIDE_IS_OPEN
+        );
+        sqlx::query_scalar::<_, bool>(&query)
+            .bind(question_id)
+            .fetch_one(db_pool)
+            .await
+    }
+
+    /// Whether the candidate can still edit their intake profile: either they aren't in any
+    /// guide or one of their guides is accepting submissions
+    pub async fn is_candidate_open(
+        db_pool: &PgPool,
+        politician_id: Uuid,
+    ) -> Result<bool, sqlx::Error> {
+        let query = format!(
+            r#"
+            WITH guides AS (
+                SELECT cg.* FROM candidate_guide cg
+                JOIN candidate_guide_races cgr ON cgr.candidate_guide_id = cg.id
+                JOIN race_candidates rc ON rc.race_id = cgr.race_id
+                WHERE rc.candidate_id = $1 AND cg.deleted_at IS NULL
+            )
+            SELECT NOT EXISTS (SELECT 1 FROM guides)
+                OR EXISTS (SELECT 1 FROM guides cg WHERE {})
+            "#,
+            GUIDE_IS_OPEN
+        );
+        sqlx::query_scalar::<_, bool>(&query)
+            .bind(politician_id)
+            .fetch_one(db_pool)
+            .await
+    }
+
     pub async fn upsert(
         db_pool: &PgPool,
         input: &UpsertCandidateGuideInput,
@@ -94,15 +156,17 @@ impl CandidateGuide {
         Ok(true)
     }
 
+    /// Soft deletes the guide along with its embeds. They share a `deleted_at`, so
+    /// restoring the guide brings back the embeds deleted with it.
     pub async fn delete(db_pool: &PgPool, id: Uuid) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"
                 WITH deleted_guide AS (
-                    DELETE FROM candidate_guide
-                    WHERE id = $1
+                    UPDATE candidate_guide SET deleted_at = now()
+                    WHERE id = $1 AND deleted_at IS NULL
                 )
-                DELETE FROM embed
-                WHERE attributes->>'candidate_guide_id' = $1::text
+                UPDATE embed SET deleted_at = now()
+                WHERE attributes->>'candidateGuideId' = $1::text AND deleted_at IS NULL
             "#,
             id,
         )
@@ -126,7 +190,7 @@ impl CandidateGuide {
                     updated_at,
                     organization_id
                 FROM candidate_guide
-                WHERE id = $1
+                WHERE id = $1 AND deleted_at IS NULL
             "#,
             id,
         )
@@ -153,7 +217,7 @@ impl CandidateGuide {
                     updated_at,
                     organization_id
                 FROM candidate_guide
-                WHERE organization_id = $1
+                WHERE organization_id = $1 AND deleted_at IS NULL
             "#,
             organization_id,
         )
@@ -0,0 +1,470 @@
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{DateTime, Error};
+
+pub const SEND_OUTREACH_REMINDERS_JOB: &str = "send_outreach_reminders";
+
+/// Reminders sent this close to the deadline wouldn't give candidates time to respond
+pub const DEFAULT_REMINDER_DAYS: [i32; 2] = [7, 2];
+
+/// Emailing a candidate guide's candidates their intake form links
+#[derive(FromRow, Debug, Clone)]
+pub struct CandidateGuideOutreach {
+    pub id: Uuid,
+    pub candidate_guide_id: Uuid,
+    pub deadline: DateTime,
+    pub reminder_days: Vec<i32>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// A candidate to email, with what their invite and reminders need
+#[derive(FromRow, Debug, Clone)]
+pub struct OutreachRecipient {
+    pub id: Uuid,
+    pub outreach_id: Uuid,
+    pub candidate_guide_id: Uuid,
+    pub race_id: Uuid,
+    pub race_title: String,
+    pub politician_id: Uuid,
+    pub candidate_name: String,
+    pub email: String,
+    pub tracking_token: String,
+    pub intake_token: Option<String>,
+    pub organization_name: String,
+    pub guide_name: Option<String>,
+    pub deadline: DateTime,
+    pub reminder_days: Vec<i32>,
+    pub reminders_sent: i32,
+    pub last_reminded_at: Option<DateTime>,
+    pub delivered_at: Option<DateTime>,
+    pub opened_at: Option<DateTime>,
+    pub clicked_at: Option<DateTime>,
+    pub submitted_at: Option<DateTime>,
+}
+
+/// How a race's candidates have responded to outreach
+#[derive(FromRow, Debug, Clone)]
+pub struct OutreachRaceStats {
+    pub race_id: Uuid,
+    pub race_title: String,
+    pub recipients: i64,
+    pub delivered: i64,
+    pub opened: i64,
+    pub clicked: i64,
+    pub submitted: i64,
+}
+
+impl OutreachRaceStats {
+    /// Share of emailed candidates who submitted answers
+    pub fn response_rate(&self) -> f64 {
+        if self.recipients == 0 {
+            0.0
+        } else {
+            self.submitted as f64 / self.recipients as f64
+        }
+    }
+}
+
+/// Joins everything `OutreachRecipient` needs onto `candidate_guide_outreach_recipient r`.
+/// Submissions only count when they answer one of the guide's questions.
+const RECIPIENT_QUERY: &str = r#"
+    SELECT
+        r.id,
+        r.outreach_id,
+        o.candidate_guide_id,
+        r.race_id,
+        race.title AS race_title,
+        r.politician_id,
+        concat_ws(' ', COALESCE(p.preferred_name, p.first_name), p.last_name, p.suffix)
+            AS candidate_name,
+        r.email,
+        r.tracking_token,
+        p.intake_token,
+        org.name AS organization_name,
+        cg.name AS guide_name,
+        o.deadline,
+        o.reminder_days,
+        r.reminders_sent,
+        r.last_reminded_at,
+        (SELECT sent_at FROM email_outbox e WHERE e.id = r.invite_email_id) AS delivered_at,
+        r.opened_at,
+        r.clicked_at,
+        (
+            SELECT MIN(qs.created_at)
+            FROM question_submission qs
+            JOIN candidate_guide_questions cgq ON cgq.question_id = qs.question_id
+            WHERE cgq.candidate_guide_id = o.candidate_guide_id
+                AND qs.candidate_id = r.politician_id
+                AND qs.response IS NOT NULL
+                AND qs.response != ''
+                AND qs.status != 'draft'
+        ) AS submitted_at
+    FROM candidate_guide_outreach_recipient r
+    JOIN candidate_guide_outreach o ON o.id = r.outreach_id
+    JOIN candidate_guide cg ON cg.id = o.candidate_guide_id
+    JOIN organization org ON org.id = cg.organization_id
+    JOIN race ON race.id = r.race_id
+    JOIN politician p ON p.id = r.politician_id
+"#;
+
+/// Number of reminders that should have gone out by `now`, one for each of `reminder_days`
+/// that has passed since the candidate was invited. Nothing is due once the deadline itself
+/// has passed.
+pub fn reminders_due(
+    deadline: DateTime,
+    reminder_days: &[i32],
+    invited_at: DateTime,
+    now: DateTime,
+) -> i32 {
+    if now >= deadline {
+        return 0;
+    }
+    reminder_days
+        .iter()
+        .map(|days| deadline - chrono::Duration::days(*days as i64))
+        .filter(|remind_at| *remind_at > invited_at && *remind_at <= now)
+        .count() as i32
+}
+
+impl CandidateGuideOutreach {
+    /// Starts outreach for a guide, or updates the deadline and reminder schedule of its
+    /// existing outreach
+    pub async fn upsert(
+        db_pool: &PgPool,
+        candidate_guide_id: Uuid,
+        deadline: DateTime,
+        reminder_days: &[i32],
+        created_by: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let outreach = sqlx::query_as!(
+            CandidateGuideOutreach,
+            r#"
+            INSERT INTO candidate_guide_outreach
+                (candidate_guide_id, deadline, reminder_days, created_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (candidate_guide_id) DO UPDATE SET
+                deadline = EXCLUDED.deadline,
+                reminder_days = EXCLUDED.reminder_days
+            RETURNING id, candidate_guide_id, deadline, reminder_days, created_by, created_at,
+                updated_at
+            "#,
+            candidate_guide_id,
+            deadline,
+            reminder_days,
+            created_by,
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(outreach)
+    }
+
+    pub async fn find_by_candidate_guide_id(
+        db_pool: &PgPool,
+        candidate_guide_id: Uuid,
+    ) -> Result<Option<Self>, Error> {
+        let outreach = sqlx::query_as!(
+            CandidateGuideOutreach,
+            r#"
+            SELECT id, candidate_guide_id, deadline, reminder_days, created_by, created_at,
+                updated_at
+            FROM candidate_guide_outreach
+            WHERE candidate_guide_id = $1
+            "#,
+            candidate_guide_id,
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(outreach)
+    }
+
+    /// Adds the candidates in the guide's races (or just `race_ids`) who have an email
+    /// address, giving each an intake token if they don't have one yet. Candidates who were
+    /// already added are left alone. Returns the recipients who haven't been invited yet.
+    pub async fn add_recipients(
+        &self,
+        db_pool: &PgPool,
+        race_ids: Option<&[Uuid]>,
+    ) -> Result<Vec<OutreachRecipient>, Error> {
+        let mut tx = db_pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE politician p
+            SET intake_token = encode(gen_random_bytes(32), 'hex')
+            FROM race_candidates rc
+            JOIN candidate_guide_races cgr ON cgr.race_id = rc.race_id
+            WHERE rc.candidate_id = p.id
+                AND cgr.candidate_guide_id = $1
+                AND ($2::uuid[] IS NULL OR rc.race_id = ANY($2))
+                AND p.intake_token IS NULL
+                AND p.deleted_at IS NULL
+            "#,
+            self.candidate_guide_id,
+            race_ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO candidate_guide_outreach_recipient (outreach_id, race_id, politician_id, email)
+            SELECT $1, rc.race_id, p.id, trim(p.email)
+            FROM race_candidates rc
+            JOIN candidate_guide_races cgr ON cgr.race_id = rc.race_id
+            JOIN race ON race.id = rc.race_id
+            JOIN politician p ON p.id = rc.candidate_id
+            WHERE cgr.candidate_guide_id = $2
+                AND ($3::uuid[] IS NULL OR rc.race_id = ANY($3))
+                AND rc.is_running
+                AND race.deleted_at IS NULL
+                AND p.deleted_at IS NULL
+                AND NULLIF(trim(p.email), '') IS NOT NULL
+            ON CONFLICT (outreach_id, race_id, politician_id) DO NOTHING
+            "#,
+            self.id,
+            self.candidate_guide_id,
+            race_ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE candidate_guide_races SET were_candidates_emailed = true
+            WHERE candidate_guide_id = $1 AND ($2::uuid[] IS NULL OR race_id = ANY($2))
+            "#,
+            self.candidate_guide_id,
+            race_ids,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let query = format!(
+            "{} WHERE r.outreach_id = $1 AND r.invite_email_id IS NULL",
+            RECIPIENT_QUERY
+        );
+        let recipients = sqlx::query_as::<_, OutreachRecipient>(&query)
+            .bind(self.id)
+            .fetch_all(db_pool)
+            .await?;
+
+        Ok(recipients)
+    }
+
+    /// Everyone emailed for this outreach, by race and then name
+    pub async fn recipients(&self, db_pool: &PgPool) -> Result<Vec<OutreachRecipient>, Error> {
+        let query = format!(
+            "{} WHERE r.outreach_id = $1 ORDER BY race.title, candidate_name",
+            RECIPIENT_QUERY
+        );
+        let recipients = sqlx::query_as::<_, OutreachRecipient>(&query)
+            .bind(self.id)
+            .fetch_all(db_pool)
+            .await?;
+
+        Ok(recipients)
+    }
+
+    pub async fn stats_by_race(&self, db_pool: &PgPool) -> Result<Vec<OutreachRaceStats>, Error> {
+        let query = format!(
+            r#"
+            WITH recipients AS ({} WHERE r.outreach_id = $1)
+            SELECT
+                race_id,
+                race_title,
+                COUNT(*) AS recipients,
+                COUNT(delivered_at) AS delivered,
+                COUNT(opened_at) AS opened,
+                COUNT(clicked_at) AS clicked,
+                COUNT(submitted_at) AS submitted
+            FROM recipients
+            GROUP BY race_id, race_title
+            ORDER BY race_title
+            "#,
+            RECIPIENT_QUERY
+        );
+        let stats = sqlx::query_as::<_, OutreachRaceStats>(&query)
+            .bind(self.id)
+            .fetch_all(db_pool)
+            .await?;
+
+        Ok(stats)
+    }
+}
+
+impl OutreachRecipient {
+    pub async fn set_invite_email(db_pool: &PgPool, id: Uuid, email_id: Uuid) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE candidate_guide_outreach_recipient SET invite_email_id = $2 WHERE id = $1",
+            id,
+            email_id,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Invited candidates of guides, races and politicians that haven't been deleted who
+    /// haven't submitted answers and have a reminder due, along with how many reminders
+    /// should have gone out to them
+    pub async fn due_for_reminder(db_pool: &PgPool) -> Result<Vec<(Self, i32)>, Error> {
+        let query = format!(
+            r#"
+            {} WHERE r.invite_email_id IS NOT NULL
+            AND o.deadline > now()
+            AND r.reminders_sent < cardinality(o.reminder_days)
+            AND cg.deleted_at IS NULL
+            AND race.deleted_at IS NULL
+            AND p.deleted_at IS NULL
+            "#,
+            RECIPIENT_QUERY
+        );
+        let recipients = sqlx::query_as::<_, OutreachRecipient>(&query)
+            .fetch_all(db_pool)
+            .await?;
+
+        let now = chrono::Utc::now();
+        let due = recipients
+            .into_iter()
+            .filter(|recipient| recipient.submitted_at.is_none())
+            .filter_map(|recipient| {
+                let invited_at = recipient.delivered_at?;
+                let due = reminders_due(
+                    recipient.deadline,
+                    &recipient.reminder_days,
+                    invited_at,
+                    now,
+                );
+                (due > recipient.reminders_sent).then_some((recipient, due))
+            })
+            .collect();
+
+        Ok(due)
+    }
+
+    /// Records that `reminders_sent` reminders have gone out. Reminders that came due
+    /// together are only sent once.
+    pub async fn set_reminders_sent(
+        db_pool: &PgPool,
+        id: Uuid,
+        reminders_sent: i32,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE candidate_guide_outreach_recipient
+            SET reminders_sent = $2, last_reminded_at = now()
+            WHERE id = $1
+            "#,
+            id,
+            reminders_sent,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the first time the tracking pixel in an email loaded
+    pub async fn record_open(db_pool: &PgPool, tracking_token: &str) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE candidate_guide_outreach_recipient SET opened_at = now()
+            WHERE tracking_token = $1 AND opened_at IS NULL
+            "#,
+            tracking_token,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the first click on an email's form link, which also means it was opened.
+    /// Returns the recipient so they can be sent on to their intake form.
+    pub async fn record_click(
+        db_pool: &PgPool,
+        tracking_token: &str,
+    ) -> Result<Option<Self>, Error> {
+        sqlx::query!(
+            r#"
+            UPDATE candidate_guide_outreach_recipient SET
+                clicked_at = COALESCE(clicked_at, now()),
+                opened_at = COALESCE(opened_at, now())
+            WHERE tracking_token = $1
+            "#,
+            tracking_token,
+        )
+        .execute(db_pool)
+        .await?;
+
+        let query = format!("{} WHERE r.tracking_token = $1", RECIPIENT_QUERY);
+        let recipient = sqlx::query_as::<_, OutreachRecipient>(&query)
+            .bind(tracking_token)
+            .fetch_optional(db_pool)
+            .await?;
+
+        Ok(recipient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    #[test]
+    fn test_reminders_due() {
+        let deadline = Utc.with_ymd_and_hms(2026, 10, 31, 0, 0, 0).unwrap();
+        let invited_at = deadline - Duration::days(14);
+        let days = DEFAULT_REMINDER_DAYS;
+
+        assert_eq!(
+            reminders_due(deadline, &days, invited_at, deadline - Duration::days(10)),
+            0
+        );
+        assert_eq!(
+            reminders_due(deadline, &days, invited_at, deadline - Duration::days(7)),
+            1
+        );
+        assert_eq!(
+            reminders_due(deadline, &days, invited_at, deadline - Duration::days(1)),
+            2
+        );
+        // Too late to remind anyone
+        assert_eq!(reminders_due(deadline, &days, invited_at, deadline), 0);
+        assert_eq!(
+            reminders_due(deadline, &[], invited_at, deadline - Duration::days(1)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_reminders_before_invite_are_skipped() {
+        let deadline = Utc.with_ymd_and_hms(2026, 10, 31, 0, 0, 0).unwrap();
+        let invited_at = deadline - Duration::days(2);
+        let days = [7, 3, 1];
+
+        assert_eq!(
+            reminders_due(
+                deadline,
+                &days,
+                invited_at,
+                invited_at + Duration::minutes(5)
+            ),
+            0
+        );
+        assert_eq!(
+            reminders_due(deadline, &days, invited_at, deadline - Duration::hours(12)),
+            1
+        );
+    }
+}
@@ -1,8 +1,8 @@
 use async_graphql::SimpleObject;
 use chrono::{DateTime, Utc};
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, PgPool};
 
-use crate::{ArgumentPosition, StatementModerationStatus};
+use crate::{ArgumentPosition, ConversationModerationMode, Error, StatementModerationStatus};
 
 #[derive(FromRow, Clone)]
 pub struct Conversation {
@@ -10,10 +10,75 @@ pub struct Conversation {
     pub topic: String,
     pub description: Option<String>,
     pub organization_id: uuid::Uuid,
+    pub moderation_mode: ConversationModerationMode,
+    /// Words that hold a statement for review, on top of the built in list
+    pub blocked_words: Vec<String>,
+    /// Whether new statements are also screened by an LLM
+    pub llm_screening: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Conversation {
+    pub async fn find_by_id(db_pool: &PgPool, id: uuid::Uuid) -> Result<Option<Self>, Error> {
+        let conversation = sqlx::query_as!(
+            Conversation,
+            r#"
+            SELECT
+                id,
+                topic,
+                description,
+                organization_id,
+                moderation_mode AS "moderation_mode: ConversationModerationMode",
+                blocked_words,
+                llm_screening,
+                created_at,
+                updated_at
+            FROM conversation
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(conversation)
+    }
+
+    /// The organization's conversations, newest first
+    pub async fn list_by_organization(
+        db_pool: &PgPool,
+        organization_id: uuid::Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let conversations = sqlx::query_as!(
+            Conversation,
+            r#"
+            SELECT
+                id,
+                topic,
+                description,
+                organization_id,
+                moderation_mode AS "moderation_mode: ConversationModerationMode",
+                blocked_words,
+                llm_screening,
+                created_at,
+                updated_at
+            FROM conversation
+            WHERE organization_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            organization_id,
+            limit
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(conversations)
+    }
+}
+
 #[derive(FromRow, SimpleObject, Clone)]
 pub struct Statement {
     pub id: uuid::Uuid,
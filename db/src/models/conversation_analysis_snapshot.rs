@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JSON;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{DateTime, Error};
+
+pub const REFRESH_CONVERSATION_ANALYSIS_JOB: &str = "refresh_conversation_analysis";
+
+/// Snapshots kept per conversation, older ones are dropped as new ones are stored
+const SNAPSHOTS_KEPT: i64 = 10;
+
+/// A conversation's opinion analysis, groups and map as of `created_at`, computed by the
+/// job worker so resolvers don't have to
+#[derive(FromRow, Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationAnalysisSnapshot {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub vote_count: i32,
+    pub participant_count: i32,
+    pub opinion_analysis: JSON,
+    pub opinion_groups: JSON,
+    pub opinion_map: JSON,
+    /// Each participant's group label, keyed by participant
+    pub group_labels: JSON,
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct InsertConversationAnalysisSnapshotInput {
+    pub conversation_id: Uuid,
+    pub vote_count: i32,
+    pub participant_count: i32,
+    pub opinion_analysis: JSON,
+    pub opinion_groups: JSON,
+    pub opinion_map: JSON,
+    pub group_labels: JSON,
+    /// When the votes were read
+    pub created_at: DateTime,
+}
+
+impl ConversationAnalysisSnapshot {
+    pub async fn latest(db_pool: &PgPool, conversation_id: Uuid) -> Result<Option<Self>, Error> {
+        let snapshot = sqlx::query_as!(
+            ConversationAnalysisSnapshot,
+            r#"
+            SELECT id, conversation_id, vote_count, participant_count, opinion_analysis,
+                opinion_groups, opinion_map, group_labels, created_at
+            FROM conversation_analysis_snapshot
+            WHERE conversation_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            conversation_id
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(snapshot)
+    }
+
+    /// Stores a new snapshot and drops all but the most recent few for the conversation
+    pub async fn insert(
+        db_pool: &PgPool,
+        input: &InsertConversationAnalysisSnapshotInput,
+    ) -> Result<Self, Error> {
+        let mut tx = db_pool.begin().await?;
+        let snapshot = sqlx::query_as!(
+            ConversationAnalysisSnapshot,
+            r#"
+            INSERT INTO conversation_analysis_snapshot (conversation_id, vote_count,
+                participant_count, opinion_analysis, opinion_groups, opinion_map, group_labels,
+                created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, conversation_id, vote_count, participant_count, opinion_analysis,
+                opinion_groups, opinion_map, group_labels, created_at
+            "#,
+            input.conversation_id,
+            input.vote_count,
+            input.participant_count,
+            input.opinion_analysis,
+            input.opinion_groups,
+            input.opinion_map,
+            input.group_labels,
+            input.created_at,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM conversation_analysis_snapshot
+            WHERE conversation_id = $1
+            AND id NOT IN (
+                SELECT id FROM conversation_analysis_snapshot
+                WHERE conversation_id = $1
+                ORDER BY created_at DESC
+                LIMIT $2
+            )
+            "#,
+            input.conversation_id,
+            SNAPSHOTS_KEPT,
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(snapshot)
+    }
+
+    /// Conversations with at least `min_new_votes` votes cast or changed since their latest
+    /// snapshot, and conversations with votes that have never been analyzed
+    pub async fn stale_conversations(
+        db_pool: &PgPool,
+        min_new_votes: i64,
+    ) -> Result<Vec<Uuid>, Error> {
+        let conversation_ids = sqlx::query_scalar!(
+            r#"
+            SELECT s.conversation_id AS "conversation_id!"
+            FROM statement_vote v
+            JOIN statement s ON s.id = v.statement_id
+            LEFT JOIN LATERAL (
+                SELECT created_at FROM conversation_analysis_snapshot cas
+                WHERE cas.conversation_id = s.conversation_id
+                ORDER BY created_at DESC
+                LIMIT 1
+            ) latest ON true
+            WHERE latest.created_at IS NULL OR v.updated_at > latest.created_at
+            GROUP BY s.conversation_id, latest.created_at
+            HAVING latest.created_at IS NULL OR COUNT(*) >= $1
+            ORDER BY s.conversation_id
+            "#,
+            min_new_votes
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(conversation_ids)
+    }
+}
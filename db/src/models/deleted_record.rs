@@ -0,0 +1,204 @@
+use async_graphql::{Enum, InputObject};
+use sqlx::PgPool;
+use strum_macros::Display;
+
+use crate::{DateTime, Error};
+
+pub const PURGE_DELETED_RECORDS_JOB: &str = "purge_deleted_records";
+
+/// Days a deleted record can be restored before it's purged, unless overridden with
+/// `DELETED_RECORD_RETENTION_DAYS`
+const DEFAULT_RETENTION_DAYS: i32 = 30;
+
+/// Records that are soft deleted with a `deleted_at` tombstone instead of removed
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq)]
+#[strum(serialize_all = "snake_case")]
+pub enum DeletableEntityType {
+    Politician,
+    Race,
+    Embed,
+    CandidateGuide,
+}
+
+impl DeletableEntityType {
+    /// Children first, so purging a guide's embeds doesn't wait on a cascade
+    pub const ALL: [DeletableEntityType; 4] = [
+        DeletableEntityType::CandidateGuide,
+        DeletableEntityType::Embed,
+        DeletableEntityType::Race,
+        DeletableEntityType::Politician,
+    ];
+
+    fn table(&self) -> &'static str {
+        match self {
+            DeletableEntityType::Politician => "politician",
+            DeletableEntityType::Race => "race",
+            DeletableEntityType::Embed => "embed",
+            DeletableEntityType::CandidateGuide => "candidate_guide",
+        }
+    }
+
+    /// Column shown when listing deleted records
+    fn name_column(&self) -> &'static str {
+        match self {
+            DeletableEntityType::Politician => "full_name",
+            DeletableEntityType::Race => "title",
+            DeletableEntityType::Embed | DeletableEntityType::CandidateGuide => "name",
+        }
+    }
+
+    fn organization_column(&self) -> &'static str {
+        match self {
+            DeletableEntityType::Politician | DeletableEntityType::Race => "NULL::uuid",
+            DeletableEntityType::Embed | DeletableEntityType::CandidateGuide => "organization_id",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeletedRecord {
+    pub entity_type: DeletableEntityType,
+    pub id: uuid::Uuid,
+    pub name: Option<String>,
+    pub organization_id: Option<uuid::Uuid>,
+    pub deleted_at: DateTime,
+    /// When the purge job removes the record for good
+    pub purge_at: DateTime,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct DeletedRecordFilter {
+    pub entity_type: Option<DeletableEntityType>,
+    pub organization_id: Option<uuid::Uuid>,
+}
+
+type DeletedRow = (uuid::Uuid, Option<String>, Option<uuid::Uuid>, DateTime);
+
+impl DeletedRecord {
+    pub fn retention_days() -> i32 {
+        std::env::var("DELETED_RECORD_RETENTION_DAYS")
+            .ok()
+            .and_then(|days| days.parse().ok())
+            .filter(|days| *days > 0)
+            .unwrap_or(DEFAULT_RETENTION_DAYS)
+    }
+
+    fn from_row(entity_type: DeletableEntityType, row: DeletedRow) -> Self {
+        let (id, name, organization_id, deleted_at) = row;
+        DeletedRecord {
+            entity_type,
+            id,
+            name,
+            organization_id,
+            deleted_at,
+            purge_at: deleted_at + chrono::Duration::days(Self::retention_days() as i64),
+        }
+    }
+
+    /// Deleted records that can still be restored, most recently deleted first
+    pub async fn filter(
+        db_pool: &PgPool,
+        filter: &DeletedRecordFilter,
+    ) -> Result<Vec<Self>, Error> {
+        let mut records = vec![];
+        for entity_type in DeletableEntityType::ALL {
+            if filter.entity_type.is_some_and(|t| t != entity_type) {
+                continue;
+            }
+            // Table and column names come from the enum, never from input
+            let query = format!(
+                r#"
+                SELECT id, {name}::text, {organization} AS organization_id, deleted_at
+                FROM {table}
+                WHERE deleted_at > now() - make_interval(days => $1)
+                AND ($2::uuid IS NULL OR {organization} = $2)
+                "#,
+                name = entity_type.name_column(),
+                organization = entity_type.organization_column(),
+                table = entity_type.table(),
+            );
+            let rows = sqlx::query_as::<_, DeletedRow>(&query)
+                .bind(Self::retention_days())
+                .bind(filter.organization_id)
+                .fetch_all(db_pool)
+                .await?;
+            records.extend(rows.into_iter().map(|row| Self::from_row(entity_type, row)));
+        }
+        records.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+
+        Ok(records)
+    }
+
+    /// Clears the tombstone of a record deleted within the retention window. Restoring a
+    /// candidate guide also restores the embeds that were deleted along with it.
+    pub async fn restore(
+        db_pool: &PgPool,
+        entity_type: DeletableEntityType,
+        id: uuid::Uuid,
+    ) -> Result<Self, Error> {
+        let mut tx = db_pool.begin().await?;
+        let query = format!(
+            r#"
+            UPDATE {table} t SET deleted_at = NULL
+            FROM (
+                SELECT id, deleted_at FROM {table}
+                WHERE id = $1 AND deleted_at > now() - make_interval(days => $2)
+            ) deleted
+            WHERE t.id = deleted.id
+            RETURNING t.id, {name}::text, {organization} AS organization_id, deleted.deleted_at
+            "#,
+            table = entity_type.table(),
+            name = entity_type.name_column(),
+            organization = entity_type.organization_column(),
+        );
+        let row = sqlx::query_as::<_, DeletedRow>(&query)
+            .bind(id)
+            .bind(Self::retention_days())
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| {
+                Error::Custom(format!(
+                    "No {} with id {} was deleted in the last {} days",
+                    entity_type,
+                    id,
+                    Self::retention_days()
+                ))
+            })?;
+        let record = Self::from_row(entity_type, row);
+
+        if entity_type == DeletableEntityType::CandidateGuide {
+            sqlx::query!(
+                r#"
+                UPDATE embed SET deleted_at = NULL
+                WHERE attributes->>'candidateGuideId' = $1::text AND deleted_at = $2
+                "#,
+                id,
+                record.deleted_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(record)
+    }
+
+    /// Permanently removes records deleted before the retention window, returning how
+    /// many of each type were removed
+    pub async fn purge(db_pool: &PgPool) -> Result<Vec<(DeletableEntityType, u64)>, Error> {
+        let mut purged = vec![];
+        for entity_type in DeletableEntityType::ALL {
+            let query = format!(
+                "DELETE FROM {} WHERE deleted_at <= now() - make_interval(days => $1)",
+                entity_type.table()
+            );
+            let result = sqlx::query(&query)
+                .bind(Self::retention_days())
+                .execute(db_pool)
+                .await?;
+            purged.push((entity_type, result.rows_affected()));
+        }
+
+        Ok(purged)
+    }
+}
@@ -0,0 +1,214 @@
+use async_graphql::InputObject;
+use chrono::Utc;
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::{enums::EmailStatus, job::Job},
+    DateTime, Error,
+};
+
+/// Emails being sent for longer than this are assumed to belong to a worker that died
+const STALE_LOCK_MINUTES: i32 = 10;
+
+#[derive(FromRow, Debug, Clone)]
+pub struct EmailOutbox {
+    pub id: uuid::Uuid,
+    pub to_address: String,
+    pub template: String,
+    pub subject: String,
+    pub html_body: String,
+    pub text_body: String,
+    pub dedup_key: Option<String>,
+    pub status: EmailStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub send_at: DateTime,
+    pub locked_at: Option<DateTime>,
+    pub last_error: Option<String>,
+    pub sent_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// A rendered email to queue for delivery
+#[derive(Debug, Clone)]
+pub struct NewEmail<'a> {
+    pub to_address: &'a str,
+    pub template: &'a str,
+    pub subject: &'a str,
+    pub html_body: &'a str,
+    pub text_body: &'a str,
+    /// Emails sharing a key are only ever sent once, e.g. `invite:<token>`
+    pub dedup_key: Option<&'a str>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct EmailOutboxFilter {
+    pub status: Option<EmailStatus>,
+    pub template: Option<String>,
+    pub to_address: Option<String>,
+}
+
+impl EmailOutbox {
+    /// Queues an email to be sent right away. Returns `None` when an email with the same
+    /// dedup key is already in the outbox.
+    pub async fn enqueue(db_pool: &PgPool, email: NewEmail<'_>) -> Result<Option<Self>, Error> {
+        let record = sqlx::query_as!(
+            EmailOutbox,
+            r#"
+            INSERT INTO email_outbox
+                (to_address, template, subject, html_body, text_body, dedup_key)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (dedup_key) DO NOTHING
+            RETURNING id, to_address, template, subject, html_body, text_body, dedup_key,
+                status AS "status:EmailStatus", attempts, max_attempts, send_at, locked_at,
+                last_error, sent_at, created_at, updated_at
+            "#,
+            email.to_address,
+            email.template,
+            email.subject,
+            email.html_body,
+            email.text_body,
+            email.dedup_key,
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Locks up to `limit` due emails for sending. Uses `SKIP LOCKED` so several server
+    /// instances can deliver from the same outbox.
+    pub async fn claim_due(db_pool: &PgPool, limit: i64) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            EmailOutbox,
+            r#"
+            UPDATE email_outbox SET
+                status = 'sending',
+                attempts = attempts + 1,
+                locked_at = now()
+            WHERE id IN (
+                SELECT id FROM email_outbox
+                WHERE send_at <= now()
+                AND (
+                    status = 'pending'
+                    OR (status = 'sending' AND locked_at < now() - make_interval(mins => $1))
+                )
+                ORDER BY send_at
+                LIMIT $2
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, to_address, template, subject, html_body, text_body, dedup_key,
+                status AS "status:EmailStatus", attempts, max_attempts, send_at, locked_at,
+                last_error, sent_at, created_at, updated_at
+            "#,
+            STALE_LOCK_MINUTES,
+            limit
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    pub async fn mark_sent(&self, db_pool: &PgPool) -> Result<(), Error> {
+        sqlx::query!(
+            r#"
+            UPDATE email_outbox SET
+                status = 'sent',
+                sent_at = now(),
+                locked_at = NULL,
+                last_error = NULL
+            WHERE id = $1
+            "#,
+            self.id
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records a failed send and schedules a retry with the same backoff as jobs. Emails
+    /// that used all of their attempts are left `dead` until a staff member retries them.
+    /// Returns the status the email ended with.
+    pub async fn mark_failed(&self, db_pool: &PgPool, error: &str) -> Result<EmailStatus, Error> {
+        let status = if self.attempts >= self.max_attempts {
+            EmailStatus::Dead
+        } else {
+            EmailStatus::Pending
+        };
+        sqlx::query!(
+            r#"
+            UPDATE email_outbox SET
+                status = $2,
+                send_at = $3,
+                locked_at = NULL,
+                last_error = $4
+            WHERE id = $1
+            "#,
+            self.id,
+            status as EmailStatus,
+            Utc::now() + Job::retry_delay(self.attempts),
+            error
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(status)
+    }
+
+    /// Queues an email to be sent again right away with a fresh set of attempts
+    pub async fn retry(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let record = sqlx::query_as!(
+            EmailOutbox,
+            r#"
+            UPDATE email_outbox SET
+                status = 'pending',
+                attempts = 0,
+                send_at = now(),
+                locked_at = NULL
+            WHERE id = $1 AND status != 'sending'
+            RETURNING id, to_address, template, subject, html_body, text_body, dedup_key,
+                status AS "status:EmailStatus", attempts, max_attempts, send_at, locked_at,
+                last_error, sent_at, created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(db_pool)
+        .await?
+        .ok_or_else(|| Error::Custom(format!("No retryable email with id {}", id)))?;
+
+        Ok(record)
+    }
+
+    /// Matching emails, newest first
+    pub async fn filter(
+        db_pool: &PgPool,
+        filter: &EmailOutboxFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            EmailOutbox,
+            r#"
+            SELECT id, to_address, template, subject, html_body, text_body, dedup_key,
+                status AS "status:EmailStatus", attempts, max_attempts, send_at, locked_at,
+                last_error, sent_at, created_at, updated_at
+            FROM email_outbox
+            WHERE ($1::email_status IS NULL OR status = $1)
+                AND ($2::text IS NULL OR template = $2)
+                AND ($3::text IS NULL OR lower(to_address) = lower($3))
+            ORDER BY created_at DESC
+            LIMIT $4
+            "#,
+            filter.status as Option<EmailStatus>,
+            filter.template,
+            filter.to_address,
+            limit,
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
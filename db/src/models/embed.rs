@@ -111,8 +111,8 @@ impl Embed {
     pub async fn delete(pool: &sqlx::PgPool, id: uuid::Uuid) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"
-            DELETE FROM embed
-            WHERE id = $1
+            UPDATE embed SET deleted_at = now()
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -137,7 +137,7 @@ impl Embed {
                 created_at,
                 updated_at
             FROM embed
-            WHERE id = $1
+            WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -167,6 +167,7 @@ impl Embed {
                 updated_at
             FROM embed
             WHERE organization_id = $1
+            AND deleted_at IS NULL
             AND ($2::embed_type IS NULL OR embed_type = $2)
             ORDER BY updated_at DESC
             "#,
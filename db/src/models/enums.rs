@@ -366,11 +366,172 @@ pub enum BillType {
     None,
 }
 
-#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[sqlx(type_name = "statement_moderation_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
 pub enum StatementModerationStatus {
     Unmoderated,
     Accepted,
     Rejected,
     Seed,
 }
+
+/// Whether a conversation's new statements wait for a moderator before they're shown
+#[derive(sqlx::Type, Enum, Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[sqlx(type_name = "conversation_moderation_mode", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationModerationMode {
+    /// Statements are held as unmoderated until a moderator accepts them
+    Pre,
+    /// Statements are accepted right away unless the pre-screen flags them, and moderators
+    /// reject them afterwards
+    Post,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "job_status", rename_all = "lowercase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    /// A run that failed and will be retried
+    Failed,
+    /// A job or run that exhausted its retries
+    Dead,
+    Cancelled,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "results_source_format", rename_all = "snake_case")]
+pub enum ResultsSourceFormat {
+    /// Semicolon delimited results files from the Minnesota Secretary of State
+    MnSos,
+    /// Zipped CSV summary exports from a Texas county's Clarity results site
+    TxClarity,
+    /// Cumulative results PDFs from a Texas county's Hart InterCivic system
+    TxHart,
+    /// Texas SoS election night results site, scraped with a headless browser
+    TxCivix,
+}
+
+impl ResultsSourceFormat {
+    /// Staging table each format loads into, if fixed by its loader
+    pub fn staging_table(&self) -> Option<&'static str> {
+        match self {
+            ResultsSourceFormat::MnSos => None,
+            ResultsSourceFormat::TxClarity => Some("ingest_staging.stg_tx_results_clarity"),
+            ResultsSourceFormat::TxHart => Some("ingest_staging.stg_tx_results_hart"),
+            ResultsSourceFormat::TxCivix => Some("ingest_staging.stg_tx_results_sos_civix"),
+        }
+    }
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "race_status", rename_all = "lowercase")]
+pub enum RaceStatus {
+    Uncalled,
+    /// Winners computed from complete results, awaiting review by staff
+    Projected,
+    Called,
+    Certified,
+    Recount,
+}
+
+impl RaceStatus {
+    /// Whether staff may move a race from this status to `next`
+    pub fn can_transition_to(&self, next: RaceStatus) -> bool {
+        use RaceStatus::*;
+        matches!(
+            (self, next),
+            (Uncalled, Projected | Called)
+                | (Projected, Uncalled | Projected | Called)
+                | (Called, Uncalled | Called | Certified | Recount)
+                | (Certified, Recount)
+                | (Recount, Called | Certified)
+        )
+    }
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, EnumString, sqlx::Type)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+#[sqlx(type_name = "scrape_stage", rename_all = "lowercase")]
+pub enum ScrapeStage {
+    /// Fetch source data into raw or staging tables
+    Scrape,
+    /// Normalize raw data into ingest_staging tables
+    Process,
+    /// Merge staging tables into production
+    Merge,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "session_revocation_reason", rename_all = "snake_case")]
+pub enum SessionRevocationReason {
+    Logout,
+    /// Revoked by the user from their list of sessions
+    Revoked,
+    /// A rotated refresh token was presented again
+    TokenReuse,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "api_key_scope", rename_all = "lowercase")]
+pub enum ApiKeyScope {
+    /// Queries only, checked like a read only member
+    Read,
+    /// Queries, plus mutations on the key's embed types, checked like a member
+    Write,
+}
+
+/// Kinds of records changes are recorded for in `audit_event`
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[strum(serialize_all = "snake_case")]
+#[sqlx(type_name = "audit_entity_type", rename_all = "snake_case")]
+pub enum AuditEntityType {
+    Politician,
+    Race,
+    Office,
+    Bill,
+    BallotMeasure,
+    Election,
+    IssueTag,
+    Organization,
+    Embed,
+    CandidateGuide,
+    Question,
+    Poll,
+    Conversation,
+    Statement,
+    ResultsSource,
+    QuestionSubmission,
+    OrganizationApiKey,
+    UserSession,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "audit_operation", rename_all = "lowercase")]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "email_status", rename_all = "lowercase")]
+pub enum EmailStatus {
+    Pending,
+    /// Claimed by a worker that's handing it to the transport
+    Sending,
+    Sent,
+    /// An email that exhausted its retries
+    Dead,
+}
+
+/// Kinds of records kept in `revision`
+#[derive(Enum, Debug, Display, Copy, Clone, Eq, PartialEq, sqlx::Type)]
+#[strum(serialize_all = "snake_case")]
+#[sqlx(type_name = "revision_entity_type", rename_all = "snake_case")]
+pub enum RevisionEntityType {
+    QuestionSubmission,
+    Politician,
+}
@@ -193,7 +193,7 @@ impl IssueTag {
                         p.updated_at FROM politician p
                 JOIN politician_issue_tags
                 ON politician_issue_tags.politician_id = p.id
-                WHERE politician_issue_tags.issue_tag_id = $1
+                WHERE politician_issue_tags.issue_tag_id = $1 AND p.deleted_at IS NULL
             "#,
             issue_tag_id
         )
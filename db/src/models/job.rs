@@ -0,0 +1,547 @@
+use std::str::FromStr;
+
+use async_graphql::InputObject;
+use chrono::Utc;
+use serde_json::Value as JSON;
+use sqlx::{FromRow, PgPool};
+
+use crate::{models::enums::JobStatus, DateTime, Error};
+
+/// Delay before the first retry, doubled for each subsequent attempt
+const BASE_RETRY_DELAY_SECONDS: i64 = 30;
+/// Upper bound on the delay between retries
+const MAX_RETRY_DELAY_SECONDS: i64 = 60 * 60;
+/// Running jobs whose lock hasn't been refreshed by a `heartbeat` for this long are assumed
+/// to belong to a worker that died
+pub const STALE_LOCK_MINUTES: i32 = 5;
+/// Finished runs, and one-off jobs that succeeded or were cancelled, are deleted after this
+const JOB_RETENTION_DAYS: i32 = 14;
+
+pub const PRUNE_JOBS_JOB: &str = "prune_jobs";
+
+#[derive(FromRow, Debug, Clone)]
+pub struct Job {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub payload: JSON,
+    pub schedule: Option<String>,
+    pub status: JobStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime,
+    pub locked_at: Option<DateTime>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct JobRun {
+    pub id: uuid::Uuid,
+    pub job_id: uuid::Uuid,
+    pub attempt: i32,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub started_at: DateTime,
+    pub finished_at: Option<DateTime>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct EnqueueJobInput {
+    pub name: String,
+    pub payload: Option<JSON>,
+    /// Cron expression including seconds, e.g. `0 0 8 * * *`. Recurring jobs are unique by name,
+    /// so enqueuing one that already exists replaces its schedule and payload.
+    pub schedule: Option<String>,
+    /// Defaults to now for one-off jobs and the next scheduled time for recurring jobs
+    pub run_at: Option<DateTime>,
+    pub max_attempts: Option<i32>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct JobFilter {
+    pub name: Option<String>,
+    pub status: Option<JobStatus>,
+}
+
+impl Job {
+    /// Returns the next time a cron schedule fires after now
+    pub fn next_run_at(schedule: &str) -> Result<DateTime, Error> {
+        let schedule = cron::Schedule::from_str(schedule)
+            .map_err(|e| Error::Custom(format!("Invalid job schedule '{}': {}", schedule, e)))?;
+        schedule
+            .upcoming(Utc)
+            .next()
+            .ok_or_else(|| Error::Custom("Job schedule has no upcoming runs".to_string()))
+    }
+
+    /// Exponential backoff for the given (1-based) attempt number
+    pub fn retry_delay(attempt: i32) -> chrono::Duration {
+        let exponent = attempt.saturating_sub(1).clamp(0, 16) as u32;
+        let seconds = BASE_RETRY_DELAY_SECONDS
+            .saturating_mul(2_i64.pow(exponent))
+            .min(MAX_RETRY_DELAY_SECONDS);
+        chrono::Duration::seconds(seconds)
+    }
+
+    /// Queues a one-off job, or creates a recurring job or updates its schedule. Re-queueing
+    /// a recurring job doesn't interrupt a run in progress or undo a cancel.
+    pub async fn enqueue(db_pool: &PgPool, input: &EnqueueJobInput) -> Result<Self, Error> {
+        let payload = input
+            .payload
+            .clone()
+            .unwrap_or(JSON::Object(Default::default()));
+        let max_attempts = input.max_attempts.unwrap_or(5).max(1);
+
+        let job = match &input.schedule {
+            Some(schedule) => {
+                let run_at = match input.run_at {
+                    Some(run_at) => run_at,
+                    None => Self::next_run_at(schedule)?,
+                };
+                sqlx::query_as!(
+                    Job,
+                    r#"
+                    INSERT INTO job (name, payload, schedule, max_attempts, run_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (name) WHERE schedule IS NOT NULL DO UPDATE SET
+                        payload = EXCLUDED.payload,
+                        schedule = EXCLUDED.schedule,
+                        max_attempts = EXCLUDED.max_attempts,
+                        run_at = (CASE WHEN job.status = 'running' THEN job.run_at ELSE EXCLUDED.run_at END),
+                        status = (CASE WHEN job.status IN ('running', 'cancelled') THEN job.status ELSE 'pending' END),
+                        attempts = (CASE WHEN job.status IN ('running', 'cancelled') THEN job.attempts ELSE 0 END)
+                    RETURNING id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                        max_attempts, run_at, locked_at, last_error, created_at, updated_at
+                    "#,
+                    input.name,
+                    payload,
+                    schedule,
+                    max_attempts,
+                    run_at
+                )
+                .fetch_one(db_pool)
+                .await?
+            }
+            None => {
+                sqlx::query_as!(
+                    Job,
+                    r#"
+                    INSERT INTO job (name, payload, max_attempts, run_at)
+                    VALUES ($1, $2, $3, COALESCE($4, now()))
+                    RETURNING id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                        max_attempts, run_at, locked_at, last_error, created_at, updated_at
+                    "#,
+                    input.name,
+                    payload,
+                    max_attempts,
+                    input.run_at
+                )
+                .fetch_one(db_pool)
+                .await?
+            }
+        };
+
+        Ok(job)
+    }
+
+    /// Locks the next due job and records the start of a run. Only jobs named in
+    /// `names` are claimed when it's given, and jobs named in `excluded_names` are left
+    /// for another worker. Uses `SKIP LOCKED` so several workers can poll the same table.
+    pub async fn claim_next(
+        db_pool: &PgPool,
+        names: Option<&[String]>,
+        excluded_names: &[String],
+    ) -> Result<Option<(Self, JobRun)>, Error> {
+        let mut tx = db_pool.begin().await?;
+
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE job SET
+                status = 'running',
+                attempts = attempts + 1,
+                locked_at = now()
+            WHERE id = (
+                SELECT id FROM job
+                WHERE ($1::text[] IS NULL OR name = ANY($1))
+                AND NOT (name = ANY($2))
+                AND run_at <= now()
+                AND (
+                    status = 'pending'
+                    OR (status = 'running' AND locked_at < now() - make_interval(mins => $3))
+                )
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            "#,
+            names,
+            excluded_names,
+            STALE_LOCK_MINUTES
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(job) = job else {
+            tx.commit().await?;
+            return Ok(None);
+        };
+
+        // Close out runs abandoned by a worker that stopped mid-run
+        sqlx::query!(
+            r#"
+            UPDATE job_run SET
+                status = 'failed',
+                error = 'Worker stopped before the run finished',
+                finished_at = now()
+            WHERE job_id = $1 AND status = 'running'
+            "#,
+            job.id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let run = sqlx::query_as!(
+            JobRun,
+            r#"
+            INSERT INTO job_run (job_id, attempt)
+            VALUES ($1, $2)
+            RETURNING id, job_id, attempt, status AS "status:JobStatus", error, started_at,
+                finished_at
+            "#,
+            job.id,
+            job.attempts
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(Some((job, run)))
+    }
+
+    /// Refreshes the lock on a running job so it isn't taken for abandoned. Returns false
+    /// once the job has been claimed again by another worker or stopped running.
+    pub async fn heartbeat(&self, db_pool: &PgPool) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE job SET locked_at = now()
+            WHERE id = $1 AND status = 'running' AND attempts = $2
+            "#,
+            self.id,
+            self.attempts
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Marks a run as succeeded. Recurring jobs are rescheduled for their next tick.
+    pub async fn complete(&self, db_pool: &PgPool, run_id: uuid::Uuid) -> Result<(), Error> {
+        let next_run_at = self
+            .schedule
+            .as_deref()
+            .map(Self::next_run_at)
+            .transpose()?;
+        let mut tx = db_pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE job_run SET status = 'succeeded', finished_at = now()
+            WHERE id = $1
+            "#,
+            run_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE job SET
+                status = (CASE WHEN $2::timestamptz IS NULL THEN 'succeeded' ELSE 'pending' END)::job_status,
+                attempts = (CASE WHEN $2::timestamptz IS NULL THEN attempts ELSE 0 END),
+                run_at = COALESCE($2, run_at),
+                locked_at = NULL,
+                last_error = NULL
+            WHERE id = $1 AND status = 'running' AND attempts = $3
+            "#,
+            self.id,
+            next_run_at,
+            self.attempts
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Records a failed run and schedules a retry with exponential backoff. Once the job
+    /// has used all of its attempts the run is dead-lettered: one-off jobs stay `dead`
+    /// until a staff member retries them, recurring jobs wait for their next tick.
+    /// Returns the status the run ended with.
+    pub async fn fail(
+        &self,
+        db_pool: &PgPool,
+        run_id: uuid::Uuid,
+        error: &str,
+    ) -> Result<JobStatus, Error> {
+        let exhausted = self.attempts >= self.max_attempts;
+        let run_status = if exhausted {
+            JobStatus::Dead
+        } else {
+            JobStatus::Failed
+        };
+        let (job_status, attempts, run_at) = match (exhausted, self.schedule.as_deref()) {
+            (false, _) => (
+                JobStatus::Pending,
+                self.attempts,
+                Utc::now() + Self::retry_delay(self.attempts),
+            ),
+            (true, Some(schedule)) => (JobStatus::Pending, 0, Self::next_run_at(schedule)?),
+            (true, None) => (JobStatus::Dead, self.attempts, self.run_at),
+        };
+        let mut tx = db_pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE job_run SET status = $2, error = $3, finished_at = now()
+            WHERE id = $1
+            "#,
+            run_id,
+            run_status as JobStatus,
+            error
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE job SET
+                status = $2,
+                attempts = $3,
+                run_at = $4,
+                locked_at = NULL,
+                last_error = $5
+            WHERE id = $1 AND status = 'running' AND attempts = $6
+            "#,
+            self.id,
+            job_status as JobStatus,
+            attempts,
+            run_at,
+            error,
+            self.attempts
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(run_status)
+    }
+
+    /// Queues a job to run immediately with a fresh set of attempts
+    pub async fn retry(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE job SET
+                status = 'pending',
+                attempts = 0,
+                run_at = now(),
+                locked_at = NULL
+            WHERE id = $1 AND status != 'running'
+            RETURNING id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        job.ok_or_else(|| Error::Custom("Job not found or currently running".to_string()))
+    }
+
+    /// Stops a pending job, including future occurrences of a recurring job
+    pub async fn cancel(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            UPDATE job SET status = 'cancelled', locked_at = NULL
+            WHERE id = $1 AND status != 'running'
+            RETURNING id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        job.ok_or_else(|| Error::Custom("Job not found or currently running".to_string()))
+    }
+
+    /// Deletes runs that finished more than `JOB_RETENTION_DAYS` ago, and one-off jobs that
+    /// succeeded or were cancelled that long ago along with their runs. Dead jobs are kept
+    /// until staff retry or cancel them. Returns the number of jobs and runs deleted.
+    pub async fn prune(db_pool: &PgPool) -> Result<(u64, u64), Error> {
+        let mut tx = db_pool.begin().await?;
+
+        let jobs = sqlx::query!(
+            r#"
+            DELETE FROM job
+            WHERE schedule IS NULL
+            AND status IN ('succeeded', 'cancelled')
+            AND updated_at < now() - make_interval(days => $1)
+            "#,
+            JOB_RETENTION_DAYS
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let runs = sqlx::query!(
+            r#"
+            DELETE FROM job_run
+            WHERE finished_at < now() - make_interval(days => $1)
+            "#,
+            JOB_RETENTION_DAYS
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok((jobs.rows_affected(), runs.rows_affected()))
+    }
+
+    pub async fn find_by_id(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let job = sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            FROM job
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(job)
+    }
+
+    pub async fn filter(db_pool: &PgPool, filter: &JobFilter) -> Result<Vec<Self>, Error> {
+        let jobs = sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            FROM job
+            WHERE ($1::text IS NULL OR name = $1)
+            AND ($2::job_status IS NULL OR status = $2)
+            ORDER BY run_at DESC
+            LIMIT 200
+            "#,
+            filter.name,
+            filter.status as Option<JobStatus>
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(jobs)
+    }
+}
+
+impl JobRun {
+    pub async fn find_by_job_id(
+        db_pool: &PgPool,
+        job_id: uuid::Uuid,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let runs = sqlx::query_as!(
+            JobRun,
+            r#"
+            SELECT id, job_id, attempt, status AS "status:JobStatus", error, started_at,
+                finished_at
+            FROM job_run
+            WHERE job_id = $1
+            ORDER BY started_at DESC
+            LIMIT $2
+            "#,
+            job_id,
+            limit
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    /// Most recent runs across all jobs, optionally limited to a status
+    pub async fn recent(
+        db_pool: &PgPool,
+        status: Option<JobStatus>,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let runs = sqlx::query_as!(
+            JobRun,
+            r#"
+            SELECT id, job_id, attempt, status AS "status:JobStatus", error, started_at,
+                finished_at
+            FROM job_run
+            WHERE ($1::job_status IS NULL OR status = $1)
+            ORDER BY started_at DESC
+            LIMIT $2
+            "#,
+            status as Option<JobStatus>,
+            limit
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(runs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Timelike;
+
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(Job::retry_delay(1), chrono::Duration::seconds(30));
+        assert_eq!(Job::retry_delay(2), chrono::Duration::seconds(60));
+        assert_eq!(Job::retry_delay(4), chrono::Duration::seconds(240));
+        // Capped at an hour, including attempts far past the exponent clamp
+        assert_eq!(Job::retry_delay(8), chrono::Duration::seconds(60 * 60));
+        assert_eq!(
+            Job::retry_delay(i32::MAX),
+            chrono::Duration::seconds(60 * 60)
+        );
+        // Attempts before the first are treated as the first
+        assert_eq!(Job::retry_delay(0), chrono::Duration::seconds(30));
+        assert_eq!(Job::retry_delay(-3), chrono::Duration::seconds(30));
+    }
+
+    #[test]
+    fn test_next_run_at() {
+        let now = Utc::now();
+        let next = Job::next_run_at("0 0 8 * * *").unwrap();
+        assert!(next > now);
+        assert!(next <= now + chrono::Duration::days(1));
+        assert_eq!((next.hour(), next.minute(), next.second()), (8, 0, 0));
+
+        let next = Job::next_run_at("0 * * * * *").unwrap();
+        assert!(next > now);
+        assert!(next <= now + chrono::Duration::minutes(1));
+        assert_eq!(next.second(), 0);
+
+        // Cron expressions need the seconds field
+        assert!(Job::next_run_at("0 8 * * *").is_err());
+        assert!(Job::next_run_at("not a schedule").is_err());
+    }
+}
@@ -1,23 +1,39 @@
 pub mod address;
 pub mod argument;
+pub mod audit_event;
 pub mod ballot_measure;
 pub mod bill;
 pub mod candidate_guide;
+pub mod candidate_guide_outreach;
 pub mod committee;
 pub mod conversation;
+pub mod conversation_analysis_snapshot;
+pub mod deleted_record;
 pub mod election;
+pub mod email_outbox;
 pub mod embed;
 pub mod enums;
 pub mod issue_tag;
+pub mod job;
 pub mod office;
 pub mod organization;
+pub mod organization_api_key;
 pub mod organization_politician_note;
 pub mod party;
 pub mod politician;
 pub mod poll;
+pub mod precinct;
 pub mod question;
 pub mod race;
+pub mod race_advancement;
+pub mod race_call;
+pub mod ranked_choice;
 pub mod respondent;
+pub mod results_source;
+pub mod revision;
+pub mod scrape_run;
+pub mod statement_moderation;
 pub mod user;
+pub mod user_session;
 pub mod vote;
 pub mod voting_guide;
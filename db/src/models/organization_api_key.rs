@@ -0,0 +1,332 @@
+use async_graphql::InputObject;
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::{
+        embed::EmbedType,
+        enums::ApiKeyScope,
+        user::{OrganizationRole, OrganizationRoleType},
+    },
+    DateTime, Error,
+};
+
+/// Every key starts with this, so `graphql_handler` can tell keys from JWTs
+pub const API_KEY_PREFIX: &str = "pop_";
+
+/// Characters of the key stored in the clear to recognize it by
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+/// An organization API key. The key hash is never loaded.
+#[derive(FromRow, Debug, Clone)]
+pub struct OrganizationApiKey {
+    pub id: uuid::Uuid,
+    pub organization_id: uuid::Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scope: ApiKeyScope,
+    pub embed_types: Vec<EmbedType>,
+    pub expires_at: Option<DateTime>,
+    pub last_used_at: Option<DateTime>,
+    pub created_by: Option<uuid::Uuid>,
+    pub created_at: DateTime,
+    pub revoked_at: Option<DateTime>,
+}
+
+#[derive(InputObject, Debug)]
+pub struct CreateOrganizationApiKeyInput {
+    pub organization_id: uuid::Uuid,
+    pub name: String,
+    pub scope: ApiKeyScope,
+    /// Required for write keys
+    pub embed_types: Option<Vec<EmbedType>>,
+    pub expires_at: Option<DateTime>,
+}
+
+fn generate_key() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(40)
+        .map(char::from)
+        .collect();
+    format!("{}{}", API_KEY_PREFIX, secret)
+}
+
+impl OrganizationApiKey {
+    /// The organization role calls made with this key are checked as
+    pub fn role(&self) -> OrganizationRole {
+        OrganizationRole {
+            organization_id: self.organization_id,
+            role: match self.scope {
+                ApiKeyScope::Read => OrganizationRoleType::ReadOnly,
+                ApiKeyScope::Write => OrganizationRoleType::Member,
+            },
+        }
+    }
+
+    /// Whether the key may run an operation that requires `min_role` in `organization_id`.
+    /// Mutations are refused unless they're embed writes, which only write keys may run and
+    /// which check the key's embed types with `can_write_embed`.
+    pub fn allows(
+        &self,
+        organization_id: uuid::Uuid,
+        min_role: OrganizationRoleType,
+        is_mutation: bool,
+        embed_write: bool,
+    ) -> bool {
+        let role = self.role();
+        role.organization_id == organization_id
+            && role.role as i32 >= min_role as i32
+            && (!is_mutation || (embed_write && self.scope == ApiKeyScope::Write))
+    }
+
+    pub fn can_write_embed(&self, organization_id: uuid::Uuid, embed_type: EmbedType) -> bool {
+        self.organization_id == organization_id
+            && self.scope == ApiKeyScope::Write
+            && self.embed_types.contains(&embed_type)
+    }
+
+    /// Creates a key, returning it along with the plaintext key, which can't be retrieved later
+    pub async fn create(
+        db_pool: &PgPool,
+        input: &CreateOrganizationApiKeyInput,
+        created_by: uuid::Uuid,
+    ) -> Result<(Self, String), Error> {
+        let embed_types = input.embed_types.clone().unwrap_or_default();
+        if input.scope == ApiKeyScope::Write && embed_types.is_empty() {
+            return Err(Error::Custom(
+                "Write keys need at least one embed type".to_string(),
+            ));
+        }
+        if input.name.trim().is_empty() {
+            return Err(Error::Custom("API keys need a name".to_string()));
+        }
+
+        let key = generate_key();
+        let api_key = sqlx::query_as!(
+            OrganizationApiKey,
+            r#"
+            INSERT INTO organization_api_key
+                (organization_id, name, prefix, key_hash, scope, embed_types, expires_at, created_by)
+            VALUES ($1, $2, $3, encode(sha256(convert_to($4, 'UTF8')), 'hex'), $5, $6, $7, $8)
+            RETURNING id, organization_id, name, prefix, scope AS "scope:ApiKeyScope",
+                embed_types AS "embed_types:Vec<EmbedType>", expires_at, last_used_at, created_by,
+                created_at, revoked_at
+            "#,
+            input.organization_id,
+            input.name.trim(),
+            &key[..DISPLAY_PREFIX_LENGTH],
+            key,
+            input.scope as ApiKeyScope,
+            embed_types as Vec<EmbedType>,
+            input.expires_at,
+            created_by,
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok((api_key, key))
+    }
+
+    /// Looks up an active, unexpired key and records its use
+    pub async fn authenticate(db_pool: &PgPool, key: &str) -> Result<Option<Self>, Error> {
+        if !key.starts_with(API_KEY_PREFIX) {
+            return Ok(None);
+        }
+        let api_key = sqlx::query_as!(
+            OrganizationApiKey,
+            r#"
+            UPDATE organization_api_key SET last_used_at = now()
+            WHERE key_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+                AND revoked_at IS NULL
+                AND (expires_at IS NULL OR expires_at > now())
+            RETURNING id, organization_id, name, prefix, scope AS "scope:ApiKeyScope",
+                embed_types AS "embed_types:Vec<EmbedType>", expires_at, last_used_at, created_by,
+                created_at, revoked_at
+            "#,
+            key,
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(api_key)
+    }
+
+    pub async fn find_by_organization_id(
+        db_pool: &PgPool,
+        organization_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let api_keys = sqlx::query_as!(
+            OrganizationApiKey,
+            r#"
+            SELECT id, organization_id, name, prefix, scope AS "scope:ApiKeyScope",
+                embed_types AS "embed_types:Vec<EmbedType>", expires_at, last_used_at, created_by,
+                created_at, revoked_at
+            FROM organization_api_key
+            WHERE organization_id = $1
+            ORDER BY revoked_at IS NOT NULL, created_at DESC
+            "#,
+            organization_id,
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(api_keys)
+    }
+
+    /// Replaces a key with a new one with the same name, scope and expiry. The old key stops
+    /// working immediately, or after `grace_hours` so callers can be updated without downtime.
+    pub async fn rotate(
+        db_pool: &PgPool,
+        organization_id: uuid::Uuid,
+        id: uuid::Uuid,
+        grace_hours: Option<i32>,
+        rotated_by: uuid::Uuid,
+    ) -> Result<(Self, String), Error> {
+        let mut tx = db_pool.begin().await?;
+        let old = sqlx::query!(
+            r#"
+            SELECT name, scope AS "scope:ApiKeyScope",
+                embed_types AS "embed_types:Vec<EmbedType>", expires_at
+            FROM organization_api_key
+            WHERE id = $1 AND organization_id = $2 AND revoked_at IS NULL
+            FOR UPDATE
+            "#,
+            id,
+            organization_id,
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| Error::Custom("No active API key with this id".to_string()))?;
+
+        match grace_hours.filter(|hours| *hours > 0) {
+            Some(hours) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE organization_api_key
+                    SET expires_at = LEAST(COALESCE(expires_at, 'infinity'), now() + make_interval(hours => $2))
+                    WHERE id = $1
+                    "#,
+                    id,
+                    hours,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query!(
+                    "UPDATE organization_api_key SET revoked_at = now() WHERE id = $1",
+                    id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let key = generate_key();
+        let api_key = sqlx::query_as!(
+            OrganizationApiKey,
+            r#"
+            INSERT INTO organization_api_key
+                (organization_id, name, prefix, key_hash, scope, embed_types, expires_at, created_by)
+            VALUES ($1, $2, $3, encode(sha256(convert_to($4, 'UTF8')), 'hex'), $5, $6, $7, $8)
+            RETURNING id, organization_id, name, prefix, scope AS "scope:ApiKeyScope",
+                embed_types AS "embed_types:Vec<EmbedType>", expires_at, last_used_at, created_by,
+                created_at, revoked_at
+            "#,
+            organization_id,
+            old.name,
+            &key[..DISPLAY_PREFIX_LENGTH],
+            key,
+            old.scope as ApiKeyScope,
+            old.embed_types as Vec<EmbedType>,
+            old.expires_at,
+            rotated_by,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok((api_key, key))
+    }
+
+    /// Revokes a key, returning whether it was active
+    pub async fn revoke(
+        db_pool: &PgPool,
+        organization_id: uuid::Uuid,
+        id: uuid::Uuid,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE organization_api_key SET revoked_at = now()
+            WHERE id = $1 AND organization_id = $2 AND revoked_at IS NULL
+            "#,
+            id,
+            organization_id,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_key(scope: ApiKeyScope, embed_types: Vec<EmbedType>) -> OrganizationApiKey {
+        OrganizationApiKey {
+            id: uuid::Uuid::new_v4(),
+            organization_id: uuid::Uuid::from_u128(1),
+            name: "CMS sync".to_string(),
+            prefix: "pop_abcdefgh".to_string(),
+            scope,
+            embed_types,
+            expires_at: None,
+            last_used_at: None,
+            created_by: None,
+            created_at: chrono::Utc::now(),
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn test_read_key_allows_queries_only() {
+        let key = api_key(ApiKeyScope::Read, vec![]);
+        let org = uuid::Uuid::from_u128(1);
+
+        assert!(key.allows(org, OrganizationRoleType::ReadOnly, false, false));
+        assert!(!key.allows(org, OrganizationRoleType::ReadOnly, true, false));
+        assert!(!key.allows(org, OrganizationRoleType::ReadOnly, true, true));
+        assert!(!key.allows(org, OrganizationRoleType::Member, false, false));
+        assert!(!key.allows(
+            uuid::Uuid::from_u128(2),
+            OrganizationRoleType::ReadOnly,
+            false,
+            false
+        ));
+        assert!(!key.can_write_embed(org, EmbedType::Race));
+    }
+
+    #[test]
+    fn test_write_key_only_writes_its_embed_types() {
+        let key = api_key(ApiKeyScope::Write, vec![EmbedType::CandidateGuide]);
+        let org = uuid::Uuid::from_u128(1);
+
+        assert!(key.allows(org, OrganizationRoleType::Member, true, true));
+        assert!(!key.allows(org, OrganizationRoleType::Member, true, false));
+        assert!(!key.allows(org, OrganizationRoleType::Admin, false, false));
+        assert!(key.can_write_embed(org, EmbedType::CandidateGuide));
+        assert!(!key.can_write_embed(org, EmbedType::Poll));
+        assert!(!key.can_write_embed(uuid::Uuid::from_u128(2), EmbedType::CandidateGuide));
+    }
+
+    #[test]
+    fn test_generated_keys() {
+        let key = generate_key();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 40);
+        assert_ne!(key, generate_key());
+    }
+}
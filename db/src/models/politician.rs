@@ -656,9 +656,12 @@ impl Politician {
     }
 
     pub async fn delete(db_pool: &PgPool, id: uuid::Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM politician WHERE id=$1", id)
-            .execute(db_pool)
-            .await?;
+        sqlx::query!(
+            "UPDATE politician SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .execute(db_pool)
+        .await?;
         Ok(())
     }
 
@@ -705,7 +708,8 @@ impl Politician {
                         residence_address_id,
                         campaign_address_id,
                         created_at,
-                        updated_at FROM politician"#,
+                        updated_at FROM politician
+                WHERE deleted_at IS NULL"#,
         )
         .fetch_all(db_pool)
         .await?;
@@ -757,7 +761,7 @@ impl Politician {
                         campaign_address_id,
                         created_at,
                         updated_at FROM politician
-                WHERE id = $1
+                WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -811,7 +815,7 @@ impl Politician {
                         campaign_address_id,
                         created_at,
                         updated_at FROM politician
-                WHERE slug = $1
+                WHERE slug = $1 AND deleted_at IS NULL
             "#,
             slug
         )
@@ -869,7 +873,7 @@ impl Politician {
                         campaign_address_id,
                         created_at,
                         updated_at FROM politician
-                WHERE intake_token = $1
+                WHERE intake_token = $1 AND deleted_at IS NULL
             "#,
             token
         )
@@ -939,6 +943,7 @@ impl Politician {
                 NULLIF(ts_rank(to_tsvector(preferred_name), query), 0) rank_preferred_name,
                 NULLIF(ts_rank(to_tsvector(o.title), query), 0) rank_office_title
                 WHERE (($1::text = '') IS NOT FALSE OR query @@ document)
+                AND p.deleted_at IS NULL
                 AND ($2::state IS NULL OR home_state = $2)
                 AND ($3::political_scope IS NULL OR political_scope = $3)
                 AND ($4::text IS NULL OR $4 = 'All' OR (
@@ -1037,6 +1042,7 @@ impl Politician {
                 JOIN politician_politician_endorsements
                 ON politician_politician_endorsements.politician_endorsement_id = p.id
                 WHERE politician_politician_endorsements.politician_id = $1
+                AND p.deleted_at IS NULL
                 AND (NOW() BETWEEN politician_politician_endorsements.start_date AND politician_politician_endorsements.end_date
                      OR (politician_politician_endorsements.start_date <= NOW() AND politician_politician_endorsements.end_date IS NULL))
             "#,
@@ -0,0 +1,225 @@
+use sqlx::{FromRow, PgPool};
+
+use crate::{models::enums::State, DateTime, Error};
+
+#[derive(FromRow, Debug, Clone)]
+pub struct Precinct {
+    pub id: uuid::Uuid,
+    pub state: State,
+    pub county_code: String,
+    pub county: Option<String>,
+    pub code: String,
+    pub name: String,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+/// Votes for one candidate in one precinct, along with the precinct's turnout in the
+/// race's election
+#[derive(FromRow, Debug, Clone)]
+pub struct PrecinctCandidateVotes {
+    pub precinct_id: uuid::Uuid,
+    pub precinct_code: String,
+    pub precinct_name: String,
+    pub county: Option<String>,
+    pub candidate_id: uuid::Uuid,
+    pub votes: i32,
+    pub total_votes: Option<i32>,
+    pub registered_voters: Option<i32>,
+    pub ballots_cast: Option<i32>,
+    pub has_reported: Option<bool>,
+}
+
+/// Votes for one candidate in one county, either reported by the county itself or
+/// summed from its precincts
+#[derive(FromRow, Debug, Clone)]
+pub struct CountyCandidateVotes {
+    pub county: String,
+    pub candidate_id: uuid::Uuid,
+    pub votes: i64,
+    pub total_votes: Option<i64>,
+    pub precincts_reporting: Option<i64>,
+    pub total_precincts: Option<i64>,
+    pub registered_voters: Option<i64>,
+    pub ballots_cast: Option<i64>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct Turnout {
+    /// `None` for the election wide total
+    pub county: Option<String>,
+    /// Includes voters who registered on election day
+    pub registered_voters: Option<i64>,
+    pub ballots_cast: Option<i64>,
+    pub precincts_reporting: i64,
+    pub total_precincts: i64,
+}
+
+impl Precinct {
+    pub async fn find_by_id(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let record = sqlx::query_as!(
+            Precinct,
+            r#"
+            SELECT id, state AS "state:State", county_code, county, code, name, created_at, updated_at
+            FROM precinct
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    pub async fn results_by_precinct(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+        county: Option<String>,
+    ) -> Result<Vec<PrecinctCandidateVotes>, Error> {
+        let records = sqlx::query_as!(
+            PrecinctCandidateVotes,
+            r#"
+            SELECT
+                p.id AS precinct_id,
+                p.code AS precinct_code,
+                p.name AS precinct_name,
+                p.county,
+                pr.candidate_id,
+                pr.votes,
+                pr.total_votes,
+                pt.registered_voters + COALESCE(pt.election_day_registrations, 0) AS registered_voters,
+                pt.ballots_cast AS "ballots_cast?",
+                pt.has_reported AS "has_reported?"
+            FROM precinct_result pr
+            JOIN precinct p ON p.id = pr.precinct_id
+            JOIN race r ON r.id = pr.race_id
+            LEFT JOIN precinct_turnout pt ON pt.precinct_id = p.id AND pt.election_id = r.election_id
+            WHERE pr.race_id = $1
+            AND ($2::text IS NULL OR p.county ILIKE $2)
+            ORDER BY p.county, p.name, p.id, pr.votes DESC
+            "#,
+            race_id,
+            county
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// County totals for a race. Counties that report their own totals are used as is,
+    /// otherwise precinct results are summed per county.
+    pub async fn results_by_county(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+    ) -> Result<Vec<CountyCandidateVotes>, Error> {
+        let records = sqlx::query_as!(
+            CountyCandidateVotes,
+            r#"
+            WITH race_precinct AS (
+                SELECT DISTINCT ON (pr.precinct_id)
+                    pr.precinct_id,
+                    pr.total_votes,
+                    COALESCE(p.county, p.county_code) AS county
+                FROM precinct_result pr
+                JOIN precinct p ON p.id = pr.precinct_id
+                WHERE pr.race_id = $1
+            ),
+            county_total AS (
+                SELECT
+                    rp.county,
+                    SUM(rp.total_votes)::bigint AS total_votes,
+                    COUNT(*) FILTER (WHERE pt.has_reported)::bigint AS precincts_reporting,
+                    COUNT(*)::bigint AS total_precincts,
+                    SUM(pt.registered_voters + COALESCE(pt.election_day_registrations, 0))::bigint AS registered_voters,
+                    SUM(pt.ballots_cast)::bigint AS ballots_cast
+                FROM race_precinct rp
+                LEFT JOIN precinct_turnout pt ON pt.precinct_id = rp.precinct_id
+                    AND pt.election_id = (SELECT election_id FROM race WHERE id = $1)
+                GROUP BY rp.county
+            ),
+            county_votes AS (
+                SELECT COALESCE(p.county, p.county_code) AS county, pr.candidate_id, SUM(pr.votes)::bigint AS votes
+                FROM precinct_result pr
+                JOIN precinct p ON p.id = pr.precinct_id
+                WHERE pr.race_id = $1
+                AND NOT EXISTS (
+                    SELECT 1 FROM county_result cr
+                    WHERE cr.race_id = $1 AND cr.county = COALESCE(p.county, p.county_code)
+                )
+                GROUP BY 1, 2
+            )
+            SELECT
+                cv.county AS "county!",
+                cv.candidate_id AS "candidate_id!",
+                cv.votes AS "votes!",
+                ct.total_votes,
+                ct.precincts_reporting,
+                ct.total_precincts,
+                ct.registered_voters,
+                ct.ballots_cast
+            FROM county_votes cv
+            JOIN county_total ct ON ct.county = cv.county
+            UNION ALL
+            SELECT
+                cr.county,
+                cr.candidate_id,
+                cr.votes::bigint,
+                cr.total_votes::bigint,
+                cr.precincts_reporting::bigint,
+                cr.total_precincts::bigint,
+                NULL::bigint,
+                NULL::bigint
+            FROM county_result cr
+            WHERE cr.race_id = $1
+            ORDER BY 1, 3 DESC
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
+
+impl Turnout {
+    pub fn percentage(&self) -> Option<f64> {
+        match (self.ballots_cast, self.registered_voters) {
+            (Some(ballots_cast), Some(registered_voters)) if registered_voters > 0 => {
+                Some(ballots_cast as f64 / registered_voters as f64 * 100.0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Turnout across an election's reporting precincts, overall (first row, no county)
+    /// and for each county
+    pub async fn for_election(
+        db_pool: &PgPool,
+        election_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            Turnout,
+            r#"
+            SELECT
+                COALESCE(p.county, p.county_code) AS county,
+                SUM(pt.registered_voters + COALESCE(pt.election_day_registrations, 0))::bigint AS registered_voters,
+                SUM(pt.ballots_cast)::bigint AS ballots_cast,
+                COUNT(*) FILTER (WHERE pt.has_reported) AS "precincts_reporting!",
+                COUNT(*) AS "total_precincts!"
+            FROM precinct_turnout pt
+            JOIN precinct p ON p.id = pt.precinct_id
+            WHERE pt.election_id = $1
+            GROUP BY GROUPING SETS ((), (COALESCE(p.county, p.county_code)))
+            ORDER BY 1 NULLS FIRST
+            "#,
+            election_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
@@ -32,6 +32,7 @@ pub struct QuestionSubmission {
     pub translations: Option<serde_json::Value>,
     pub sentiment: Option<Sentiment>,
     pub copied_from_id: Option<uuid::Uuid>,
+    pub status: SubmissionStatus,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -77,6 +78,8 @@ pub struct UpsertQuestionSubmissionInput {
     pub translations: Option<serde_json::Value>,
     pub should_translate: Option<bool>,
     pub copied_from_id: Option<uuid::Uuid>,
+    /// Candidates can save a `DRAFT` or `SUBMITTED` answer. New answers default to `SUBMITTED`.
+    pub status: Option<SubmissionStatus>,
 }
 
 #[derive(Display, Copy, Clone, Eq, PartialEq, Debug, Enum, EnumString, sqlx::Type)]
@@ -89,6 +92,57 @@ pub enum Sentiment {
     Unknown,
 }
 
+/// Where a candidate's answer is in editorial review. Only `PUBLISHED` answers are shown
+/// in candidate guides.
+#[derive(
+    Display, Copy, Clone, Eq, PartialEq, Debug, Enum, EnumString, sqlx::Type, Serialize, Deserialize,
+)]
+#[strum(ascii_case_insensitive)]
+#[sqlx(type_name = "submission_status", rename_all = "snake_case")]
+pub enum SubmissionStatus {
+    Draft,
+    Submitted,
+    InReview,
+    Approved,
+    Published,
+    Rejected,
+}
+
+impl SubmissionStatus {
+    /// Candidates can keep editing their answer until an editor picks it up
+    pub fn is_editable_by_candidate(&self) -> bool {
+        matches!(
+            self,
+            SubmissionStatus::Draft | SubmissionStatus::Submitted | SubmissionStatus::Rejected
+        )
+    }
+
+    /// The statuses an editor can move a submission to from this one. Drafts haven't been
+    /// submitted yet, so they can't be reviewed.
+    pub fn can_review_to(&self, to: SubmissionStatus) -> bool {
+        use SubmissionStatus::*;
+        matches!(
+            (self, to),
+            (Submitted, InReview | Approved | Rejected)
+                | (InReview, Approved | Rejected)
+                | (Approved, Published | InReview | Rejected)
+                | (Published, InReview | Rejected)
+                | (Rejected, InReview)
+        )
+    }
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct QuestionSubmissionReview {
+    pub id: uuid::Uuid,
+    pub question_submission_id: uuid::Uuid,
+    pub from_status: SubmissionStatus,
+    pub to_status: SubmissionStatus,
+    pub editorial: Option<String>,
+    pub reviewer_id: Option<uuid::Uuid>,
+    pub created_at: DateTime,
+}
+
 #[derive(Default, Debug, Serialize, Deserialize, InputObject)]
 pub struct QuestionSubmissionsFilter {
     query: Option<String>,
@@ -96,6 +150,7 @@ pub struct QuestionSubmissionsFilter {
     race_type: Option<RaceType>,
     state: Option<State>,
     county: Option<String>,
+    status: Option<SubmissionStatus>,
 }
 
 impl Question {
@@ -133,6 +188,7 @@ impl Question {
                     allow_anonymous_responses = $5,
                     embed_id = $6,
                     translations = $7
+                WHERE question.organization_id IS NOT DISTINCT FROM EXCLUDED.organization_id
                 RETURNING *
             "#,
             id,
@@ -261,7 +317,8 @@ impl QuestionSubmission {
                     sentiment,
                     translations,
                     editorial,
-                    copied_from_id
+                    copied_from_id,
+                    status
                 ) VALUES (
                     $1,
                     $2,
@@ -271,14 +328,17 @@ impl QuestionSubmission {
                     $6,
                     $7,
                     $8,
-                    $9
+                    $9,
+                    COALESCE($10, 'submitted')
                 ) ON CONFLICT (id) DO UPDATE SET
                     response = $5,
                     sentiment = $6,
                     translations = $7,
                     editorial = $8,
                     updated_at = now(),
-                    copied_from_id = $9
+                    copied_from_id = $9,
+                    status = COALESCE($10, question_submission.status)
+                WHERE question_submission.candidate_id IS NOT DISTINCT FROM EXCLUDED.candidate_id
                 RETURNING 
                     id,
                     question_id,
@@ -289,6 +349,7 @@ impl QuestionSubmission {
                     translations,
                     sentiment AS "sentiment:Sentiment",
                     copied_from_id,
+                    status AS "status:SubmissionStatus",
                     created_at,
                     updated_at
             "#,
@@ -300,7 +361,8 @@ impl QuestionSubmission {
             input.sentiment as Option<Sentiment>,
             translations,
             input.editorial,
-            input.copied_from_id
+            input.copied_from_id,
+            input.status as Option<SubmissionStatus>
         )
         .fetch_one(db_pool)
         .await?;
@@ -327,6 +389,7 @@ impl QuestionSubmission {
                   qs.translations,
                   sentiment AS "sentiment: Sentiment",
                   copied_from_id,
+                  qs.status AS "status: SubmissionStatus",
                   qs.created_at,
                   qs.updated_at
                 FROM question_submission qs
@@ -343,11 +406,15 @@ impl QuestionSubmission {
                   ) document,
                 websearch_to_tsquery($2) AS query
                 WHERE cg.organization_id = $1::uuid
+                  AND cg.deleted_at IS NULL
+                  AND r.deleted_at IS NULL
+                  AND p.deleted_at IS NULL
                   AND(($2::text = '') IS NOT FALSE OR query @@ document)
                   AND ($3::race_type IS NULL OR r.race_type = $3::race_type)
                   AND ($4::political_scope IS NULL OR o.political_scope = $4::political_scope)
                   AND ($5::state IS NULL OR o.state = $5::state)
                   AND ($6::text IS NULL OR o.county = $6::text)
+                  AND ($7::submission_status IS NULL OR qs.status = $7::submission_status)
                 ORDER BY qs.created_at DESC
                 LIMIT 250
                             "#,
@@ -356,10 +423,200 @@ impl QuestionSubmission {
             filter.race_type as Option<RaceType>,
             filter.political_scope as Option<PoliticalScope>,
             filter.state as Option<State>,
-            filter.county
+            filter.county,
+            filter.status as Option<SubmissionStatus>
         )
         .fetch_all(db_pool)
         .await?;
         Ok(records)
     }
+
+    pub async fn find_by_id(db_pool: &PgPool, id: Uuid) -> Result<Self, Error> {
+        let record = sqlx::query_as!(
+            QuestionSubmission,
+            r#"
+                SELECT
+                  id,
+                  question_id,
+                  respondent_id,
+                  candidate_id,
+                  response,
+                  editorial,
+                  translations,
+                  sentiment AS "sentiment: Sentiment",
+                  copied_from_id,
+                  status AS "status: SubmissionStatus",
+                  created_at,
+                  updated_at
+                FROM question_submission
+                WHERE id = $1
+            "#,
+            id,
+        )
+        .fetch_one(db_pool)
+        .await?;
+        Ok(record)
+    }
+
+    /// Moves the submission to `status` and/or replaces its editorial note, keeping the
+    /// previous note in its review history
+    pub async fn review(
+        db_pool: &PgPool,
+        id: Uuid,
+        status: Option<SubmissionStatus>,
+        editorial: Option<String>,
+        reviewer_id: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let mut tx = db_pool.begin().await?;
+        let from_status = sqlx::query_scalar!(
+            r#"
+                SELECT status AS "status: SubmissionStatus"
+                FROM question_submission
+                WHERE id = $1
+                FOR UPDATE
+            "#,
+            id,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let to_status = status.unwrap_or(from_status);
+        if to_status != from_status && !from_status.can_review_to(to_status) {
+            return Err(Error::Custom(format!(
+                "A {} submission can't be moved to {}",
+                from_status, to_status
+            )));
+        }
+
+        let record = sqlx::query_as!(
+            QuestionSubmission,
+            r#"
+                UPDATE question_submission
+                SET status = $2,
+                    editorial = COALESCE($3, editorial)
+                WHERE id = $1
+                RETURNING
+                  id,
+                  question_id,
+                  respondent_id,
+                  candidate_id,
+                  response,
+                  editorial,
+                  translations,
+                  sentiment AS "sentiment: Sentiment",
+                  copied_from_id,
+                  status AS "status: SubmissionStatus",
+                  created_at,
+                  updated_at
+            "#,
+            id,
+            to_status as SubmissionStatus,
+            editorial,
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO question_submission_review
+                    (question_submission_id, from_status, to_status, editorial, reviewer_id)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            from_status as SubmissionStatus,
+            to_status as SubmissionStatus,
+            record.editorial,
+            reviewer_id,
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(record)
+    }
+
+    /// Publishes every approved submission to the guide's questions, returning how many
+    pub async fn publish_approved(
+        db_pool: &PgPool,
+        candidate_guide_id: Uuid,
+        reviewer_id: Option<Uuid>,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+                WITH published AS (
+                    UPDATE question_submission qs
+                    SET status = 'published'
+                    FROM candidate_guide_questions cgq
+                    WHERE cgq.question_id = qs.question_id
+                        AND cgq.candidate_guide_id = $1
+                        AND qs.status = 'approved'
+                    RETURNING qs.id, qs.editorial
+                )
+                INSERT INTO question_submission_review
+                    (question_submission_id, from_status, to_status, editorial, reviewer_id)
+                SELECT id, 'approved', 'published', editorial, $2
+                FROM published
+            "#,
+            candidate_guide_id,
+            reviewer_id,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Every review of the submission, oldest first
+    pub async fn reviews(
+        db_pool: &PgPool,
+        question_submission_id: Uuid,
+    ) -> Result<Vec<QuestionSubmissionReview>, Error> {
+        let reviews = sqlx::query_as!(
+            QuestionSubmissionReview,
+            r#"
+                SELECT
+                  id,
+                  question_submission_id,
+                  from_status AS "from_status: SubmissionStatus",
+                  to_status AS "to_status: SubmissionStatus",
+                  editorial,
+                  reviewer_id,
+                  created_at
+                FROM question_submission_review
+                WHERE question_submission_id = $1
+                ORDER BY created_at
+            "#,
+            question_submission_id,
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(reviews)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubmissionStatus::*;
+
+    #[test]
+    fn test_review_transitions() {
+        assert!(Submitted.can_review_to(InReview));
+        assert!(InReview.can_review_to(Approved));
+        assert!(Approved.can_review_to(Published));
+        assert!(Published.can_review_to(InReview));
+        assert!(Rejected.can_review_to(InReview));
+        assert!(!Draft.can_review_to(InReview));
+        assert!(!Submitted.can_review_to(Published));
+        assert!(!Rejected.can_review_to(Published));
+        assert!(!Draft.can_review_to(Submitted));
+    }
+
+    #[test]
+    fn test_candidates_can_edit_until_review() {
+        assert!(Draft.is_editable_by_candidate());
+        assert!(Rejected.is_editable_by_candidate());
+        assert!(!InReview.is_editable_by_candidate());
+        assert!(!Published.is_editable_by_candidate());
+    }
 }
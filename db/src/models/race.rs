@@ -1,4 +1,4 @@
-use super::enums::{PoliticalScope, RaceType, State, VoteType};
+use super::enums::{PoliticalScope, RaceStatus, RaceType, State, VoteType};
 use crate::{DateTime, ElectionScope};
 use async_graphql::InputObject;
 use chrono::NaiveDate;
@@ -29,6 +29,8 @@ pub struct Race {
     pub total_precincts: Option<i32>,
     pub is_special_election: bool,
     pub num_elect: Option<i32>,
+    pub status: RaceStatus,
+    pub status_updated_at: Option<DateTime>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -130,7 +132,7 @@ impl Race {
                     total_votes = COALESCE($15, race.total_votes),
                     is_special_election = COALESCE($16, race.is_special_election),
                     num_elect = COALESCE($17, race.num_elect)
-                RETURNING id, slug, title,  office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, official_website, election_id, total_votes, num_precincts_reporting, total_precincts, is_special_election, num_elect, created_at, updated_at
+                RETURNING id, slug, title,  office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, official_website, election_id, total_votes, num_precincts_reporting, total_precincts, is_special_election, num_elect, status AS "status:RaceStatus", status_updated_at, created_at, updated_at
             "#,
             id,
             slug,
@@ -187,7 +189,7 @@ impl Race {
                     total_votes = COALESCE($14, race.total_votes),
                     is_special_election = COALESCE($15, race.is_special_election),
                     num_elect = COALESCE($16, race.num_elect)
-                RETURNING id, slug, title,  office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, official_website, election_id, total_votes, num_precincts_reporting, total_precincts, is_special_election, num_elect, created_at, updated_at
+                RETURNING id, slug, title,  office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, official_website, election_id, total_votes, num_precincts_reporting, total_precincts, is_special_election, num_elect, status AS "status:RaceStatus", status_updated_at, created_at, updated_at
             "#,
             input.slug,
             input.title,
@@ -211,9 +213,12 @@ impl Race {
     }
 
     pub async fn delete(db_pool: &PgPool, id: uuid::Uuid) -> Result<(), sqlx::Error> {
-        sqlx::query!("DELETE FROM race WHERE id=$1", id)
-            .execute(db_pool)
-            .await?;
+        sqlx::query!(
+            "UPDATE race SET deleted_at = now() WHERE id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .execute(db_pool)
+        .await?;
         Ok(())
     }
 
@@ -221,8 +226,8 @@ impl Race {
         let record = sqlx::query_as!(
             Race,
             r#"
-                SELECT id, slug, title, office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, total_votes, num_precincts_reporting, total_precincts, official_website, election_id, is_special_election, num_elect, created_at, updated_at FROM race
-                WHERE id = $1
+                SELECT id, slug, title, office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, total_votes, num_precincts_reporting, total_precincts, official_website, election_id, is_special_election, num_elect, status AS "status:RaceStatus", status_updated_at, created_at, updated_at FROM race
+                WHERE id = $1 AND deleted_at IS NULL
             "#,
             id
         )
@@ -236,8 +241,8 @@ impl Race {
         let record = sqlx::query_as!(
             Race,
             r#"
-                SELECT id, slug, title, office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, total_votes, num_precincts_reporting, total_precincts, official_website, election_id, is_special_election, num_elect, created_at, updated_at FROM race
-                WHERE slug = $1
+                SELECT id, slug, title, office_id, race_type AS "race_type:RaceType", vote_type AS "vote_type:VoteType", party_id, state AS "state:State", description, ballotpedia_link, early_voting_begins_date, winner_ids, total_votes, num_precincts_reporting, total_precincts, official_website, election_id, is_special_election, num_elect, status AS "status:RaceStatus", status_updated_at, created_at, updated_at FROM race
+                WHERE slug = $1 AND deleted_at IS NULL
             "#,
             slug
         )
@@ -281,6 +286,8 @@ impl Race {
                 r.election_id,
                 r.is_special_election,
                 r.num_elect,
+                r.status AS "status:RaceStatus",
+                r.status_updated_at,
                 r.created_at,
                 r.updated_at
             FROM race r
@@ -289,6 +296,7 @@ impl Race {
                 AS t(office_id, is_special_election)
             ) i ON r.office_id = i.office_id AND r.is_special_election = i.is_special_election
             WHERE r.race_type = 'primary'
+              AND r.deleted_at IS NULL
               AND (r.winner_ids IS NULL OR r.winner_ids = '{}')
             ORDER BY r.office_id, r.is_special_election, r.election_id, r.id
             "#,
@@ -329,6 +337,8 @@ impl Race {
                     election_id,
                     is_special_election,
                     num_elect,
+                    race.status,
+                    race.status_updated_at,
                     race.created_at,
                     race.updated_at,
                     o.title,
@@ -339,7 +349,7 @@ impl Race {
                     o.school_district,
                     o.hospital_district
                 FROM
-                    race
+                    (SELECT * FROM race WHERE deleted_at IS NULL) race
                 LEFT JOIN office o ON race.office_id = o.id
                 LEFT JOIN election e ON race.election_id = e.id
                 LEFT JOIN us_states s ON o.state = s.code
@@ -0,0 +1,548 @@
+//! Builds runoff and general election races from a primary's results. Races that need a
+//! runoff under the state's rules get a runoff race with the advancing candidates, the
+//! rest send their winners to the general election race for the same office, which is
+//! shared by all party primaries.
+
+use std::collections::{BTreeMap, HashMap};
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use sqlx::PgPool;
+
+use crate::{
+    models::enums::{RaceStatus, RaceType, State},
+    project_winners, runoff_rule, Error, ProjectionOutcome,
+};
+
+#[derive(InputObject, Debug)]
+pub struct AdvanceRacesInput {
+    pub state: State,
+    /// The primary (or primary runoff) election whose races are advanced
+    pub election_id: uuid::Uuid,
+    /// Required to create runoff races
+    pub runoff_election_id: Option<uuid::Uuid>,
+    /// Required to create general election races
+    pub general_election_id: Option<uuid::Uuid>,
+    /// Returns the changes without making them
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AdvancementKind {
+    Runoff,
+    General,
+}
+
+#[derive(SimpleObject, Debug, Clone, PartialEq)]
+pub struct PlannedRace {
+    pub kind: AdvancementKind,
+    pub slug: String,
+    pub title: String,
+    pub election_id: uuid::Uuid,
+    pub office_id: uuid::Uuid,
+    pub party_id: Option<uuid::Uuid>,
+    pub num_elect: Option<i32>,
+    /// Primaries the candidates advance from, one per party for general races
+    pub source_race_ids: Vec<uuid::Uuid>,
+    pub candidate_ids: Vec<uuid::Uuid>,
+    /// Set when a race with the slug already exists and only its candidates are added
+    pub existing_race_id: Option<uuid::Uuid>,
+    /// Candidates not yet in the race
+    pub new_candidate_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(SimpleObject, Debug, Clone, PartialEq)]
+pub struct SkippedRace {
+    pub race_id: uuid::Uuid,
+    pub slug: String,
+    pub reason: String,
+}
+
+#[derive(SimpleObject, Debug, Clone, PartialEq)]
+pub struct WinnerUpdate {
+    pub race_id: uuid::Uuid,
+    pub winner_ids: Vec<uuid::Uuid>,
+}
+
+/// Changes made, or in a dry run the changes that would be made, by `advance_races`
+#[derive(SimpleObject, Debug, Clone, Default)]
+pub struct AdvancementPlan {
+    pub dry_run: bool,
+    /// Only races that are created or gain candidates
+    pub races: Vec<PlannedRace>,
+    pub skipped: Vec<SkippedRace>,
+    /// Primaries without winners that get the winners or runoff candidates computed here
+    pub winner_updates: Vec<WinnerUpdate>,
+}
+
+/// A primary race with its results, as loaded for planning
+#[derive(Debug, Clone)]
+pub struct PrimaryRace {
+    pub id: uuid::Uuid,
+    pub slug: String,
+    pub title: String,
+    pub office_id: uuid::Uuid,
+    pub party_id: Option<uuid::Uuid>,
+    pub num_elect: Option<i32>,
+    pub total_votes: Option<i32>,
+    pub is_fully_reported: bool,
+    pub status: RaceStatus,
+    pub winner_ids: Vec<uuid::Uuid>,
+    /// Whether staff called the race for a runoff
+    pub called_for_runoff: bool,
+    pub votes: Vec<(uuid::Uuid, i64)>,
+}
+
+pub fn runoff_slug(slug: &str) -> String {
+    format!("{}-runoff", slug.trim_end_matches(['-', ' ']))
+}
+
+pub fn runoff_title(title: &str) -> String {
+    format!("{} - Runoff", title.trim())
+}
+
+/// `tx-us-house-1-republican-primary` and `tx-us-house-1-democratic-primary` (and their
+/// runoffs) all become `tx-us-house-1-general`
+pub fn general_slug(slug: &str) -> String {
+    slug.to_lowercase()
+        .trim_end_matches(['-', ' '])
+        .trim_end_matches("-runoff")
+        .replace("-primary", "-general")
+        .replace("-democratic-", "-")
+        .replace("-republican-", "-")
+        .trim_end_matches(['-', ' '])
+        .to_string()
+}
+
+pub fn general_title(title: &str) -> String {
+    title
+        .trim()
+        .trim_end_matches(" - Runoff")
+        .replace(" Primary", " General")
+        .replace(" Democratic -", "")
+        .replace(" Republican -", "")
+}
+
+/// Decides where each primary's candidates advance to. Races already advanced are
+/// planned again, `advance_races` drops them once it knows nothing would change.
+pub fn plan_races(
+    state: State,
+    primaries: &[PrimaryRace],
+    runoff_election_id: Option<uuid::Uuid>,
+    general_election_id: Option<uuid::Uuid>,
+) -> AdvancementPlan {
+    let mut plan = AdvancementPlan::default();
+    let mut general_races: BTreeMap<String, PlannedRace> = BTreeMap::new();
+    let rule = runoff_rule(Some(state), RaceType::Primary);
+
+    for race in primaries {
+        let mut skip = |reason: &str| {
+            plan.skipped.push(SkippedRace {
+                race_id: race.id,
+                slug: race.slug.clone(),
+                reason: reason.to_string(),
+            })
+        };
+
+        let (advancing, is_runoff) = match race.status {
+            RaceStatus::Called | RaceStatus::Certified if !race.winner_ids.is_empty() => {
+                (race.winner_ids.clone(), race.called_for_runoff)
+            }
+            RaceStatus::Recount => {
+                skip("Recount in progress");
+                continue;
+            }
+            _ if !race.is_fully_reported => {
+                skip("Not all precincts have reported");
+                continue;
+            }
+            _ => {
+                let projection = project_winners(
+                    &race.votes,
+                    race.total_votes.map(i64::from),
+                    race.num_elect.unwrap_or(1).max(1) as usize,
+                    rule,
+                );
+                match projection.outcome {
+                    ProjectionOutcome::Decided => (projection.winner_ids, false),
+                    ProjectionOutcome::Runoff => (projection.winner_ids, true),
+                    ProjectionOutcome::Tie => {
+                        skip("Candidates are tied, call the race to resolve it");
+                        continue;
+                    }
+                    ProjectionOutcome::NoVotes => {
+                        skip("No votes reported");
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let election_id = match (is_runoff, runoff_election_id, general_election_id) {
+            (true, Some(id), _) | (false, _, Some(id)) => id,
+            (true, None, _) => {
+                skip("Needs a runoff but no runoff election was given");
+                continue;
+            }
+            (false, _, None) => {
+                skip("No general election was given");
+                continue;
+            }
+        };
+
+        if race.winner_ids.is_empty() {
+            plan.winner_updates.push(WinnerUpdate {
+                race_id: race.id,
+                winner_ids: advancing.clone(),
+            });
+        }
+
+        if is_runoff {
+            plan.races.push(PlannedRace {
+                kind: AdvancementKind::Runoff,
+                slug: runoff_slug(&race.slug),
+                title: runoff_title(&race.title),
+                election_id,
+                office_id: race.office_id,
+                party_id: race.party_id,
+                num_elect: race.num_elect,
+                source_race_ids: vec![race.id],
+                candidate_ids: advancing,
+                existing_race_id: None,
+                new_candidate_ids: vec![],
+            });
+        } else {
+            let general = general_races
+                .entry(general_slug(&race.slug))
+                .or_insert_with_key(|slug| PlannedRace {
+                    kind: AdvancementKind::General,
+                    slug: slug.clone(),
+                    title: general_title(&race.title),
+                    election_id,
+                    office_id: race.office_id,
+                    party_id: None,
+                    num_elect: race.num_elect,
+                    source_race_ids: vec![],
+                    candidate_ids: vec![],
+                    existing_race_id: None,
+                    new_candidate_ids: vec![],
+                });
+            general.source_race_ids.push(race.id);
+            general.candidate_ids.extend(advancing);
+        }
+    }
+
+    plan.races.extend(general_races.into_values());
+    plan
+}
+
+async fn load_primaries(
+    db_pool: &PgPool,
+    state: State,
+    election_id: uuid::Uuid,
+) -> Result<Vec<PrimaryRace>, Error> {
+    let races = sqlx::query!(
+        r#"
+        SELECT
+            r.id,
+            r.slug,
+            r.title,
+            r.office_id,
+            r.party_id,
+            r.num_elect,
+            r.total_votes,
+            COALESCE(r.total_precincts IS NOT NULL
+                AND r.num_precincts_reporting = r.total_precincts, false) AS "is_fully_reported!",
+            r.status AS "status:RaceStatus",
+            COALESCE(r.winner_ids, '{}') AS "winner_ids!",
+            COALESCE((
+                SELECT rc.is_runoff FROM race_call rc
+                WHERE rc.race_id = r.id
+                ORDER BY rc.created_at DESC
+                LIMIT 1
+            ), false) AS "called_for_runoff!"
+        FROM race r
+        WHERE r.election_id = $1 AND r.state = $2 AND r.race_type = 'primary'
+        ORDER BY r.slug
+        "#,
+        election_id,
+        state as State
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    let mut votes: HashMap<uuid::Uuid, Vec<(uuid::Uuid, i64)>> = HashMap::new();
+    for record in sqlx::query!(
+        r#"
+        SELECT rc.race_id, rc.candidate_id, COALESCE(rc.votes, 0)::bigint AS "votes!"
+        FROM race_candidates rc
+        JOIN race r ON r.id = rc.race_id
+        WHERE r.election_id = $1 AND r.state = $2 AND r.race_type = 'primary' AND rc.is_running
+        "#,
+        election_id,
+        state as State
+    )
+    .fetch_all(db_pool)
+    .await?
+    {
+        votes
+            .entry(record.race_id)
+            .or_default()
+            .push((record.candidate_id, record.votes));
+    }
+
+    Ok(races
+        .into_iter()
+        .map(|r| PrimaryRace {
+            votes: votes.remove(&r.id).unwrap_or_default(),
+            id: r.id,
+            slug: r.slug,
+            title: r.title,
+            office_id: r.office_id,
+            party_id: r.party_id,
+            num_elect: r.num_elect,
+            total_votes: r.total_votes,
+            is_fully_reported: r.is_fully_reported,
+            status: r.status,
+            winner_ids: r.winner_ids,
+            called_for_runoff: r.called_for_runoff,
+        })
+        .collect())
+}
+
+/// Plans runoff and general races for a state's primary election and, unless it's a dry
+/// run, creates them. Safe to run repeatedly: existing races are matched by slug and only
+/// gain missing candidates.
+pub async fn advance_races(
+    db_pool: &PgPool,
+    input: &AdvanceRacesInput,
+) -> Result<AdvancementPlan, Error> {
+    let primaries = load_primaries(db_pool, input.state, input.election_id).await?;
+    let mut plan = plan_races(
+        input.state,
+        &primaries,
+        input.runoff_election_id,
+        input.general_election_id,
+    );
+    plan.dry_run = input.dry_run.unwrap_or(false);
+
+    let slugs: Vec<String> = plan.races.iter().map(|r| r.slug.clone()).collect();
+    let existing = sqlx::query!(
+        r#"
+        SELECT r.id, r.slug, ARRAY(
+            SELECT candidate_id FROM race_candidates WHERE race_id = r.id
+        ) AS "candidate_ids!"
+        FROM race r
+        WHERE r.slug = ANY($1)
+        "#,
+        &slugs
+    )
+    .fetch_all(db_pool)
+    .await?
+    .into_iter()
+    .map(|r| (r.slug, (r.id, r.candidate_ids)))
+    .collect::<HashMap<_, _>>();
+
+    for race in plan.races.iter_mut() {
+        let current = existing.get(&race.slug);
+        race.existing_race_id = current.map(|(id, _)| *id);
+        race.new_candidate_ids = race
+            .candidate_ids
+            .iter()
+            .filter(|id| !current.is_some_and(|(_, ids)| ids.contains(id)))
+            .copied()
+            .collect();
+    }
+    plan.races
+        .retain(|r| r.existing_race_id.is_none() || !r.new_candidate_ids.is_empty());
+
+    if plan.dry_run {
+        return Ok(plan);
+    }
+
+    let mut tx = db_pool.begin().await?;
+    for race in &plan.races {
+        let race_id = match race.existing_race_id {
+            Some(id) => id,
+            None => {
+                let race_type = match race.kind {
+                    AdvancementKind::Runoff => RaceType::Primary,
+                    AdvancementKind::General => RaceType::General,
+                };
+                sqlx::query!(
+                    r#"
+                    INSERT INTO race (slug, title, office_id, race_type, vote_type, party_id, state, election_id, is_special_election, num_elect)
+                    VALUES ($1, $2, $3, $4, 'plurality', $5, $6, $7, false, $8)
+                    RETURNING id
+                    "#,
+                    race.slug,
+                    race.title,
+                    race.office_id,
+                    race_type as RaceType,
+                    race.party_id,
+                    input.state as State,
+                    race.election_id,
+                    race.num_elect
+                )
+                .fetch_one(&mut *tx)
+                .await?
+                .id
+            }
+        };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO race_candidates (race_id, candidate_id)
+            SELECT $1, UNNEST($2::uuid[])
+            ON CONFLICT (race_id, candidate_id) DO NOTHING
+            "#,
+            race_id,
+            &race.new_candidate_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for update in &plan.winner_updates {
+        sqlx::query!(
+            r#"
+            UPDATE race SET winner_ids = $2
+            WHERE id = $1 AND (winner_ids IS NULL OR winner_ids = '{}')
+            "#,
+            update.race_id,
+            &update.winner_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(n: u128) -> uuid::Uuid {
+        uuid::Uuid::from_u128(n)
+    }
+
+    fn primary(id: u128, slug: &str, title: &str, votes: &[(u128, i64)]) -> PrimaryRace {
+        PrimaryRace {
+            id: uuid::Uuid::from_u128(1000 + id),
+            slug: slug.to_string(),
+            title: title.to_string(),
+            office_id: uuid::Uuid::from_u128(2000),
+            party_id: Some(uuid::Uuid::from_u128(3000 + id)),
+            num_elect: Some(1),
+            total_votes: None,
+            is_fully_reported: true,
+            status: RaceStatus::Uncalled,
+            winner_ids: vec![],
+            called_for_runoff: false,
+            votes: votes.iter().map(|(c, v)| (candidate(*c), *v)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_slugs_and_titles() {
+        assert_eq!(
+            general_slug("tx-us-house-1-republican-primary-2026"),
+            "tx-us-house-1-general-2026"
+        );
+        assert_eq!(
+            general_slug("tx-us-house-1-democratic-primary-runoff"),
+            "tx-us-house-1-general"
+        );
+        assert_eq!(
+            runoff_slug("tx-us-house-1-primary- "),
+            "tx-us-house-1-primary-runoff"
+        );
+        assert_eq!(
+            general_title("U.S. House 1 Republican - Primary - Runoff"),
+            "U.S. House 1 General"
+        );
+        assert_eq!(runoff_title(" U.S. House 1 "), "U.S. House 1 - Runoff");
+    }
+
+    #[test]
+    fn test_party_primaries_share_general_race() {
+        let general_election_id = uuid::Uuid::from_u128(9);
+        let primaries = [
+            primary(
+                1,
+                "tx-house-1-republican-primary",
+                "House 1 Republican - Primary",
+                &[(1, 60), (2, 40)],
+            ),
+            primary(
+                2,
+                "tx-house-1-democratic-primary",
+                "House 1 Democratic - Primary",
+                &[(3, 70), (4, 20), (5, 10)],
+            ),
+        ];
+        let plan = plan_races(State::TX, &primaries, None, Some(general_election_id));
+
+        assert!(plan.skipped.is_empty());
+        assert_eq!(plan.races.len(), 1);
+        assert_eq!(plan.races[0].slug, "tx-house-1-general");
+        assert_eq!(plan.races[0].party_id, None);
+        assert_eq!(
+            plan.races[0].candidate_ids,
+            vec![candidate(1), candidate(3)]
+        );
+        assert_eq!(plan.winner_updates.len(), 2);
+    }
+
+    #[test]
+    fn test_runoff_and_skipped_races() {
+        let runoff_election_id = uuid::Uuid::from_u128(8);
+        let mut unreported = primary(2, "tx-house-2-republican-primary", "House 2", &[(4, 10)]);
+        unreported.is_fully_reported = false;
+        let primaries = [
+            primary(
+                1,
+                "tx-house-1-republican-primary",
+                "House 1 Republican - Primary",
+                &[(1, 45), (2, 35), (3, 20)],
+            ),
+            unreported,
+        ];
+        let plan = plan_races(State::TX, &primaries, Some(runoff_election_id), None);
+
+        assert_eq!(plan.races.len(), 1);
+        assert_eq!(plan.races[0].kind, AdvancementKind::Runoff);
+        assert_eq!(plan.races[0].slug, "tx-house-1-republican-primary-runoff");
+        assert_eq!(plan.races[0].election_id, runoff_election_id);
+        assert_eq!(
+            plan.races[0].candidate_ids,
+            vec![candidate(1), candidate(2)]
+        );
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].race_id, primaries[1].id);
+
+        // Without a majority rule the plurality winner goes straight to the general
+        let plan = plan_races(State::MN, &primaries[..1], None, Some(runoff_election_id));
+        assert_eq!(plan.races[0].kind, AdvancementKind::General);
+        assert_eq!(plan.races[0].candidate_ids, vec![candidate(1)]);
+    }
+
+    #[test]
+    fn test_called_race_uses_staff_winners() {
+        let mut race = primary(
+            1,
+            "tx-house-1-primary",
+            "House 1",
+            &[(1, 45), (2, 35), (3, 20)],
+        );
+        race.status = RaceStatus::Called;
+        race.winner_ids = vec![candidate(1)];
+        race.is_fully_reported = false;
+        let plan = plan_races(State::TX, &[race], None, Some(uuid::Uuid::from_u128(9)));
+
+        assert_eq!(plan.races[0].kind, AdvancementKind::General);
+        assert_eq!(plan.races[0].candidate_ids, vec![candidate(1)]);
+        assert!(plan.winner_updates.is_empty());
+    }
+}
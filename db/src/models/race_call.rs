@@ -0,0 +1,535 @@
+use async_graphql::{Enum, InputObject};
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::enums::{RaceStatus, RaceType, State, VoteType},
+    DateTime, Error,
+};
+
+/// Share of the vote a candidate needs to win outright before a runoff is held, and how
+/// many candidates advance to it
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RunoffRule {
+    pub threshold: f64,
+    pub advancing: usize,
+}
+
+const MAJORITY: RunoffRule = RunoffRule {
+    threshold: 0.5,
+    advancing: 2,
+};
+
+/// Runoff rules for single seat races. States not listed here elect by plurality.
+pub fn runoff_rule(state: Option<State>, race_type: RaceType) -> Option<RunoffRule> {
+    match (state?, race_type) {
+        (State::TX | State::AL | State::AR | State::MS | State::OK, RaceType::Primary) => {
+            Some(MAJORITY)
+        }
+        (State::GA, _) => Some(MAJORITY),
+        (State::NC, RaceType::Primary) => Some(RunoffRule {
+            threshold: 0.3,
+            advancing: 2,
+        }),
+        _ => None,
+    }
+}
+
+#[derive(Enum, Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ProjectionOutcome {
+    /// The race has its winners
+    Decided,
+    /// No candidate reached the runoff threshold, the leaders advance to a runoff
+    Runoff,
+    /// Candidates are tied for the last winning or advancing place
+    Tie,
+    NoVotes,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Projection {
+    pub outcome: ProjectionOutcome,
+    /// Winners, or the candidates advancing to a runoff. On a tie, only the candidates
+    /// ahead of the tie.
+    pub winner_ids: Vec<uuid::Uuid>,
+    pub tied_candidate_ids: Vec<uuid::Uuid>,
+}
+
+/// Picks winners from candidate vote totals. `total_votes` is the race's total, which can
+/// include write-ins and is used for the runoff threshold; the sum of `votes` is used if
+/// it's missing or smaller.
+pub fn project_winners(
+    votes: &[(uuid::Uuid, i64)],
+    total_votes: Option<i64>,
+    num_elect: usize,
+    runoff: Option<RunoffRule>,
+) -> Projection {
+    let mut ranked = votes.to_vec();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let sum: i64 = ranked.iter().map(|(_, v)| v).sum();
+    let total_votes = total_votes.unwrap_or(0).max(sum);
+    if total_votes == 0 {
+        return Projection {
+            outcome: ProjectionOutcome::NoVotes,
+            winner_ids: vec![],
+            tied_candidate_ids: vec![],
+        };
+    }
+
+    let num_elect = num_elect.max(1);
+    let (places, outcome) = match runoff {
+        Some(rule) if num_elect == 1 && ranked.len() > rule.advancing => {
+            let leader_share = ranked[0].1 as f64 / total_votes as f64;
+            if leader_share > rule.threshold {
+                (1, ProjectionOutcome::Decided)
+            } else {
+                (rule.advancing, ProjectionOutcome::Runoff)
+            }
+        }
+        _ => (num_elect, ProjectionOutcome::Decided),
+    };
+
+    if ranked.len() <= places {
+        return Projection {
+            outcome,
+            winner_ids: ranked.iter().map(|(id, _)| *id).collect(),
+            tied_candidate_ids: vec![],
+        };
+    }
+
+    // A tie straddling the last place means staff have to resolve it, e.g. by lot
+    let cutoff = ranked[places - 1].1;
+    if ranked[places].1 == cutoff {
+        return Projection {
+            outcome: ProjectionOutcome::Tie,
+            winner_ids: ranked
+                .iter()
+                .filter(|(_, v)| *v > cutoff)
+                .map(|(id, _)| *id)
+                .collect(),
+            tied_candidate_ids: ranked
+                .iter()
+                .filter(|(_, v)| *v == cutoff)
+                .map(|(id, _)| *id)
+                .collect(),
+        };
+    }
+
+    Projection {
+        outcome,
+        winner_ids: ranked.iter().take(places).map(|(id, _)| *id).collect(),
+        tied_candidate_ids: vec![],
+    }
+}
+
+/// A change in a race's status, with the winners it was called for
+#[derive(FromRow, Debug, Clone)]
+pub struct RaceCall {
+    pub id: uuid::Uuid,
+    pub race_id: uuid::Uuid,
+    pub status: RaceStatus,
+    pub winner_ids: Vec<uuid::Uuid>,
+    pub is_runoff: bool,
+    pub tied_candidate_ids: Vec<uuid::Uuid>,
+    pub note: Option<String>,
+    pub created_by: Option<uuid::Uuid>,
+    pub created_at: DateTime,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RaceStatusRecord {
+    pub status: RaceStatus,
+    pub status_updated_at: Option<DateTime>,
+    pub status_updated_by: Option<uuid::Uuid>,
+}
+
+#[derive(InputObject, Debug)]
+pub struct UpdateRaceStatusInput {
+    pub race_id: uuid::Uuid,
+    pub status: RaceStatus,
+    /// Defaults to the latest projected winners, or the race's current winners if it
+    /// hasn't been projected
+    pub winner_ids: Option<Vec<uuid::Uuid>>,
+    pub is_runoff: Option<bool>,
+    pub note: Option<String>,
+}
+
+impl RaceCall {
+    pub async fn find_by_race_id(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            RaceCall,
+            r#"
+            SELECT id, race_id, status AS "status:RaceStatus", winner_ids, is_runoff,
+                tied_candidate_ids, note, created_by, created_at
+            FROM race_call
+            WHERE race_id = $1
+            ORDER BY created_at DESC
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    pub async fn status(db_pool: &PgPool, race_id: uuid::Uuid) -> Result<RaceStatusRecord, Error> {
+        let record = sqlx::query_as!(
+            RaceStatusRecord,
+            r#"
+            SELECT status AS "status:RaceStatus", status_updated_at, status_updated_by
+            FROM race
+            WHERE id = $1
+            "#,
+            race_id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Computes winners from a race's current candidate votes without saving anything
+    pub async fn projection(db_pool: &PgPool, race_id: uuid::Uuid) -> Result<Projection, Error> {
+        let race = sqlx::query!(
+            r#"
+            SELECT state AS "state:State", race_type AS "race_type:RaceType", num_elect, total_votes
+            FROM race
+            WHERE id = $1
+            "#,
+            race_id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        let votes = sqlx::query!(
+            r#"
+            SELECT candidate_id, COALESCE(votes, 0)::bigint AS "votes!"
+            FROM race_candidates
+            WHERE race_id = $1 AND is_running
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?
+        .into_iter()
+        .map(|r| (r.candidate_id, r.votes))
+        .collect::<Vec<_>>();
+
+        Ok(project_winners(
+            &votes,
+            race.total_votes.map(i64::from),
+            race.num_elect.unwrap_or(1).max(1) as usize,
+            runoff_rule(race.state, race.race_type),
+        ))
+    }
+
+    /// Projects winners for a race and records the projection, unless staff have already
+    /// called it. Ties and races without votes are recorded but leave the race uncalled.
+    /// Projected winners stay in `race_call` until staff confirm them. Ranked choice races
+    /// are decided by tabulating their ballots, so they're never projected.
+    pub async fn project(db_pool: &PgPool, race_id: uuid::Uuid) -> Result<Option<Self>, Error> {
+        let current = sqlx::query!(
+            r#"
+            SELECT status AS "status:RaceStatus", vote_type AS "vote_type:VoteType"
+            FROM race
+            WHERE id = $1
+            "#,
+            race_id
+        )
+        .fetch_one(db_pool)
+        .await?;
+        if current.vote_type == VoteType::RankedChoice
+            || !matches!(current.status, RaceStatus::Uncalled | RaceStatus::Projected)
+        {
+            return Ok(None);
+        }
+
+        let projection = Self::projection(db_pool, race_id).await?;
+        let status = match projection.outcome {
+            ProjectionOutcome::Decided | ProjectionOutcome::Runoff => RaceStatus::Projected,
+            ProjectionOutcome::Tie | ProjectionOutcome::NoVotes => RaceStatus::Uncalled,
+        };
+
+        // Don't log the same projection on every results poll
+        let latest = sqlx::query!(
+            r#"
+            SELECT winner_ids, tied_candidate_ids, status AS "status:RaceStatus"
+            FROM race_call
+            WHERE race_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            race_id
+        )
+        .fetch_optional(db_pool)
+        .await?;
+        if let Some(latest) = latest {
+            if latest.status == status
+                && latest.winner_ids == projection.winner_ids
+                && latest.tied_candidate_ids == projection.tied_candidate_ids
+            {
+                return Ok(None);
+            }
+        }
+
+        let note = match projection.outcome {
+            ProjectionOutcome::Tie => Some("Tied for the last place, needs review".to_string()),
+            ProjectionOutcome::NoVotes => Some("No votes reported".to_string()),
+            _ => None,
+        };
+        let call = Self::record(
+            db_pool,
+            race_id,
+            status,
+            &projection.winner_ids,
+            projection.outcome == ProjectionOutcome::Runoff,
+            &projection.tied_candidate_ids,
+            note,
+            None,
+        )
+        .await?;
+
+        Ok(Some(call))
+    }
+
+    /// Projects every plurality race of an election that has all of its precincts
+    /// reporting. Returns the number of races whose projection changed.
+    pub async fn project_election(
+        db_pool: &PgPool,
+        election_id: uuid::Uuid,
+    ) -> Result<usize, Error> {
+        let race_ids = sqlx::query!(
+            r#"
+            SELECT id FROM race
+            WHERE election_id = $1
+            AND status IN ('uncalled', 'projected')
+            AND vote_type <> 'ranked_choice'
+            AND total_precincts IS NOT NULL
+            AND num_precincts_reporting = total_precincts
+            "#,
+            election_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        let mut changed = 0;
+        for record in race_ids {
+            if Self::project(db_pool, record.id).await?.is_some() {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Moves a race to a new status on behalf of a staff member
+    pub async fn update_status(
+        db_pool: &PgPool,
+        input: &UpdateRaceStatusInput,
+        user_id: uuid::Uuid,
+    ) -> Result<Self, Error> {
+        let current = Self::status(db_pool, input.race_id).await?;
+        if !current.status.can_transition_to(input.status) {
+            return Err(Error::Custom(format!(
+                "A {} race can't be marked as {}",
+                current.status, input.status
+            )));
+        }
+
+        let race = sqlx::query!(
+            r#"
+            SELECT
+                CASE WHEN status = 'projected' THEN (
+                    SELECT winner_ids FROM race_call
+                    WHERE race_id = $1
+                    ORDER BY created_at DESC
+                    LIMIT 1
+                ) ELSE winner_ids END AS winner_ids,
+                ARRAY(
+                    SELECT candidate_id FROM race_candidates WHERE race_id = $1
+                ) AS "candidate_ids!"
+            FROM race
+            WHERE id = $1
+            "#,
+            input.race_id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        let winner_ids = match input.status {
+            RaceStatus::Uncalled => vec![],
+            _ => input
+                .winner_ids
+                .clone()
+                .or(race.winner_ids)
+                .unwrap_or_default(),
+        };
+        if matches!(input.status, RaceStatus::Called | RaceStatus::Certified)
+            && winner_ids.is_empty()
+        {
+            return Err(Error::Custom(format!(
+                "Winners are required to mark a race as {}",
+                input.status
+            )));
+        }
+        if let Some(id) = winner_ids
+            .iter()
+            .find(|id| !race.candidate_ids.contains(id))
+        {
+            return Err(Error::Custom(format!(
+                "Candidate {} is not running in this race",
+                id
+            )));
+        }
+
+        Self::record(
+            db_pool,
+            input.race_id,
+            input.status,
+            &winner_ids,
+            input.is_runoff.unwrap_or(false),
+            &[],
+            input.note.clone(),
+            Some(user_id),
+        )
+        .await
+    }
+
+    /// Saves a status change. Only calls made by staff change the race's public winners.
+    #[allow(clippy::too_many_arguments)]
+    async fn record(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+        status: RaceStatus,
+        winner_ids: &[uuid::Uuid],
+        is_runoff: bool,
+        tied_candidate_ids: &[uuid::Uuid],
+        note: Option<String>,
+        created_by: Option<uuid::Uuid>,
+    ) -> Result<Self, Error> {
+        let mut tx = db_pool.begin().await?;
+
+        let published_winner_ids = created_by.map(|_| winner_ids);
+        sqlx::query!(
+            r#"
+            UPDATE race SET
+                status = $2,
+                winner_ids = COALESCE($3, winner_ids),
+                status_updated_at = now(),
+                status_updated_by = $4
+            WHERE id = $1
+            "#,
+            race_id,
+            status as RaceStatus,
+            published_winner_ids,
+            created_by
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let call = sqlx::query_as!(
+            RaceCall,
+            r#"
+            INSERT INTO race_call (race_id, status, winner_ids, is_runoff, tied_candidate_ids, note, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, race_id, status AS "status:RaceStatus", winner_ids, is_runoff,
+                tied_candidate_ids, note, created_by, created_at
+            "#,
+            race_id,
+            status as RaceStatus,
+            winner_ids,
+            is_runoff,
+            tied_candidate_ids,
+            note,
+            created_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(call)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(n: u128) -> uuid::Uuid {
+        uuid::Uuid::from_u128(n)
+    }
+
+    #[test]
+    fn test_plurality_winners() {
+        let votes = [(candidate(1), 40), (candidate(2), 35), (candidate(3), 25)];
+        let projection = project_winners(&votes, None, 2, None);
+
+        assert_eq!(projection.outcome, ProjectionOutcome::Decided);
+        assert_eq!(projection.winner_ids, vec![candidate(1), candidate(2)]);
+    }
+
+    #[test]
+    fn test_primary_runoff() {
+        let votes = [(candidate(1), 45), (candidate(2), 35), (candidate(3), 20)];
+        let rule = runoff_rule(Some(State::TX), RaceType::Primary);
+        let projection = project_winners(&votes, None, 1, rule);
+
+        assert_eq!(projection.outcome, ProjectionOutcome::Runoff);
+        assert_eq!(projection.winner_ids, vec![candidate(1), candidate(2)]);
+
+        // Write-ins count towards the total, so 51 of 105 votes is not a majority
+        let votes = [(candidate(1), 51), (candidate(2), 30), (candidate(3), 19)];
+        assert_eq!(
+            project_winners(&votes, Some(105), 1, rule).outcome,
+            ProjectionOutcome::Runoff
+        );
+        assert_eq!(
+            project_winners(&votes, Some(100), 1, rule).winner_ids,
+            vec![candidate(1)]
+        );
+    }
+
+    #[test]
+    fn test_general_has_no_runoff_in_tx() {
+        assert_eq!(runoff_rule(Some(State::TX), RaceType::General), None);
+        assert_eq!(runoff_rule(None, RaceType::Primary), None);
+    }
+
+    #[test]
+    fn test_tie_for_last_place() {
+        let votes = [
+            (candidate(1), 50),
+            (candidate(2), 30),
+            (candidate(3), 30),
+            (candidate(4), 10),
+        ];
+        let projection = project_winners(&votes, None, 2, None);
+
+        assert_eq!(projection.outcome, ProjectionOutcome::Tie);
+        assert_eq!(projection.winner_ids, vec![candidate(1)]);
+        assert_eq!(
+            projection.tied_candidate_ids,
+            vec![candidate(2), candidate(3)]
+        );
+    }
+
+    #[test]
+    fn test_no_votes() {
+        let votes = [(candidate(1), 0), (candidate(2), 0)];
+        assert_eq!(
+            project_winners(&votes, None, 1, None).outcome,
+            ProjectionOutcome::NoVotes
+        );
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        assert!(RaceStatus::Projected.can_transition_to(RaceStatus::Called));
+        assert!(RaceStatus::Called.can_transition_to(RaceStatus::Recount));
+        assert!(!RaceStatus::Certified.can_transition_to(RaceStatus::Uncalled));
+        assert!(!RaceStatus::Uncalled.can_transition_to(RaceStatus::Certified));
+    }
+}
@@ -0,0 +1,667 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+use async_graphql::InputObject;
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::{enums::VoteType, race::Race},
+    DateTime, Error,
+};
+
+/// Stands in for write-in candidates on imported ballots. Write-ins are counted together
+/// like a candidate, so their ballots stay with them until they're eliminated.
+pub const WRITE_IN_CANDIDATE_ID: uuid::Uuid = uuid::Uuid::nil();
+
+/// A group of identical ballots cast in a ranked choice race
+#[derive(FromRow, Debug, Clone)]
+pub struct RankedChoiceBallot {
+    pub id: uuid::Uuid,
+    pub race_id: uuid::Uuid,
+    pub rankings: Vec<uuid::Uuid>,
+    pub count: i32,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(InputObject, Debug, Clone)]
+pub struct RankedBallotInput {
+    /// Candidate ids in order of preference, first choice first
+    pub rankings: Vec<uuid::Uuid>,
+    /// Number of ballots with these exact rankings, defaults to 1
+    pub count: Option<i32>,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RankedChoiceRound {
+    pub race_id: uuid::Uuid,
+    pub round: i32,
+    pub quota: f64,
+    pub exhausted_votes: f64,
+    pub write_in_votes: f64,
+    pub elected_ids: Vec<uuid::Uuid>,
+    pub eliminated_ids: Vec<uuid::Uuid>,
+    pub created_at: DateTime,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct RankedChoiceRoundTally {
+    pub race_id: uuid::Uuid,
+    pub round: i32,
+    pub candidate_id: uuid::Uuid,
+    pub votes: f64,
+    pub transferred: f64,
+}
+
+/// Drops repeated rankings of the same candidate, keeping the highest one
+fn dedup_rankings(rankings: &[uuid::Uuid]) -> Vec<uuid::Uuid> {
+    let mut seen = HashSet::new();
+    rankings
+        .iter()
+        .filter(|id| seen.insert(**id))
+        .copied()
+        .collect()
+}
+
+impl RankedChoiceBallot {
+    /// Imports ballot rankings for a race, adding to the counts of rankings that were
+    /// already imported unless `replace` is set. Returns the race's total ballot count.
+    pub async fn import(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+        ballots: &[RankedBallotInput],
+        replace: bool,
+    ) -> Result<i64, Error> {
+        let mut aggregated: BTreeMap<Vec<uuid::Uuid>, i32> = BTreeMap::new();
+        for ballot in ballots {
+            let count = ballot.count.unwrap_or(1);
+            if count < 1 {
+                return Err(Error::Custom(format!(
+                    "Ballot count must be positive, got {}",
+                    count
+                )));
+            }
+            let rankings = dedup_rankings(&ballot.rankings);
+            // Fully blank ballots don't count towards any round
+            if rankings.is_empty() {
+                continue;
+            }
+            *aggregated.entry(rankings).or_insert(0) += count;
+        }
+
+        let mut tx = db_pool.begin().await?;
+        if replace {
+            sqlx::query!(
+                "DELETE FROM ranked_choice_ballot WHERE race_id = $1",
+                race_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for (rankings, count) in aggregated {
+            sqlx::query!(
+                r#"
+                INSERT INTO ranked_choice_ballot (race_id, rankings, count)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (race_id, rankings) DO UPDATE SET
+                    count = ranked_choice_ballot.count + EXCLUDED.count
+                "#,
+                race_id,
+                &rankings,
+                count
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let total = sqlx::query!(
+            r#"
+            SELECT COALESCE(SUM(count), 0)::BIGINT AS "total!"
+            FROM ranked_choice_ballot
+            WHERE race_id = $1
+            "#,
+            race_id
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .total;
+
+        tx.commit().await?;
+        Ok(total)
+    }
+
+    pub async fn find_by_race_id(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            RankedChoiceBallot,
+            r#"
+            SELECT id, race_id, rankings, count, created_at, updated_at
+            FROM ranked_choice_ballot
+            WHERE race_id = $1
+            ORDER BY count DESC
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
+
+impl RankedChoiceRound {
+    pub async fn find_by_race_id(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            RankedChoiceRound,
+            r#"
+            SELECT race_id, round, quota, exhausted_votes, write_in_votes, elected_ids,
+                eliminated_ids, created_at
+            FROM ranked_choice_round
+            WHERE race_id = $1
+            ORDER BY round
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Tabulates a ranked choice race from its imported ballots, replacing any stored
+    /// rounds and setting the race's winners
+    pub async fn tabulate_race(db_pool: &PgPool, race_id: uuid::Uuid) -> Result<Tabulation, Error> {
+        let race = Race::find_by_id(db_pool, race_id).await?;
+        if race.vote_type != VoteType::RankedChoice {
+            return Err(Error::Custom(format!(
+                "Race {} is not a ranked choice race",
+                race.title
+            )));
+        }
+
+        let candidate_ids = sqlx::query!(
+            "SELECT candidate_id FROM race_candidates WHERE race_id = $1",
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?
+        .into_iter()
+        .map(|r| r.candidate_id)
+        .collect::<Vec<uuid::Uuid>>();
+
+        let ballots = RankedChoiceBallot::find_by_race_id(db_pool, race_id).await?;
+        if ballots.is_empty() {
+            return Err(Error::Custom(format!(
+                "No ballots have been imported for race {}",
+                race.title
+            )));
+        }
+
+        let unknown_ids = ballots
+            .iter()
+            .flat_map(|b| b.rankings.iter())
+            .filter(|id| **id != WRITE_IN_CANDIDATE_ID && !candidate_ids.contains(id))
+            .collect::<HashSet<_>>();
+        if !unknown_ids.is_empty() {
+            return Err(Error::Custom(format!(
+                "Ballots rank {} candidate(s) who are not in race {}",
+                unknown_ids.len(),
+                race.title
+            )));
+        }
+
+        let seats = race.num_elect.unwrap_or(1).max(1) as usize;
+        let tabulation = tabulate(&candidate_ids, &ballots, seats);
+        if tabulation.winner_ids.contains(&WRITE_IN_CANDIDATE_ID) {
+            return Err(Error::Custom(format!(
+                "Write-ins won a seat in race {}, which has to be resolved by hand",
+                race.title
+            )));
+        }
+
+        let mut tx = db_pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM ranked_choice_round WHERE race_id = $1",
+            race_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let candidates = |ids: &[uuid::Uuid]| -> Vec<uuid::Uuid> {
+            ids.iter()
+                .filter(|id| **id != WRITE_IN_CANDIDATE_ID)
+                .copied()
+                .collect()
+        };
+        for round in &tabulation.rounds {
+            // Write-ins aren't a politician, so their votes are kept on the round
+            let write_in_votes = round
+                .tallies
+                .iter()
+                .find(|t| t.candidate_id == WRITE_IN_CANDIDATE_ID)
+                .map_or(0.0, |t| t.votes);
+            sqlx::query!(
+                r#"
+                INSERT INTO ranked_choice_round (race_id, round, quota, exhausted_votes, write_in_votes, elected_ids, eliminated_ids)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                race_id,
+                round.round,
+                round.quota,
+                round.exhausted_votes,
+                write_in_votes,
+                &candidates(&round.elected_ids),
+                &candidates(&round.eliminated_ids)
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            let (candidate_ids, (votes, transferred)): (Vec<uuid::Uuid>, (Vec<f64>, Vec<f64>)) =
+                round
+                    .tallies
+                    .iter()
+                    .filter(|t| t.candidate_id != WRITE_IN_CANDIDATE_ID)
+                    .map(|t| (t.candidate_id, (t.votes, t.transferred)))
+                    .unzip();
+            sqlx::query!(
+                r#"
+                INSERT INTO ranked_choice_round_tally (race_id, round, candidate_id, votes, transferred)
+                SELECT $1, $2, * FROM UNNEST($3::uuid[], $4::float8[], $5::float8[])
+                "#,
+                race_id,
+                round.round,
+                &candidate_ids,
+                &votes,
+                &transferred
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query!(
+            "UPDATE race SET winner_ids = $2 WHERE id = $1",
+            race_id,
+            &tabulation.winner_ids
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(tabulation)
+    }
+}
+
+impl RankedChoiceRoundTally {
+    pub async fn find_by_race_id(
+        db_pool: &PgPool,
+        race_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            RankedChoiceRoundTally,
+            r#"
+            SELECT race_id, round, candidate_id, votes, transferred
+            FROM ranked_choice_round_tally
+            WHERE race_id = $1
+            ORDER BY round, votes DESC
+            "#,
+            race_id
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateTally {
+    pub candidate_id: uuid::Uuid,
+    pub votes: f64,
+    pub transferred: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabulationRound {
+    pub round: i32,
+    pub quota: f64,
+    pub exhausted_votes: f64,
+    /// Continuing and elected candidates, most votes first
+    pub tallies: Vec<CandidateTally>,
+    pub elected_ids: Vec<uuid::Uuid>,
+    pub eliminated_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tabulation {
+    pub rounds: Vec<TabulationRound>,
+    /// In the order they were elected
+    pub winner_ids: Vec<uuid::Uuid>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum CandidateStatus {
+    Continuing,
+    Elected,
+    Eliminated,
+}
+
+/// Runs an instant runoff (one seat) or single transferable vote (several seats) count.
+///
+/// Single seat races elect the first candidate with a majority of continuing votes.
+/// Multi-seat races use a Droop quota fixed in the first round, and transfer the surplus
+/// of elected candidates at a fractional value, as in Minneapolis' multi-seat races.
+/// The last place candidate is eliminated when nobody reaches the quota, or every
+/// candidate without votes at once. Ties for last place are broken by the earlier round
+/// with the fewest votes, then by candidate id so recounts are reproducible.
+pub fn tabulate(
+    candidate_ids: &[uuid::Uuid],
+    ballots: &[RankedChoiceBallot],
+    seats: usize,
+) -> Tabulation {
+    let mut status: BTreeMap<uuid::Uuid, CandidateStatus> = candidate_ids
+        .iter()
+        .chain(ballots.iter().flat_map(|b| b.rankings.iter()))
+        .map(|id| (*id, CandidateStatus::Continuing))
+        .collect();
+    let seats = seats.clamp(1, status.len().max(1));
+
+    let mut weighted: Vec<(Vec<uuid::Uuid>, f64)> = ballots
+        .iter()
+        .map(|b| (dedup_rankings(&b.rankings), b.count as f64))
+        .collect();
+
+    let mut rounds: Vec<TabulationRound> = vec![];
+    let mut winner_ids: Vec<uuid::Uuid> = vec![];
+    let mut history: Vec<BTreeMap<uuid::Uuid, f64>> = vec![];
+    let mut fixed_quota: Option<f64> = None;
+
+    loop {
+        let continuing: Vec<uuid::Uuid> = status
+            .iter()
+            .filter(|(_, s)| **s == CandidateStatus::Continuing)
+            .map(|(id, _)| *id)
+            .collect();
+        if winner_ids.len() >= seats || continuing.is_empty() {
+            break;
+        }
+
+        // Each ballot counts for its highest ranked continuing candidate
+        let mut votes: BTreeMap<uuid::Uuid, f64> = continuing.iter().map(|id| (*id, 0.0)).collect();
+        let mut exhausted_votes = 0.0;
+        let mut assigned: Vec<Option<uuid::Uuid>> = Vec::with_capacity(weighted.len());
+        for (rankings, weight) in &weighted {
+            let top = rankings
+                .iter()
+                .find(|id| status.get(id) == Some(&CandidateStatus::Continuing))
+                .copied();
+            match top {
+                Some(id) => *votes.entry(id).or_insert(0.0) += weight,
+                None => exhausted_votes += weight,
+            }
+            assigned.push(top);
+        }
+
+        let continuing_votes: f64 = votes.values().sum();
+        let quota = if seats == 1 {
+            (continuing_votes / 2.0).floor() + 1.0
+        } else {
+            *fixed_quota
+                .get_or_insert_with(|| (continuing_votes / (seats as f64 + 1.0)).floor() + 1.0)
+        };
+
+        let remaining_seats = seats - winner_ids.len();
+        let mut by_votes = continuing.clone();
+        by_votes.sort_by(|a, b| votes[b].total_cmp(&votes[a]).then(a.cmp(b)));
+
+        let mut elected_ids: Vec<uuid::Uuid> = if continuing.len() <= remaining_seats {
+            by_votes.clone()
+        } else {
+            by_votes
+                .iter()
+                .filter(|id| votes[*id] >= quota)
+                .take(remaining_seats)
+                .copied()
+                .collect()
+        };
+
+        let mut eliminated_ids = vec![];
+        if elected_ids.is_empty() {
+            let without_votes: Vec<uuid::Uuid> = continuing
+                .iter()
+                .filter(|id| votes[*id] == 0.0)
+                .copied()
+                .collect();
+            if !without_votes.is_empty()
+                && continuing.len() - without_votes.len() >= remaining_seats
+            {
+                eliminated_ids = without_votes;
+            } else {
+                eliminated_ids.push(last_place(&continuing, &votes, &history));
+            }
+        }
+        elected_ids.sort_by(|a, b| votes[b].total_cmp(&votes[a]).then(a.cmp(b)));
+
+        let previous = history.last();
+        let mut tallies: Vec<CandidateTally> = by_votes
+            .iter()
+            .map(|id| CandidateTally {
+                candidate_id: *id,
+                votes: votes[id],
+                transferred: previous
+                    .map_or(0.0, |p| votes[id] - p.get(id).copied().unwrap_or(0.0)),
+            })
+            .collect();
+        // Candidates elected in earlier rounds keep the quota and pass on the rest
+        tallies.splice(
+            0..0,
+            winner_ids.iter().map(|id| CandidateTally {
+                candidate_id: *id,
+                votes: quota,
+                transferred: 0.0,
+            }),
+        );
+
+        rounds.push(TabulationRound {
+            round: rounds.len() as i32 + 1,
+            quota,
+            exhausted_votes,
+            tallies,
+            elected_ids: elected_ids.clone(),
+            eliminated_ids: eliminated_ids.clone(),
+        });
+
+        for id in &elected_ids {
+            let surplus = votes[id] - quota;
+            if surplus > 0.0 {
+                let transfer_value = surplus / votes[id];
+                for ((_, weight), top) in weighted.iter_mut().zip(&assigned) {
+                    if top.as_ref() == Some(id) {
+                        *weight *= transfer_value;
+                    }
+                }
+            }
+            status.insert(*id, CandidateStatus::Elected);
+            winner_ids.push(*id);
+        }
+        for id in &eliminated_ids {
+            status.insert(*id, CandidateStatus::Eliminated);
+        }
+        history.push(votes);
+    }
+
+    Tabulation { rounds, winner_ids }
+}
+
+fn last_place(
+    continuing: &[uuid::Uuid],
+    votes: &BTreeMap<uuid::Uuid, f64>,
+    history: &[BTreeMap<uuid::Uuid, f64>],
+) -> uuid::Uuid {
+    *continuing
+        .iter()
+        .min_by(|a, b| {
+            let earlier = history.iter().rev().map(|round| {
+                let a_votes = round.get(*a).copied().unwrap_or(0.0);
+                let b_votes = round.get(*b).copied().unwrap_or(0.0);
+                a_votes.total_cmp(&b_votes)
+            });
+            std::iter::once(votes[*a].total_cmp(&votes[*b]))
+                .chain(earlier)
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or_else(|| a.cmp(b))
+        })
+        .expect("at least one continuing candidate")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(n: u128) -> uuid::Uuid {
+        uuid::Uuid::from_u128(n)
+    }
+
+    fn ballot(rankings: &[u128], count: i32) -> RankedChoiceBallot {
+        RankedChoiceBallot {
+            id: uuid::Uuid::new_v4(),
+            race_id: uuid::Uuid::nil(),
+            rankings: rankings.iter().map(|n| candidate(*n)).collect(),
+            count,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_first_round_majority() {
+        let ballots = vec![ballot(&[1, 2], 6), ballot(&[2, 1], 4)];
+        let tabulation = tabulate(&[candidate(1), candidate(2)], &ballots, 1);
+
+        assert_eq!(tabulation.rounds.len(), 1);
+        assert_eq!(tabulation.rounds[0].quota, 6.0);
+        assert_eq!(tabulation.winner_ids, vec![candidate(1)]);
+    }
+
+    #[test]
+    fn test_instant_runoff_transfers_and_exhausts() {
+        let ballots = vec![
+            ballot(&[1], 8),
+            ballot(&[2, 3], 7),
+            ballot(&[3, 2], 4),
+            ballot(&[3], 2),
+        ];
+        let candidates = [candidate(1), candidate(2), candidate(3)];
+        let tabulation = tabulate(&candidates, &ballots, 1);
+
+        assert_eq!(tabulation.rounds.len(), 2);
+        let first = &tabulation.rounds[0];
+        assert_eq!(first.eliminated_ids, vec![candidate(3)]);
+        assert!(first.elected_ids.is_empty());
+
+        let second = &tabulation.rounds[1];
+        assert_eq!(second.exhausted_votes, 2.0);
+        assert_eq!(second.quota, 10.0);
+        assert_eq!(second.elected_ids, vec![candidate(2)]);
+        let runner_up = second
+            .tallies
+            .iter()
+            .find(|t| t.candidate_id == candidate(2))
+            .unwrap();
+        assert_eq!(runner_up.votes, 11.0);
+        assert_eq!(runner_up.transferred, 4.0);
+    }
+
+    #[test]
+    fn test_single_transferable_vote_surplus() {
+        // 30 votes, 2 seats: Droop quota is 11
+        let ballots = vec![
+            ballot(&[1, 2], 16),
+            ballot(&[2], 4),
+            ballot(&[3], 6),
+            ballot(&[4, 3], 4),
+        ];
+        let candidates = [candidate(1), candidate(2), candidate(3), candidate(4)];
+        let tabulation = tabulate(&candidates, &ballots, 2);
+
+        let first = &tabulation.rounds[0];
+        assert_eq!(first.quota, 11.0);
+        assert_eq!(first.elected_ids, vec![candidate(1)]);
+
+        // 5 surplus votes move to candidate 2, who then leads candidate 3
+        let second = &tabulation.rounds[1];
+        let second_choice = second
+            .tallies
+            .iter()
+            .find(|t| t.candidate_id == candidate(2))
+            .unwrap();
+        assert!((second_choice.votes - 9.0).abs() < 1e-9);
+        assert!((second_choice.transferred - 5.0).abs() < 1e-9);
+        assert_eq!(second.eliminated_ids, vec![candidate(4)]);
+
+        assert_eq!(tabulation.winner_ids, vec![candidate(1), candidate(3)]);
+    }
+
+    #[test]
+    fn test_candidates_without_votes_are_eliminated_together() {
+        let ballots = vec![ballot(&[1], 3), ballot(&[2], 2), ballot(&[3], 2)];
+        let candidates = [
+            candidate(1),
+            candidate(2),
+            candidate(3),
+            candidate(4),
+            candidate(5),
+        ];
+        let tabulation = tabulate(&candidates, &ballots, 1);
+
+        assert_eq!(
+            tabulation.rounds[0].eliminated_ids,
+            vec![candidate(4), candidate(5)]
+        );
+    }
+
+    #[test]
+    fn test_write_ins_hold_ballots_until_eliminated() {
+        // Write-ins are candidate 0, the nil id
+        let ballots = vec![ballot(&[0, 1], 3), ballot(&[1], 4), ballot(&[2], 5)];
+        let tabulation = tabulate(&[candidate(1), candidate(2)], &ballots, 1);
+
+        let first = &tabulation.rounds[0];
+        assert!(first.elected_ids.is_empty());
+        assert_eq!(first.eliminated_ids, vec![WRITE_IN_CANDIDATE_ID]);
+        assert_eq!(
+            first
+                .tallies
+                .iter()
+                .find(|t| t.candidate_id == WRITE_IN_CANDIDATE_ID)
+                .map(|t| t.votes),
+            Some(3.0)
+        );
+        assert_eq!(tabulation.rounds[1].elected_ids, vec![candidate(1)]);
+        assert_eq!(tabulation.winner_ids, vec![candidate(1)]);
+    }
+
+    #[test]
+    fn test_last_place_tie_uses_earlier_rounds() {
+        let votes: BTreeMap<uuid::Uuid, f64> = [(candidate(1), 5.0), (candidate(2), 5.0)]
+            .into_iter()
+            .collect();
+        let history = vec![[(candidate(1), 4.0), (candidate(2), 3.0)]
+            .into_iter()
+            .collect()];
+
+        assert_eq!(
+            last_place(&[candidate(1), candidate(2)], &votes, &history),
+            candidate(2)
+        );
+        assert_eq!(
+            last_place(&[candidate(1), candidate(2)], &votes, &[]),
+            candidate(1)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use async_graphql::InputObject;
+use serde_json::Value as JSON;
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::enums::{ResultsSourceFormat, State},
+    DateTime, EnqueueJobInput, Error, Job, JobStatus,
+};
+
+/// Job the server's worker runs to fetch and merge a single results source
+pub const POLL_RESULTS_SOURCE_JOB: &str = "poll_results_source";
+
+/// MN SoS sources are loaded into a table of this prefix followed by the source's id
+pub const MN_STAGING_TABLE_PREFIX: &str = "p6t_state_mn.stg_results_";
+
+/// A published feed of election results, e.g. one MN SoS results file or one
+/// TX county's Clarity export, polled while its active window is open.
+#[derive(FromRow, Debug, Clone)]
+pub struct ResultsSource {
+    pub id: uuid::Uuid,
+    pub state: State,
+    pub election_id: uuid::Uuid,
+    pub name: String,
+    pub url: String,
+    pub format: ResultsSourceFormat,
+    pub options: JSON,
+    pub poll_interval_seconds: i32,
+    pub active_from: DateTime,
+    pub active_until: DateTime,
+    pub is_enabled: bool,
+    pub last_polled_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(InputObject, Debug)]
+pub struct UpsertResultsSourceInput {
+    pub id: Option<uuid::Uuid>,
+    pub state: State,
+    pub election_id: uuid::Uuid,
+    pub name: String,
+    pub url: String,
+    pub format: ResultsSourceFormat,
+    /// Format specific settings: `county` and `party` for TX Clarity and Hart,
+    /// `webdriverUrl` for TX Civix
+    pub options: Option<JSON>,
+    pub poll_interval_seconds: Option<i32>,
+    pub active_from: DateTime,
+    pub active_until: DateTime,
+    pub is_enabled: Option<bool>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct ResultsSourceFilter {
+    pub state: Option<State>,
+    pub election_id: Option<uuid::Uuid>,
+    /// Only sources whose active window includes now
+    pub active: Option<bool>,
+}
+
+impl ResultsSource {
+    /// Staging table the source is loaded into before results are merged into races. TX
+    /// formats share their format's table, MN SoS sources get one of their own.
+    pub fn staging_table(&self) -> String {
+        match self.format.staging_table() {
+            Some(staging_table) => staging_table.to_string(),
+            None => format!("{}{}", MN_STAGING_TABLE_PREFIX, self.id.simple()),
+        }
+    }
+
+    pub async fn upsert(db_pool: &PgPool, input: &UpsertResultsSourceInput) -> Result<Self, Error> {
+        let id = input.id.unwrap_or_else(uuid::Uuid::new_v4);
+
+        let record = sqlx::query_as!(
+            ResultsSource,
+            r#"
+            INSERT INTO results_source (
+                id,
+                state,
+                election_id,
+                name,
+                url,
+                format,
+                options,
+                poll_interval_seconds,
+                active_from,
+                active_until,
+                is_enabled
+            ) VALUES (
+                $1,
+                $2,
+                $3,
+                $4,
+                $5,
+                $6,
+                COALESCE($7, '{}'::jsonb),
+                COALESCE($8, 600),
+                $9,
+                $10,
+                COALESCE($11, true)
+            ) ON CONFLICT (id) DO UPDATE SET
+                state = EXCLUDED.state,
+                election_id = EXCLUDED.election_id,
+                name = EXCLUDED.name,
+                url = EXCLUDED.url,
+                format = EXCLUDED.format,
+                options = EXCLUDED.options,
+                poll_interval_seconds = EXCLUDED.poll_interval_seconds,
+                active_from = EXCLUDED.active_from,
+                active_until = EXCLUDED.active_until,
+                is_enabled = EXCLUDED.is_enabled
+            RETURNING id, state AS "state:State", election_id, name, url,
+                format AS "format:ResultsSourceFormat", options,
+                poll_interval_seconds, active_from, active_until, is_enabled, last_polled_at,
+                created_at, updated_at
+            "#,
+            id,
+            input.state as State,
+            input.election_id,
+            input.name,
+            input.url,
+            input.format as ResultsSourceFormat,
+            input.options,
+            input.poll_interval_seconds,
+            input.active_from,
+            input.active_until,
+            input.is_enabled
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    pub async fn delete(db_pool: &PgPool, id: uuid::Uuid) -> Result<(), Error> {
+        sqlx::query!("DELETE FROM results_source WHERE id = $1", id)
+            .execute(db_pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_id(db_pool: &PgPool, id: uuid::Uuid) -> Result<Self, Error> {
+        let record = sqlx::query_as!(
+            ResultsSource,
+            r#"
+            SELECT id, state AS "state:State", election_id, name, url,
+                format AS "format:ResultsSourceFormat", options,
+                poll_interval_seconds, active_from, active_until, is_enabled, last_polled_at,
+                created_at, updated_at
+            FROM results_source
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    pub async fn filter(
+        db_pool: &PgPool,
+        filter: &ResultsSourceFilter,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            ResultsSource,
+            r#"
+            SELECT id, state AS "state:State", election_id, name, url,
+                format AS "format:ResultsSourceFormat", options,
+                poll_interval_seconds, active_from, active_until, is_enabled, last_polled_at,
+                created_at, updated_at
+            FROM results_source
+            WHERE ($1::state IS NULL OR state = $1)
+            AND ($2::uuid IS NULL OR election_id = $2)
+            AND ($3::bool IS NULL OR (now() BETWEEN active_from AND active_until) = $3)
+            ORDER BY active_from DESC, name
+            "#,
+            filter.state as Option<State>,
+            filter.election_id,
+            filter.active
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Queues a poll of this source on the job queue, or returns the poll already waiting
+    /// to run. Polls are not retried since the next interval fetches fresher results anyway.
+    pub async fn enqueue_poll(&self, db_pool: &PgPool) -> Result<Job, Error> {
+        let pending = sqlx::query_as!(
+            Job,
+            r#"
+            SELECT id, name, payload, schedule, status AS "status:JobStatus", attempts,
+                max_attempts, run_at, locked_at, last_error, created_at, updated_at
+            FROM job
+            WHERE name = $1
+            AND status = 'pending'
+            AND payload->>'resultsSourceId' = $2
+            ORDER BY run_at
+            LIMIT 1
+            "#,
+            POLL_RESULTS_SOURCE_JOB,
+            self.id.to_string()
+        )
+        .fetch_optional(db_pool)
+        .await?;
+        if let Some(job) = pending {
+            return Ok(job);
+        }
+
+        let input = EnqueueJobInput {
+            name: POLL_RESULTS_SOURCE_JOB.to_string(),
+            payload: Some(serde_json::json!({ "resultsSourceId": self.id })),
+            max_attempts: Some(1),
+            ..Default::default()
+        };
+        Job::enqueue(db_pool, &input).await
+    }
+
+    /// Marks every enabled source that is inside its active window and has waited at least
+    /// its poll interval as polled, returning them. Rows locked by another instance are
+    /// skipped so each source is polled once per interval across the cluster.
+    pub async fn claim_due(db_pool: &PgPool) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(
+            ResultsSource,
+            r#"
+            UPDATE results_source SET last_polled_at = now()
+            WHERE id IN (
+                SELECT id FROM results_source
+                WHERE is_enabled
+                AND now() BETWEEN active_from AND active_until
+                AND (
+                    last_polled_at IS NULL
+                    OR last_polled_at + make_interval(secs => poll_interval_seconds) <= now()
+                )
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, state AS "state:State", election_id, name, url,
+                format AS "format:ResultsSourceFormat", options,
+                poll_interval_seconds, active_from, active_until, is_enabled, last_polled_at,
+                created_at, updated_at
+            "#
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(records)
+    }
+}
@@ -0,0 +1,197 @@
+use serde_json::{json, Value as JSON};
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::{audit_event::diff, enums::RevisionEntityType},
+    DateTime, Error,
+};
+
+/// A snapshot of a record's tracked columns, written by the `record_revision` trigger
+/// whenever they change
+#[derive(FromRow, Debug, Clone)]
+pub struct Revision {
+    pub id: uuid::Uuid,
+    pub entity_type: RevisionEntityType,
+    pub entity_id: uuid::Uuid,
+    pub revision_number: i32,
+    pub data: JSON,
+    pub created_at: DateTime,
+}
+
+impl RevisionEntityType {
+    pub fn table(&self) -> &'static str {
+        match self {
+            RevisionEntityType::QuestionSubmission => "question_submission",
+            RevisionEntityType::Politician => "politician",
+        }
+    }
+
+    /// Columns kept in each revision. Keep in sync with the `record_revision` triggers.
+    pub fn columns(&self) -> &'static [&'static str] {
+        match self {
+            RevisionEntityType::QuestionSubmission => &["response", "editorial", "translations"],
+            RevisionEntityType::Politician => &[
+                "first_name",
+                "middle_name",
+                "last_name",
+                "suffix",
+                "preferred_name",
+                "biography",
+                "biography_source",
+                "home_state",
+                "party_id",
+                "date_of_birth",
+                "thumbnail_image_url",
+                "assets",
+                "official_website_url",
+                "ballotpedia_url",
+                "campaign_website_url",
+                "facebook_url",
+                "twitter_url",
+                "instagram_url",
+                "youtube_url",
+                "linkedin_url",
+                "tiktok_url",
+                "email",
+                "phone",
+            ],
+        }
+    }
+}
+
+/// Changed columns between two revisions as `{"column": {"old": ..., "new": ...}}`
+pub fn revision_diff(from: &JSON, to: &JSON) -> JSON {
+    diff(Some(from), Some(to))
+        .map(|(_, changes)| changes)
+        .unwrap_or_else(|| json!({}))
+}
+
+impl Revision {
+    /// The record's revisions, newest first
+    pub async fn list(
+        db_pool: &PgPool,
+        entity_type: RevisionEntityType,
+        entity_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let revisions = sqlx::query_as!(
+            Revision,
+            r#"
+            SELECT id, entity_type AS "entity_type:RevisionEntityType", entity_id,
+                revision_number, data, created_at
+            FROM revision
+            WHERE entity_type = $1 AND entity_id = $2
+            ORDER BY revision_number DESC
+            "#,
+            entity_type as RevisionEntityType,
+            entity_id,
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    pub async fn find(
+        db_pool: &PgPool,
+        entity_type: RevisionEntityType,
+        entity_id: uuid::Uuid,
+        revision_number: i32,
+    ) -> Result<Self, Error> {
+        let revision = sqlx::query_as!(
+            Revision,
+            r#"
+            SELECT id, entity_type AS "entity_type:RevisionEntityType", entity_id,
+                revision_number, data, created_at
+            FROM revision
+            WHERE entity_type = $1 AND entity_id = $2 AND revision_number = $3
+            "#,
+            entity_type as RevisionEntityType,
+            entity_id,
+            revision_number,
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        revision.ok_or_else(|| {
+            Error::Custom(format!(
+                "No revision {} of {} {}",
+                revision_number, entity_type, entity_id
+            ))
+        })
+    }
+
+    /// What changed going from revision `from` to revision `to`
+    pub async fn diff(
+        db_pool: &PgPool,
+        entity_type: RevisionEntityType,
+        entity_id: uuid::Uuid,
+        from: i32,
+        to: i32,
+    ) -> Result<JSON, Error> {
+        let from = Self::find(db_pool, entity_type, entity_id, from).await?;
+        let to = Self::find(db_pool, entity_type, entity_id, to).await?;
+        Ok(revision_diff(&from.data, &to.data))
+    }
+
+    /// Puts the record's tracked columns back to how they were in `revision_number`. The
+    /// revert is itself recorded as a new revision.
+    pub async fn revert(
+        db_pool: &PgPool,
+        entity_type: RevisionEntityType,
+        entity_id: uuid::Uuid,
+        revision_number: i32,
+    ) -> Result<(), Error> {
+        let revision = Self::find(db_pool, entity_type, entity_id, revision_number).await?;
+        // Table and column names come from the enum, never from input
+        let columns = entity_type.columns();
+        let query = format!(
+            r#"
+            UPDATE {table} t SET ({columns}) = (
+                SELECT {revision_columns} FROM jsonb_populate_record(NULL::{table}, $2) r
+            )
+            WHERE t.id = $1
+            "#,
+            table = entity_type.table(),
+            columns = columns.join(", "),
+            revision_columns = columns
+                .iter()
+                .map(|column| format!("r.{}", column))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        let result = sqlx::query(&query)
+            .bind(entity_id)
+            .bind(&revision.data)
+            .execute(db_pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(Error::Custom(format!(
+                "No {} found with id {}",
+                entity_type, entity_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::revision_diff;
+
+    #[test]
+    fn test_revision_diff() {
+        let from = json!({ "response": "Roads", "editorial": null, "translations": null });
+        let to = json!({ "response": "Roads and bridges", "editorial": "Trimmed", "translations": null });
+        assert_eq!(
+            revision_diff(&from, &to),
+            json!({
+                "response": { "old": "Roads", "new": "Roads and bridges" },
+                "editorial": { "old": null, "new": "Trimmed" },
+            })
+        );
+        assert_eq!(revision_diff(&to, &to), json!({}));
+    }
+}
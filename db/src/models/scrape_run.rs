@@ -0,0 +1,139 @@
+use async_graphql::InputObject;
+use serde_json::Value as JSON;
+use sqlx::{FromRow, PgPool};
+
+use crate::{
+    models::enums::{JobStatus, ScrapeStage},
+    DateTime, Error,
+};
+
+#[derive(FromRow, Debug, Clone)]
+pub struct ScrapeRun {
+    pub id: uuid::Uuid,
+    pub source_id: String,
+    pub stage: ScrapeStage,
+    pub dry_run: bool,
+    pub options: JSON,
+    pub status: JobStatus,
+    pub counts: JSON,
+    pub error: Option<String>,
+    pub started_at: DateTime,
+    pub finished_at: Option<DateTime>,
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(InputObject, Debug, Default)]
+pub struct ScrapeRunFilter {
+    pub source_id: Option<String>,
+    pub stage: Option<ScrapeStage>,
+    pub status: Option<JobStatus>,
+}
+
+impl ScrapeRun {
+    pub async fn start(
+        db_pool: &PgPool,
+        source_id: &str,
+        stage: ScrapeStage,
+        dry_run: bool,
+        options: JSON,
+    ) -> Result<Self, Error> {
+        let run = sqlx::query_as!(
+            ScrapeRun,
+            r#"
+            INSERT INTO scrape_run (source_id, stage, dry_run, options)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, source_id, stage AS "stage:ScrapeStage", dry_run, options,
+                status AS "status:JobStatus", counts, error, started_at, finished_at, duration_ms
+            "#,
+            source_id,
+            stage as ScrapeStage,
+            dry_run,
+            options
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Records the outcome of a run, failed when `error` is set
+    pub async fn finish(
+        &self,
+        db_pool: &PgPool,
+        counts: JSON,
+        error: Option<String>,
+    ) -> Result<Self, Error> {
+        let status = match error {
+            Some(_) => JobStatus::Failed,
+            None => JobStatus::Succeeded,
+        };
+        let run = sqlx::query_as!(
+            ScrapeRun,
+            r#"
+            UPDATE scrape_run SET
+                status = $2,
+                counts = $3,
+                error = $4,
+                finished_at = now(),
+                duration_ms = (EXTRACT(EPOCH FROM now() - started_at) * 1000)::bigint
+            WHERE id = $1
+            RETURNING id, source_id, stage AS "stage:ScrapeStage", dry_run, options,
+                status AS "status:JobStatus", counts, error, started_at, finished_at, duration_ms
+            "#,
+            self.id,
+            status as JobStatus,
+            counts,
+            error
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    pub async fn filter(
+        db_pool: &PgPool,
+        filter: &ScrapeRunFilter,
+        limit: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let runs = sqlx::query_as!(
+            ScrapeRun,
+            r#"
+            SELECT id, source_id, stage AS "stage:ScrapeStage", dry_run, options,
+                status AS "status:JobStatus", counts, error, started_at, finished_at, duration_ms
+            FROM scrape_run
+            WHERE ($1::text IS NULL OR source_id = $1)
+            AND ($2::scrape_stage IS NULL OR stage = $2)
+            AND ($3::job_status IS NULL OR status = $3)
+            ORDER BY started_at DESC
+            LIMIT $4
+            "#,
+            filter.source_id,
+            filter.stage as Option<ScrapeStage>,
+            filter.status as Option<JobStatus>,
+            limit
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    /// The most recent run of each source and stage
+    pub async fn latest(db_pool: &PgPool) -> Result<Vec<Self>, Error> {
+        let runs = sqlx::query_as!(
+            ScrapeRun,
+            r#"
+            SELECT DISTINCT ON (source_id, stage)
+                id, source_id, stage AS "stage:ScrapeStage", dry_run, options,
+                status AS "status:JobStatus", counts, error, started_at, finished_at, duration_ms
+            FROM scrape_run
+            ORDER BY source_id, stage, started_at DESC
+            "#
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(runs)
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use async_graphql::{Enum, InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JSON;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{models::conversation::Statement, DateTime, Error, StatementModerationStatus};
+
+/// Trigram similarity to a statement already in the conversation at which a new one is
+/// flagged as a duplicate
+pub const DUPLICATE_SIMILARITY: f32 = 0.8;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningReason {
+    Profanity,
+    PersonalAttack,
+    /// Matched one of the conversation's own blocked words
+    BlockedWord,
+    Duplicate,
+    OffTopic,
+    Spam,
+}
+
+/// Why the pre-screen held a statement for review
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreeningFlag {
+    pub reason: ScreeningReason,
+    /// The matched words, the statement it duplicates or the classifier's explanation
+    pub detail: String,
+}
+
+/// A statement in an organization's moderation queue
+#[derive(FromRow, Debug, Clone)]
+pub struct ModerationQueueEntry {
+    pub id: Uuid,
+    pub conversation_id: Uuid,
+    pub conversation_topic: String,
+    pub author_id: Option<Uuid>,
+    pub content: String,
+    pub moderation_status: StatementModerationStatus,
+    pub screening_flags: JSON,
+    pub moderated_by: Option<Uuid>,
+    pub moderated_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+#[derive(Debug, Clone, Default, InputObject)]
+pub struct ModerationQueueFilter {
+    pub conversation_id: Option<Uuid>,
+    /// Unmoderated statements when not given
+    pub moderation_statuses: Option<Vec<StatementModerationStatus>>,
+    /// Only statements the pre-screen flagged
+    #[graphql(default)]
+    pub flagged_only: bool,
+}
+
+#[derive(FromRow, Debug, Clone)]
+pub struct NearDuplicate {
+    pub id: Uuid,
+    pub content: String,
+    pub similarity: f32,
+}
+
+impl ModerationQueueEntry {
+    pub fn flags(&self) -> Vec<ScreeningFlag> {
+        serde_json::from_value(self.screening_flags.clone()).unwrap_or_default()
+    }
+
+    /// Statements in the organization's conversations, oldest first
+    pub async fn list(
+        db_pool: &PgPool,
+        organization_id: Uuid,
+        filter: &ModerationQueueFilter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Self>, Error> {
+        let moderation_statuses = filter
+            .moderation_statuses
+            .clone()
+            .unwrap_or_else(|| vec![StatementModerationStatus::Unmoderated]);
+
+        let entries = sqlx::query_as!(
+            ModerationQueueEntry,
+            r#"
+            SELECT
+                s.id,
+                s.conversation_id,
+                c.topic AS conversation_topic,
+                s.author_id,
+                s.content,
+                s.moderation_status AS "moderation_status: StatementModerationStatus",
+                s.screening_flags,
+                s.moderated_by,
+                s.moderated_at,
+                s.created_at
+            FROM statement s
+            JOIN conversation c ON c.id = s.conversation_id
+            WHERE c.organization_id = $1
+            AND ($2::uuid IS NULL OR s.conversation_id = $2)
+            AND s.moderation_status = ANY($3::_statement_moderation_status)
+            AND (NOT $4 OR s.screening_flags <> '[]'::jsonb)
+            ORDER BY s.created_at, s.id
+            LIMIT $5 OFFSET $6
+            "#,
+            organization_id,
+            filter.conversation_id,
+            moderation_statuses as _,
+            filter.flagged_only,
+            limit,
+            offset
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
+
+impl Statement {
+    /// The statement in the conversation most like `content`, when one is at least
+    /// `DUPLICATE_SIMILARITY` alike. Rejected statements don't count.
+    pub async fn find_near_duplicate(
+        db_pool: &PgPool,
+        conversation_id: Uuid,
+        content: &str,
+    ) -> Result<Option<NearDuplicate>, Error> {
+        let duplicate = sqlx::query_as!(
+            NearDuplicate,
+            r#"
+            SELECT
+                id,
+                content,
+                similarity(lower(content), lower($2)) AS "similarity!"
+            FROM statement
+            WHERE conversation_id = $1
+            AND moderation_status <> 'rejected'
+            AND similarity(lower(content), lower($2)) >= $3
+            ORDER BY 3 DESC
+            LIMIT 1
+            "#,
+            conversation_id,
+            content,
+            DUPLICATE_SIMILARITY
+        )
+        .fetch_optional(db_pool)
+        .await?;
+
+        Ok(duplicate)
+    }
+
+    /// Sets the moderation status of statements in the organization's conversations. Nothing
+    /// changes if any of them doesn't exist or is in another organization.
+    pub async fn moderate_many(
+        db_pool: &PgPool,
+        organization_id: Uuid,
+        statement_ids: &[Uuid],
+        moderation_status: StatementModerationStatus,
+        moderated_by: Option<Uuid>,
+    ) -> Result<Vec<Statement>, Error> {
+        let mut tx = db_pool.begin().await?;
+        let statements = sqlx::query_as!(
+            Statement,
+            r#"
+            UPDATE statement s
+            SET
+                moderation_status = $3::statement_moderation_status,
+                moderated_by = $4,
+                moderated_at = CURRENT_TIMESTAMP,
+                updated_at = CURRENT_TIMESTAMP
+            FROM conversation c
+            WHERE c.id = s.conversation_id
+            AND c.organization_id = $1
+            AND s.id = ANY($2)
+            RETURNING
+                s.id,
+                s.conversation_id,
+                s.content,
+                s.author_id,
+                s.moderation_status AS "moderation_status: StatementModerationStatus",
+                s.created_at,
+                s.updated_at
+            "#,
+            organization_id,
+            statement_ids,
+            moderation_status as StatementModerationStatus,
+            moderated_by
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let requested: HashSet<&Uuid> = statement_ids.iter().collect();
+        if statements.len() != requested.len() {
+            return Err(Error::Custom(
+                "Some statements don't exist or belong to another organization".to_string(),
+            ));
+        }
+        tx.commit().await?;
+
+        Ok(statements)
+    }
+}
@@ -331,10 +331,12 @@ impl User {
         }
     }
 
+    /// Refresh tokens issued before `user_session` existed were stored here. The column is
+    /// cleared once its token has been exchanged for a session.
     pub async fn update_refresh_token(
         db_pool: &PgPool,
         id: uuid::Uuid,
-        token: &str,
+        token: Option<&str>,
     ) -> Result<Self, Error> {
         let record = sqlx::query_as!(
             User,
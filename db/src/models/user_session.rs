@@ -0,0 +1,274 @@
+use sqlx::{FromRow, PgPool};
+
+use crate::{models::enums::SessionRevocationReason, DateTime, Error};
+
+/// How long the token a session was just rotated away from is still accepted. Browsers
+/// often send several requests at once when an access token expires, and all but the
+/// first carry the old refresh token.
+const ROTATION_GRACE_SECONDS: i64 = 30;
+
+/// A signed in device. Token hashes are never loaded.
+#[derive(FromRow, Debug, Clone)]
+pub struct UserSession {
+    pub id: uuid::Uuid,
+    pub user_id: uuid::Uuid,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime,
+    pub last_used_at: DateTime,
+    pub revoked_at: Option<DateTime>,
+    pub revoked_reason: Option<SessionRevocationReason>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshOutcome {
+    /// The presented token was current and has been replaced by the next one
+    Rotated,
+    /// The presented token was replaced moments ago by a concurrent request, so it is
+    /// accepted without rotating again
+    Concurrent,
+    /// A rotated token was presented again, the session has been revoked
+    Reused,
+    /// Unknown or revoked session
+    Invalid,
+}
+
+/// Decides what to do with a refresh token presented for a session
+pub(crate) fn refresh_outcome(
+    revoked: bool,
+    matches_current: bool,
+    matches_previous: bool,
+    rotated_at: DateTime,
+    now: DateTime,
+) -> RefreshOutcome {
+    if revoked {
+        RefreshOutcome::Invalid
+    } else if matches_current {
+        RefreshOutcome::Rotated
+    } else if matches_previous && (now - rotated_at).num_seconds() <= ROTATION_GRACE_SECONDS {
+        RefreshOutcome::Concurrent
+    } else {
+        // Signed for this session but neither its current nor its previous token, so an
+        // older token of the family is being replayed
+        RefreshOutcome::Reused
+    }
+}
+
+impl UserSession {
+    /// Starts a session for a new login. `id` must be the session id the refresh token
+    /// was signed with.
+    pub async fn create(
+        db_pool: &PgPool,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        refresh_token: &str,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<Self, Error> {
+        let session = sqlx::query_as!(
+            UserSession,
+            r#"
+            INSERT INTO user_session (id, user_id, token_hash, user_agent, ip_address)
+            VALUES ($1, $2, encode(sha256(convert_to($3, 'UTF8')), 'hex'), $4, $5)
+            RETURNING id, user_id, user_agent, ip_address, created_at, last_used_at, revoked_at,
+                revoked_reason AS "revoked_reason:SessionRevocationReason"
+            "#,
+            id,
+            user_id,
+            refresh_token,
+            user_agent,
+            ip_address,
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// Exchanges `presented` for `next_token`. If a rotated token of the session comes
+    /// back, the whole session is revoked so neither the thief nor the device can refresh.
+    pub async fn refresh(
+        db_pool: &PgPool,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        presented: &str,
+        next_token: &str,
+    ) -> Result<RefreshOutcome, Error> {
+        let mut tx = db_pool.begin().await?;
+        let state = sqlx::query!(
+            r#"
+            SELECT
+                revoked_at IS NOT NULL AS "revoked!",
+                token_hash = encode(sha256(convert_to($3, 'UTF8')), 'hex') AS "matches_current!",
+                COALESCE(previous_token_hash = encode(sha256(convert_to($3, 'UTF8')), 'hex'), false)
+                    AS "matches_previous!",
+                rotated_at,
+                now() AS "now!"
+            FROM user_session
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+            id,
+            user_id,
+            presented,
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(state) = state else {
+            return Ok(RefreshOutcome::Invalid);
+        };
+        let outcome = refresh_outcome(
+            state.revoked,
+            state.matches_current,
+            state.matches_previous,
+            state.rotated_at,
+            state.now,
+        );
+
+        match outcome {
+            RefreshOutcome::Rotated => {
+                sqlx::query!(
+                    r#"
+                    UPDATE user_session SET
+                        previous_token_hash = token_hash,
+                        token_hash = encode(sha256(convert_to($2, 'UTF8')), 'hex'),
+                        rotated_at = now(),
+                        last_used_at = now()
+                    WHERE id = $1
+                    "#,
+                    id,
+                    next_token,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            RefreshOutcome::Concurrent => {
+                sqlx::query!(
+                    "UPDATE user_session SET last_used_at = now() WHERE id = $1",
+                    id
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            RefreshOutcome::Reused => {
+                tracing::warn!(session_id = %id, %user_id, "Refresh token reuse, revoking session");
+                sqlx::query!(
+                    r#"
+                    UPDATE user_session SET revoked_at = now(), revoked_reason = $2
+                    WHERE id = $1
+                    "#,
+                    id,
+                    SessionRevocationReason::TokenReuse as SessionRevocationReason,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+            RefreshOutcome::Invalid => {}
+        }
+        tx.commit().await?;
+
+        Ok(outcome)
+    }
+
+    /// Sessions that haven't been revoked, most recently used first
+    pub async fn find_active_by_user_id(
+        db_pool: &PgPool,
+        user_id: uuid::Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let sessions = sqlx::query_as!(
+            UserSession,
+            r#"
+            SELECT id, user_id, user_agent, ip_address, created_at, last_used_at, revoked_at,
+                revoked_reason AS "revoked_reason:SessionRevocationReason"
+            FROM user_session
+            WHERE user_id = $1 AND revoked_at IS NULL
+            ORDER BY last_used_at DESC
+            "#,
+            user_id,
+        )
+        .fetch_all(db_pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Revokes one of the user's sessions, returning whether it was active
+    pub async fn revoke(
+        db_pool: &PgPool,
+        id: uuid::Uuid,
+        user_id: uuid::Uuid,
+        reason: SessionRevocationReason,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_session SET revoked_at = now(), revoked_reason = $3
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            "#,
+            id,
+            user_id,
+            reason as SessionRevocationReason,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revokes every active session of the user except `except`, returning how many
+    pub async fn revoke_all(
+        db_pool: &PgPool,
+        user_id: uuid::Uuid,
+        except: Option<uuid::Uuid>,
+        reason: SessionRevocationReason,
+    ) -> Result<u64, Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE user_session SET revoked_at = now(), revoked_reason = $3
+            WHERE user_id = $1 AND revoked_at IS NULL AND ($2::uuid IS NULL OR id != $2)
+            "#,
+            user_id,
+            except,
+            reason as SessionRevocationReason,
+        )
+        .execute(db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    #[test]
+    fn test_refresh_outcome() {
+        let now = Utc::now();
+        let just_rotated = now - Duration::seconds(5);
+        let long_ago = now - Duration::days(2);
+
+        assert_eq!(
+            refresh_outcome(false, true, false, long_ago, now),
+            RefreshOutcome::Rotated
+        );
+        assert_eq!(
+            refresh_outcome(false, false, true, just_rotated, now),
+            RefreshOutcome::Concurrent
+        );
+        assert_eq!(
+            refresh_outcome(false, false, true, long_ago, now),
+            RefreshOutcome::Reused
+        );
+        assert_eq!(
+            refresh_outcome(false, false, false, just_rotated, now),
+            RefreshOutcome::Reused
+        );
+        // Once revoked, even the current token is refused
+        assert_eq!(
+            refresh_outcome(true, true, false, long_ago, now),
+            RefreshOutcome::Invalid
+        );
+    }
+}
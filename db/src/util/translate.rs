@@ -1,21 +1,14 @@
-use async_openai::types::{
-    ChatCompletionRequestSystemMessageArgs, CreateChatCompletionRequestArgs,
-};
-use serde_json::Value as JSON;
-
-pub async fn translate_text(
-    text: &str,
-    languages: Vec<&str>,
-) -> Result<JSON, Box<dyn std::error::Error + Send>> {
-    let client = async_openai::Client::new();
+use llm::Message;
+use serde_json::{json, Map, Value as JSON};
 
+/// Translates `text` into each of `languages`, returning an object keyed by language code
+pub async fn translate_text(text: &str, languages: Vec<&str>) -> Result<JSON, llm::Error> {
     let prompt = format!(
         r#"
                     Translate the following text into the following languages: {languages}
-                    Response should be a parsable JSON object with the language code as the key and the translation as the value.
-                    Be sure to escape any special characters in the text, including quotations.
+                    Respond with a JSON object with the language code as the key and the translation as the value.
                     Do not include the original text in the response and only translate everything after "Text:".
-                    
+
                     Text: {text}
 
                 "#,
@@ -23,38 +16,24 @@ pub async fn translate_text(
         text = text
     );
 
-    let request = CreateChatCompletionRequestArgs::default()
-        .max_tokens(2048u16)
-        .model("gpt-3.5-turbo")
-        .messages([ChatCompletionRequestSystemMessageArgs::default()
-            .content(prompt)
-            .build()
-            .unwrap()
-            .into()])
-        .build()
-        .unwrap();
-
-    let response = client.chat().create(request).await.unwrap();
-    let suggested_translations = serde_json::from_str(
-        response.choices[0]
-            .message
-            .content
-            .as_deref()
-            .unwrap_or_default(),
-    );
-
-    if suggested_translations.is_err() {
-        eprintln!(
-            "Failed to parse translation response with: {}",
-            response.choices[0]
-                .message
-                .content
-                .as_deref()
-                .unwrap_or_default()
-        );
-    }
-
-    let translations: serde_json::Value = suggested_translations.unwrap();
+    let properties: Map<String, JSON> = languages
+        .iter()
+        .map(|language| (language.to_string(), json!({ "type": "string" })))
+        .collect();
+    let schema = json!({
+        "type": "object",
+        "properties": properties,
+        "required": languages,
+        "additionalProperties": false
+    });
+
+    let translations = llm::client()?
+        .complete_json("translation", vec![Message::system(prompt)], &schema)
+        .await
+        .map_err(|err| {
+            tracing::warn!("Failed to translate text: {}", err);
+            err
+        })?;
 
     Ok(translations)
 }
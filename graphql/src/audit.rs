@@ -0,0 +1,324 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use async_graphql::{
+    extensions::{
+        Extension, ExtensionContext, ExtensionFactory, NextPrepareRequest, NextResolve, ResolveInfo,
+    },
+    parser::types::{Field, Selection},
+    Request, ServerResult, Value, Variables,
+};
+use auth::AccessTokenClaims;
+use db::{AuditEntityType, AuditEvent, NewAuditEvent, OrganizationApiKey};
+use jsonwebtoken::TokenData;
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::context::ApiContext;
+
+/// Where a mutation's target record id is found
+#[derive(Debug, Clone, Copy)]
+enum IdSource {
+    Argument(&'static str),
+    /// A list of ids for mutations of several records, with one event recorded for each
+    ArgumentList(&'static str),
+    InputField(&'static str),
+    /// Only known once the record has been created
+    Result,
+    /// Created records returned inside an object field of the result
+    ResultField(&'static str),
+}
+
+/// Public participation and sign in mutations, which aren't recorded. Every other mutation
+/// is, whether or not `mutation_entity` knows its target record.
+const UNAUDITED_MUTATIONS: &[&str] = &[
+    "voteOnStatement",
+    "addStatement",
+    "upsertPollSubmission",
+    "upsertQuestionSubmission",
+    "upsertBillPublicVote",
+    "pingEmbedOrigin",
+    "upvoteArgument",
+    "downvoteArgument",
+    "beginUserRegistration",
+    "confirmUserEmail",
+    "login",
+    "logout",
+    "requestPasswordReset",
+    "resetPassword",
+];
+
+/// The record a mutation changes, by GraphQL field name, so its before and after can be
+/// compared. Upserts without an id are creates, whose id is read from the `id` selected on
+/// the result. Mutations not listed here are recorded without an entity.
+fn mutation_entity(name: &str) -> Option<(AuditEntityType, IdSource)> {
+    use AuditEntityType::*;
+    use IdSource::*;
+
+    let entity = match name {
+        "insertPolitician" => (Politician, Result),
+        "updatePolitician" => (Politician, InputField("id")),
+        "deletePolitician" => (Politician, Argument("id")),
+        "restorePolitician" => (Politician, Argument("id")),
+        "revertPolitician" => (Politician, Argument("id")),
+        "generateIntakeTokenLink" => (Politician, Argument("politicianId")),
+        "upsertRace" => (Race, InputField("id")),
+        "deleteRace" => (Race, Argument("id")),
+        "restoreRace" => (Race, Argument("id")),
+        "updateRaceStatus" => (Race, InputField("raceId")),
+        "importRankedChoiceBallots" => (Race, Argument("raceId")),
+        "tabulateRankedChoiceRace" => (Race, Argument("raceId")),
+        "projectRaceWinners" => (Race, Argument("raceId")),
+        "upsertOffice" => (Office, InputField("id")),
+        "deleteOffice" => (Office, Argument("id")),
+        "upsertBill" => (Bill, InputField("id")),
+        "deleteBill" => (Bill, Argument("id")),
+        "upsertBallotMeasure" => (BallotMeasure, InputField("id")),
+        "deleteBallotMeasure" => (BallotMeasure, Argument("id")),
+        "upsertElection" => (Election, InputField("id")),
+        "deleteElection" => (Election, Argument("id")),
+        "upsertIssueTag" => (IssueTag, InputField("id")),
+        "deleteIssueTag" => (IssueTag, Argument("id")),
+        "updateOrganization" => (Organization, InputField("id")),
+        "deleteOrganization" => (Organization, Argument("id")),
+        "uploadOrganizationThumbnail" => (Organization, Argument("id")),
+        "deleteOrganizationUser" => (Organization, Argument("id")),
+        "createOrganizationApiKey" => (OrganizationApiKey, ResultField("apiKey")),
+        "rotateOrganizationApiKey" => (OrganizationApiKey, Argument("id")),
+        "revokeOrganizationApiKey" => (OrganizationApiKey, Argument("id")),
+        "revokeUserSession" => (UserSession, Argument("id")),
+        "upsertEmbed" => (Embed, InputField("id")),
+        "deleteEmbed" => (Embed, Argument("id")),
+        "restoreEmbed" => (Embed, Argument("id")),
+        "upsertCandidateGuide" => (CandidateGuide, InputField("id")),
+        "deleteCandidateGuide" => (CandidateGuide, Argument("id")),
+        "restoreCandidateGuide" => (CandidateGuide, Argument("id")),
+        "openAllCandidateGuideSubmissions"
+        | "publishApprovedCandidateGuideSubmissions"
+        | "setAllCandidateGuideRacesEmailed"
+        | "sendCandidateGuideOutreach"
+        | "updateCandidateGuideRace"
+        | "removeCandidateGuideRace"
+        | "downloadAllCandidateGuideData" => (CandidateGuide, Argument("candidateGuideId")),
+        "upsertQuestion" => (Question, InputField("id")),
+        "deleteQuestion" => (Question, Argument("id")),
+        "reviewQuestionSubmission" => (QuestionSubmission, Argument("questionSubmissionId")),
+        "revertQuestionSubmission" => (QuestionSubmission, Argument("id")),
+        "copyQuestionSubmission" => (QuestionSubmission, Result),
+        "upsertPoll" => (Poll, InputField("id")),
+        "deletePoll" => (Poll, Argument("id")),
+        "createConversation" => (Conversation, Result),
+        "updateConversation" => (Conversation, Argument("conversationId")),
+        "recomputeConversationAnalysis" => (Conversation, Argument("conversationId")),
+        "exportConversation" => (Conversation, Argument("conversationId")),
+        "moderateStatement" => (Statement, Argument("statementId")),
+        "bulkModerateStatements" => (Statement, ArgumentList("statementIds")),
+        "upsertResultsSource" => (ResultsSource, InputField("id")),
+        "deleteResultsSource" => (ResultsSource, Argument("id")),
+        "pollResultsSource" => (ResultsSource, Argument("id")),
+        _ => return None,
+    };
+    Some(entity)
+}
+
+fn uuid_value(value: &Value) -> Option<uuid::Uuid> {
+    match value {
+        Value::String(id) => uuid::Uuid::parse_str(id).ok(),
+        _ => None,
+    }
+}
+
+/// The value selected as `name` on a result, under whatever alias the client gave it
+fn selected<'a>(field: &'a Field, result: &'a Value, name: &str) -> Option<(&'a Field, &'a Value)> {
+    let Value::Object(object) = result else {
+        return None;
+    };
+    field
+        .selection_set
+        .node
+        .items
+        .iter()
+        .find_map(|selection| match &selection.node {
+            Selection::Field(selection) if selection.node.name.node == name => object
+                .get(selection.node.response_key().node.as_str())
+                .map(|value| (&selection.node, value)),
+            _ => None,
+        })
+}
+
+/// The `id` selected on a mutation's result, or on one of its object fields
+fn result_id(field: &Field, result: &Value, object_field: Option<&str>) -> Option<uuid::Uuid> {
+    let (field, result) = match object_field {
+        Some(name) => selected(field, result, name)?,
+        None => (field, result),
+    };
+    selected(field, result, "id").and_then(|(_, id)| uuid_value(id))
+}
+
+/// Records an `audit_event` for every successful mutation other than public participation
+/// and sign in, comparing the record before and after the mutation ran where the record is
+/// known. Failing to record an event is logged and never fails the mutation itself.
+pub struct AuditExtension;
+
+impl ExtensionFactory for AuditExtension {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(AuditExtensionImpl::default())
+    }
+}
+
+#[derive(Default)]
+struct AuditExtensionImpl {
+    variables: Mutex<Variables>,
+}
+
+impl AuditExtensionImpl {
+    fn argument(&self, field: &Field, name: &str) -> Option<Value> {
+        let value = field.get_argument(name)?;
+        let variables = self.variables.lock().unwrap();
+        value
+            .node
+            .clone()
+            .into_const_with(|variable| variables.get(&variable).cloned().ok_or(()))
+            .ok()
+    }
+
+    fn entity_ids(&self, field: &Field, source: IdSource) -> Vec<uuid::Uuid> {
+        match source {
+            IdSource::Argument(name) => self
+                .argument(field, name)
+                .as_ref()
+                .and_then(uuid_value)
+                .into_iter()
+                .collect(),
+            IdSource::ArgumentList(name) => match self.argument(field, name) {
+                Some(Value::List(ids)) => ids.iter().filter_map(uuid_value).collect(),
+                _ => vec![],
+            },
+            IdSource::InputField(name) => match self.argument(field, "input") {
+                Some(Value::Object(input)) => {
+                    input.get(name).and_then(uuid_value).into_iter().collect()
+                }
+                _ => vec![],
+            },
+            IdSource::Result | IdSource::ResultField(_) => vec![],
+        }
+    }
+
+    /// The `organizationId` argument or input field, for events whose record has none
+    fn organization_id(&self, field: &Field) -> Option<uuid::Uuid> {
+        match self.argument(field, "organizationId") {
+            Some(id) => uuid_value(&id),
+            None => match self.argument(field, "input") {
+                Some(Value::Object(input)) => input.get("organizationId").and_then(uuid_value),
+                _ => None,
+            },
+        }
+    }
+}
+
+async fn snapshot(
+    db_pool: &PgPool,
+    entity_type: AuditEntityType,
+    entity_id: uuid::Uuid,
+) -> Option<serde_json::Value> {
+    AuditEvent::snapshot(db_pool, entity_type, entity_id)
+        .await
+        .unwrap_or_else(|err| {
+            warn!("Failed to snapshot {} {}: {}", entity_type, entity_id, err);
+            None
+        })
+}
+
+#[async_trait::async_trait]
+impl Extension for AuditExtensionImpl {
+    async fn prepare_request(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        request: Request,
+        next: NextPrepareRequest<'_>,
+    ) -> ServerResult<Request> {
+        *self.variables.lock().unwrap() = request.variables.clone();
+        next.run(ctx, request).await
+    }
+
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let audited = info.parent_type == "Mutation" && !UNAUDITED_MUTATIONS.contains(&info.name);
+        let (true, Ok(api_context)) = (audited, ctx.data::<ApiContext>()) else {
+            return next.run(ctx, info).await;
+        };
+        let db_pool = api_context.pool.clone();
+        let field = info.field;
+        let mutation = info.name.to_string();
+        let entity = mutation_entity(info.name);
+        let organization_id = self.organization_id(field);
+
+        let mut entity_ids = match entity {
+            Some((_, id_source)) => self.entity_ids(field, id_source),
+            None => vec![],
+        };
+        let mut seen = HashSet::new();
+        entity_ids.retain(|entity_id| seen.insert(*entity_id));
+        let mut befores = Vec::with_capacity(entity_ids.len());
+        if let Some((entity_type, _)) = entity {
+            for &entity_id in &entity_ids {
+                befores.push(snapshot(&db_pool, entity_type, entity_id).await);
+            }
+        }
+
+        let result = next.run(ctx, info).await?;
+
+        let mut entity_ids: Vec<Option<uuid::Uuid>> = entity_ids.into_iter().map(Some).collect();
+        if entity_ids.is_empty() {
+            // Recorded without an id when the client didn't select it on the result
+            let object_field = match entity {
+                Some((_, IdSource::ResultField(name))) => Some(name),
+                _ => None,
+            };
+            entity_ids.push(
+                result
+                    .as_ref()
+                    .and_then(|value| result_id(field, value, object_field)),
+            );
+            befores.push(None);
+        }
+        let actor_user_id = ctx
+            .data_opt::<Option<TokenData<AccessTokenClaims>>>()
+            .and_then(|token| token.as_ref())
+            .map(|token| token.claims.sub);
+        let actor_api_key_id = ctx
+            .data_opt::<OrganizationApiKey>()
+            .map(|api_key| api_key.id);
+
+        for (entity_id, before) in entity_ids.into_iter().zip(befores) {
+            let entity_type = entity.map(|(entity_type, _)| entity_type);
+            let after = match (entity_type, entity_id) {
+                (Some(entity_type), Some(entity_id)) => {
+                    snapshot(&db_pool, entity_type, entity_id).await
+                }
+                _ => None,
+            };
+            let event = NewAuditEvent {
+                actor_user_id,
+                actor_api_key_id,
+                organization_id,
+                entity_type,
+                entity_id,
+                mutation: &mutation,
+                before,
+                after,
+            };
+            if let Err(err) = AuditEvent::record(&db_pool, event).await {
+                warn!("Failed to record audit event for {}: {}", mutation, err);
+            }
+        }
+
+        Ok(result)
+    }
+}
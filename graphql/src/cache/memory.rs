@@ -0,0 +1,148 @@
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use lru::LruCache;
+use serde_json::Value;
+
+use super::CacheBackend;
+
+struct Entry {
+    value: Value,
+    tags: Vec<String>,
+    inserted_at: Instant,
+}
+
+/// In-process LRU cache bounded by entry count, local to a single server instance
+pub struct MemoryCache {
+    entries: Mutex<LruCache<String, Entry>>,
+    ttl: Duration,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(
+                NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN),
+            )),
+            ttl,
+        }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.inserted_at.elapsed() < self.ttl => Some(entry.value.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn set(&self, key: &str, value: Value, tags: &[String]) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let entry = Entry {
+            value,
+            tags: tags.to_vec(),
+            inserted_at: Instant::now(),
+        };
+        // Returns the replaced entry when the key was already cached, or the evicted one
+        match entries.push(key.to_string(), entry) {
+            Some((replaced, _)) if replaced != key => 1,
+            _ => 0,
+        }
+    }
+
+    async fn invalidate(&self, key: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.pop(key);
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let tagged: Vec<String> = entries
+            .iter()
+            .filter(|(_, entry)| entry.tags.iter().any(|t| t == tag))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &tagged {
+            entries.pop(key);
+        }
+        tagged.len()
+    }
+
+    async fn purge_expired(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let expired: Vec<String> = entries
+            .iter()
+            .filter(|(_, entry)| entry.inserted_at.elapsed() >= self.ttl)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            entries.pop(key);
+        }
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2, Duration::from_secs(60));
+        cache.set("a", json!(1), &[]).await;
+        cache.set("b", json!(2), &[]).await;
+        // Touch "a" so "b" becomes the least recently used entry
+        assert_eq!(cache.get("a").await, Some(json!(1)));
+
+        assert_eq!(cache.set("c", json!(3), &[]).await, 1);
+        assert_eq!(cache.get("b").await, None);
+        assert_eq!(cache.get("a").await, Some(json!(1)));
+        assert_eq!(cache.get("c").await, Some(json!(3)));
+    }
+
+    #[tokio::test]
+    async fn test_replacing_an_entry_evicts_nothing() {
+        let cache = MemoryCache::new(2, Duration::from_secs(60));
+        cache.set("a", json!(1), &[]).await;
+        cache.set("b", json!(2), &[]).await;
+
+        assert_eq!(cache.set("a", json!(3), &[]).await, 0);
+        assert_eq!(cache.get("a").await, Some(json!(3)));
+        assert_eq!(cache.get("b").await, Some(json!(2)));
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_tag() {
+        let cache = MemoryCache::new(10, Duration::from_secs(60));
+        let tags = vec!["conversation:1".to_string()];
+        cache.set("analysis", json!("x"), &tags).await;
+        cache.set("groups", json!("y"), &tags).await;
+        cache.set("other", json!("z"), &[]).await;
+
+        assert_eq!(cache.invalidate_tag("conversation:1").await, 2);
+        assert_eq!(cache.get("analysis").await, None);
+        assert_eq!(cache.get("other").await, Some(json!("z")));
+    }
+
+    #[tokio::test]
+    async fn test_expired_entries_are_not_returned() {
+        let cache = MemoryCache::new(10, Duration::ZERO);
+        cache.set("a", json!(1), &[]).await;
+        assert_eq!(cache.get("a").await, None);
+        assert_eq!(cache.purge_expired().await, 0);
+    }
+}
@@ -0,0 +1,112 @@
+mod memory;
+mod postgres;
+
+pub use memory::MemoryCache;
+pub use postgres::PostgresCache;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use tokio::time::interval;
+
+/// Storage for cached resolver output. Implementations must be safe to share across requests.
+#[async_trait]
+pub trait CacheBackend: Send + Sync {
+    /// Short label used for metrics, e.g. `memory` or `postgres`
+    fn name(&self) -> &'static str;
+
+    async fn get(&self, key: &str) -> Option<Value>;
+
+    /// Stores a value under `key`, associated with `tags` for bulk invalidation.
+    /// Returns the number of entries evicted to make room.
+    async fn set(&self, key: &str, value: Value, tags: &[String]) -> usize;
+
+    async fn invalidate(&self, key: &str);
+
+    /// Removes every entry stored with the given tag, returning how many were removed
+    async fn invalidate_tag(&self, tag: &str) -> usize;
+
+    /// Removes expired entries, returning how many were removed
+    async fn purge_expired(&self) -> usize;
+}
+
+/// Receives cache hit, miss and eviction counts, e.g. to export them to Prometheus
+pub trait CacheMetrics: Send + Sync {
+    fn record_hit(&self, backend: &str);
+    fn record_miss(&self, backend: &str);
+    fn record_evictions(&self, backend: &str, count: usize);
+}
+
+struct NoopMetrics;
+
+impl CacheMetrics for NoopMetrics {
+    fn record_hit(&self, _backend: &str) {}
+    fn record_miss(&self, _backend: &str) {}
+    fn record_evictions(&self, _backend: &str, _count: usize) {}
+}
+
+/// Tag shared by every cache entry derived from a conversation's statements and votes
+pub fn conversation_tag(conversation_id: impl std::fmt::Display) -> String {
+    format!("conversation:{}", conversation_id)
+}
+
+#[derive(Clone)]
+pub struct Cache {
+    backend: Arc<dyn CacheBackend>,
+    metrics: Arc<dyn CacheMetrics>,
+}
+
+impl Cache {
+    pub fn new(backend: impl CacheBackend + 'static) -> Self {
+        Self::with_metrics(backend, NoopMetrics)
+    }
+
+    pub fn with_metrics(
+        backend: impl CacheBackend + 'static,
+        metrics: impl CacheMetrics + 'static,
+    ) -> Self {
+        let cache = Cache {
+            backend: Arc::new(backend),
+            metrics: Arc::new(metrics),
+        };
+
+        let cache_clone = cache.clone();
+        tokio::spawn(async move {
+            let mut interval = interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let purged = cache_clone.backend.purge_expired().await;
+                cache_clone
+                    .metrics
+                    .record_evictions(cache_clone.backend.name(), purged);
+            }
+        });
+
+        cache
+    }
+
+    pub async fn get(&self, key: &str) -> Option<Value> {
+        let value = self.backend.get(key).await;
+        match value {
+            Some(_) => self.metrics.record_hit(self.backend.name()),
+            None => self.metrics.record_miss(self.backend.name()),
+        }
+        value
+    }
+
+    pub async fn set(&self, key: &str, value: Value, tags: &[String]) {
+        let evicted = self.backend.set(key, value, tags).await;
+        self.metrics.record_evictions(self.backend.name(), evicted);
+    }
+
+    pub async fn invalidate(&self, key: &str) {
+        self.backend.invalidate(key).await;
+    }
+
+    pub async fn invalidate_tag(&self, tag: &str) {
+        let removed = self.backend.invalidate_tag(tag).await;
+        tracing::debug!("Invalidated {} cache entries tagged {}", removed, tag);
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::Value;
+use sqlx::PgPool;
+
+use super::CacheBackend;
+
+/// Cache stored in the `cache_entry` table so it is shared by every server instance
+/// and survives restarts. Size is bounded by TTL rather than entry count.
+pub struct PostgresCache {
+    pool: PgPool,
+    ttl: Duration,
+}
+
+impl PostgresCache {
+    pub fn new(pool: PgPool, ttl: Duration) -> Self {
+        Self { pool, ttl }
+    }
+}
+
+#[async_trait]
+impl CacheBackend for PostgresCache {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn get(&self, key: &str) -> Option<Value> {
+        let record = sqlx::query!(
+            r#"
+            SELECT value FROM cache_entry
+            WHERE key = $1 AND expires_at > now()
+            "#,
+            key
+        )
+        .fetch_optional(&self.pool)
+        .await;
+
+        match record {
+            Ok(record) => record.map(|r| r.value),
+            Err(e) => {
+                tracing::warn!("Failed to read cache entry {}: {}", key, e);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: Value, tags: &[String]) -> usize {
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO cache_entry (key, value, tags, expires_at)
+            VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+            ON CONFLICT (key) DO UPDATE SET
+                value = EXCLUDED.value,
+                tags = EXCLUDED.tags,
+                expires_at = EXCLUDED.expires_at,
+                created_at = now()
+            "#,
+            key,
+            value,
+            tags,
+            self.ttl.as_secs_f64()
+        )
+        .execute(&self.pool)
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Failed to write cache entry {}: {}", key, e);
+        }
+        0
+    }
+
+    async fn invalidate(&self, key: &str) {
+        if let Err(e) = sqlx::query!("DELETE FROM cache_entry WHERE key = $1", key)
+            .execute(&self.pool)
+            .await
+        {
+            tracing::warn!("Failed to invalidate cache entry {}: {}", key, e);
+        }
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> usize {
+        match sqlx::query!("DELETE FROM cache_entry WHERE $1 = ANY(tags)", tag)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() as usize,
+            Err(e) => {
+                tracing::warn!("Failed to invalidate cache tag {}: {}", tag, e);
+                0
+            }
+        }
+    }
+
+    async fn purge_expired(&self) -> usize {
+        match sqlx::query!("DELETE FROM cache_entry WHERE expires_at <= now()")
+            .execute(&self.pool)
+            .await
+        {
+            Ok(result) => result.rows_affected() as usize,
+            Err(e) => {
+                tracing::warn!("Failed to purge expired cache entries: {}", e);
+                0
+            }
+        }
+    }
+}
@@ -0,0 +1,678 @@
+//! Opinion analysis, groups and map for conversations. These take a while to compute and
+//! call an LLM, so the job worker stores them as `conversation_analysis_snapshot`s once
+//! enough new votes come in, and resolvers read the latest snapshot.
+
+use std::collections::{HashMap, HashSet};
+
+use async_graphql::{Error, Result, ID};
+use db::{
+    models::conversation::{StatementView, StatementVote},
+    ArgumentPosition, ConversationAnalysisSnapshot, InsertConversationAnalysisSnapshotInput,
+};
+use llm::Message;
+use ndarray::Array2;
+use sqlx::PgPool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    cache::{conversation_tag, Cache},
+    opinion_map::{opinion_map, OpinionMap},
+    types::{
+        CharacteristicVote, OpinionAnalysis, OpinionGroup, OpinionMapGroup, OpinionMapParticipant,
+        OpinionMapResult, OpinionScore,
+    },
+};
+
+/// Votes cast or changed since the last snapshot before a conversation is reanalyzed
+pub const MIN_NEW_VOTES: i64 = 10;
+
+/// Consensus and divisive opinions described in the analysis overview
+const SUMMARIZED_OPINIONS: usize = 5;
+
+/// Reanalyzes every conversation with enough new votes, returning how many were analyzed.
+/// Cached snapshots of the reanalyzed conversations are dropped.
+pub async fn refresh_conversation_analyses(db_pool: &PgPool, cache: &Cache) -> Result<usize> {
+    let conversation_ids =
+        ConversationAnalysisSnapshot::stale_conversations(db_pool, MIN_NEW_VOTES).await?;
+    let mut analyzed = 0;
+    for conversation_id in conversation_ids {
+        match analyze_conversation(db_pool, conversation_id).await {
+            Ok(_) => {
+                cache
+                    .invalidate_tag(&conversation_tag(conversation_id))
+                    .await;
+                analyzed += 1;
+            }
+            Err(e) => warn!(
+                "Failed to analyze conversation {}: {}",
+                conversation_id, e.message
+            ),
+        }
+    }
+    Ok(analyzed)
+}
+
+/// Computes the conversation's opinion analysis, groups and map from its current votes
+/// and stores them as its latest snapshot
+pub async fn analyze_conversation(
+    db_pool: &PgPool,
+    conversation_id: Uuid,
+) -> Result<ConversationAnalysisSnapshot> {
+    // The database's clock, since it's what stamps the votes we compare against later
+    let votes_read_at = sqlx::query_scalar!(r#"SELECT now() AS "now!""#)
+        .fetch_one(db_pool)
+        .await?;
+    let votes = sqlx::query_as!(
+        StatementVote,
+        r#"
+        SELECT v.id, statement_id, s.content, user_id, session_id, vote_type AS "vote_type: ArgumentPosition", v.created_at, v.updated_at
+        FROM statement_vote v
+        JOIN statement s ON v.statement_id = s.id
+        WHERE s.conversation_id = $1
+        "#,
+        conversation_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+    let matrix = prepare_voting_matrix(&votes);
+
+    // Participants keep the group they were in last time where the groups still line up
+    let previous_labels: HashMap<String, usize> =
+        ConversationAnalysisSnapshot::latest(db_pool, conversation_id)
+            .await?
+            .and_then(|snapshot| serde_json::from_value(snapshot.group_labels).ok())
+            .unwrap_or_default();
+    let map = opinion_map(
+        &matrix.votes,
+        &matrix.voted,
+        None,
+        &matrix
+            .voter_ids
+            .iter()
+            .map(|voter_id| previous_labels.get(&voter_id.key()).copied())
+            .collect::<Vec<_>>(),
+    );
+    let group_labels: HashMap<String, usize> = matrix
+        .voter_ids
+        .iter()
+        .zip(&map.labels)
+        .map(|(voter_id, &label)| (voter_id.key(), label))
+        .collect();
+
+    let statements = fetch_statements_with_votes(db_pool, conversation_id, &votes).await?;
+    let opinion_analysis = build_opinion_analysis(statements).await;
+    let opinion_groups = build_opinion_groups(db_pool, &map, &matrix).await;
+    let opinion_map = build_opinion_map(&map, &matrix);
+
+    let snapshot = ConversationAnalysisSnapshot::insert(
+        db_pool,
+        &InsertConversationAnalysisSnapshotInput {
+            conversation_id,
+            vote_count: votes.len() as i32,
+            participant_count: matrix.voter_ids.len() as i32,
+            opinion_analysis: serde_json::to_value(&opinion_analysis)?,
+            opinion_groups: serde_json::to_value(&opinion_groups)?,
+            opinion_map: serde_json::to_value(&opinion_map)?,
+            group_labels: serde_json::to_value(&group_labels)?,
+            created_at: votes_read_at,
+        },
+    )
+    .await?;
+
+    Ok(snapshot)
+}
+
+/// The most consensus and most divisive statements, best first, with an overview of them
+async fn build_opinion_analysis(statements_with_votes: Vec<StatementWithMeta>) -> OpinionAnalysis {
+    // Process statements once to get both consensus and divisive opinions
+    let mut consensus_statements: Vec<(StatementWithMeta, f64)> = Vec::new();
+    let mut divisive_statements: Vec<(StatementWithMeta, f64)> = Vec::new();
+
+    for statement in statements_with_votes
+        .into_iter()
+        .filter(|s| !s.votes.is_empty())
+    {
+        let vote_counts = count_votes(&statement.votes);
+        let total_votes = statement.votes.len() as f64;
+
+        // Only add to divisive if there's actually a mix of votes
+        let has_vote_variety = vote_counts.values().filter(|&&count| count > 0).count() > 1;
+
+        let consensus_score = calculate_consensus_score(&vote_counts, total_votes);
+        consensus_statements.push((statement.clone(), consensus_score));
+
+        if has_vote_variety {
+            let divisiveness_score = calculate_divisiveness_score(&vote_counts, total_votes);
+            divisive_statements.push((statement, divisiveness_score));
+        }
+    }
+
+    consensus_statements.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    divisive_statements.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+
+    let consensus_opinions: Vec<OpinionScore> = consensus_statements
+        .into_iter()
+        .map(|(statement, score)| opinion_score(statement, score))
+        .collect();
+    let divisive_opinions: Vec<OpinionScore> = divisive_statements
+        .into_iter()
+        .map(|(statement, score)| opinion_score(statement, score))
+        .collect();
+
+    let overview = generate_opinion_summary(
+        consensus_opinions
+            .iter()
+            .take(SUMMARIZED_OPINIONS)
+            .cloned()
+            .collect(),
+        divisive_opinions
+            .iter()
+            .take(SUMMARIZED_OPINIONS)
+            .cloned()
+            .collect(),
+    )
+    .await
+    .ok();
+
+    OpinionAnalysis {
+        overview,
+        consensus_opinions,
+        divisive_opinions,
+    }
+}
+
+fn opinion_score(statement: StatementWithMeta, score: f64) -> OpinionScore {
+    let counts = count_votes(&statement.votes);
+    // Get unique voting sessions
+    let voting_sessions: HashSet<_> = statement
+        .votes
+        .iter()
+        .filter_map(|v| v.session_id.as_ref())
+        .collect();
+
+    // Get unique viewing sessions
+    let viewing_sessions: HashSet<_> = statement.views.iter().map(|v| &v.session_id).collect();
+
+    // Calculate non-voting views
+    let non_voting_views = viewing_sessions.difference(&voting_sessions).count();
+    OpinionScore {
+        id: statement.id.to_string(),
+        content: statement.content,
+        score,
+        total_votes: statement.votes.len() as i32,
+        support_votes: counts.get(&ArgumentPosition::Support).copied().unwrap_or(0),
+        oppose_votes: counts.get(&ArgumentPosition::Oppose).copied().unwrap_or(0),
+        neutral_votes: counts.get(&ArgumentPosition::Neutral).copied().unwrap_or(0),
+        total_views: viewing_sessions.len() as i32,
+        non_voting_views: non_voting_views as i32,
+    }
+}
+
+async fn build_opinion_groups(
+    db_pool: &PgPool,
+    map: &OpinionMap,
+    matrix: &VotingMatrix,
+) -> Vec<OpinionGroup> {
+    let mut opinion_groups = Vec::new();
+    for (group_id, group_indices) in map.groups() {
+        let users: Vec<ID> = group_indices
+            .iter()
+            .map(|&idx| matrix.voter_ids[idx].id())
+            .collect();
+
+        let characteristic_votes: Vec<CharacteristicVote> =
+            analyze_group_votes(&matrix.votes, &group_indices, &matrix.statement_ids);
+
+        let summary = match generate_group_summary(db_pool, &characteristic_votes).await {
+            Ok(summary) => summary,
+            Err(e) => {
+                tracing::debug!("Error generating group summary: {:?}", e);
+                "Group summary unavailable.".to_string()
+            }
+        };
+
+        opinion_groups.push(OpinionGroup {
+            id: ID::from(group_id.to_string()),
+            users,
+            characteristic_votes,
+            summary,
+        });
+    }
+    opinion_groups
+}
+
+fn build_opinion_map(map: &OpinionMap, matrix: &VotingMatrix) -> OpinionMapResult {
+    let participants = matrix
+        .voter_ids
+        .iter()
+        .zip(map.points.rows())
+        .zip(&map.labels)
+        .map(|((voter_id, point), label)| {
+            let (user_id, session_id) = match voter_id {
+                VoterId::User(_) => (Some(voter_id.id()), None),
+                VoterId::Session(_) => (None, Some(voter_id.id())),
+            };
+            OpinionMapParticipant {
+                user_id,
+                session_id,
+                x: point[0],
+                y: point[1],
+                group_id: ID::from(label.to_string()),
+            }
+        })
+        .collect();
+    let groups = map
+        .groups()
+        .into_iter()
+        .map(|(label, members)| OpinionMapGroup {
+            id: ID::from(label.to_string()),
+            x: map.centroids[&label][0],
+            y: map.centroids[&label][1],
+            size: members.len() as i32,
+        })
+        .collect();
+
+    OpinionMapResult {
+        participants,
+        groups,
+        explained_variance: map.explained_variance.to_vec(),
+    }
+}
+
+fn analyze_group_votes(
+    matrix: &Array2<f64>,
+    group_user_indices: &[usize],
+    statement_ids: &[Uuid],
+) -> Vec<CharacteristicVote> {
+    let mut candidates = Vec::new();
+
+    // First pass: collect all potential characteristic votes with their metrics
+    for (stmt_idx, &stmt_id) in statement_ids.iter().enumerate() {
+        let votes = matrix.column(stmt_idx);
+
+        let group_votes: Vec<f64> = group_user_indices
+            .iter()
+            .map(|&user_idx| votes[user_idx])
+            .filter(|&vote| vote != 0.0)
+            .collect();
+
+        if !group_votes.is_empty() {
+            let mean = group_votes.iter().sum::<f64>() / group_votes.len() as f64;
+
+            let variance = if group_votes.len() > 1 {
+                group_votes.iter().map(|&x| (x - mean).powi(2)).sum::<f64>()
+                    / (group_votes.len() - 1) as f64
+            } else {
+                0.0
+            };
+            let std_dev = variance.sqrt();
+            let mut consensus = 1.0 - (std_dev / 1.0).min(1.0);
+            let significance = group_votes.len() as f64 / group_user_indices.len() as f64;
+
+            // Adjust consensus score for high-consensus, low-participation cases
+            if consensus > 0.7 && significance <= 0.5 {
+                consensus *= significance / 0.5;
+            }
+
+            candidates.push((stmt_id, mean, consensus, significance));
+        }
+    }
+
+    // Sort candidates by combined score (consensus * significance)
+    candidates.sort_by(|a, b| {
+        let score_a = a.2 * a.3; // consensus * significance
+        let score_b = b.2 * b.3;
+        score_b
+            .partial_cmp(&score_a)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // Take top 10 votes or adjust thresholds to get at least some characteristic votes
+    let mut characteristic_votes = Vec::new();
+    let initial_consensus_threshold = 0.3;
+    let initial_significance_threshold = 0.5;
+    let mut consensus_threshold = initial_consensus_threshold;
+    let mut significance_threshold = initial_significance_threshold;
+
+    while characteristic_votes.is_empty()
+        && consensus_threshold > 0.0
+        && significance_threshold > 0.0
+    {
+        characteristic_votes = candidates
+            .iter()
+            .filter(|&(_, _, consensus, significance)| {
+                *consensus >= consensus_threshold && *significance >= significance_threshold
+            })
+            .take(10)
+            .map(
+                |&(stmt_id, mean, consensus, significance)| CharacteristicVote {
+                    statement_id: ID::from(stmt_id),
+                    mean_sentiment: mean,
+                    consensus_level: consensus,
+                    significance_level: significance,
+                },
+            )
+            .collect();
+
+        // Reduce thresholds if no votes meet criteria
+        if characteristic_votes.is_empty() {
+            consensus_threshold *= 0.8;
+            significance_threshold *= 0.8;
+        }
+    }
+
+    // If still no votes meet thresholds, take top 3 votes regardless of thresholds
+    if characteristic_votes.is_empty() {
+        characteristic_votes = candidates
+            .iter()
+            .take(3)
+            .map(
+                |&(stmt_id, mean, consensus, significance)| CharacteristicVote {
+                    statement_id: ID::from(stmt_id),
+                    mean_sentiment: mean,
+                    consensus_level: consensus,
+                    significance_level: significance,
+                },
+            )
+            .collect();
+    }
+
+    characteristic_votes
+}
+
+#[derive(Hash, Eq, PartialEq, Clone, Ord, PartialOrd, Debug)]
+pub(crate) enum VoterId {
+    User(Uuid),
+    Session(Uuid),
+}
+
+impl VoterId {
+    /// The user who voted, or their session when they weren't signed in
+    pub(crate) fn of(user_id: Option<Uuid>, session_id: Option<Uuid>) -> Option<Self> {
+        user_id
+            .map(VoterId::User)
+            .or(session_id.map(VoterId::Session))
+    }
+
+    /// Key used to remember the voter's opinion group between computations
+    pub(crate) fn key(&self) -> String {
+        match self {
+            VoterId::User(id) => format!("user:{}", id),
+            VoterId::Session(id) => format!("session:{}", id),
+        }
+    }
+
+    fn id(&self) -> ID {
+        match self {
+            VoterId::User(uuid) => uuid.into(),
+            VoterId::Session(session) => ID::from(session.to_string()),
+        }
+    }
+}
+
+struct VotingMatrix {
+    votes: Array2<f64>,
+    /// Which cells hold a vote, since a neutral vote and no vote are both 0.0
+    voted: Array2<bool>,
+    voter_ids: Vec<VoterId>,
+    statement_ids: Vec<Uuid>,
+}
+
+// Helper function to prepare the voting matrix
+fn prepare_voting_matrix(votes: &[StatementVote]) -> VotingMatrix {
+    let mut unique_voters = std::collections::HashSet::new();
+    let mut unique_statements = std::collections::HashSet::new();
+
+    // Collect unique voters and statements
+    for vote in votes {
+        if let Some(voter_id) = VoterId::of(vote.user_id, vote.session_id) {
+            unique_voters.insert(voter_id);
+        }
+        unique_statements.insert(vote.statement_id);
+    }
+
+    let mut voter_ids: Vec<VoterId> = unique_voters.into_iter().collect();
+    let mut statement_ids: Vec<Uuid> = unique_statements.into_iter().collect();
+    voter_ids.sort();
+    statement_ids.sort();
+
+    // Create vote matrix
+    let mut matrix = Array2::zeros((voter_ids.len(), statement_ids.len()));
+    let mut voted = Array2::from_elem(matrix.dim(), false);
+
+    for vote in votes {
+        let voter_idx = voter_ids
+            .iter()
+            .position(|v| match (v, &vote.user_id, &vote.session_id) {
+                (VoterId::User(id), Some(user_id), _) => id == user_id,
+                (VoterId::Session(id), _, Some(session_id)) => id == session_id,
+                _ => false,
+            })
+            .unwrap();
+        let statement_idx = statement_ids
+            .iter()
+            .position(|&id| id == vote.statement_id)
+            .unwrap();
+        let value = vote.vote_type.as_f64();
+        matrix[[voter_idx, statement_idx]] = value;
+        voted[[voter_idx, statement_idx]] = true;
+    }
+
+    VotingMatrix {
+        votes: matrix,
+        voted,
+        voter_ids,
+        statement_ids,
+    }
+}
+
+#[derive(Clone)]
+struct StatementWithMeta {
+    id: Uuid,
+    content: String,
+    votes: Vec<StatementVote>,
+    views: Vec<StatementView>,
+}
+
+/// Groups the conversation's votes and views by statement
+async fn fetch_statements_with_votes(
+    db_pool: &PgPool,
+    conversation_id: Uuid,
+    votes: &[StatementVote],
+) -> Result<Vec<StatementWithMeta>> {
+    let views = sqlx::query_as!(
+        StatementView,
+        r#"
+        SELECT sv.id, statement_id, session_id, user_id, sv.created_at, sv.updated_at
+        FROM statement_view sv
+        JOIN statement s ON sv.statement_id = s.id
+        WHERE s.conversation_id = $1
+        "#,
+        conversation_id
+    )
+    .fetch_all(db_pool)
+    .await?;
+
+    let mut statements_map: HashMap<Uuid, StatementWithMeta> = HashMap::new();
+
+    // Process votes first to establish content
+    for vote in votes.iter().cloned() {
+        statements_map
+            .entry(vote.statement_id)
+            .or_insert_with(|| StatementWithMeta {
+                id: vote.statement_id,
+                content: vote.content.clone(),
+                votes: Vec::new(),
+                views: Vec::new(),
+            })
+            .votes
+            .push(vote);
+    }
+
+    // Process views, skipping statements that don't exist
+    for view in views {
+        if let Some(statement) = statements_map.get_mut(&view.statement_id) {
+            statement.views.push(view);
+        }
+    }
+
+    Ok(statements_map.into_values().collect())
+}
+
+fn count_votes(votes: &[StatementVote]) -> HashMap<ArgumentPosition, i32> {
+    let mut counts = HashMap::new();
+    for vote in votes {
+        *counts.entry(vote.vote_type).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn calculate_consensus_score(
+    vote_counts: &HashMap<ArgumentPosition, i32>,
+    total_votes: f64,
+) -> f64 {
+    let support = *vote_counts.get(&ArgumentPosition::Support).unwrap_or(&0) as f64;
+    let oppose = *vote_counts.get(&ArgumentPosition::Oppose).unwrap_or(&0) as f64;
+    let neutral = *vote_counts.get(&ArgumentPosition::Neutral).unwrap_or(&0) as f64;
+
+    let max_votes = support.max(oppose).max(neutral);
+    let max_vote_ratio = max_votes / total_votes;
+
+    // Penalize statements with few votes
+    let vote_volume_factor = (total_votes / 10.0).min(1.0);
+
+    max_vote_ratio * vote_volume_factor
+}
+
+fn calculate_divisiveness_score(
+    vote_counts: &HashMap<ArgumentPosition, i32>,
+    total_votes: f64,
+) -> f64 {
+    let support = vote_counts
+        .get(&ArgumentPosition::Support)
+        .copied()
+        .unwrap_or(0) as f64;
+    let oppose = vote_counts
+        .get(&ArgumentPosition::Oppose)
+        .copied()
+        .unwrap_or(0) as f64;
+
+    // Ignore neutral votes for divisiveness calculation
+    let active_votes = support + oppose;
+    if active_votes == 0.0 {
+        return 0.0;
+    }
+
+    // Calculate the proportion of support vs oppose among non-neutral votes
+    let support_ratio = support / active_votes;
+
+    // Score is highest when support_ratio is close to 0.5 (perfect split)
+    // and lowest when it's close to 0.0 or 1.0 (consensus)
+    let balance_score = 1.0 - (support_ratio - 0.5).abs() * 2.0;
+
+    // Consider total engagement (non-neutral votes) as a factor
+    let engagement_ratio = active_votes / total_votes;
+
+    // Penalize low vote counts
+    let vote_volume_factor = (total_votes / 10.0).min(1.0);
+
+    // Combine factors with more weight on the balance score
+    balance_score * engagement_ratio * vote_volume_factor
+}
+
+async fn generate_group_summary(
+    db_pool: &PgPool,
+    characteristic_votes: &[CharacteristicVote],
+) -> Result<String, Error> {
+    // Fetch statement contents for context
+    let mut statement_details = Vec::new();
+    for vote in characteristic_votes {
+        let statement = sqlx::query!(
+            r#"
+            SELECT content
+            FROM statement
+            WHERE id = $1
+            "#,
+            Uuid::parse_str(&vote.statement_id)?
+        )
+        .fetch_one(db_pool)
+        .await?;
+
+        statement_details.push((
+            statement.content,
+            vote.mean_sentiment,
+            vote.consensus_level,
+            vote.significance_level,
+        ));
+    }
+
+    let prompt = format!(
+        "You are analyzing a group of users in a discussion. Here are their most characteristic voting patterns:\n\n{}{}",
+        statement_details.iter()
+            .map(|(content, sentiment, consensus, significance)| {
+                format!(
+                    "Statement: '{}'\nSentiment: {:.2} (-1 to +1)\nConsensus: {:.2}\nParticipation: {:.2}\n",
+                    content, sentiment, consensus, significance
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        "\nBased on these voting patterns, write a 2-3 sentence summary describing this group's positions and characteristics. Focus on the statement content and the most strongly held views and areas of agreement."
+    );
+
+    let summary = llm::client()?
+        .complete("group_summary", vec![Message::user(prompt)])
+        .await?;
+    Ok(summary)
+}
+
+async fn generate_opinion_summary(
+    consensus_opinions: Vec<OpinionScore>,
+    divisive_opinions: Vec<OpinionScore>,
+) -> Result<String, Error> {
+    if consensus_opinions.is_empty() && divisive_opinions.is_empty() {
+        return Ok("No opinions to summarize.".to_string());
+    }
+
+    let mut opinion_details = Vec::new();
+
+    // Format consensus opinions
+    for opinion in consensus_opinions {
+        opinion_details.push(format!(
+            "Consensus Opinion: '{}'\nSupport: {}\nOppose: {}\nNeutral: {}\nTotal Votes: {}\n",
+            opinion.content,
+            opinion.support_votes,
+            opinion.oppose_votes,
+            opinion.neutral_votes,
+            opinion.total_votes
+        ));
+    }
+
+    // Format divisive opinions
+    for opinion in divisive_opinions {
+        opinion_details.push(format!(
+            "Divisive Opinion: '{}'\nSupport: {}\nOppose: {}\nNeutral: {}\nTotal Votes: {}\n",
+            opinion.content,
+            opinion.support_votes,
+            opinion.oppose_votes,
+            opinion.neutral_votes,
+            opinion.total_votes
+        ));
+    }
+
+    let prompt = format!(
+        "You are analyzing voting patterns on various opinions. Here are the most notable consensus and divisive opinions:\n\n{}{}",
+        opinion_details.join("\n"),
+        "\nWrite a 2-3 sentence summary describing the overall patterns in these opinions. Focus on what unites and divides the community, without directly quoting the statements. Highlight any particularly strong consensus or notable divisions."
+    );
+
+    let overview = llm::client()?
+        .complete("opinion_summary", vec![Message::user(prompt)])
+        .await
+        .map_err(|err| {
+            tracing::debug!("Error generating opinion summary: {}", err);
+            err
+        })?;
+    Ok(overview)
+}
@@ -0,0 +1,552 @@
+//! Exports a conversation's statements, votes and groups in the layout of pol.is data
+//! exports, so researchers can reuse tools built for them. Statements and participants
+//! are numbered from 0 the way pol.is numbers comments and participants, which keeps
+//! user and session ids out of the export.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Write},
+};
+
+use async_graphql::{Enum, Result};
+use chrono::{DateTime, Utc};
+use db::{ArgumentPosition, Conversation, ConversationAnalysisSnapshot, StatementModerationStatus};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::conversation_analysis::VoterId;
+
+/// Where exports are uploaded in the private S3 bucket, which expires them with a lifecycle
+/// rule on this prefix
+pub const EXPORT_DIRECTORY: &str = "exports/conversations";
+
+/// How long the link to an export works
+pub const EXPORT_LINK_EXPIRY_SECS: u32 = 24 * 60 * 60;
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ConversationExportFormat {
+    /// A zip of `comments.csv`, `votes.csv` and `participants-votes.csv`
+    Csv,
+    /// A single JSON document
+    Json,
+}
+
+impl ConversationExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConversationExportFormat::Csv => "zip",
+            ConversationExportFormat::Json => "json",
+        }
+    }
+
+    pub fn mimetype(&self) -> &'static str {
+        match self {
+            ConversationExportFormat::Csv => "application/zip",
+            ConversationExportFormat::Json => "application/json",
+        }
+    }
+}
+
+pub struct ExportStatement {
+    pub id: Uuid,
+    pub content: String,
+    pub author_id: Option<Uuid>,
+    pub moderation_status: StatementModerationStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct ExportVote {
+    pub statement_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub vote_type: ArgumentPosition,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedComment {
+    pub comment_id: usize,
+    /// Empty for seed statements
+    pub author_id: Option<usize>,
+    pub agrees: usize,
+    pub disagrees: usize,
+    /// 1 when accepted or seeded, -1 when rejected and 0 when not moderated yet
+    pub moderated: i8,
+    pub comment_body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedVote {
+    pub comment_id: usize,
+    pub voter_id: usize,
+    /// 1 to agree, -1 to disagree and 0 to pass
+    pub vote: i8,
+    pub voted_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedParticipant {
+    pub participant: usize,
+    /// The opinion group from the latest analysis, if the participant was in it
+    pub group_id: Option<usize>,
+    pub n_comments: usize,
+    pub n_votes: usize,
+    pub n_agree: usize,
+    pub n_disagree: usize,
+    /// Vote on each comment the participant voted on, by comment id
+    pub votes: BTreeMap<usize, i8>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationExport {
+    pub topic: String,
+    pub description: Option<String>,
+    pub exported_at: DateTime<Utc>,
+    /// When the groups were computed
+    pub analyzed_at: Option<DateTime<Utc>>,
+    pub comments: Vec<ExportedComment>,
+    pub votes: Vec<ExportedVote>,
+    pub participants: Vec<ExportedParticipant>,
+}
+
+fn vote_value(vote_type: ArgumentPosition) -> i8 {
+    match vote_type {
+        ArgumentPosition::Support => 1,
+        ArgumentPosition::Neutral => 0,
+        ArgumentPosition::Oppose => -1,
+    }
+}
+
+fn moderated_value(moderation_status: StatementModerationStatus) -> i8 {
+    match moderation_status {
+        StatementModerationStatus::Accepted | StatementModerationStatus::Seed => 1,
+        StatementModerationStatus::Unmoderated => 0,
+        StatementModerationStatus::Rejected => -1,
+    }
+}
+
+/// The date as JavaScript prints it, which is how pol.is exports write `datetime`
+fn js_datetime(datetime: &DateTime<Utc>) -> String {
+    datetime
+        .format("%a %b %d %Y %H:%M:%S GMT+0000 (Coordinated Universal Time)")
+        .to_string()
+}
+
+impl ConversationExport {
+    /// Loads everything in the conversation, including rejected statements, with groups
+    /// from its latest analysis snapshot
+    pub async fn load(db_pool: &PgPool, conversation: &Conversation) -> Result<Self> {
+        let statements = sqlx::query_as!(
+            ExportStatement,
+            r#"
+            SELECT
+                id,
+                content,
+                author_id,
+                moderation_status AS "moderation_status: StatementModerationStatus",
+                created_at
+            FROM statement
+            WHERE conversation_id = $1
+            ORDER BY created_at, id
+            "#,
+            conversation.id
+        )
+        .fetch_all(db_pool)
+        .await?;
+        let votes = sqlx::query_as!(
+            ExportVote,
+            r#"
+            SELECT
+                v.statement_id,
+                v.user_id,
+                v.session_id,
+                v.vote_type AS "vote_type: ArgumentPosition",
+                v.created_at,
+                v.updated_at
+            FROM statement_vote v
+            JOIN statement s ON s.id = v.statement_id
+            WHERE s.conversation_id = $1
+            ORDER BY v.updated_at, v.id
+            "#,
+            conversation.id
+        )
+        .fetch_all(db_pool)
+        .await?;
+        let snapshot = ConversationAnalysisSnapshot::latest(db_pool, conversation.id).await?;
+        let analyzed_at = snapshot.as_ref().map(|snapshot| snapshot.created_at);
+        let group_labels: HashMap<String, usize> = snapshot
+            .and_then(|snapshot| serde_json::from_value(snapshot.group_labels).ok())
+            .unwrap_or_default();
+
+        let mut export = Self::build(statements, votes, &group_labels);
+        export.topic = conversation.topic.clone();
+        export.description = conversation.description.clone();
+        export.analyzed_at = analyzed_at;
+        Ok(export)
+    }
+
+    /// Numbers statements in the order they were added and participants in the order
+    /// they first wrote or voted on a statement. `group_labels` is keyed like the
+    /// analysis snapshot's.
+    pub fn build(
+        statements: Vec<ExportStatement>,
+        votes: Vec<ExportVote>,
+        group_labels: &HashMap<String, usize>,
+    ) -> Self {
+        let comment_ids: HashMap<Uuid, usize> = statements
+            .iter()
+            .enumerate()
+            .map(|(comment_id, statement)| (statement.id, comment_id))
+            .collect();
+
+        let mut activity: Vec<(DateTime<Utc>, VoterId)> = statements
+            .iter()
+            .filter_map(|statement| {
+                let author = VoterId::of(statement.author_id, None)?;
+                Some((statement.created_at, author))
+            })
+            .chain(votes.iter().filter_map(|vote| {
+                let voter = VoterId::of(vote.user_id, vote.session_id)?;
+                Some((vote.created_at, voter))
+            }))
+            .collect();
+        activity.sort();
+        let mut participant_ids: HashMap<VoterId, usize> = HashMap::new();
+        let mut participants: Vec<ExportedParticipant> = Vec::new();
+        for (_, voter_id) in activity {
+            if participant_ids.contains_key(&voter_id) {
+                continue;
+            }
+            participant_ids.insert(voter_id.clone(), participants.len());
+            participants.push(ExportedParticipant {
+                participant: participants.len(),
+                group_id: group_labels.get(&voter_id.key()).copied(),
+                n_comments: 0,
+                n_votes: 0,
+                n_agree: 0,
+                n_disagree: 0,
+                votes: BTreeMap::new(),
+            });
+        }
+
+        let mut comments: Vec<ExportedComment> = statements
+            .into_iter()
+            .enumerate()
+            .map(|(comment_id, statement)| {
+                let author_id = VoterId::of(statement.author_id, None)
+                    .and_then(|author| participant_ids.get(&author).copied());
+                if let Some(author_id) = author_id {
+                    participants[author_id].n_comments += 1;
+                }
+                ExportedComment {
+                    comment_id,
+                    author_id,
+                    agrees: 0,
+                    disagrees: 0,
+                    moderated: moderated_value(statement.moderation_status),
+                    comment_body: statement.content,
+                    created_at: statement.created_at,
+                }
+            })
+            .collect();
+
+        let votes: Vec<ExportedVote> = votes
+            .into_iter()
+            .filter_map(|vote| {
+                let comment_id = *comment_ids.get(&vote.statement_id)?;
+                let voter = VoterId::of(vote.user_id, vote.session_id)?;
+                let voter_id = participant_ids[&voter];
+                let value = vote_value(vote.vote_type);
+
+                let participant = &mut participants[voter_id];
+                participant.n_votes += 1;
+                participant.votes.insert(comment_id, value);
+                match value {
+                    1 => {
+                        participant.n_agree += 1;
+                        comments[comment_id].agrees += 1;
+                    }
+                    -1 => {
+                        participant.n_disagree += 1;
+                        comments[comment_id].disagrees += 1;
+                    }
+                    _ => {}
+                }
+
+                Some(ExportedVote {
+                    comment_id,
+                    voter_id,
+                    vote: value,
+                    voted_at: vote.updated_at,
+                })
+            })
+            .collect();
+
+        ConversationExport {
+            topic: String::new(),
+            description: None,
+            exported_at: Utc::now(),
+            analyzed_at: None,
+            comments,
+            votes,
+            participants,
+        }
+    }
+
+    pub fn comments_csv(&self) -> Result<Vec<u8>> {
+        let mut csv = Vec::new();
+        {
+            let mut wtr = csv::Writer::from_writer(&mut csv);
+            wtr.write_record([
+                "timestamp",
+                "datetime",
+                "comment-id",
+                "author-id",
+                "agrees",
+                "disagrees",
+                "moderated",
+                "comment-body",
+            ])?;
+            for comment in &self.comments {
+                wtr.write_record([
+                    comment.created_at.timestamp().to_string(),
+                    js_datetime(&comment.created_at),
+                    comment.comment_id.to_string(),
+                    comment
+                        .author_id
+                        .map(|author_id| author_id.to_string())
+                        .unwrap_or_default(),
+                    comment.agrees.to_string(),
+                    comment.disagrees.to_string(),
+                    comment.moderated.to_string(),
+                    comment.comment_body.clone(),
+                ])?;
+            }
+            wtr.flush()?;
+        }
+        Ok(csv)
+    }
+
+    pub fn votes_csv(&self) -> Result<Vec<u8>> {
+        let mut csv = Vec::new();
+        {
+            let mut wtr = csv::Writer::from_writer(&mut csv);
+            wtr.write_record(["timestamp", "datetime", "comment-id", "voter-id", "vote"])?;
+            for vote in &self.votes {
+                wtr.write_record([
+                    vote.voted_at.timestamp().to_string(),
+                    js_datetime(&vote.voted_at),
+                    vote.comment_id.to_string(),
+                    vote.voter_id.to_string(),
+                    vote.vote.to_string(),
+                ])?;
+            }
+            wtr.flush()?;
+        }
+        Ok(csv)
+    }
+
+    /// One row per participant with their vote on every comment, empty where they didn't
+    /// vote
+    pub fn participants_votes_csv(&self) -> Result<Vec<u8>> {
+        let mut csv = Vec::new();
+        {
+            let mut wtr = csv::Writer::from_writer(&mut csv);
+            let mut header: Vec<String> = [
+                "participant",
+                "group-id",
+                "n-comments",
+                "n-votes",
+                "n-agree",
+                "n-disagree",
+            ]
+            .iter()
+            .map(|column| column.to_string())
+            .collect();
+            header.extend(
+                self.comments
+                    .iter()
+                    .map(|comment| comment.comment_id.to_string()),
+            );
+            wtr.write_record(&header)?;
+
+            for participant in &self.participants {
+                let mut row = vec![
+                    participant.participant.to_string(),
+                    participant
+                        .group_id
+                        .map(|group_id| group_id.to_string())
+                        .unwrap_or_default(),
+                    participant.n_comments.to_string(),
+                    participant.n_votes.to_string(),
+                    participant.n_agree.to_string(),
+                    participant.n_disagree.to_string(),
+                ];
+                row.extend(self.comments.iter().map(|comment| {
+                    participant
+                        .votes
+                        .get(&comment.comment_id)
+                        .map(|vote| vote.to_string())
+                        .unwrap_or_default()
+                }));
+                wtr.write_record(&row)?;
+            }
+            wtr.flush()?;
+        }
+        Ok(csv)
+    }
+
+    /// The export as a file in `format`
+    pub fn render(&self, format: ConversationExportFormat) -> Result<Vec<u8>> {
+        match format {
+            ConversationExportFormat::Json => Ok(serde_json::to_vec_pretty(self)?),
+            ConversationExportFormat::Csv => {
+                let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+                for (filename, content) in [
+                    ("comments.csv", self.comments_csv()?),
+                    ("votes.csv", self.votes_csv()?),
+                    ("participants-votes.csv", self.participants_votes_csv()?),
+                ] {
+                    zip.start_file(filename, SimpleFileOptions::default())?;
+                    zip.write_all(&content)?;
+                }
+                Ok(zip.finish()?.into_inner())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    fn statement(
+        seconds: i64,
+        content: &str,
+        author_id: Option<Uuid>,
+        moderation_status: StatementModerationStatus,
+    ) -> ExportStatement {
+        ExportStatement {
+            id: Uuid::new_v4(),
+            content: content.to_string(),
+            author_id,
+            moderation_status,
+            created_at: at(seconds),
+        }
+    }
+
+    fn vote(
+        seconds: i64,
+        statement: &ExportStatement,
+        user_id: Option<Uuid>,
+        session_id: Option<Uuid>,
+        vote_type: ArgumentPosition,
+    ) -> ExportVote {
+        ExportVote {
+            statement_id: statement.id,
+            user_id,
+            session_id,
+            vote_type,
+            created_at: at(seconds),
+            updated_at: at(seconds),
+        }
+    }
+
+    /// A seed statement, a statement by a signed in user and votes from that user and an
+    /// anonymous session
+    fn export() -> ConversationExport {
+        let user = Uuid::new_v4();
+        let session = Uuid::new_v4();
+        let seed = statement(
+            0,
+            "Open the pool later",
+            None,
+            StatementModerationStatus::Seed,
+        );
+        let authored = statement(
+            20,
+            "Hire more lifeguards, \"now\"",
+            Some(user),
+            StatementModerationStatus::Rejected,
+        );
+        let votes = vec![
+            vote(10, &seed, None, Some(session), ArgumentPosition::Oppose),
+            vote(30, &seed, Some(user), None, ArgumentPosition::Support),
+            vote(40, &authored, Some(user), None, ArgumentPosition::Support),
+            vote(
+                50,
+                &authored,
+                None,
+                Some(session),
+                ArgumentPosition::Neutral,
+            ),
+        ];
+        let group_labels = HashMap::from([(format!("user:{}", user), 1)]);
+        ConversationExport::build(vec![seed, authored], votes, &group_labels)
+    }
+
+    #[test]
+    fn test_participants_are_numbered_by_first_activity() {
+        let export = export();
+        // The session voted before the user wrote their statement
+        assert_eq!(export.participants.len(), 2);
+        assert_eq!(export.participants[0].group_id, None);
+        assert_eq!(export.participants[0].n_comments, 0);
+        assert_eq!(
+            export.participants[0].votes,
+            BTreeMap::from([(0, -1), (1, 0)])
+        );
+        assert_eq!(export.participants[1].group_id, Some(1));
+        assert_eq!(export.participants[1].n_comments, 1);
+        assert_eq!(export.participants[1].n_agree, 2);
+        assert_eq!(export.comments[0].author_id, None);
+        assert_eq!(export.comments[1].author_id, Some(1));
+        assert_eq!(export.comments[1].moderated, -1);
+    }
+
+    #[test]
+    fn test_csv_layout() {
+        let export = export();
+        let comments = String::from_utf8(export.comments_csv().unwrap()).unwrap();
+        assert_eq!(
+            comments.lines().collect::<Vec<_>>(),
+            vec![
+                "timestamp,datetime,comment-id,author-id,agrees,disagrees,moderated,comment-body",
+                "1700000000,Tue Nov 14 2023 22:13:20 GMT+0000 (Coordinated Universal Time),0,,1,1,1,Open the pool later",
+                "1700000020,Tue Nov 14 2023 22:13:40 GMT+0000 (Coordinated Universal Time),1,1,1,0,-1,\"Hire more lifeguards, \"\"now\"\"\"",
+            ]
+        );
+
+        let votes = String::from_utf8(export.votes_csv().unwrap()).unwrap();
+        assert_eq!(
+            votes.lines().nth(1),
+            Some(
+                "1700000010,Tue Nov 14 2023 22:13:30 GMT+0000 (Coordinated Universal Time),0,0,-1"
+            )
+        );
+
+        let participants = String::from_utf8(export.participants_votes_csv().unwrap()).unwrap();
+        assert_eq!(
+            participants.lines().collect::<Vec<_>>(),
+            vec![
+                "participant,group-id,n-comments,n-votes,n-agree,n-disagree,0,1",
+                "0,,0,2,0,1,-1,0",
+                "1,1,1,2,2,0,1,1",
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+use db::{EmailOutbox, EmailStatus, NewEmail};
+use mailers::{EmailTemplate, EmailTransport};
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use crate::types::Error;
+
+/// Emails claimed from the outbox per delivery pass
+const DELIVERY_BATCH_SIZE: i64 = 50;
+
+/// Renders an email and queues it in the outbox, to be sent by `deliver_outbox`. Emails
+/// with a `dedup_key` that's already been queued are skipped, so retried mutations don't
+/// send the same invite or reset link twice.
+pub async fn enqueue_email(
+    db_pool: &PgPool,
+    to: &str,
+    template: EmailTemplate,
+    dedup_key: Option<&str>,
+) -> Result<Option<EmailOutbox>, Error> {
+    let message = template.render(to)?;
+    let email = EmailOutbox::enqueue(
+        db_pool,
+        NewEmail {
+            to_address: &message.to,
+            template: template.name(),
+            subject: &message.subject,
+            html_body: &message.html,
+            text_body: &message.text,
+            dedup_key,
+        },
+    )
+    .await?;
+
+    Ok(email)
+}
+
+/// Hands due emails in the outbox to the transport, returning how many were sent.
+/// Failed sends are retried with backoff until they run out of attempts.
+pub async fn deliver_outbox(
+    db_pool: &PgPool,
+    transport: &dyn EmailTransport,
+) -> Result<usize, Error> {
+    let mut sent = 0;
+    for email in EmailOutbox::claim_due(db_pool, DELIVERY_BATCH_SIZE).await? {
+        let message = mailers::EmailMessage {
+            to: email.to_address.clone(),
+            subject: email.subject.clone(),
+            html: email.html_body.clone(),
+            text: email.text_body.clone(),
+        };
+        match transport.send(&message).await {
+            Ok(()) => {
+                email.mark_sent(db_pool).await?;
+                sent += 1;
+            }
+            Err(err) => match email.mark_failed(db_pool, &err.to_string()).await? {
+                EmailStatus::Dead => error!(
+                    "Giving up on {} email {} after {} attempts: {}",
+                    email.template, email.id, email.attempts, err
+                ),
+                _ => warn!(
+                    "Failed to send {} email {}, will retry: {}",
+                    email.template, email.id, err
+                ),
+            },
+        }
+    }
+
+    Ok(sent)
+}
@@ -0,0 +1,100 @@
+use db::StatementModerationStatus;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Postgres NOTIFY channel emitted when vote counts or totals for a race change
+pub const RACE_RESULTS_UPDATED_CHANNEL: &str = "race_results_updated";
+/// Postgres NOTIFY channel emitted when a statement is added or its moderation status changes
+pub const STATEMENT_UPDATED_CHANNEL: &str = "statement_updated";
+/// Postgres NOTIFY channel emitted when a vote on a statement is cast, changed or removed
+pub const STATEMENT_VOTE_UPDATED_CHANNEL: &str = "statement_vote_updated";
+
+/// Every channel the server should LISTEN on to feed the broker
+pub const CHANNELS: [&str; 3] = [
+    RACE_RESULTS_UPDATED_CHANNEL,
+    STATEMENT_UPDATED_CHANNEL,
+    STATEMENT_VOTE_UPDATED_CHANNEL,
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaceResultsUpdated {
+    pub race_id: Uuid,
+    pub election_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementUpdated {
+    pub statement_id: Uuid,
+    pub conversation_id: Uuid,
+    pub moderation_status: StatementModerationStatus,
+    /// `None` when the statement was just created
+    pub previous_moderation_status: Option<StatementModerationStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementVoteUpdated {
+    pub statement_id: Uuid,
+    pub conversation_id: Uuid,
+}
+
+/// Fans out realtime events received from Postgres to GraphQL subscribers.
+/// A single broker is shared between the Postgres listener and the schema.
+#[derive(Clone)]
+pub struct EventBroker {
+    race_results: broadcast::Sender<RaceResultsUpdated>,
+    statements: broadcast::Sender<StatementUpdated>,
+    statement_votes: broadcast::Sender<StatementVoteUpdated>,
+}
+
+impl Default for EventBroker {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl EventBroker {
+    pub fn new(capacity: usize) -> Self {
+        let (race_results, _) = broadcast::channel(capacity);
+        let (statements, _) = broadcast::channel(capacity);
+        let (statement_votes, _) = broadcast::channel(capacity);
+        Self {
+            race_results,
+            statements,
+            statement_votes,
+        }
+    }
+
+    pub fn subscribe_race_results(&self) -> broadcast::Receiver<RaceResultsUpdated> {
+        self.race_results.subscribe()
+    }
+
+    pub fn subscribe_statements(&self) -> broadcast::Receiver<StatementUpdated> {
+        self.statements.subscribe()
+    }
+
+    pub fn subscribe_statement_votes(&self) -> broadcast::Receiver<StatementVoteUpdated> {
+        self.statement_votes.subscribe()
+    }
+
+    /// Routes a raw notification payload to subscribers based on its channel.
+    /// Sending only fails when there are no active subscribers, so those errors are ignored.
+    pub fn publish(&self, channel: &str, payload: &str) -> Result<(), serde_json::Error> {
+        match channel {
+            RACE_RESULTS_UPDATED_CHANNEL => {
+                let _ = self.race_results.send(serde_json::from_str(payload)?);
+            }
+            STATEMENT_UPDATED_CHANNEL => {
+                let _ = self.statements.send(serde_json::from_str(payload)?);
+            }
+            STATEMENT_VOTE_UPDATED_CHANNEL => {
+                let _ = self.statement_votes.send(serde_json::from_str(payload)?);
+            }
+            _ => tracing::warn!("No subscribers registered for channel {}", channel),
+        }
+        Ok(())
+    }
+}
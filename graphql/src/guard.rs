@@ -1,10 +1,19 @@
-use async_graphql::{Context, Guard, Result, ID};
+use async_graphql::{parser::types::OperationType, Context, ErrorExtensions, Guard, Result, ID};
 use auth::AccessTokenClaims;
-use db::OrganizationRoleType;
+use db::{
+    models::candidate_guide::CandidateGuide, OrganizationApiKey, OrganizationRoleType,
+    SubmissionStatus, UpsertQuestionSubmissionInput,
+};
 use jsonwebtoken::TokenData;
+use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::context::ApiContext;
+use crate::{
+    context::ApiContext,
+    rate_limit::{RateLimitScope, RateLimiter},
+    types::Error,
+    SessionData,
+};
 
 // Could genericize and expand this struct to take a role (for gating certain API calls to certain roles, e.g.)
 //
@@ -30,38 +39,175 @@ impl Guard for StaffOnly {
     }
 }
 
+const SUBMISSIONS_CLOSED: &str = "Submissions for this candidate guide are closed";
+
+enum IntakeTarget<'a> {
+    /// The candidate's profile, by slug
+    Politician(&'a str),
+    /// An answer to a candidate guide question
+    Submission(&'a UpsertQuestionSubmissionInput),
+}
+
+/// Lets candidates edit their own profile and answers with their `intake_token` while one of
+/// their candidate guides is accepting submissions
 pub struct IntakeTokenGuard<'a> {
-    intake_token: &'a str,
-    slug: &'a str,
+    intake_token: Option<&'a str>,
+    target: IntakeTarget<'a>,
 }
 
 impl<'a> IntakeTokenGuard<'a> {
     pub fn new(intake_token: &'a str, slug: &'a str) -> Self {
-        Self { intake_token, slug }
+        Self {
+            intake_token: Some(intake_token),
+            target: IntakeTarget::Politician(slug),
+        }
     }
-}
 
-impl<'a> Guard for IntakeTokenGuard<'a> {
-    async fn check(&self, ctx: &Context<'_>) -> Result<(), async_graphql::Error> {
+    /// Anyone can leave a new answer that isn't from a candidate, but existing answers,
+    /// statuses and editorial notes are for the candidate's token or editors. Once an editor
+    /// starts reviewing an answer, the candidate can no longer change it.
+    pub fn submission(
+        intake_token: Option<&'a str>,
+        input: &'a UpsertQuestionSubmissionInput,
+    ) -> Self {
+        Self {
+            intake_token,
+            target: IntakeTarget::Submission(input),
+        }
+    }
+
+    async fn check_politician(&self, ctx: &Context<'_>, slug: &str) -> Result<()> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-        let record = sqlx::query!(
+        let politician_id = sqlx::query_scalar!(
             r#"
             SELECT id FROM politician WHERE intake_token = $1 AND slug = $2
         "#,
             self.intake_token,
-            self.slug,
+            slug,
         )
         .fetch_optional(&db_pool)
         .await?;
 
-        match record {
-            Some(_) => Ok(()),
+        match politician_id {
+            Some(politician_id) => {
+                if CandidateGuide::is_candidate_open(&db_pool, politician_id).await? {
+                    Ok(())
+                } else {
+                    Err(SUBMISSIONS_CLOSED.into())
+                }
+            }
             None => {
                 let staff_guard = StaffOnly;
                 staff_guard.check(ctx).await
             }
         }
     }
+
+    /// Staff and members of the question's organization can edit answers at any time
+    async fn check_editor(&self, ctx: &Context<'_>, question_id: Uuid) -> Result<()> {
+        if StaffOnly.check(ctx).await.is_ok() {
+            return Ok(());
+        }
+        OrganizationGuard::owner_of(
+            OrganizationResource::Question,
+            &ID::from(question_id),
+            &OrganizationRoleType::Member,
+        )
+        .check(ctx)
+        .await
+    }
+
+    async fn check_submission(
+        &self,
+        ctx: &Context<'_>,
+        input: &UpsertQuestionSubmissionInput,
+    ) -> Result<()> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+
+        // An existing answer keeps its question and candidate, so check against those
+        let existing = match input.id {
+            Some(id) => {
+                sqlx::query!(
+                    r#"
+                SELECT question_id, candidate_id, status AS "status: SubmissionStatus"
+                FROM question_submission
+                WHERE id = $1
+                "#,
+                    id,
+                )
+                .fetch_optional(&db_pool)
+                .await?
+            }
+            None => None,
+        };
+        if let Some(existing) = &existing {
+            if existing.candidate_id != input.candidate_id {
+                return Err("You don't have permission to to run this query/mutation".into());
+            }
+        }
+        let question_id = existing
+            .as_ref()
+            .map_or(input.question_id, |existing| existing.question_id);
+
+        let Some(candidate_id) = input.candidate_id else {
+            // Anyone can leave a new answer that isn't from a candidate, but only editors
+            // can change one or set its status and editorial note
+            if existing.is_none() && input.editorial.is_none() && input.status.is_none() {
+                return Ok(());
+            }
+            return self.check_editor(ctx, question_id).await;
+        };
+
+        let holds_token = match self.intake_token {
+            Some(intake_token) => {
+                sqlx::query_scalar!(
+                    r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM politician
+                    WHERE id = $1 AND intake_token = $2 AND deleted_at IS NULL
+                ) AS "exists!"
+                "#,
+                    candidate_id,
+                    intake_token,
+                )
+                .fetch_one(&db_pool)
+                .await?
+            }
+            None => false,
+        };
+        if !holds_token {
+            return self.check_editor(ctx, question_id).await;
+        }
+
+        if input.editorial.is_some() {
+            return Err("Only editors can add an editorial note".into());
+        }
+        if input.status.is_some_and(|status| {
+            !matches!(
+                status,
+                SubmissionStatus::Draft | SubmissionStatus::Submitted
+            )
+        }) {
+            return Err("Answers can only be saved as a draft or submitted".into());
+        }
+        if !CandidateGuide::is_question_open(&db_pool, question_id).await? {
+            return Err(SUBMISSIONS_CLOSED.into());
+        }
+        if existing.is_some_and(|existing| !existing.status.is_editable_by_candidate()) {
+            return Err("This answer is being reviewed and can't be changed".into());
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Guard for IntakeTokenGuard<'a> {
+    async fn check(&self, ctx: &Context<'_>) -> Result<(), async_graphql::Error> {
+        match self.target {
+            IntakeTarget::Politician(slug) => self.check_politician(ctx, slug).await,
+            IntakeTarget::Submission(input) => self.check_submission(ctx, input).await,
+        }
+    }
 }
 
 pub struct UserGuard<'a> {
@@ -88,33 +234,272 @@ impl<'a> Guard for UserGuard<'a> {
     }
 }
 
+/// Content that belongs to an organization
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrganizationResource {
+    Embed,
+    CandidateGuide,
+    Question,
+    /// Owned by the organization of its question
+    QuestionSubmission,
+    Poll,
+    Conversation,
+    /// Owned by the organization of its conversation
+    Statement,
+}
+
+impl OrganizationResource {
+    fn name(&self) -> &'static str {
+        match self {
+            OrganizationResource::Embed => "embed",
+            OrganizationResource::CandidateGuide => "candidate guide",
+            OrganizationResource::Question => "question",
+            OrganizationResource::QuestionSubmission => "question submission",
+            OrganizationResource::Poll => "poll",
+            OrganizationResource::Conversation => "conversation",
+            OrganizationResource::Statement => "statement",
+        }
+    }
+
+    /// The organization that owns the record, or `None` if there is no such record
+    pub async fn organization_id(&self, db_pool: &PgPool, id: Uuid) -> Result<Option<Uuid>> {
+        let organization_id = match self {
+            OrganizationResource::Embed => {
+                sqlx::query_scalar!(
+                    r#"SELECT organization_id AS "organization_id!" FROM embed WHERE id = $1"#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::CandidateGuide => {
+                sqlx::query_scalar!(
+                    r#"SELECT organization_id AS "organization_id!" FROM candidate_guide WHERE id = $1"#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::Question => {
+                sqlx::query_scalar!(
+                    r#"SELECT organization_id AS "organization_id!" FROM question WHERE id = $1"#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::QuestionSubmission => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT q.organization_id AS "organization_id!"
+                    FROM question_submission qs
+                    JOIN question q ON q.id = qs.question_id
+                    WHERE qs.id = $1
+                    "#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::Poll => {
+                sqlx::query_scalar!(
+                    r#"SELECT organization_id AS "organization_id!" FROM poll WHERE id = $1"#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::Conversation => {
+                sqlx::query_scalar!(
+                    r#"SELECT organization_id AS "organization_id!" FROM conversation WHERE id = $1"#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+            OrganizationResource::Statement => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT c.organization_id AS "organization_id!"
+                    FROM statement s
+                    JOIN conversation c ON c.id = s.conversation_id
+                    WHERE s.id = $1
+                    "#,
+                    id
+                )
+                .fetch_optional(db_pool)
+                .await?
+            }
+        };
+        Ok(organization_id)
+    }
+}
+
+enum OrganizationTarget<'a> {
+    Organization(&'a ID),
+    Owner(OrganizationResource, &'a ID),
+    Upsert {
+        resource: OrganizationResource,
+        id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+    },
+}
+
 pub struct OrganizationGuard<'a> {
-    organization_id: &'a ID,
+    target: OrganizationTarget<'a>,
     min_role: &'a OrganizationRoleType,
+    embed_write: bool,
 }
 
 impl<'a> OrganizationGuard<'a> {
     pub fn new(organization_id: &'a ID, min_role: &'a OrganizationRoleType) -> Self {
         Self {
-            organization_id,
+            target: OrganizationTarget::Organization(organization_id),
             min_role,
+            embed_write: false,
+        }
+    }
+
+    /// Checks the caller's role in the organization that owns the record `id`
+    pub fn owner_of(
+        resource: OrganizationResource,
+        id: &'a ID,
+        min_role: &'a OrganizationRoleType,
+    ) -> Self {
+        Self {
+            target: OrganizationTarget::Owner(resource, id),
+            min_role,
+            embed_write: false,
+        }
+    }
+
+    /// For upserts: updates are checked against the organization that owns the existing
+    /// record, which can't be moved to another organization, and creates against
+    /// `organization_id`.
+    pub fn upsert(
+        resource: OrganizationResource,
+        id: Option<Uuid>,
+        organization_id: Option<Uuid>,
+        min_role: &'a OrganizationRoleType,
+    ) -> Self {
+        Self {
+            target: OrganizationTarget::Upsert {
+                resource,
+                id,
+                organization_id,
+            },
+            min_role,
+            embed_write: false,
+        }
+    }
+
+    /// Lets write API keys run the mutation, which must check the key's embed types itself
+    pub fn embed_write(mut self) -> Self {
+        self.embed_write = true;
+        self
+    }
+
+    async fn resolve_organization_id(&self, ctx: &Context<'_>) -> Result<Uuid> {
+        match &self.target {
+            OrganizationTarget::Organization(organization_id) => {
+                Ok(Uuid::parse_str(organization_id.as_str())?)
+            }
+            OrganizationTarget::Owner(resource, id) => {
+                let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+                resource
+                    .organization_id(&db_pool, Uuid::parse_str(id.as_str())?)
+                    .await?
+                    .ok_or_else(|| format!("No {} found with this id", resource.name()).into())
+            }
+            OrganizationTarget::Upsert {
+                resource,
+                id,
+                organization_id,
+            } => {
+                let existing = match id {
+                    Some(id) => {
+                        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+                        resource.organization_id(&db_pool, *id).await?
+                    }
+                    None => None,
+                };
+                match (existing, organization_id) {
+                    (Some(existing), Some(organization_id)) if existing != *organization_id => Err(
+                        format!("This {} belongs to another organization", resource.name()).into(),
+                    ),
+                    (Some(existing), _) => Ok(existing),
+                    (None, Some(organization_id)) => Ok(*organization_id),
+                    (None, None) => Err("organization_id is required".into()),
+                }
+            }
         }
     }
 }
 
 impl<'a> Guard for OrganizationGuard<'a> {
     async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let organization_id = self.resolve_organization_id(ctx).await?;
         if let Some(token_data) = ctx.data_unchecked::<Option<TokenData<AccessTokenClaims>>>() {
             if token_data.claims.organizations.iter().any(|o| {
-                o.organization_id == Uuid::parse_str(self.organization_id.as_str()).unwrap()
-                    && o.role as i32 >= *self.min_role as i32
+                o.organization_id == organization_id && o.role as i32 >= *self.min_role as i32
             }) {
                 Ok(())
             } else {
                 Err("You don't have permission to to run this query/mutation".into())
             }
+        } else if let Some(api_key) = ctx.data_opt::<OrganizationApiKey>() {
+            // Keys are checked like a member with the role their scope grants, and can only
+            // run the embed mutations
+            let is_mutation = ctx.query_env.operation.node.ty == OperationType::Mutation;
+            if api_key.allows(
+                organization_id,
+                *self.min_role,
+                is_mutation,
+                self.embed_write,
+            ) {
+                Ok(())
+            } else {
+                Err("This API key doesn't have permission to run this query/mutation".into())
+            }
         } else {
             Err("You don't have permission to to run this query/mutation".into())
         }
     }
 }
+
+/// Counts the request against the operation's budget for the client's address, session
+/// and user. Does nothing when the schema has no `RateLimiter`, as in tests.
+pub struct RateLimitGuard {
+    operation: &'static str,
+}
+
+impl RateLimitGuard {
+    pub fn new(operation: &'static str) -> Self {
+        Self { operation }
+    }
+}
+
+impl Guard for RateLimitGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        let Some(limiter) = ctx.data_opt::<RateLimiter>() else {
+            return Ok(());
+        };
+
+        let mut clients = vec![];
+        if let Some(session_data) = ctx.data_opt::<SessionData>() {
+            clients.push((RateLimitScope::Ip, session_data.ip.to_string()));
+            clients.push((RateLimitScope::Session, session_data.session_id.to_string()));
+        }
+        if let Some(Some(token_data)) = ctx.data_opt::<Option<TokenData<AccessTokenClaims>>>() {
+            clients.push((RateLimitScope::User, token_data.claims.sub.to_string()));
+        }
+
+        limiter.check(self.operation, &clients).map_err(|limited| {
+            Error::RateLimited {
+                operation: self.operation.to_string(),
+                retry_after_seconds: limited.retry_after.as_secs().max(1),
+            }
+            .extend()
+        })
+    }
+}
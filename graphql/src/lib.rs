@@ -1,14 +1,23 @@
+pub mod audit;
 pub mod cache;
 pub mod context;
+pub mod conversation_analysis;
+pub mod conversation_export;
+pub mod email;
+pub mod events;
 pub mod guard;
+pub mod moderation;
 pub mod mutation;
+pub mod opinion_map;
+pub mod outreach;
 pub mod query;
+pub mod rate_limit;
 pub mod relay;
 pub mod subscription;
 pub mod tests;
 pub mod types;
 
-use std::{fmt, net::SocketAddr};
+use std::{fmt, net::IpAddr};
 
 use crate::{mutation::Mutation, query::Query, types::Error};
 use async_graphql::extensions::Tracing;
@@ -30,7 +39,9 @@ pub struct SessionID(String);
 #[derive(Debug, Clone)]
 pub struct SessionData {
     pub session_id: SessionID,
-    pub ip: SocketAddr,
+    /// The client's address, which behind a proxy comes from `X-Forwarded-For`
+    pub ip: IpAddr,
+    pub user_agent: Option<String>,
 }
 
 impl From<String> for SessionID {
@@ -105,6 +116,45 @@ pub async fn upload_to_s3(file: File, directory: String) -> Result<Url, Error> {
     Ok(image_url)
 }
 
+/// The bucket for files that are only reachable through `presigned_s3_url`, such as
+/// conversation exports. Unlike `AWS_S3_BUCKET` it must not be publicly readable.
+fn private_bucket() -> Result<Bucket, Error> {
+    dotenv().ok();
+    let access_key = std::env::var("AWS_ACCESS_KEY")?;
+    let secret_key = std::env::var("AWS_SECRET_KEY")?;
+    let bucket_name = std::env::var("AWS_S3_PRIVATE_BUCKET")?;
+    let region =
+        std::env::var("AWS_S3_PRIVATE_BUCKET_REGION").unwrap_or_else(|_| "us-east-2".to_string());
+    let region = region
+        .parse()
+        .map_err(|_| Error::S3RegionError(region.clone()))?;
+    let credentials = Credentials::new(Some(&access_key), Some(&secret_key), None, None, None)?;
+    let mut bucket = Bucket::new(&bucket_name, region, credentials)?;
+    bucket.add_header("x-amz-acl", "private");
+
+    Ok(bucket)
+}
+
+/// Uploads a file to the private bucket at `path`, where only `presigned_s3_url` links
+/// can read it
+pub async fn upload_private_to_s3(file: File, path: &str) -> Result<(), Error> {
+    info!("Uploading private file to s3");
+    let bucket = private_bucket()?;
+    bucket
+        .put_object_with_content_type(path, &file.content, &file.mimetype.unwrap_or_default())
+        .await?;
+
+    Ok(())
+}
+
+/// A link to a file uploaded with `upload_private_to_s3` that works for `expiry_secs`
+pub fn presigned_s3_url(path: &str, expiry_secs: u32) -> Result<Url, Error> {
+    let bucket = private_bucket()?;
+    let url = bucket.presign_get(path, expiry_secs, None)?;
+
+    Ok(Url::parse(&url).expect("S3 presigned URLs are valid"))
+}
+
 pub async fn delete_from_s3(path: String) -> Result<(), Error> {
     info!("Deleting file from s3");
     dotenv().ok();
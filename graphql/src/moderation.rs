@@ -0,0 +1,279 @@
+//! Pre-screens statements as they're added to a conversation. Statements that match the
+//! word lists, repeat one already in the conversation or that an LLM classifies as off
+//! topic or spam are flagged and held for a moderator, with the reasons recorded on the
+//! statement.
+
+use std::collections::HashSet;
+
+use async_graphql::Result;
+use db::{
+    Conversation, ConversationModerationMode, ScreeningFlag, ScreeningReason, Statement,
+    StatementModerationStatus,
+};
+use llm::Message;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::PgPool;
+use tracing::warn;
+
+const PROFANITY: &[&str] = &[
+    "asshole",
+    "assholes",
+    "bastard",
+    "bastards",
+    "bitch",
+    "bitches",
+    "bullshit",
+    "cunt",
+    "damn",
+    "dick",
+    "dickhead",
+    "fuck",
+    "fucked",
+    "fucker",
+    "fucking",
+    "motherfucker",
+    "piss off",
+    "shit",
+    "shitty",
+];
+
+const PERSONAL_ATTACKS: &[&str] = &[
+    "dumbass",
+    "go to hell",
+    "idiot",
+    "idiots",
+    "imbecile",
+    "kill yourself",
+    "loser",
+    "losers",
+    "moron",
+    "morons",
+    "retard",
+    "retards",
+    "scum",
+    "shut up",
+    "you are stupid",
+    "you're stupid",
+];
+
+/// Lowercased words of `text`, keeping apostrophes so contractions match as written
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Entries of `list` that appear in `content_words` as whole words or phrases
+fn matches<'a>(content_words: &[String], list: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut found = Vec::new();
+    for entry in list {
+        let entry_words = words(entry);
+        if entry_words.is_empty() {
+            continue;
+        }
+        let is_match = content_words
+            .windows(entry_words.len())
+            .any(|window| window == entry_words.as_slice());
+        if is_match && !found.contains(&entry_words.join(" ")) {
+            found.push(entry_words.join(" "));
+        }
+    }
+    found
+}
+
+/// Checks a statement against the built in profanity and personal attack lists and the
+/// conversation's own blocked words, with one flag per list that matched
+pub fn screen_words(content: &str, blocked_words: &[String]) -> Vec<ScreeningFlag> {
+    let content_words = words(content);
+    [
+        (
+            ScreeningReason::Profanity,
+            matches(&content_words, PROFANITY.iter().copied()),
+        ),
+        (
+            ScreeningReason::PersonalAttack,
+            matches(&content_words, PERSONAL_ATTACKS.iter().copied()),
+        ),
+        (
+            ScreeningReason::BlockedWord,
+            matches(&content_words, blocked_words.iter().map(String::as_str)),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, found)| !found.is_empty())
+    .map(|(reason, found)| ScreeningFlag {
+        reason,
+        detail: found.join(", "),
+    })
+    .collect()
+}
+
+/// The status a new statement starts with. Flagged statements always wait for a moderator.
+pub fn initial_status(
+    moderation_mode: ConversationModerationMode,
+    flags: &[ScreeningFlag],
+) -> StatementModerationStatus {
+    match moderation_mode {
+        ConversationModerationMode::Post if flags.is_empty() => StatementModerationStatus::Accepted,
+        _ => StatementModerationStatus::Unmoderated,
+    }
+}
+
+#[derive(Deserialize)]
+struct LlmScreening {
+    flags: Vec<ScreeningFlag>,
+}
+
+async fn screen_with_llm(conversation: &Conversation, content: &str) -> Result<Vec<ScreeningFlag>> {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "flags": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "reason": {
+                            "type": "string",
+                            "enum": ["off_topic", "spam", "personal_attack", "profanity"]
+                        },
+                        "detail": { "type": "string" }
+                    },
+                    "required": ["reason", "detail"]
+                }
+            }
+        },
+        "required": ["flags"]
+    });
+    let screening: LlmScreening = llm::client()?
+        .complete_json(
+            "statement_screening",
+            vec![
+                Message::system(
+                    "You screen statements submitted to a public deliberation about a topic. \
+                     Flag a statement only when it is clearly off topic, spam or advertising, \
+                     a personal attack, or profane, with a short explanation for the moderator. \
+                     Disagreement and strong opinions are not reasons to flag. Return no flags \
+                     for acceptable statements.",
+                ),
+                Message::user(format!(
+                    "Topic: {}\nDescription: {}\nStatement: {}",
+                    conversation.topic,
+                    conversation.description.as_deref().unwrap_or(""),
+                    content
+                )),
+            ],
+            &schema,
+        )
+        .await?;
+    Ok(screening.flags)
+}
+
+/// Runs the pre-screen for a statement about to be added to `conversation`. The LLM
+/// classifier only runs when the conversation turns it on, and a failed call is logged
+/// rather than holding the statement back.
+pub async fn prescreen_statement(
+    db_pool: &PgPool,
+    conversation: &Conversation,
+    content: &str,
+) -> Result<Vec<ScreeningFlag>> {
+    let mut flags = screen_words(content, &conversation.blocked_words);
+
+    if let Some(duplicate) =
+        Statement::find_near_duplicate(db_pool, conversation.id, content).await?
+    {
+        flags.push(ScreeningFlag {
+            reason: ScreeningReason::Duplicate,
+            detail: format!(
+                "{:.0}% similar to statement {}: {}",
+                duplicate.similarity * 100.0,
+                duplicate.id,
+                duplicate.content
+            ),
+        });
+    }
+
+    if conversation.llm_screening {
+        match screen_with_llm(conversation, content).await {
+            Ok(llm_flags) => {
+                let found: HashSet<ScreeningReason> = flags.iter().map(|f| f.reason).collect();
+                flags.extend(
+                    llm_flags
+                        .into_iter()
+                        .filter(|flag| !found.contains(&flag.reason)),
+                );
+            }
+            Err(err) => warn!(
+                "LLM screening failed for conversation {}: {}",
+                conversation.id, err.message
+            ),
+        }
+    }
+
+    Ok(flags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_words_flags_each_list_once() {
+        let flags = screen_words(
+            "Shut up, you IDIOT. This shit plan is shit.",
+            &["plan".to_string()],
+        );
+        assert_eq!(
+            flags,
+            vec![
+                ScreeningFlag {
+                    reason: ScreeningReason::Profanity,
+                    detail: "shit".to_string(),
+                },
+                ScreeningFlag {
+                    reason: ScreeningReason::PersonalAttack,
+                    detail: "idiot, shut up".to_string(),
+                },
+                ScreeningFlag {
+                    reason: ScreeningReason::BlockedWord,
+                    detail: "plan".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_screen_words_matches_whole_words() {
+        // "Scunthorpe" and "classic" contain listed words without being them
+        assert!(screen_words("Scunthorpe has a classic bus depot", &[]).is_empty());
+        assert!(screen_words("We should shut the upper road", &[]).is_empty());
+        assert_eq!(
+            screen_words("You're stupid if you think so", &[])[0].reason,
+            ScreeningReason::PersonalAttack
+        );
+    }
+
+    #[test]
+    fn test_initial_status() {
+        let flag = ScreeningFlag {
+            reason: ScreeningReason::Duplicate,
+            detail: String::new(),
+        };
+        assert_eq!(
+            initial_status(ConversationModerationMode::Pre, &[]),
+            StatementModerationStatus::Unmoderated
+        );
+        assert_eq!(
+            initial_status(ConversationModerationMode::Post, &[]),
+            StatementModerationStatus::Accepted
+        );
+        assert_eq!(
+            initial_status(ConversationModerationMode::Post, &[flag]),
+            StatementModerationStatus::Unmoderated
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use crate::{
     context::ApiContext,
+    email::enqueue_email,
     guard::StaffOnly,
     is_admin,
     types::{CreateUserResult, Error, LoginResult},
+    SessionData,
 };
 use async_graphql::{Context, InputObject, Object, Result, ID};
 use auth::{
@@ -11,11 +13,11 @@ use auth::{
 };
 use db::{
     AddressInput, Coordinates, CreateUserInput, CreateUserWithProfileInput, OrganizationRoleType,
-    SystemRoleType, User,
+    SessionRevocationReason, SystemRoleType, User, UserSession,
 };
 use geocodio::GeocodioProxy;
 use jsonwebtoken::TokenData;
-use mailers::EmailClient;
+use mailers::EmailTemplate;
 use pwhash::bcrypt;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
@@ -155,6 +157,65 @@ async fn consume_invite_token(
     Ok(())
 }
 
+/// Starts a session for the requesting device and sets its access and refresh token cookies
+async fn start_session(ctx: &Context<'_>, db_pool: &PgPool, user: User) -> Result<(), Error> {
+    let session_id = uuid::Uuid::new_v4();
+    let organization_roles = User::organization_roles(db_pool, user.id).await?;
+    let access_token =
+        create_access_token_for_user(user.clone(), organization_roles, Some(session_id))?;
+    let refresh_token = create_refresh_token_for_user(user.clone(), session_id)?;
+
+    let session_data = ctx.data_opt::<SessionData>();
+    UserSession::create(
+        db_pool,
+        session_id,
+        user.id,
+        &refresh_token,
+        session_data.and_then(|s| s.user_agent.as_deref()),
+        session_data.map(|s| s.ip.to_string()).as_deref(),
+    )
+    .await?;
+
+    ctx.insert_http_header(
+        "Set-Cookie",
+        format_auth_cookie(auth::TokenType::Access, &access_token),
+    );
+    ctx.append_http_header(
+        "Set-Cookie",
+        format_auth_cookie(auth::TokenType::Refresh, &refresh_token),
+    );
+    Ok(())
+}
+
+fn clear_auth_cookies(ctx: &Context<'_>) {
+    let expiry = (chrono::Utc::now() - chrono::Duration::try_days(100).unwrap())
+        .format("%a, %d %b %Y %T GMT");
+    let config::Config {
+        root_domain,
+        same_site,
+        ..
+    } = config::Config::default();
+
+    ctx.insert_http_header(
+        "Set-Cookie",
+        format!(
+            "refresh_token=null; expires={}; Max-Age=0; HttpOnly; SameSite={}; Secure; Domain={}; Path=/",
+            expiry,
+            same_site,
+            root_domain
+        ),
+    );
+    ctx.append_http_header(
+        "Set-Cookie",
+        format!(
+            "access_token=null; expires={}; Max-Age=0; HttpOnly; SameSite={}; Secure; Domain={}; Path=/",
+            expiry,
+            same_site,
+            root_domain
+        ),
+    );
+}
+
 #[Object]
 impl AuthMutation {
     #[graphql(guard = "StaffOnly", visible = "is_admin")]
@@ -273,17 +334,18 @@ impl AuthMutation {
                     None
                 };
 
-                if let Err(err) = EmailClient::default()
-                    .send_invite_email(
-                        invite.email,
-                        invite_url.clone(),
-                        organization.map(|o| o.name),
-                        politician.map(|p| format!("{} {}", p.first_name, p.last_name)),
-                    )
-                    .await
-                {
-                    println!("Error sending welcome email: {}", err)
-                }
+                enqueue_email(
+                    &db_pool,
+                    &invite.email,
+                    EmailTemplate::Invite {
+                        invite_url: invite_url.clone(),
+                        organization_name: organization.map(|o| o.name),
+                        politician_name: politician
+                            .map(|p| format!("{} {}", p.first_name, p.last_name)),
+                    },
+                    Some(&format!("invite:{}", invite.token)),
+                )
+                .await?;
 
                 Ok(Some(invite_url))
             }
@@ -433,12 +495,7 @@ impl AuthMutation {
                     consume_invite_token(&db_pool, invite_token, &new_user.email, new_user.id)
                         .await?;
                 }
-                let organization_roles = User::organization_roles(&db_pool, new_user.id).await?;
-
-                let access_token =
-                    create_access_token_for_user(new_user.clone(), organization_roles)?;
-                let refresh_token = create_refresh_token_for_user(new_user.clone())?;
-                db::User::update_refresh_token(&db_pool, new_user.id, &refresh_token).await?;
+                start_session(ctx, &db_pool, new_user.clone()).await?;
 
                 let account_confirmation_url = format!(
                     "{}auth/confirm?token={}",
@@ -446,28 +503,25 @@ impl AuthMutation {
                     confirmation_token
                 );
 
-                // Attempt to send welcome email and log failure
+                // Attempt to queue welcome email and log failure
                 if !new_user.email.contains("staging.email.test") {
-                    if let Err(err) = EmailClient::default()
-                        .send_welcome_email(new_user.email.clone(), account_confirmation_url)
-                        .await
+                    if let Err(err) = enqueue_email(
+                        &db_pool,
+                        &new_user.email,
+                        EmailTemplate::Welcome {
+                            account_confirmation_url,
+                        },
+                        Some(&format!("welcome:{}", new_user.id)),
+                    )
+                    .await
                     {
                         warn!(
                             error = ?err,
-                            "Failed to send welcome email — continuing registration"
+                            "Failed to queue welcome email — continuing registration"
                         );
                     }
                 }
 
-                ctx.insert_http_header(
-                    "Set-Cookie",
-                    format_auth_cookie(auth::TokenType::Access, &access_token),
-                );
-                ctx.insert_http_header(
-                    "Set-Cookie",
-                    format_auth_cookie(auth::TokenType::Refresh, &refresh_token),
-                );
-
                 Ok(LoginResult {
                     user_id: new_user.id.into(),
                 })
@@ -517,18 +571,7 @@ impl AuthMutation {
                 if let Some(invite_token) = input.invite_token.as_deref() {
                     consume_invite_token(&db_pool, invite_token, &user.email, user.id).await?;
                 }
-                let organization_roles = User::organization_roles(&db_pool, user.id).await?;
-                let access_token = create_access_token_for_user(user.clone(), organization_roles)?;
-                ctx.insert_http_header(
-                    "Set-Cookie",
-                    format_auth_cookie(auth::TokenType::Access, &access_token),
-                );
-                let refresh_token = create_refresh_token_for_user(user.clone())?;
-                db::User::update_refresh_token(&db_pool, user.id, &refresh_token).await?;
-                ctx.append_http_header(
-                    "Set-Cookie",
-                    format_auth_cookie(auth::TokenType::Refresh, &refresh_token),
-                );
+                start_session(ctx, &db_pool, user.clone()).await?;
                 User::set_last_login_at(&db_pool, user.id).await?;
                 Ok(LoginResult {
                     user_id: user.id.into(),
@@ -574,10 +617,13 @@ impl AuthMutation {
             );
 
             // Send out email with link to reset new password
-            EmailClient::default()
-                .send_reset_password_email(email, reset_password_url)
-                .await
-                .expect("Failed to send reset password email");
+            enqueue_email(
+                &db_pool,
+                &email,
+                EmailTemplate::ResetPassword { reset_password_url },
+                Some(&format!("reset_password:{}", reset_token)),
+            )
+            .await?;
 
             Ok(true)
         } else {
@@ -597,11 +643,7 @@ impl AuthMutation {
             User::reset_password(&db_pool, input.new_password, input.reset_token).await;
 
         if let Ok(user) = update_result {
-            let email = user.email;
-            EmailClient::default()
-                .send_password_changed_email(email)
-                .await
-                .expect("Failed to send password changed email");
+            enqueue_email(&db_pool, &user.email, EmailTemplate::PasswordChanged, None).await?;
 
             Ok(true)
         } else {
@@ -637,10 +679,13 @@ impl AuthMutation {
                             User::update_password(&db_pool, input.new_password, user.claims.sub)
                                 .await;
                         if update_result.is_ok() {
-                            EmailClient::default()
-                                .send_password_changed_email(user.claims.email.clone())
-                                .await
-                                .expect("Failed to send password changed email");
+                            enqueue_email(
+                                &db_pool,
+                                &user.claims.email,
+                                EmailTemplate::PasswordChanged,
+                                None,
+                            )
+                            .await?;
                             Ok(true)
                         } else {
                             Err(Error::ResetTokenInvalid)
@@ -672,34 +717,73 @@ impl AuthMutation {
         Ok(true)
     }
 
+    /// Signs out of the current session
     #[graphql(visible = "is_admin")]
     async fn logout(&self, ctx: &Context<'_>) -> Result<bool, Error> {
-        let expiry = (chrono::Utc::now() - chrono::Duration::try_days(100).unwrap())
-            .format("%a, %d %b %Y %T GMT");
-        let config::Config {
-            root_domain,
-            same_site,
-            ..
-        } = config::Config::default();
-
-        ctx.insert_http_header(
-            "Set-Cookie",
-            format!(
-                "refresh_token=null; expires={}; Max-Age=0; HttpOnly; SameSite={}; Secure; Domain={}; Path=/",
-                expiry,
-                same_site,
-                root_domain
-            ),
-        );
-        ctx.append_http_header(
-            "Set-Cookie",
-            format!(
-                "access_token=null; expires={}; Max-Age=0; HttpOnly; SameSite={}; Secure; Domain={}; Path=/",
-                expiry,
-                same_site,
-                root_domain
-            ),
-        );
+        if let Some(token_data) = ctx.data::<Option<TokenData<AccessTokenClaims>>>().unwrap() {
+            if let Some(session_id) = token_data.claims.sid {
+                let db_pool = ctx.data::<ApiContext>().unwrap().pool.clone();
+                UserSession::revoke(
+                    &db_pool,
+                    session_id,
+                    token_data.claims.sub,
+                    SessionRevocationReason::Logout,
+                )
+                .await?;
+            }
+        }
+        clear_auth_cookies(ctx);
         Ok(true)
     }
+
+    /// Signs one of the current user's devices out. It can still use its access token
+    /// until it expires, at most 15 minutes.
+    #[graphql(visible = "is_admin")]
+    async fn revoke_user_session(&self, ctx: &Context<'_>, id: ID) -> Result<bool, Error> {
+        let token_data = match ctx.data::<Option<TokenData<AccessTokenClaims>>>().unwrap() {
+            Some(token_data) => token_data,
+            None => return Err(Error::Unauthorized),
+        };
+        let db_pool = ctx.data::<ApiContext>().unwrap().pool.clone();
+        let session_id = uuid::Uuid::parse_str(&id)?;
+        let revoked = UserSession::revoke(
+            &db_pool,
+            session_id,
+            token_data.claims.sub,
+            SessionRevocationReason::Revoked,
+        )
+        .await?;
+        if token_data.claims.sid == Some(session_id) {
+            clear_auth_cookies(ctx);
+        }
+        Ok(revoked)
+    }
+
+    /// Signs the current user out everywhere, or everywhere else when `keep_current` is set.
+    /// Returns the number of sessions revoked.
+    #[graphql(visible = "is_admin")]
+    async fn revoke_all_user_sessions(
+        &self,
+        ctx: &Context<'_>,
+        keep_current: Option<bool>,
+    ) -> Result<u64, Error> {
+        let token_data = match ctx.data::<Option<TokenData<AccessTokenClaims>>>().unwrap() {
+            Some(token_data) => token_data,
+            None => return Err(Error::Unauthorized),
+        };
+        let db_pool = ctx.data::<ApiContext>().unwrap().pool.clone();
+        let keep_current = keep_current.unwrap_or(false);
+        let except = token_data.claims.sid.filter(|_| keep_current);
+        let revoked = UserSession::revoke_all(
+            &db_pool,
+            token_data.claims.sub,
+            except,
+            SessionRevocationReason::Revoked,
+        )
+        .await?;
+        if !keep_current {
+            clear_auth_cookies(ctx);
+        }
+        Ok(revoked)
+    }
 }
@@ -1,4 +1,10 @@
-use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::BillResult, SessionData};
+use crate::{
+    context::ApiContext,
+    guard::{RateLimitGuard, StaffOnly},
+    is_admin,
+    types::BillResult,
+    SessionData,
+};
 use async_graphql::*;
 use auth::AccessTokenClaims;
 use db::{
@@ -60,7 +66,10 @@ impl BillMutation {
         Ok(DeleteBillResult { id })
     }
 
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "RateLimitGuard::new(\"upsert_bill_public_vote\")"
+    )]
     async fn upsert_bill_public_vote(
         &self,
         ctx: &Context<'_>,
@@ -1,9 +1,16 @@
-use crate::{context::ApiContext, types::CandidateGuideResult};
+use crate::{
+    context::ApiContext,
+    guard::{OrganizationGuard, OrganizationResource, StaffOnly},
+    is_admin,
+    outreach::send_outreach_invites,
+    types::{CandidateGuideOutreachResult, CandidateGuideResult},
+};
 use async_graphql::{Context, Error, InputObject, Object, Result, SimpleObject, ID};
 use auth::AccessTokenClaims;
 use db::{
     models::candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
-    EmbedType, UpsertEmbedInput,
+    CandidateGuideOutreach, DateTime, DeletableEntityType, DeletedRecord, EmbedType,
+    OrganizationRoleType, QuestionSubmission, UpsertEmbedInput, DEFAULT_REMINDER_DAYS,
 };
 use jsonwebtoken::TokenData;
 
@@ -22,15 +29,39 @@ pub struct UpdateCandidateGuideRaceResult {
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
+#[derive(InputObject)]
+struct SendCandidateGuideOutreachInput {
+    /// Defaults to when the guide closes submissions
+    deadline: Option<DateTime>,
+    /// Days before the deadline to remind candidates who haven't responded, 7 and 2 by default
+    reminder_days: Option<Vec<i32>>,
+    /// Only email the candidates in these races
+    race_ids: Option<Vec<uuid::Uuid>>,
+}
+
+#[derive(SimpleObject)]
+struct SendCandidateGuideOutreachResult {
+    outreach: CandidateGuideOutreachResult,
+    /// Candidates emailed by this call. Candidates who were already invited aren't emailed again.
+    invites_sent: i32,
+}
+
 #[Object]
 impl CandidateGuideMutation {
+    #[graphql(
+        guard = "OrganizationGuard::upsert(OrganizationResource::CandidateGuide, input.id, input.organization_id, &OrganizationRoleType::Member)"
+    )]
     async fn upsert_candidate_guide(
         &self,
         ctx: &Context<'_>,
         input: UpsertCandidateGuideInput,
     ) -> Result<CandidateGuideResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-        let user = ctx.data::<Option<TokenData<AccessTokenClaims>>>().unwrap();
+        let user_id = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub)
+            .ok_or_else(|| Error::new("Unauthorized"))?;
 
         let organization_id = input
             .organization_id
@@ -87,7 +118,7 @@ impl CandidateGuideMutation {
         }
 
         let input = UpsertCandidateGuideInput {
-            user_id: Some(user.as_ref().unwrap().claims.sub),
+            user_id: Some(user_id),
             organization_id: Some(organization_id),
             ..input
         };
@@ -107,14 +138,16 @@ impl CandidateGuideMutation {
                         "raceId": race_id
                     })),
                 };
-                let updated_by = user.as_ref().unwrap().claims.sub;
-                db::models::embed::Embed::upsert(&db_pool, &embed_input, &updated_by).await?;
+                db::models::embed::Embed::upsert(&db_pool, &embed_input, &user_id).await?;
             }
         }
 
         Ok(upsert.into())
     }
 
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn open_all_candidate_guide_submissions(
         &self,
         ctx: &Context<'_>,
@@ -130,6 +163,32 @@ impl CandidateGuideMutation {
         Ok(result)
     }
 
+    /// Publishes every approved answer to the guide's questions, returning how many
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
+    async fn publish_approved_candidate_guide_submissions(
+        &self,
+        ctx: &Context<'_>,
+        candidate_guide_id: ID,
+    ) -> Result<i32> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let reviewer_id = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub);
+        let published = QuestionSubmission::publish_approved(
+            &db_pool,
+            uuid::Uuid::parse_str(&candidate_guide_id)?,
+            reviewer_id,
+        )
+        .await?;
+        Ok(published as i32)
+    }
+
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn set_all_candidate_guide_races_emailed(
         &self,
         ctx: &Context<'_>,
@@ -152,6 +211,58 @@ impl CandidateGuideMutation {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Emails each candidate in the guide's races a link to their intake form, and schedules
+    /// reminders for those who haven't responded before the deadline. Can be called again to
+    /// change the deadline or invite candidates added since.
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
+    async fn send_candidate_guide_outreach(
+        &self,
+        ctx: &Context<'_>,
+        candidate_guide_id: ID,
+        input: SendCandidateGuideOutreachInput,
+    ) -> Result<SendCandidateGuideOutreachResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let user_id = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub);
+        let guide =
+            CandidateGuide::find_by_id(&db_pool, uuid::Uuid::parse_str(&candidate_guide_id)?)
+                .await?;
+
+        let deadline = input
+            .deadline
+            .or(guide.submissions_close_at)
+            .ok_or_else(|| Error::new("Set a deadline or a date submissions close"))?;
+        if deadline <= chrono::Utc::now() {
+            return Err(Error::new("The deadline has already passed"));
+        }
+        let reminder_days = input
+            .reminder_days
+            .unwrap_or_else(|| DEFAULT_REMINDER_DAYS.to_vec());
+        if reminder_days.iter().any(|days| *days <= 0) {
+            return Err(Error::new(
+                "Reminders must be at least a day before the deadline",
+            ));
+        }
+
+        let outreach =
+            CandidateGuideOutreach::upsert(&db_pool, guide.id, deadline, &reminder_days, user_id)
+                .await?;
+        let invites_sent =
+            send_outreach_invites(&db_pool, &outreach, input.race_ids.as_deref()).await?;
+
+        Ok(SendCandidateGuideOutreachResult {
+            outreach: outreach.into(),
+            invites_sent: invites_sent as i32,
+        })
+    }
+
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn update_candidate_guide_race(
         &self,
         ctx: &Context<'_>,
@@ -184,6 +295,9 @@ impl CandidateGuideMutation {
         Ok(result)
     }
 
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn remove_candidate_guide_race(
         &self,
         ctx: &Context<'_>,
@@ -211,6 +325,9 @@ impl CandidateGuideMutation {
         Ok(result.rows_affected() == 2)
     }
 
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn generate_intake_token_link(
         &self,
         ctx: &Context<'_>,
@@ -224,12 +341,23 @@ impl CandidateGuideMutation {
             UPDATE politician
             SET intake_token = COALESCE(intake_token, encode(gen_random_bytes(32), 'hex'))
             WHERE id = $1
+                AND EXISTS (
+                    SELECT 1
+                    FROM race_candidates rc
+                    JOIN candidate_guide_races cgr ON cgr.race_id = rc.race_id
+                    WHERE rc.candidate_id = $1
+                        AND rc.race_id = $2
+                        AND cgr.candidate_guide_id = $3
+                )
             RETURNING intake_token
         "#,
             uuid::Uuid::parse_str(&politician_id)?,
+            uuid::Uuid::parse_str(&race_id)?,
+            uuid::Uuid::parse_str(&candidate_guide_id)?,
         )
-        .fetch_one(&db_pool)
-        .await?;
+        .fetch_optional(&db_pool)
+        .await?
+        .ok_or_else(|| Error::new("This politician isn't a candidate in this guide's race"))?;
 
         let url = format!(
             "{}/intakes/candidate-guides/{}?raceId={}&token={}",
@@ -245,6 +373,9 @@ impl CandidateGuideMutation {
     // We should expand this fn to allow clients to download fine grained data for these
     // candidate guides, intakes, etc.
     /// Download all candidate guide data as a CSV string, must be converted to CSV file by client
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
     async fn download_all_candidate_guide_data(
         &self,
         ctx: &Context<'_>,
@@ -397,9 +528,26 @@ impl CandidateGuideMutation {
         Ok(String::from_utf8(csv_string).unwrap())
     }
 
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &id, &OrganizationRoleType::Member)"
+    )]
     async fn delete_candidate_guide(&self, ctx: &Context<'_>, id: ID) -> Result<bool> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         CandidateGuide::delete(&db_pool, uuid::Uuid::parse_str(id.as_str()).unwrap()).await?;
         Ok(true)
     }
+
+    /// Undoes `deleteCandidateGuide` within the retention window, along with the guide's embeds
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn restore_candidate_guide(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+    ) -> Result<CandidateGuideResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let id = uuid::Uuid::parse_str(&id)?;
+        DeletedRecord::restore(&db_pool, DeletableEntityType::CandidateGuide, id).await?;
+        let guide = CandidateGuide::find_by_id(&db_pool, id).await?;
+        Ok(CandidateGuideResult::from(guide))
+    }
 }
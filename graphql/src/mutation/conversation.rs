@@ -1,14 +1,27 @@
-use async_graphql::{Context, Error, InputObject, Object, Result, ID};
+use async_graphql::{Context, Error, Guard, InputObject, Object, Result, ID};
 use auth::AccessTokenClaims;
 
 use db::{
     models::conversation::{Conversation, Statement, StatementView, StatementVote},
-    ArgumentPosition, StatementModerationStatus,
+    ArgumentPosition, ConversationModerationMode, OrganizationRoleType, StatementModerationStatus,
 };
 use jsonwebtoken::TokenData;
 use uuid::Uuid;
 
-use crate::{context::ApiContext, is_admin, types::ConversationResult, SessionData};
+use crate::{
+    cache::{conversation_tag, Cache},
+    context::ApiContext,
+    conversation_analysis::analyze_conversation,
+    conversation_export::{
+        ConversationExport, ConversationExportFormat, EXPORT_DIRECTORY, EXPORT_LINK_EXPIRY_SECS,
+    },
+    guard::{OrganizationGuard, OrganizationResource, RateLimitGuard, StaffOnly},
+    is_admin,
+    moderation::{initial_status, prescreen_statement},
+    presigned_s3_url,
+    types::{ConversationExportResult, ConversationResult},
+    upload_private_to_s3, File, SessionData,
+};
 
 #[derive(Default)]
 pub struct ConversationMutation;
@@ -23,6 +36,10 @@ struct CreateConversationInput {
     description: Option<String>,
     organization_id: ID,
     seed_statements: Option<Vec<String>>,
+    /// Pre-moderation when not given
+    moderation_mode: Option<ConversationModerationMode>,
+    blocked_words: Option<Vec<String>>,
+    llm_screening: Option<bool>,
 }
 
 #[derive(InputObject)]
@@ -31,12 +48,17 @@ struct AddStatementInput {
     conversation_id: ID,
     content: String,
     user_id: Option<ID>,
+    /// Only honored for staff and members of the conversation's organization. Everyone
+    /// else's statements go through the pre-screen.
     moderation_status: Option<StatementModerationStatus>,
 }
 
 #[Object]
 impl ConversationMutation {
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::new(&input.organization_id, &OrganizationRoleType::Member)"
+    )]
     async fn create_conversation(
         &self,
         ctx: &Context<'_>,
@@ -55,9 +77,19 @@ impl ConversationMutation {
                 INSERT INTO conversation (
                     topic, 
                     description, 
-                    organization_id
+                    organization_id,
+                    moderation_mode,
+                    blocked_words,
+                    llm_screening
+                )
+                VALUES (
+                    $1,
+                    $2,
+                    $3,
+                    COALESCE($6::conversation_moderation_mode, 'pre'),
+                    COALESCE($7::text[], '{}'),
+                    COALESCE($8::boolean, false)
                 )
-                VALUES ($1, $2, $3)
                 RETURNING *
             ),
             statement_insert AS (
@@ -88,7 +120,17 @@ impl ConversationMutation {
                 )
                 SELECT $1, $2, $3, 'conversation', $5, $5, jsonb_build_object('conversationId', id) FROM new_conversation
             )
-            SELECT * FROM new_conversation
+            SELECT
+                id,
+                topic,
+                description,
+                organization_id,
+                moderation_mode AS "moderation_mode: ConversationModerationMode",
+                blocked_words,
+                llm_screening,
+                created_at,
+                updated_at
+            FROM new_conversation
             "#,
             input.topic,
             input.description,
@@ -99,7 +141,9 @@ impl ConversationMutation {
                 .as_ref()
                 .map(|seed_statements| seed_statements.as_slice()),
             user_id,
-
+            input.moderation_mode as Option<ConversationModerationMode>,
+            input.blocked_words.as_deref(),
+            input.llm_screening
         )
         .fetch_one(&db_pool)
         .await?;
@@ -107,13 +151,19 @@ impl ConversationMutation {
         Ok(conversation.into())
     }
 
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Conversation, &conversation_id, &OrganizationRoleType::Member)"
+    )]
     async fn update_conversation(
         &self,
         ctx: &Context<'_>,
         conversation_id: ID,
         topic: Option<String>,
         description: Option<String>,
+        moderation_mode: Option<ConversationModerationMode>,
+        blocked_words: Option<Vec<String>>,
+        llm_screening: Option<bool>,
     ) -> async_graphql::Result<ConversationResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
 
@@ -127,13 +177,28 @@ impl ConversationMutation {
             SET
                 topic = COALESCE($2, topic),
                 description = COALESCE($3, description),
+                moderation_mode = COALESCE($4, moderation_mode),
+                blocked_words = COALESCE($5, blocked_words),
+                llm_screening = COALESCE($6, llm_screening),
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = $1
-            RETURNING *
+            RETURNING
+                id,
+                topic,
+                description,
+                organization_id,
+                moderation_mode AS "moderation_mode: ConversationModerationMode",
+                blocked_words,
+                llm_screening,
+                created_at,
+                updated_at
             "#,
             conversation_id,
             topic,
-            description
+            description,
+            moderation_mode as Option<ConversationModerationMode>,
+            blocked_words.as_deref(),
+            llm_screening
         )
         .fetch_one(&db_pool)
         .await?
@@ -142,7 +207,73 @@ impl ConversationMutation {
         Ok(conversation)
     }
 
-    #[graphql(visible = "is_admin")]
+    /// Recomputes the conversation's opinion analysis, groups and map now rather than
+    /// waiting for enough new votes
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn recompute_conversation_analysis(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+    ) -> async_graphql::Result<ConversationResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let conversation_id = Uuid::parse_str(&conversation_id.to_string())
+            .map_err(|_| Error::new("Invalid conversation ID"))?;
+
+        let conversation = Conversation::find_by_id(&db_pool, conversation_id)
+            .await?
+            .ok_or_else(|| Error::new("Conversation not found"))?;
+        analyze_conversation(&db_pool, conversation_id).await?;
+        invalidate_conversation_cache(ctx, conversation_id).await;
+
+        Ok(conversation.into())
+    }
+
+    /// Exports the conversation's statements, votes and groups in pol.is's layout, either as
+    /// a zip of CSVs or as JSON, and returns a link to download it that expires after a day
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Conversation, &conversation_id, &OrganizationRoleType::Admin)"
+    )]
+    async fn export_conversation(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+        format: ConversationExportFormat,
+    ) -> async_graphql::Result<ConversationExportResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let conversation_id = Uuid::parse_str(&conversation_id.to_string())
+            .map_err(|_| Error::new("Invalid conversation ID"))?;
+        let conversation = Conversation::find_by_id(&db_pool, conversation_id)
+            .await?
+            .ok_or_else(|| Error::new("Conversation not found"))?;
+
+        let export = ConversationExport::load(&db_pool, &conversation).await?;
+        let filename = format!(
+            "{}-{}.{}",
+            conversation.id,
+            export.exported_at.format("%Y%m%dT%H%M%SZ"),
+            format.extension()
+        );
+        let file = File {
+            id: ID::from(Uuid::new_v4()),
+            filename: filename.clone(),
+            content: export.render(format)?,
+            mimetype: Some(format.mimetype().to_string()),
+        };
+        // The random file id keeps the key from being guessed from the conversation
+        let path = format!("{}/{}/{}", EXPORT_DIRECTORY, *file.id, filename);
+        upload_private_to_s3(file, &path).await?;
+        let url = presigned_s3_url(&path, EXPORT_LINK_EXPIRY_SECS)?;
+
+        Ok(ConversationExportResult {
+            url: url.to_string(),
+            format,
+            expires_at: export.exported_at
+                + chrono::Duration::seconds(EXPORT_LINK_EXPIRY_SECS as i64),
+        })
+    }
+
+    #[graphql(visible = "is_admin", guard = "RateLimitGuard::new(\"add_statement\")")]
     async fn add_statement(
         &self,
         ctx: &Context<'_>,
@@ -164,9 +295,33 @@ impl ConversationMutation {
             },
         };
 
-        let moderation_status = match input.moderation_status {
-            Some(status) => status,
-            None => StatementModerationStatus::Unmoderated,
+        let conversation = Conversation::find_by_id(&db_pool, conversation_id)
+            .await?
+            .ok_or_else(|| Error::new("Conversation not found"))?;
+
+        // Moderators can pick the status, everyone else goes through the pre-screen
+        let requested_status = match input.moderation_status {
+            Some(status)
+                if ctx
+                    .data_opt::<Option<TokenData<AccessTokenClaims>>>()
+                    .is_some() =>
+            {
+                let organization_id = ID::from(conversation.organization_id);
+                let is_moderator = StaffOnly.check(ctx).await.is_ok()
+                    || OrganizationGuard::new(&organization_id, &OrganizationRoleType::Member)
+                        .check(ctx)
+                        .await
+                        .is_ok();
+                is_moderator.then_some(status)
+            }
+            _ => None,
+        };
+        let (moderation_status, screening_flags) = match requested_status {
+            Some(status) => (status, vec![]),
+            None => {
+                let flags = prescreen_statement(&db_pool, &conversation, &input.content).await?;
+                (initial_status(conversation.moderation_mode, &flags), flags)
+            }
         };
 
         let statement = sqlx::query_as!(
@@ -176,9 +331,10 @@ impl ConversationMutation {
                 conversation_id,
                 content,
                 author_id,
-                moderation_status
+                moderation_status,
+                screening_flags
             )
-            VALUES ($1, $2, $3, $4::statement_moderation_status)
+            VALUES ($1, $2, $3, $4::statement_moderation_status, $5)
             RETURNING 
                 id,
                 conversation_id,
@@ -191,7 +347,8 @@ impl ConversationMutation {
             conversation_id,
             input.content,
             author_id,
-            moderation_status as StatementModerationStatus
+            moderation_status as StatementModerationStatus,
+            serde_json::to_value(&screening_flags)?
         )
         .fetch_one(&db_pool)
         .await?;
@@ -199,6 +356,9 @@ impl ConversationMutation {
         Ok(statement)
     }
 
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Statement, &statement_id, &OrganizationRoleType::Member)"
+    )]
     async fn moderate_statement(
         &self,
         ctx: &Context<'_>,
@@ -209,6 +369,10 @@ impl ConversationMutation {
 
         let statement_id = Uuid::parse_str(&statement_id.to_string())
             .map_err(|_| Error::new("Invalid statement ID"))?;
+        let moderated_by = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub);
 
         let statement = sqlx::query_as!(
             Statement,
@@ -216,6 +380,8 @@ impl ConversationMutation {
             UPDATE statement
             SET
                 moderation_status = $2::statement_moderation_status,
+                moderated_by = $3,
+                moderated_at = CURRENT_TIMESTAMP,
                 updated_at = CURRENT_TIMESTAMP
             WHERE id = $1
             RETURNING 
@@ -228,7 +394,8 @@ impl ConversationMutation {
                 updated_at
             "#,
             statement_id,
-            moderation_status as StatementModerationStatus
+            moderation_status as StatementModerationStatus,
+            moderated_by
         )
         .fetch_one(&db_pool)
         .await?;
@@ -236,6 +403,42 @@ impl ConversationMutation {
         Ok(statement)
     }
 
+    /// Approves or rejects statements from the moderation queue at once. Nothing changes if
+    /// any of them is in another organization's conversation.
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::new(&organization_id, &OrganizationRoleType::Member)"
+    )]
+    async fn bulk_moderate_statements(
+        &self,
+        ctx: &Context<'_>,
+        organization_id: ID,
+        statement_ids: Vec<ID>,
+        moderation_status: StatementModerationStatus,
+    ) -> async_graphql::Result<Vec<Statement>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let statement_ids = statement_ids
+            .iter()
+            .map(|id| Uuid::parse_str(id).map_err(|_| Error::new("Invalid statement ID")))
+            .collect::<Result<Vec<_>>>()?;
+        let moderated_by = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub);
+
+        let statements = Statement::moderate_many(
+            &db_pool,
+            Uuid::parse_str(&organization_id)?,
+            &statement_ids,
+            moderation_status,
+            moderated_by,
+        )
+        .await?;
+
+        Ok(statements)
+    }
+
+    #[graphql(guard = "RateLimitGuard::new(\"vote_on_statement\")")]
     async fn vote_on_statement(
         &self,
         ctx: &Context<'_>,
@@ -349,10 +552,21 @@ impl ConversationMutation {
             .await?
         };
 
+        // Votes leave the conversation's cache alone, the worker reanalyzes the conversation
+        // once enough come in and drops its cached snapshot then
         Ok(vote)
     }
 }
 
+/// Drops the cached analysis snapshot of a conversation after it's reanalyzed
+async fn invalidate_conversation_cache(ctx: &Context<'_>, conversation_id: Uuid) {
+    if let Ok(cache) = ctx.data::<Cache>() {
+        cache
+            .invalidate_tag(&conversation_tag(conversation_id))
+            .await;
+    }
+}
+
 #[Object]
 
 impl StatementMutation {
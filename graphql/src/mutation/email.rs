@@ -0,0 +1,19 @@
+use async_graphql::{Context, Object, Result, ID};
+use db::EmailOutbox;
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::EmailResult};
+
+#[derive(Default)]
+pub struct EmailMutation;
+
+#[Object]
+impl EmailMutation {
+    /// Sends an email again on the next delivery pass, resetting its attempts. Also
+    /// revives dead emails.
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn retry_email(&self, ctx: &Context<'_>, id: ID) -> Result<EmailResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let email = EmailOutbox::retry(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        Ok(email.into())
+    }
+}
@@ -3,12 +3,16 @@ use std::collections::HashMap;
 use async_graphql::{Context, InputObject, Object, Result, SimpleObject};
 use auth::AccessTokenClaims;
 use config::Config;
-use db::{DateTime, Embed, UpsertEmbedInput};
+use db::{
+    DateTime, DeletableEntityType, DeletedRecord, Embed, OrganizationApiKey, OrganizationRoleType,
+    UpsertEmbedInput,
+};
 use jsonwebtoken::TokenData;
 use url::{Position, Url};
 
 use crate::{
     context::ApiContext,
+    guard::{OrganizationGuard, OrganizationResource, RateLimitGuard, StaffOnly},
     is_admin,
     types::{EmbedOriginResult, EmbedResult},
 };
@@ -31,37 +35,54 @@ struct PingEmbedOriginInput {
 
 #[Object]
 impl EmbedMutation {
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::upsert(OrganizationResource::Embed, input.id, input.organization_id, &OrganizationRoleType::Member).embed_write()"
+    )]
     async fn upsert_embed(
         &self,
         ctx: &Context<'_>,
         input: UpsertEmbedInput,
     ) -> Result<EmbedResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-        let user_org_id = input.organization_id;
+        let mut user_org_id = input.organization_id;
+        let mut embed_type = input.embed_type;
 
+        // The guard has checked that an existing embed stays in its organization
         if let Some(embed_id) = input.id {
-            let existing_embed_org_id =
-                Embed::find_by_id(&db_pool, embed_id).await?.organization_id;
-            if existing_embed_org_id != input.organization_id.unwrap() {
-                return Err("Unauthorized".into());
-            }
-            if existing_embed_org_id != user_org_id.unwrap() {
-                return Err("Unauthorized".into());
-            }
+            let existing_embed = Embed::find_by_id(&db_pool, embed_id).await?;
+            user_org_id = Some(existing_embed.organization_id);
+            embed_type = embed_type.or(Some(existing_embed.embed_type));
         }
 
         let user = ctx.data::<Option<TokenData<AccessTokenClaims>>>()?;
-        let user_id = match user {
-            Some(u) => Some(u.claims.sub),
-            None => return Err("Unauthorized".into()),
+        let user_id = match (user, ctx.data_opt::<OrganizationApiKey>()) {
+            (Some(u), _) => Some(u.claims.sub),
+            // API keys write on behalf of whoever created them
+            (None, Some(api_key)) => {
+                let allowed = match (user_org_id, embed_type) {
+                    (Some(org_id), Some(embed_type)) => api_key.can_write_embed(org_id, embed_type),
+                    _ => false,
+                };
+                if !allowed {
+                    return Err("This API key can't write this embed type".into());
+                }
+                match api_key.created_by {
+                    Some(created_by) => Some(created_by),
+                    None => return Err("This API key's creator no longer exists".into()),
+                }
+            }
+            (None, None) => return Err("Unauthorized".into()),
         };
 
         let upserted_record = Embed::upsert(&db_pool, &input, &user_id.unwrap()).await?;
         Ok(EmbedResult::from(upserted_record))
     }
 
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "RateLimitGuard::new(\"ping_embed_origin\")"
+    )]
     async fn ping_embed_origin(
         &self,
         ctx: &Context<'_>,
@@ -96,14 +117,31 @@ impl EmbedMutation {
         }
     }
 
-    // Needs an org guard
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Embed, &id.into(), &OrganizationRoleType::Member).embed_write()"
+    )]
     async fn delete_embed(&self, ctx: &Context<'_>, id: uuid::Uuid) -> Result<DeleteEmbedResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
 
+        if let Some(api_key) = ctx.data_opt::<OrganizationApiKey>() {
+            let embed = Embed::find_by_id(&db_pool, id).await?;
+            if !api_key.can_write_embed(embed.organization_id, embed.embed_type) {
+                return Err("This API key can't write this embed type".into());
+            }
+        }
         Embed::delete(&db_pool, id).await?;
         Ok(DeleteEmbedResult { id: id.to_string() })
     }
+
+    /// Undoes `deleteEmbed` within the retention window
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn restore_embed(&self, ctx: &Context<'_>, id: uuid::Uuid) -> Result<EmbedResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        DeletedRecord::restore(&db_pool, DeletableEntityType::Embed, id).await?;
+        let embed = Embed::find_by_id(&db_pool, id).await?;
+        Ok(EmbedResult::from(embed))
+    }
 }
 
 fn parse_url_and_retain_token_param(input_url: &str) -> Option<String> {
@@ -0,0 +1,33 @@
+use async_graphql::{Context, Object, Result, ID};
+use db::{EnqueueJobInput, Job};
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::JobResult};
+
+#[derive(Default)]
+pub struct JobMutation;
+
+#[Object]
+impl JobMutation {
+    /// Queues a one-off job, or creates/replaces a recurring job when a schedule is given
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn enqueue_job(&self, ctx: &Context<'_>, input: EnqueueJobInput) -> Result<JobResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let job = Job::enqueue(&db_pool, &input).await?;
+        Ok(job.into())
+    }
+
+    /// Re-triggers a job immediately, resetting its attempts. Also revives dead and cancelled jobs.
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn retry_job(&self, ctx: &Context<'_>, id: ID) -> Result<JobResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let job = Job::retry(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        Ok(job.into())
+    }
+
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn cancel_job(&self, ctx: &Context<'_>, id: ID) -> Result<JobResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let job = Job::cancel(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        Ok(job.into())
+    }
+}
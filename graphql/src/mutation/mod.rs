@@ -5,8 +5,10 @@ mod bill;
 mod candidate_guide;
 mod conversation;
 mod election;
+mod email;
 mod embed;
 mod issue_tag;
+mod job;
 #[allow(clippy::module_inception)]
 mod mutation;
 mod office;
@@ -15,6 +17,7 @@ mod politician;
 mod poll;
 mod question;
 mod race;
+mod results_source;
 mod user;
 mod voting_guide;
 pub use mutation::*;
@@ -6,14 +6,17 @@ use super::{
     candidate_guide::CandidateGuideMutation,
     conversation::ConversationMutation,
     election::ElectionMutation,
+    email::EmailMutation,
     embed::EmbedMutation,
     issue_tag::IssueTagMutation,
+    job::JobMutation,
     office::OfficeMutation,
     organization::OrganizationMutation,
     politician::PoliticianMutation,
     poll::PollMutation,
     question::{QuestionMutation, QuestionSubmissionMutation},
     race::RaceMutation,
+    results_source::ResultsSourceMutation,
     user::UserMutation,
     voting_guide::VotingGuideMutation,
 };
@@ -31,11 +34,14 @@ pub struct Mutation(
     BallotMeasureMutation,
     CandidateGuideMutation,
     ElectionMutation,
+    EmailMutation,
     EmbedMutation,
     IssueTagMutation,
+    JobMutation,
     AuthMutation,
     OfficeMutation,
     RaceMutation,
+    ResultsSourceMutation,
     VotingGuideMutation,
     UserMutation,
     PollMutation,
@@ -2,14 +2,16 @@ use crate::{
     context::ApiContext,
     guard::{OrganizationGuard, StaffOnly},
     is_admin,
-    types::{Error, OrganizationResult},
+    types::{CreatedOrganizationApiKeyResult, Error, OrganizationResult},
     upload_to_s3, File,
 };
 use async_graphql::*;
+use auth::AccessTokenClaims;
 use db::{
-    CreateOrConnectIssueTagInput, IssueTag, IssueTagIdentifier, Organization, OrganizationRoleType,
-    UpdateOrganizationInput,
+    CreateOrConnectIssueTagInput, CreateOrganizationApiKeyInput, IssueTag, IssueTagIdentifier,
+    Organization, OrganizationApiKey, OrganizationRoleType, UpdateOrganizationInput,
 };
+use jsonwebtoken::TokenData;
 use sqlx::{Pool, Postgres};
 use std::io::Read;
 use std::str::FromStr;
@@ -22,6 +24,14 @@ struct DeleteOrganizationResult {
     id: String,
 }
 
+/// User managing API keys. Keys can't manage keys, the Admin guard already refuses them.
+fn signed_in_user_id(ctx: &Context<'_>) -> Result<uuid::Uuid> {
+    match ctx.data::<Option<TokenData<AccessTokenClaims>>>()? {
+        Some(token_data) => Ok(token_data.claims.sub),
+        None => Err(Error::Unauthorized.into()),
+    }
+}
+
 pub async fn handle_nested_issue_tags(
     db_pool: &Pool<Postgres>,
     associated_record_id: uuid::Uuid,
@@ -174,4 +184,65 @@ impl OrganizationMutation {
         Organization::delete(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
         Ok(DeleteOrganizationResult { id })
     }
+
+    /// Creates a server-to-server API key. The key is only returned here, store it right away.
+    #[graphql(
+        guard = "OrganizationGuard::new(&input.organization_id.into(), &OrganizationRoleType::Admin)",
+        visible = "is_admin"
+    )]
+    async fn create_organization_api_key(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateOrganizationApiKeyInput,
+    ) -> Result<CreatedOrganizationApiKeyResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let user_id = signed_in_user_id(ctx)?;
+        let created = OrganizationApiKey::create(&db_pool, &input, user_id).await?;
+        Ok(created.into())
+    }
+
+    /// Replaces a key with a new one. The old key keeps working for `graceHours`, if given.
+    #[graphql(
+        guard = "OrganizationGuard::new(&organization_id, &OrganizationRoleType::Admin)",
+        visible = "is_admin"
+    )]
+    async fn rotate_organization_api_key(
+        &self,
+        ctx: &Context<'_>,
+        organization_id: ID,
+        id: ID,
+        grace_hours: Option<i32>,
+    ) -> Result<CreatedOrganizationApiKeyResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let user_id = signed_in_user_id(ctx)?;
+        let rotated = OrganizationApiKey::rotate(
+            &db_pool,
+            uuid::Uuid::parse_str(&organization_id)?,
+            uuid::Uuid::parse_str(&id)?,
+            grace_hours,
+            user_id,
+        )
+        .await?;
+        Ok(rotated.into())
+    }
+
+    #[graphql(
+        guard = "OrganizationGuard::new(&organization_id, &OrganizationRoleType::Admin)",
+        visible = "is_admin"
+    )]
+    async fn revoke_organization_api_key(
+        &self,
+        ctx: &Context<'_>,
+        organization_id: ID,
+        id: ID,
+    ) -> Result<bool> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let revoked = OrganizationApiKey::revoke(
+            &db_pool,
+            uuid::Uuid::parse_str(&organization_id)?,
+            uuid::Uuid::parse_str(&id)?,
+        )
+        .await?;
+        Ok(revoked)
+    }
 }
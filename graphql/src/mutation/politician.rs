@@ -8,9 +8,9 @@ use crate::{
 use async_graphql::{Error as GraphQLError, *};
 use db::{
     loaders::politician::PoliticianSlug, models::enums::State, CreateOrConnectIssueTagInput,
-    CreateOrConnectOrganizationInput, CreateOrConnectPoliticianInput, InsertPoliticianInput,
-    IssueTag, Organization, OrganizationIdentifier, Politician, PoliticianIdentifier,
-    UpdatePoliticianInput,
+    CreateOrConnectOrganizationInput, CreateOrConnectPoliticianInput, DeletableEntityType,
+    DeletedRecord, InsertPoliticianInput, IssueTag, Organization, OrganizationIdentifier,
+    Politician, PoliticianIdentifier, Revision, RevisionEntityType, UpdatePoliticianInput,
 };
 use sqlx::{Pool, Postgres};
 use std::io::Read;
@@ -228,6 +228,37 @@ impl PoliticianMutation {
         Ok(DeletePoliticianResult { id })
     }
 
+    /// Undoes `deletePolitician` within the retention window
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn restore_politician(&self, ctx: &Context<'_>, id: ID) -> Result<PoliticianResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let id = uuid::Uuid::parse_str(&id)?;
+        DeletedRecord::restore(&db_pool, DeletableEntityType::Politician, id).await?;
+        let record = Politician::find_by_id(&db_pool, id).await?;
+        Ok(PoliticianResult::from(record))
+    }
+
+    /// Puts the politician's profile back to how it was in `revisionNumber`
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn revert_politician(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        revision_number: i32,
+    ) -> Result<PoliticianResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let id = uuid::Uuid::parse_str(&id)?;
+        Revision::revert(
+            &db_pool,
+            RevisionEntityType::Politician,
+            id,
+            revision_number,
+        )
+        .await?;
+        let record = Politician::find_by_id(&db_pool, id).await?;
+        Ok(PoliticianResult::from(record))
+    }
+
     #[graphql(
         guard = "IntakeTokenGuard::new(&_intake_token, &slug)",
         visible = "is_admin"
@@ -1,8 +1,12 @@
 use crate::{is_admin, types::PollSubmissionResult};
 use async_graphql::{Context, Object, Result, SimpleObject, ID};
-use db::{UpsertPollInput, UpsertPollSubmissionInput, UpsertRespondentInput};
+use db::{OrganizationRoleType, UpsertPollInput, UpsertPollSubmissionInput, UpsertRespondentInput};
 
-use crate::{context::ApiContext, types::PollResult};
+use crate::{
+    context::ApiContext,
+    guard::{OrganizationGuard, OrganizationResource, RateLimitGuard},
+    types::PollResult,
+};
 
 #[derive(Default)]
 pub struct PollMutation;
@@ -15,12 +19,16 @@ struct DeletePollResult {
 
 #[Object]
 impl PollMutation {
+    #[graphql(
+        guard = "OrganizationGuard::upsert(OrganizationResource::Poll, input.id, input.organization_id, &OrganizationRoleType::Member)"
+    )]
     async fn upsert_poll(&self, ctx: &Context<'_>, input: UpsertPollInput) -> Result<PollResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         let upserted_poll = db::Poll::upsert(&db_pool, &input).await?;
         Ok(upserted_poll.into())
     }
 
+    #[graphql(guard = "RateLimitGuard::new(\"upsert_poll_submission\")")]
     async fn upsert_poll_submission(
         &self,
         ctx: &Context<'_>,
@@ -51,7 +59,10 @@ impl PollMutation {
         Ok(poll.into())
     }
 
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Poll, &id, &OrganizationRoleType::Member)"
+    )]
     async fn delete_poll(&self, ctx: &Context<'_>, id: ID) -> Result<DeletePollResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         sqlx::query!(
@@ -1,13 +1,20 @@
 use crate::{
+    guard::{IntakeTokenGuard, OrganizationGuard, OrganizationResource, RateLimitGuard, StaffOnly},
     is_admin,
     types::{QuestionResult, QuestionSubmissionResult},
 };
-use async_graphql::{Context, Object, Result, SimpleObject, ID};
-use async_openai::types::CreateCompletionRequestArgs;
+use async_graphql::{Context, Guard, GuardExt, InputObject, Object, Result, SimpleObject, ID};
+use auth::AccessTokenClaims;
 use db::{
     models::{question::UpsertQuestionInput, respondent::UpsertRespondentInput},
-    QuestionSubmission, Sentiment, UpsertQuestionSubmissionInput,
+    OrganizationRoleType, QuestionSubmission, Revision, RevisionEntityType, Sentiment,
+    SubmissionStatus, UpsertQuestionSubmissionInput,
 };
+use jsonwebtoken::TokenData;
+use llm::Message;
+use serde::Deserialize;
+use serde_json::json;
+use std::str::FromStr;
 
 use crate::context::ApiContext;
 
@@ -22,17 +29,42 @@ struct DeleteQuestionResult {
 
 #[Object]
 impl QuestionMutation {
+    #[graphql(
+        guard = "OrganizationGuard::upsert(OrganizationResource::Question, input.id, input.organization_id, &OrganizationRoleType::Member)"
+    )]
     async fn upsert_question(
         &self,
         ctx: &Context<'_>,
         input: UpsertQuestionInput,
     ) -> Result<QuestionResult> {
+        // Questions can only be attached to the organization's own guides and embeds
+        if let Some(candidate_guide_id) = input.candidate_guide_id {
+            OrganizationGuard::owner_of(
+                OrganizationResource::CandidateGuide,
+                &candidate_guide_id.into(),
+                &OrganizationRoleType::Member,
+            )
+            .check(ctx)
+            .await?;
+        }
+        if let Some(embed_id) = input.embed_id {
+            OrganizationGuard::owner_of(
+                OrganizationResource::Embed,
+                &embed_id.into(),
+                &OrganizationRoleType::Member,
+            )
+            .check(ctx)
+            .await?;
+        }
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         let new_question = db::Question::upsert(&db_pool, &input).await?;
         Ok(new_question.into())
     }
 
-    #[graphql(visible = "is_admin")]
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Question, &id, &OrganizationRoleType::Member)"
+    )]
     async fn delete_question(&self, ctx: &Context<'_>, id: ID) -> Result<DeleteQuestionResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         sqlx::query!(
@@ -50,14 +82,26 @@ impl QuestionMutation {
 #[derive(Default)]
 pub struct QuestionSubmissionMutation;
 
+#[derive(InputObject)]
+struct ReviewQuestionSubmissionInput {
+    /// Leave out to only change the editorial note
+    status: Option<SubmissionStatus>,
+    editorial: Option<String>,
+}
+
 #[Object]
 impl QuestionSubmissionMutation {
-    #[graphql(visible = "is_admin")]
+    /// Candidates answering guide questions pass their `intakeToken`
+    #[graphql(
+        visible = "is_admin",
+        guard = "RateLimitGuard::new(\"upsert_question_submission\").and(IntakeTokenGuard::submission(intake_token.as_deref(), &question_submission_input))"
+    )]
     async fn upsert_question_submission(
         &self,
         ctx: &Context<'_>,
         respondent_input: Option<UpsertRespondentInput>,
         question_submission_input: UpsertQuestionSubmissionInput,
+        intake_token: Option<String>,
     ) -> Result<QuestionSubmissionResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         let respondent = match respondent_input {
@@ -67,49 +111,25 @@ impl QuestionSubmissionMutation {
             None => None,
         };
 
-        // Use OpenAI to determine the sentiment of the response
-        let client = async_openai::Client::new();
-        let prompt = format!(
-            r#"
-                Classify the sentiment in this response as either positive, negative, or neutral:
-                Response: {}"#,
-            question_submission_input.response
-        );
-        let request = CreateCompletionRequestArgs::default()
-            .model("gpt-3.5-turbo-instruct")
-            .prompt(prompt)
-            .max_tokens(40_u16)
-            .build()?;
-        let response = client.completions().create(request).await;
-        let sentiment = match response {
-            Ok(response) => {
-                let trimmed_response_text = &response
-                    .choices
-                    .first()
-                    .unwrap()
-                    .text
-                    .as_str()
-                    .trim()
-                    .to_lowercase();
-                if trimmed_response_text.contains("positive") {
-                    Sentiment::Positive
-                } else if trimmed_response_text.contains("negative") {
-                    Sentiment::Negative
-                } else if trimmed_response_text.contains("neutral") {
-                    Sentiment::Neutral
-                } else {
-                    Sentiment::Unknown
-                }
-            }
+        let sentiment = match classify_sentiment(&question_submission_input.response).await {
+            Ok(sentiment) => sentiment,
             Err(err) => {
-                tracing::error!("Error classifying sentiment with OpenAI: {:?}", err);
+                tracing::error!("Error classifying sentiment: {}", err);
                 Sentiment::Unknown
             }
         };
 
+        // Candidates editing a rejected answer send it back for review
+        let status = match intake_token {
+            Some(_) => question_submission_input
+                .status
+                .or(Some(SubmissionStatus::Submitted)),
+            None => question_submission_input.status,
+        };
         let question_submission_input = UpsertQuestionSubmissionInput {
             respondent_id: respondent.map(|r| r.id),
             sentiment: Some(sentiment),
+            status,
             ..question_submission_input
         };
 
@@ -117,7 +137,61 @@ impl QuestionSubmissionMutation {
         Ok(question.into())
     }
 
-    #[graphql(visible = "is_admin")]
+    /// Moves a candidate's answer through editorial review. Each review is kept with its
+    /// editorial note in `questionSubmissionReviews`.
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::QuestionSubmission, &question_submission_id, &OrganizationRoleType::Member)"
+    )]
+    async fn review_question_submission(
+        &self,
+        ctx: &Context<'_>,
+        question_submission_id: ID,
+        input: ReviewQuestionSubmissionInput,
+    ) -> Result<QuestionSubmissionResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let reviewer_id = ctx
+            .data::<Option<TokenData<AccessTokenClaims>>>()?
+            .as_ref()
+            .map(|user| user.claims.sub);
+        let record = QuestionSubmission::review(
+            &db_pool,
+            uuid::Uuid::parse_str(&question_submission_id)?,
+            input.status,
+            input.editorial,
+            reviewer_id,
+        )
+        .await?;
+        Ok(record.into())
+    }
+
+    /// Puts the answer and its editorial note back to how they were in `revisionNumber`
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn revert_question_submission(
+        &self,
+        ctx: &Context<'_>,
+        id: ID,
+        revision_number: i32,
+    ) -> Result<QuestionSubmissionResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let id = uuid::Uuid::parse_str(&id)?;
+        Revision::revert(
+            &db_pool,
+            RevisionEntityType::QuestionSubmission,
+            id,
+            revision_number,
+        )
+        .await?;
+        let record = QuestionSubmission::find_by_id(&db_pool, id).await?;
+        Ok(record.into())
+    }
+
+    /// Both the answer and the question it's copied to have to belong to the caller's
+    /// organization
+    #[graphql(
+        visible = "is_admin",
+        guard = "OrganizationGuard::owner_of(OrganizationResource::Question, &target_question_id, &OrganizationRoleType::Member).and(OrganizationGuard::owner_of(OrganizationResource::QuestionSubmission, &question_submission_id, &OrganizationRoleType::Member))"
+    )]
     async fn copy_question_submission(
         &self,
         ctx: &Context<'_>,
@@ -140,6 +214,7 @@ impl QuestionSubmissionMutation {
                     translations,
                     sentiment,
                     copied_from_id,
+                    status,
                     created_at,
                     updated_at
                 )
@@ -152,6 +227,7 @@ impl QuestionSubmissionMutation {
                     qs.translations,
                     qs.sentiment,
                     $1 AS copied_from_id,  -- This is the original question_submission_id you want to copy
+                    qs.status,
                     qs.created_at,  -- Set the creation timestamp to the original timestamp
                     NOW()   -- Set the updated timestamp to now
                 FROM
@@ -168,6 +244,7 @@ impl QuestionSubmissionMutation {
                     translations,
                     sentiment AS "sentiment: Sentiment",
                     copied_from_id,
+                    status AS "status: SubmissionStatus",
                     created_at,
                     updated_at
             "#,
@@ -178,3 +255,29 @@ impl QuestionSubmissionMutation {
         Ok(result.into())
     }
 }
+
+#[derive(Deserialize)]
+struct SentimentClassification {
+    sentiment: String,
+}
+
+async fn classify_sentiment(response: &str) -> Result<Sentiment> {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "sentiment": { "type": "string", "enum": ["positive", "negative", "neutral"] }
+        },
+        "required": ["sentiment"]
+    });
+    let classification: SentimentClassification = llm::client()?
+        .complete_json(
+            "sentiment",
+            vec![Message::user(format!(
+                "Classify the sentiment in this response as either positive, negative, or neutral:\nResponse: {}",
+                response
+            ))],
+            &schema,
+        )
+        .await?;
+    Ok(Sentiment::from_str(&classification.sentiment).unwrap_or(Sentiment::Unknown))
+}
@@ -1,6 +1,17 @@
-use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::RaceResult};
-use async_graphql::{Context, Object, Result, SimpleObject};
-use db::{Race, UpsertRaceInput};
+use crate::{
+    context::ApiContext,
+    guard::StaffOnly,
+    is_admin,
+    types::{RaceCallResult, RaceResult, RankedChoiceRoundResult},
+};
+use async_graphql::{Context, Object, Result, SimpleObject, ID};
+use auth::AccessTokenClaims;
+use db::{
+    advance_races, AdvanceRacesInput, AdvancementPlan, DeletableEntityType, DeletedRecord, Race,
+    RaceCall, RankedBallotInput, RankedChoiceBallot, RankedChoiceRound, RankedChoiceRoundTally,
+    UpdateRaceStatusInput, UpsertRaceInput,
+};
+use jsonwebtoken::TokenData;
 
 #[derive(Default)]
 pub struct RaceMutation;
@@ -11,6 +22,22 @@ struct DeleteRaceResult {
     id: String,
 }
 
+#[derive(SimpleObject)]
+#[graphql(visible = "is_admin")]
+struct ProjectElectionWinnersResult {
+    election_id: ID,
+    /// Races whose projected winners or status changed
+    races_updated: i32,
+}
+
+#[derive(SimpleObject)]
+#[graphql(visible = "is_admin")]
+struct ImportRankedChoiceBallotsResult {
+    race_id: ID,
+    /// Total ballots imported for the race, including earlier imports
+    ballot_count: i64,
+}
+
 #[Object]
 impl RaceMutation {
     #[graphql(guard = "StaffOnly", visible = "is_admin")]
@@ -26,4 +53,111 @@ impl RaceMutation {
         Race::delete(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
         Ok(DeleteRaceResult { id })
     }
+
+    /// Undoes `deleteRace` within the retention window
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn restore_race(&self, ctx: &Context<'_>, id: ID) -> Result<RaceResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let id = uuid::Uuid::parse_str(&id)?;
+        DeletedRecord::restore(&db_pool, DeletableEntityType::Race, id).await?;
+        let record = Race::find_by_id(&db_pool, id).await?;
+        Ok(RaceResult::from(record))
+    }
+
+    /// Adds ballot rankings to a ranked choice race, or replaces them when `replace` is set
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn import_ranked_choice_ballots(
+        &self,
+        ctx: &Context<'_>,
+        race_id: ID,
+        ballots: Vec<RankedBallotInput>,
+        replace: Option<bool>,
+    ) -> Result<ImportRankedChoiceBallotsResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let ballot_count = RankedChoiceBallot::import(
+            &db_pool,
+            uuid::Uuid::parse_str(&race_id)?,
+            &ballots,
+            replace.unwrap_or(false),
+        )
+        .await?;
+        Ok(ImportRankedChoiceBallotsResult {
+            race_id,
+            ballot_count,
+        })
+    }
+
+    /// Runs the instant runoff or STV count for a race and sets its winners
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn tabulate_ranked_choice_race(
+        &self,
+        ctx: &Context<'_>,
+        race_id: ID,
+    ) -> Result<Vec<RankedChoiceRoundResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let race_id = uuid::Uuid::parse_str(&race_id)?;
+        RankedChoiceRound::tabulate_race(&db_pool, race_id).await?;
+        let rounds = RankedChoiceRound::find_by_race_id(&db_pool, race_id).await?;
+        let tallies = RankedChoiceRoundTally::find_by_race_id(&db_pool, race_id).await?;
+        Ok(RankedChoiceRoundResult::from_rounds(rounds, tallies))
+    }
+
+    /// Projects winners from a race's current results. Returns `null` when the race has
+    /// already been called or the projection hasn't changed.
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn project_race_winners(
+        &self,
+        ctx: &Context<'_>,
+        race_id: ID,
+    ) -> Result<Option<RaceCallResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let call = RaceCall::project(&db_pool, uuid::Uuid::parse_str(&race_id)?).await?;
+        Ok(call.map(RaceCallResult::from))
+    }
+
+    /// Projects winners for every fully reported race of an election
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn project_election_winners(
+        &self,
+        ctx: &Context<'_>,
+        election_id: ID,
+    ) -> Result<ProjectElectionWinnersResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let races_updated =
+            RaceCall::project_election(&db_pool, uuid::Uuid::parse_str(&election_id)?).await?;
+        Ok(ProjectElectionWinnersResult {
+            election_id,
+            races_updated: races_updated as i32,
+        })
+    }
+
+    /// Confirms, certifies or otherwise moves a race to a new status
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn update_race_status(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateRaceStatusInput,
+    ) -> Result<RaceCallResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let user = ctx.data::<Option<TokenData<AccessTokenClaims>>>()?;
+        let user_id = match user {
+            Some(user) => user.claims.sub,
+            None => return Err("Unauthorized".into()),
+        };
+        let call = RaceCall::update_status(&db_pool, &input, user_id).await?;
+        Ok(call.into())
+    }
+
+    /// Creates runoff races and general election races from a primary election's results,
+    /// using the state's runoff rules. With `dryRun` set, returns the changes without
+    /// making them.
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn advance_primary_races(
+        &self,
+        ctx: &Context<'_>,
+        input: AdvanceRacesInput,
+    ) -> Result<AdvancementPlan> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        Ok(advance_races(&db_pool, &input).await?)
+    }
 }
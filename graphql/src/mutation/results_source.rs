@@ -0,0 +1,52 @@
+use async_graphql::{Context, Object, Result, SimpleObject, ID};
+use db::{ResultsSource, UpsertResultsSourceInput};
+
+use crate::{
+    context::ApiContext,
+    guard::StaffOnly,
+    is_admin,
+    types::{JobResult, ResultsSourceResult},
+};
+
+#[derive(Default)]
+pub struct ResultsSourceMutation;
+
+#[derive(SimpleObject)]
+#[graphql(visible = "is_admin")]
+struct DeleteResultsSourceResult {
+    id: String,
+}
+
+#[Object]
+impl ResultsSourceMutation {
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn upsert_results_source(
+        &self,
+        ctx: &Context<'_>,
+        input: UpsertResultsSourceInput,
+    ) -> Result<ResultsSourceResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let source = ResultsSource::upsert(&db_pool, &input).await?;
+        Ok(source.into())
+    }
+
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn delete_results_source(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+    ) -> Result<DeleteResultsSourceResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        ResultsSource::delete(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        Ok(DeleteResultsSourceResult { id })
+    }
+
+    /// Queues a poll of a source right away, outside of its interval and active window
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn poll_results_source(&self, ctx: &Context<'_>, id: ID) -> Result<JobResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let source = ResultsSource::find_by_id(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        let job = source.enqueue_poll(&db_pool).await?;
+        Ok(job.into())
+    }
+}
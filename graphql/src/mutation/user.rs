@@ -32,11 +32,20 @@ struct UpdateNameResult {
     pub last_name: Option<String>,
 }
 
+/// Session of the current access token, carried over when the token is reissued
+fn current_session_id(ctx: &Context<'_>) -> Option<uuid::Uuid> {
+    ctx.data::<Option<TokenData<AccessTokenClaims>>>()
+        .ok()
+        .and_then(|token_data| token_data.as_ref())
+        .and_then(|token_data| token_data.claims.sid)
+}
+
 pub async fn refresh_access_token(ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<bool> {
     let db_pool = ctx.data::<ApiContext>()?.pool.clone();
     let user = User::find_by_id(&db_pool, user_id).await?;
     let organization_roles = User::organization_roles(&db_pool, user_id).await?;
-    let access_token = create_access_token_for_user(user, organization_roles)?;
+    let access_token =
+        create_access_token_for_user(user, organization_roles, current_session_id(ctx))?;
     ctx.insert_http_header(
         "Set-Cookie",
         format_auth_cookie(auth::TokenType::Access, &access_token),
@@ -157,7 +166,11 @@ impl UserMutation {
         match updated_record {
             Ok(user) => {
                 let organization_roles = User::organization_roles(&db_pool, user.id).await?;
-                let access_token = create_access_token_for_user(user.clone(), organization_roles)?;
+                let access_token = create_access_token_for_user(
+                    user.clone(),
+                    organization_roles,
+                    current_session_id(ctx),
+                )?;
                 ctx.insert_http_header(
                     "Set-Cookie",
                     format_auth_cookie(auth::TokenType::Access, &access_token),
@@ -208,7 +221,11 @@ impl UserMutation {
         match updated_record {
             Ok(user) => {
                 let organization_roles = User::organization_roles(&db_pool, user.id).await?;
-                let access_token = create_access_token_for_user(user.clone(), organization_roles)?;
+                let access_token = create_access_token_for_user(
+                    user.clone(),
+                    organization_roles,
+                    current_session_id(ctx),
+                )?;
                 ctx.insert_http_header(
                     "Set-Cookie",
                     format_auth_cookie(auth::TokenType::Access, &access_token),
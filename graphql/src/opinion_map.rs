@@ -0,0 +1,433 @@
+//! Projects conversation participants onto a 2D opinion map and groups them, in the
+//! spirit of pol.is: missing votes are imputed, the vote matrix is reduced to its first
+//! two principal components, and participants are clustered with k-means on the map.
+
+use std::collections::{BTreeMap, HashMap};
+
+use ndarray::{Array1, Array2, ArrayView1, Axis};
+
+const MIN_GROUPS: usize = 2;
+const MAX_GROUPS: usize = 5;
+const POWER_ITERATIONS: usize = 500;
+const KMEANS_ITERATIONS: usize = 100;
+
+/// Participants' positions on the map and the group each was placed in
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpinionMap {
+    /// One `[x, y]` row per participant, in the order of the vote matrix
+    pub points: Array2<f64>,
+    /// Each participant's group label
+    pub labels: Vec<usize>,
+    /// Group centroids keyed by label
+    pub centroids: BTreeMap<usize, [f64; 2]>,
+    /// Share of the vote variance captured by each axis
+    pub explained_variance: [f64; 2],
+}
+
+impl OpinionMap {
+    /// Participant indices in each group, keyed by label
+    pub fn groups(&self) -> BTreeMap<usize, Vec<usize>> {
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (idx, &label) in self.labels.iter().enumerate() {
+            groups.entry(label).or_default().push(idx);
+        }
+        groups
+    }
+}
+
+/// Builds the opinion map for a participant x statement vote matrix. `voted` marks the
+/// cells that hold a real vote; everything else is treated as missing rather than neutral.
+/// `previous_labels` holds each participant's label from the last computation, if any,
+/// so groups keep their labels when they're recomputed.
+pub fn opinion_map(
+    votes: &Array2<f64>,
+    voted: &Array2<bool>,
+    num_groups: Option<usize>,
+    previous_labels: &[Option<usize>],
+) -> OpinionMap {
+    let imputed = impute(votes, voted);
+    let (components, explained_variance) = principal_components(&imputed);
+    let mut points = imputed.dot(&components.t());
+
+    // Sparse voters sit near the centre of the map only because we know little about
+    // them, so push them out in proportion to how few statements they voted on
+    let n_statements = votes.ncols().max(1) as f64;
+    for (mut point, voted) in points.rows_mut().into_iter().zip(voted.rows()) {
+        let n_voted = voted.iter().filter(|&&v| v).count().max(1) as f64;
+        point *= (n_statements / n_voted).sqrt();
+    }
+
+    let k = num_groups.unwrap_or_else(|| optimal_groups(&points));
+    let (assignments, centroids) = kmeans(&points, k);
+    let mapping = stable_labels(&assignments, &centroids, previous_labels);
+
+    OpinionMap {
+        labels: assignments
+            .iter()
+            .map(|&cluster| mapping[cluster])
+            .collect(),
+        centroids: centroids
+            .iter()
+            .enumerate()
+            .filter(|(cluster, _)| assignments.contains(cluster))
+            .map(|(cluster, centroid)| (mapping[cluster], *centroid))
+            .collect(),
+        points,
+        explained_variance,
+    }
+}
+
+/// Fills each missing vote with the statement's mean among those who voted on it, so
+/// after centring a missing vote carries no weight in either direction
+pub fn impute(votes: &Array2<f64>, voted: &Array2<bool>) -> Array2<f64> {
+    let mut imputed = votes.clone();
+    for (mut column, voted) in imputed.columns_mut().into_iter().zip(voted.columns()) {
+        let (sum, count) = column
+            .iter()
+            .zip(voted.iter())
+            .filter(|(_, &v)| v)
+            .fold((0.0, 0), |(sum, count), (vote, _)| (sum + vote, count + 1));
+        let mean = if count > 0 { sum / count as f64 } else { 0.0 };
+        column
+            .iter_mut()
+            .zip(voted.iter())
+            .filter(|(_, &v)| !v)
+            .for_each(|(vote, _)| *vote = mean);
+    }
+    imputed
+}
+
+/// First two principal components of `data` (as rows of a 2 x statements matrix) and the
+/// share of variance each explains, found by power iteration on the centred data
+pub fn principal_components(data: &Array2<f64>) -> (Array2<f64>, [f64; 2]) {
+    let n_features = data.ncols();
+    let mut components = Array2::zeros((2, n_features));
+    let mut explained_variance = [0.0; 2];
+    if data.nrows() == 0 || n_features == 0 {
+        return (components, explained_variance);
+    }
+
+    let mean = data.mean_axis(Axis(0)).unwrap();
+    let centred = data - &mean.insert_axis(Axis(0));
+    let total_variance = centred.iter().map(|x| x * x).sum::<f64>();
+    if total_variance <= f64::EPSILON {
+        return (components, explained_variance);
+    }
+
+    for (i, explained) in explained_variance.iter_mut().enumerate() {
+        // A fixed, uneven start vector keeps the result deterministic
+        let mut vector: Array1<f64> = (0..n_features)
+            .map(|j| 1.0 + j as f64 / n_features as f64)
+            .collect();
+        let mut eigenvalue = 0.0;
+        for _ in 0..POWER_ITERATIONS {
+            let mut next = centred.t().dot(&centred.dot(&vector));
+            for previous in components.rows().into_iter().take(i) {
+                let overlap = next.dot(&previous);
+                next.scaled_add(-overlap, &previous);
+            }
+            let norm = next.dot(&next).sqrt();
+            if norm <= f64::EPSILON {
+                vector.fill(0.0);
+                eigenvalue = 0.0;
+                break;
+            }
+            next /= norm;
+            let shift = squared_distance(&next.view(), &vector.view());
+            vector = next;
+            eigenvalue = norm;
+            if shift < 1e-12 {
+                break;
+            }
+        }
+
+        // Eigenvectors have no inherent sign; pin it so the map doesn't flip
+        // between computations
+        let largest = vector
+            .iter()
+            .copied()
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or_default();
+        if largest < 0.0 {
+            vector.mapv_inplace(|x| -x);
+        }
+
+        components.row_mut(i).assign(&vector);
+        *explained = eigenvalue / total_variance;
+    }
+
+    (components, explained_variance)
+}
+
+/// Deterministic k-means, seeded with the point furthest from the centre and then each
+/// point furthest from the seeds so far. Returns each point's cluster and the centroids.
+pub fn kmeans(points: &Array2<f64>, k: usize) -> (Vec<usize>, Vec<[f64; 2]>) {
+    let n_points = points.nrows();
+    if n_points == 0 {
+        return (vec![], vec![]);
+    }
+    let k = k.clamp(1, n_points);
+
+    let centre = points.mean_axis(Axis(0)).unwrap();
+    let mut seeds = vec![furthest_point(points, |point| {
+        squared_distance(point, &centre.view())
+    })];
+    while seeds.len() < k {
+        seeds.push(furthest_point(points, |point| {
+            seeds
+                .iter()
+                .map(|&seed| squared_distance(point, &points.row(seed)))
+                .fold(f64::INFINITY, f64::min)
+        }));
+    }
+    let mut centroids: Vec<[f64; 2]> = seeds
+        .iter()
+        .map(|&seed| [points[[seed, 0]], points[[seed, 1]]])
+        .collect();
+
+    let mut assignments = vec![0; n_points];
+    for _ in 0..KMEANS_ITERATIONS {
+        for (assignment, point) in assignments.iter_mut().zip(points.rows()) {
+            *assignment = nearest(&centroids, &point);
+        }
+
+        let mut shift: f64 = 0.0;
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..n_points)
+                .filter(|&idx| assignments[idx] == cluster)
+                .collect();
+            // An empty cluster keeps its old centroid
+            if members.is_empty() {
+                continue;
+            }
+            let mean = points.select(Axis(0), &members).mean_axis(Axis(0)).unwrap();
+            shift = shift.max((mean[0] - centroid[0]).powi(2) + (mean[1] - centroid[1]).powi(2));
+            *centroid = [mean[0], mean[1]];
+        }
+
+        if shift < 1e-10 {
+            break;
+        }
+    }
+
+    (assignments, centroids)
+}
+
+/// Picks the number of groups with the best mean silhouette
+fn optimal_groups(points: &Array2<f64>) -> usize {
+    let n_points = points.nrows();
+    if n_points <= MIN_GROUPS {
+        return n_points.max(1);
+    }
+
+    (MIN_GROUPS..=MAX_GROUPS.min(n_points - 1))
+        .map(|k| (k, silhouette(points, &kmeans(points, k).0)))
+        .fold((MIN_GROUPS, f64::NEG_INFINITY), |best, (k, score)| {
+            if score > best.1 + 1e-9 {
+                (k, score)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+/// Mean silhouette score of a clustering, from -1 (wrong groups) to 1 (well separated)
+pub fn silhouette(points: &Array2<f64>, assignments: &[usize]) -> f64 {
+    let n_points = points.nrows();
+    if n_points == 0 {
+        return 0.0;
+    }
+
+    let mut total = 0.0;
+    for i in 0..n_points {
+        let mut distances: HashMap<usize, (f64, usize)> = HashMap::new();
+        for j in (0..n_points).filter(|&j| j != i) {
+            let entry = distances.entry(assignments[j]).or_insert((0.0, 0));
+            entry.0 += squared_distance(&points.row(i), &points.row(j)).sqrt();
+            entry.1 += 1;
+        }
+        let own = match distances.get(&assignments[i]) {
+            Some((sum, count)) => sum / *count as f64,
+            // Points alone in their cluster score 0
+            None => continue,
+        };
+        let nearest_other = distances
+            .iter()
+            .filter(|(&cluster, _)| cluster != assignments[i])
+            .map(|(_, (sum, count))| sum / *count as f64)
+            .fold(f64::INFINITY, f64::min);
+        if nearest_other.is_finite() && own.max(nearest_other) > 0.0 {
+            total += (nearest_other - own) / own.max(nearest_other);
+        }
+    }
+
+    total / n_points as f64
+}
+
+/// Maps each cluster to a label. Clusters take the label most of their members had last
+/// time, largest overlaps first; the rest take the lowest free labels, left to right
+/// across the map.
+pub fn stable_labels(
+    assignments: &[usize],
+    centroids: &[[f64; 2]],
+    previous_labels: &[Option<usize>],
+) -> Vec<usize> {
+    let mut overlaps: HashMap<(usize, usize), usize> = HashMap::new();
+    for (&cluster, previous) in assignments.iter().zip(previous_labels) {
+        if let Some(label) = previous {
+            *overlaps.entry((cluster, *label)).or_default() += 1;
+        }
+    }
+    let mut overlaps: Vec<((usize, usize), usize)> = overlaps.into_iter().collect();
+    overlaps.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut mapping: Vec<Option<usize>> = vec![None; centroids.len()];
+    let mut taken = std::collections::HashSet::new();
+    for ((cluster, label), _) in overlaps {
+        if mapping[cluster].is_none() && !taken.contains(&label) {
+            mapping[cluster] = Some(label);
+            taken.insert(label);
+        }
+    }
+
+    let mut unlabelled: Vec<usize> = (0..centroids.len())
+        .filter(|&cluster| mapping[cluster].is_none())
+        .collect();
+    unlabelled.sort_by(|&a, &b| {
+        centroids[a][0]
+            .total_cmp(&centroids[b][0])
+            .then(centroids[a][1].total_cmp(&centroids[b][1]))
+    });
+    let mut free_labels = (0..).filter(|label| !taken.contains(label));
+    for cluster in unlabelled {
+        mapping[cluster] = free_labels.next();
+    }
+
+    mapping
+        .into_iter()
+        .map(|label| label.unwrap_or_default())
+        .collect()
+}
+
+fn furthest_point(points: &Array2<f64>, distance: impl Fn(&ArrayView1<f64>) -> f64) -> usize {
+    points
+        .rows()
+        .into_iter()
+        .map(|point| distance(&point))
+        .enumerate()
+        .fold((0, f64::NEG_INFINITY), |best, (idx, distance)| {
+            if distance > best.1 {
+                (idx, distance)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+fn nearest(centroids: &[[f64; 2]], point: &ArrayView1<f64>) -> usize {
+    centroids
+        .iter()
+        .map(|centroid| (point[0] - centroid[0]).powi(2) + (point[1] - centroid[1]).powi(2))
+        .enumerate()
+        .fold((0, f64::INFINITY), |best, (cluster, distance)| {
+            if distance < best.1 {
+                (cluster, distance)
+            } else {
+                best
+            }
+        })
+        .0
+}
+
+fn squared_distance(a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array2};
+
+    use super::*;
+
+    /// Two camps of four who vote opposite ways on three statements, with a few gaps
+    fn two_camps() -> (Array2<f64>, Array2<bool>) {
+        let votes = array![
+            [1.0, 1.0, -1.0],
+            [1.0, 0.0, -1.0],
+            [1.0, 1.0, -1.0],
+            [1.0, 1.0, 0.0],
+            [-1.0, -1.0, 1.0],
+            [-1.0, 0.0, 1.0],
+            [-1.0, -1.0, 1.0],
+            [0.0, -1.0, 1.0],
+        ];
+        let voted = votes.mapv(|vote| vote != 0.0);
+        (votes, voted)
+    }
+
+    #[test]
+    fn test_impute_uses_statement_mean() {
+        let votes = array![[1.0, 0.0], [1.0, -1.0], [0.0, -1.0], [-1.0, 0.0]];
+        let voted = array![[true, false], [true, true], [false, true], [true, false]];
+        let imputed = impute(&votes, &voted);
+        assert_eq!(imputed[[2, 0]], 1.0 / 3.0);
+        assert_eq!(imputed[[0, 1]], -1.0);
+        // Neutral votes are real votes, not gaps
+        let voted = array![[true, true], [true, true], [true, true], [true, true]];
+        assert_eq!(impute(&votes, &voted), votes);
+    }
+
+    #[test]
+    fn test_principal_components_find_main_axis() {
+        let data = array![[2.0, 2.0], [1.0, 1.0], [-1.0, -1.0], [-2.0, -2.0]];
+        let (components, explained_variance) = principal_components(&data);
+        let expected = 1.0 / 2.0_f64.sqrt();
+        assert!((components[[0, 0]] - expected).abs() < 1e-6);
+        assert!((components[[0, 1]] - expected).abs() < 1e-6);
+        assert!((explained_variance[0] - 1.0).abs() < 1e-6);
+        assert!(explained_variance[1].abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_opinion_map_separates_camps() {
+        let (votes, voted) = two_camps();
+        let map = opinion_map(&votes, &voted, None, &[]);
+        assert_eq!(map.points.dim(), (8, 2));
+        assert_eq!(map.labels[..4], [map.labels[0]; 4]);
+        assert_eq!(map.labels[4..], [map.labels[4]; 4]);
+        assert_ne!(map.labels[0], map.labels[4]);
+        assert_eq!(map.centroids.len(), 2);
+        assert!(map.explained_variance[0] > 0.8);
+        // Same votes, same map
+        assert_eq!(opinion_map(&votes, &voted, None, &[]), map);
+    }
+
+    #[test]
+    fn test_labels_follow_previous_members() {
+        let (votes, voted) = two_camps();
+        let map = opinion_map(&votes, &voted, Some(2), &[]);
+        let swapped: Vec<Option<usize>> = map.labels.iter().map(|&label| Some(1 - label)).collect();
+        let relabelled = opinion_map(&votes, &voted, Some(2), &swapped);
+        assert_eq!(
+            relabelled.labels,
+            swapped
+                .iter()
+                .map(|label| label.unwrap())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_stable_labels_fill_lowest_free_labels() {
+        let assignments = [0, 0, 1, 1, 2];
+        let centroids = [[1.0, 0.0], [-1.0, 0.0], [0.0, 0.0]];
+        assert_eq!(stable_labels(&assignments, &centroids, &[]), vec![2, 0, 1]);
+        let previous = [Some(0), Some(0), Some(0), None, None];
+        assert_eq!(
+            stable_labels(&assignments, &centroids, &previous),
+            vec![0, 1, 2]
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use db::OutreachRecipient;
+use mailers::{CandidateGuideOutreach, EmailTemplate};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::{email::enqueue_email, types::Error};
+
+/// The candidate's intake form, the same link `generateIntakeTokenLink` gives staff
+pub fn intake_form_url(recipient: &OutreachRecipient) -> String {
+    format!(
+        "{}/intakes/candidate-guides/{}?raceId={}&token={}",
+        config::Config::default().web_app_url,
+        recipient.candidate_guide_id,
+        recipient.race_id,
+        recipient.intake_token.as_deref().unwrap_or_default()
+    )
+}
+
+fn outreach_email(recipient: &OutreachRecipient) -> CandidateGuideOutreach {
+    let api_url = config::Config::default().api_url;
+    let tracking_url = |action: &str| {
+        api_url
+            .join(&format!("outreach/{}/{}", recipient.tracking_token, action))
+            .map(|url| url.to_string())
+            .unwrap_or_default()
+    };
+    CandidateGuideOutreach {
+        candidate_name: recipient.candidate_name.clone(),
+        organization_name: recipient.organization_name.clone(),
+        guide_name: recipient.guide_name.clone(),
+        race_title: recipient.race_title.clone(),
+        form_url: tracking_url("click"),
+        open_url: tracking_url("open"),
+        deadline: recipient.deadline.format("%B %-d, %Y").to_string(),
+    }
+}
+
+/// Emails an intake invitation to each candidate in the guide's races (or just `race_ids`)
+/// who hasn't been invited yet, returning how many were queued
+pub async fn send_outreach_invites(
+    db_pool: &PgPool,
+    outreach: &db::CandidateGuideOutreach,
+    race_ids: Option<&[uuid::Uuid]>,
+) -> Result<usize, Error> {
+    let mut sent = 0;
+    for recipient in outreach.add_recipients(db_pool, race_ids).await? {
+        let email = enqueue_email(
+            db_pool,
+            &recipient.email,
+            EmailTemplate::CandidateGuideInvite(outreach_email(&recipient)),
+            Some(&format!("outreach_invite:{}", recipient.id)),
+        )
+        .await?;
+        if let Some(email) = email {
+            OutreachRecipient::set_invite_email(db_pool, recipient.id, email.id).await?;
+            sent += 1;
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Reminds candidates who haven't answered yet as the deadlines of their guides approach,
+/// returning how many reminders were queued
+pub async fn send_outreach_reminders(db_pool: &PgPool) -> Result<usize, Error> {
+    let mut sent = 0;
+    for (recipient, reminders_due) in OutreachRecipient::due_for_reminder(db_pool).await? {
+        let email = enqueue_email(
+            db_pool,
+            &recipient.email,
+            EmailTemplate::CandidateGuideReminder(outreach_email(&recipient)),
+            Some(&format!(
+                "outreach_reminder:{}:{}",
+                recipient.id, reminders_due
+            )),
+        )
+        .await;
+        match email {
+            Ok(_) => {
+                OutreachRecipient::set_reminders_sent(db_pool, recipient.id, reminders_due).await?;
+                sent += 1;
+            }
+            Err(err) => warn!(
+                "Failed to queue outreach reminder for recipient {}: {}",
+                recipient.id, err
+            ),
+        }
+    }
+
+    Ok(sent)
+}
@@ -0,0 +1,53 @@
+use async_graphql::{Context, Object, Result, ID};
+use db::{AuditEntityType, AuditEvent, AuditEventFilter, OrganizationRoleType};
+
+use crate::{
+    context::ApiContext,
+    guard::{OrganizationGuard, StaffOnly},
+    is_admin,
+    types::AuditEventResult,
+};
+
+#[derive(Default)]
+pub struct AuditEventQuery;
+
+#[Object]
+impl AuditEventQuery {
+    /// Changes made through the API to any record, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn audit_events(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<AuditEventFilter>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AuditEventResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let events =
+            AuditEvent::filter(&db_pool, &filter.unwrap_or_default(), limit.unwrap_or(50)).await?;
+        Ok(events.into_iter().map(AuditEventResult::from).collect())
+    }
+
+    /// Changes made to an organization's own content, newest first
+    #[graphql(
+        guard = "OrganizationGuard::new(&organization_id, &OrganizationRoleType::Admin)",
+        visible = "is_admin"
+    )]
+    async fn organization_audit_events(
+        &self,
+        ctx: &Context<'_>,
+        organization_id: ID,
+        entity_type: Option<AuditEntityType>,
+        entity_id: Option<uuid::Uuid>,
+        limit: Option<i64>,
+    ) -> Result<Vec<AuditEventResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let filter = AuditEventFilter {
+            entity_type,
+            entity_id,
+            organization_id: Some(uuid::Uuid::parse_str(&organization_id)?),
+            ..Default::default()
+        };
+        let events = AuditEvent::filter(&db_pool, &filter, limit.unwrap_or(50)).await?;
+        Ok(events.into_iter().map(AuditEventResult::from).collect())
+    }
+}
@@ -1,10 +1,11 @@
 use crate::{
     context::ApiContext,
     is_admin,
-    types::{AuthTokenResult, Error},
+    types::{AuthTokenResult, Error, UserSessionResult},
 };
 use async_graphql::{Context, Object, Result, SimpleObject};
 use auth::AccessTokenClaims;
+use db::UserSession;
 use jsonwebtoken::TokenData;
 use zxcvbn::zxcvbn;
 
@@ -87,4 +88,19 @@ impl AuthQuery {
             None => Ok(None),
         }
     }
+
+    /// Devices the current user is signed in on, most recently used first
+    #[graphql(visible = "is_admin")]
+    async fn user_sessions(&self, ctx: &Context<'_>) -> Result<Vec<UserSessionResult>, Error> {
+        let token_data = match ctx.data::<Option<TokenData<AccessTokenClaims>>>().unwrap() {
+            Some(token_data) => token_data,
+            None => return Err(Error::Unauthorized),
+        };
+        let db_pool = ctx.data::<ApiContext>().unwrap().pool.clone();
+        let sessions = UserSession::find_active_by_user_id(&db_pool, token_data.claims.sub).await?;
+        Ok(sessions
+            .into_iter()
+            .map(|session| UserSessionResult::new(session, token_data.claims.sid))
+            .collect())
+    }
 }
@@ -1,9 +1,13 @@
 use async_graphql::{Context, Object, Result, ID};
-use db::{models::candidate_guide::CandidateGuide, QuestionSubmission, Sentiment};
+use db::{
+    models::candidate_guide::CandidateGuide, CandidateGuideOutreach, OrganizationRoleType,
+    QuestionSubmission, Sentiment, SubmissionStatus,
+};
 
 use crate::{
     context::ApiContext,
-    types::{CandidateGuideResult, QuestionSubmissionResult},
+    guard::{OrganizationGuard, OrganizationResource},
+    types::{CandidateGuideOutreachResult, CandidateGuideResult, QuestionSubmissionResult},
 };
 
 #[derive(Default)]
@@ -37,6 +41,24 @@ impl CandidateGuideQuery {
         Ok(records.into_iter().map(|r| r.into()).collect())
     }
 
+    /// Who was emailed for the guide and how they've responded, if outreach was sent
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::CandidateGuide, &candidate_guide_id, &OrganizationRoleType::Member)"
+    )]
+    async fn candidate_guide_outreach(
+        &self,
+        ctx: &Context<'_>,
+        candidate_guide_id: ID,
+    ) -> Result<Option<CandidateGuideOutreachResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let outreach = CandidateGuideOutreach::find_by_candidate_guide_id(
+            &db_pool,
+            uuid::Uuid::parse_str(&candidate_guide_id)?,
+        )
+        .await?;
+        Ok(outreach.map(CandidateGuideOutreachResult::from))
+    }
+
     async fn recent_candidate_guide_question_submissions_by_organization(
         &self,
         ctx: &Context<'_>,
@@ -58,7 +80,8 @@ impl CandidateGuideQuery {
                 qs.created_at,
                 qs.updated_at,
                 qs.respondent_id,
-                qs.copied_from_id
+                qs.copied_from_id,
+                qs.status AS "status:SubmissionStatus"
             FROM
                 question_submission qs
             JOIN
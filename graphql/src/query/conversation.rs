@@ -1,7 +1,15 @@
 use async_graphql::{Context, Object, ID};
-use db::{models::conversation::Conversation, OrganizationRoleType};
+use db::{
+    models::conversation::Conversation, ModerationQueueEntry, ModerationQueueFilter,
+    OrganizationRoleType,
+};
 
-use crate::{context::ApiContext, guard::OrganizationGuard, is_admin, types::ConversationResult};
+use crate::{
+    context::ApiContext,
+    guard::OrganizationGuard,
+    is_admin,
+    types::{ConversationResult, ModerationQueueEntryResult},
+};
 
 #[derive(Default)]
 pub struct ConversationQuery;
@@ -20,15 +28,11 @@ impl ConversationQuery {
     ) -> async_graphql::Result<Vec<ConversationResult>> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         let limit = limit.unwrap_or(10);
-        let conversations = sqlx::query_as!(
-            Conversation,
-            "SELECT * FROM conversation WHERE organization_id = $1
-            ORDER BY created_at DESC
-            LIMIT $2",
+        let conversations = Conversation::list_by_organization(
+            &db_pool,
             uuid::Uuid::parse_str(&organization_id)?,
-            limit
+            limit,
         )
-        .fetch_all(&db_pool)
         .await?;
 
         Ok(conversations.into_iter().map(|c| c.into()).collect())
@@ -41,14 +45,35 @@ impl ConversationQuery {
         id: ID,
     ) -> async_graphql::Result<Option<ConversationResult>> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-        let conversation = sqlx::query_as!(
-            Conversation,
-            "SELECT * FROM conversation WHERE id = $1",
-            uuid::Uuid::parse_str(&id)?,
+        let conversation = Conversation::find_by_id(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+
+        Ok(conversation.map(|c| c.into()))
+    }
+
+    /// Statements across the organization's conversations that need a moderator, oldest
+    /// first. Defaults to unmoderated statements.
+    #[graphql(
+        guard = "OrganizationGuard::new(&organization_id, &OrganizationRoleType::Member)",
+        visible = "is_admin"
+    )]
+    async fn statement_moderation_queue(
+        &self,
+        ctx: &Context<'_>,
+        organization_id: ID,
+        filter: Option<ModerationQueueFilter>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> async_graphql::Result<Vec<ModerationQueueEntryResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let entries = ModerationQueueEntry::list(
+            &db_pool,
+            uuid::Uuid::parse_str(&organization_id)?,
+            &filter.unwrap_or_default(),
+            limit.unwrap_or(50),
+            offset.unwrap_or(0),
         )
-        .fetch_optional(&db_pool)
         .await?;
 
-        Ok(conversation.map(|c| c.into()))
+        Ok(entries.into_iter().map(|e| e.into()).collect())
     }
 }
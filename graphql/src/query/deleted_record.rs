@@ -0,0 +1,23 @@
+use async_graphql::{Context, Object, Result};
+use db::{DeletedRecord, DeletedRecordFilter};
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::DeletedRecordResult};
+
+#[derive(Default)]
+pub struct DeletedRecordQuery;
+
+#[Object]
+impl DeletedRecordQuery {
+    /// Deleted politicians, races, embeds and candidate guides that can still be restored,
+    /// most recently deleted first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn deleted_records(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<DeletedRecordFilter>,
+    ) -> Result<Vec<DeletedRecordResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let records = DeletedRecord::filter(&db_pool, &filter.unwrap_or_default()).await?;
+        Ok(records.into_iter().map(DeletedRecordResult::from).collect())
+    }
+}
@@ -0,0 +1,24 @@
+use async_graphql::{Context, Object, Result};
+use db::{EmailOutbox, EmailOutboxFilter};
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::EmailResult};
+
+#[derive(Default)]
+pub struct EmailQuery;
+
+#[Object]
+impl EmailQuery {
+    /// Queued, sent and dead emails in the outbox, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn emails(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EmailOutboxFilter>,
+        limit: Option<i64>,
+    ) -> Result<Vec<EmailResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let emails =
+            EmailOutbox::filter(&db_pool, &filter.unwrap_or_default(), limit.unwrap_or(50)).await?;
+        Ok(emails.into_iter().map(EmailResult::from).collect())
+    }
+}
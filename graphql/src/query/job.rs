@@ -0,0 +1,43 @@
+use async_graphql::{Context, Object, Result, ID};
+use db::{Job, JobFilter, JobRun, JobStatus};
+
+use crate::{
+    context::ApiContext,
+    guard::StaffOnly,
+    is_admin,
+    types::{JobResult, JobRunResult},
+};
+
+#[derive(Default)]
+pub struct JobQuery;
+
+#[Object]
+impl JobQuery {
+    /// Queued, recurring and finished background jobs
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn jobs(&self, ctx: &Context<'_>, filter: Option<JobFilter>) -> Result<Vec<JobResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let jobs = Job::filter(&db_pool, &filter.unwrap_or_default()).await?;
+        Ok(jobs.into_iter().map(JobResult::from).collect())
+    }
+
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn job_by_id(&self, ctx: &Context<'_>, id: ID) -> Result<JobResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let job = Job::find_by_id(&db_pool, uuid::Uuid::parse_str(&id)?).await?;
+        Ok(job.into())
+    }
+
+    /// Run history across all jobs, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn job_runs(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<JobStatus>,
+        limit: Option<i64>,
+    ) -> Result<Vec<JobRunResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let runs = JobRun::recent(&db_pool, status, limit.unwrap_or(50)).await?;
+        Ok(runs.into_iter().map(JobRunResult::from).collect())
+    }
+}
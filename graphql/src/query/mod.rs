@@ -1,18 +1,24 @@
 mod admin;
+mod audit_event;
 mod auth;
 mod ballot_measure;
 mod bill;
 mod candidate_guide;
 mod conversation;
+mod deleted_record;
 mod election;
+mod email;
 mod embed;
 mod issue_tag;
+mod job;
 mod office;
 mod organization;
 mod politician;
 mod question;
 mod race;
 mod respondent;
+mod results_source;
+mod scrape_run;
 mod user;
 mod voting_guide;
 
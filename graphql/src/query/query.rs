@@ -2,20 +2,26 @@ use async_graphql::{MergedObject, Object};
 
 use super::{
     admin::AdminQuery,
+    audit_event::AuditEventQuery,
     auth::AuthQuery,
     ballot_measure::BallotMeasureQuery,
     bill::BillQuery,
     candidate_guide::CandidateGuideQuery,
     conversation::ConversationQuery,
+    deleted_record::DeletedRecordQuery,
     election::ElectionQuery,
+    email::EmailQuery,
     embed::EmbedQuery,
     issue_tag::IssueTagQuery,
+    job::JobQuery,
     office::OfficeQuery,
     organization::OrganizationQuery,
     politician::PoliticianQuery,
     question::{QuestionQuery, QuestionSubmissionQuery},
     race::RaceQuery,
     respondent::RespondentQuery,
+    results_source::ResultsSourceQuery,
+    scrape_run::ScrapeRunQuery,
     user::UserQuery,
     voting_guide::VotingGuideQuery,
 };
@@ -34,19 +40,25 @@ impl HealthQuery {
 #[derive(MergedObject, Default)]
 pub struct Query(
     AdminQuery,
+    AuditEventQuery,
     BallotMeasureQuery,
     BillQuery,
     CandidateGuideQuery,
     ConversationQuery,
+    DeletedRecordQuery,
     ElectionQuery,
+    EmailQuery,
     EmbedQuery,
     IssueTagQuery,
+    JobQuery,
     HealthQuery,
     OfficeQuery,
     OrganizationQuery,
     PoliticianQuery,
     RaceQuery,
     RespondentQuery,
+    ResultsSourceQuery,
+    ScrapeRunQuery,
     AuthQuery,
     VotingGuideQuery,
     UserQuery,
@@ -1,7 +1,11 @@
-use crate::types::QuestionResult;
+use crate::guard::{OrganizationGuard, OrganizationResource};
+use crate::types::{QuestionResult, QuestionSubmissionReviewResult};
 use crate::{context::ApiContext, types::QuestionSubmissionResult};
 use async_graphql::{Context, Object, Result, ID};
-use db::{Question, QuestionSubmission, QuestionSubmissionsFilter, Sentiment};
+use db::{
+    OrganizationRoleType, Question, QuestionSubmission, QuestionSubmissionsFilter, Sentiment,
+    SubmissionStatus,
+};
 
 #[derive(Default)]
 pub struct QuestionQuery;
@@ -32,6 +36,22 @@ impl QuestionSubmissionQuery {
         Ok(records.into_iter().map(|r| r.into()).collect())
     }
 
+    /// Status changes and editorial notes on a candidate's answer, oldest first
+    #[graphql(
+        guard = "OrganizationGuard::owner_of(OrganizationResource::QuestionSubmission, &question_submission_id, &OrganizationRoleType::Member)"
+    )]
+    async fn question_submission_reviews(
+        &self,
+        ctx: &Context<'_>,
+        question_submission_id: ID,
+    ) -> Result<Vec<QuestionSubmissionReviewResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let reviews =
+            QuestionSubmission::reviews(&db_pool, uuid::Uuid::parse_str(&question_submission_id)?)
+                .await?;
+        Ok(reviews.into_iter().map(|r| r.into()).collect())
+    }
+
     async fn related_question_submission_by_candidate_and_question(
         &self,
         ctx: &Context<'_>,
@@ -53,6 +73,7 @@ impl QuestionSubmissionQuery {
                     qs.translations,
                     qs.sentiment AS "sentiment: Sentiment",
                     qs.copied_from_id,
+                    qs.status AS "status: SubmissionStatus",
                     qs.created_at,
                     qs.updated_at
                 FROM
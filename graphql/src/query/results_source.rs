@@ -0,0 +1,22 @@
+use async_graphql::{Context, Object, Result};
+use db::{ResultsSource, ResultsSourceFilter};
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::ResultsSourceResult};
+
+#[derive(Default)]
+pub struct ResultsSourceQuery;
+
+#[Object]
+impl ResultsSourceQuery {
+    /// Election results feeds polled by the results scheduler
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn results_sources(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ResultsSourceFilter>,
+    ) -> Result<Vec<ResultsSourceResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let sources = ResultsSource::filter(&db_pool, &filter.unwrap_or_default()).await?;
+        Ok(sources.into_iter().map(ResultsSourceResult::from).collect())
+    }
+}
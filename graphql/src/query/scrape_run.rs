@@ -0,0 +1,32 @@
+use async_graphql::{Context, Object, Result};
+use db::{ScrapeRun, ScrapeRunFilter};
+
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::ScrapeRunResult};
+
+#[derive(Default)]
+pub struct ScrapeRunQuery;
+
+#[Object]
+impl ScrapeRunQuery {
+    /// Scraper, processor and merger runs, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn scrape_runs(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<ScrapeRunFilter>,
+        limit: Option<i64>,
+    ) -> Result<Vec<ScrapeRunResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let runs =
+            ScrapeRun::filter(&db_pool, &filter.unwrap_or_default(), limit.unwrap_or(50)).await?;
+        Ok(runs.into_iter().map(ScrapeRunResult::from).collect())
+    }
+
+    /// Most recent run of each source and stage
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn latest_scrape_runs(&self, ctx: &Context<'_>) -> Result<Vec<ScrapeRunResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let runs = ScrapeRun::latest(&db_pool).await?;
+        Ok(runs.into_iter().map(ScrapeRunResult::from).collect())
+    }
+}
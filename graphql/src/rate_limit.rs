@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use config::RateLimits;
+use tokio::time::interval;
+
+/// What a client is counted by. A script rotating its `session_id` cookie is still
+/// caught by its address, and signed in users are caught across devices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitScope {
+    Ip,
+    Session,
+    User,
+}
+
+impl fmt::Display for RateLimitScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                RateLimitScope::Ip => "ip",
+                RateLimitScope::Session => "session",
+                RateLimitScope::User => "user",
+            }
+        )
+    }
+}
+
+/// Receives refused requests, e.g. to export them to Prometheus
+pub trait RateLimitMetrics: Send + Sync {
+    fn record_limited(&self, operation: &str, scope: RateLimitScope);
+}
+
+struct NoopMetrics;
+
+impl RateLimitMetrics for NoopMetrics {
+    fn record_limited(&self, _operation: &str, _scope: RateLimitScope) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub scope: RateLimitScope,
+    pub retry_after: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WindowKey {
+    operation: String,
+    scope: RateLimitScope,
+    client: String,
+}
+
+struct Window {
+    started_at: Instant,
+    length: Duration,
+    count: u32,
+}
+
+/// Fixed window request counters, local to a single server instance
+#[derive(Clone)]
+pub struct RateLimiter {
+    limits: Arc<RateLimits>,
+    windows: Arc<Mutex<HashMap<WindowKey, Window>>>,
+    metrics: Arc<dyn RateLimitMetrics>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: RateLimits) -> Self {
+        Self::with_metrics(limits, NoopMetrics)
+    }
+
+    pub fn with_metrics(limits: RateLimits, metrics: impl RateLimitMetrics + 'static) -> Self {
+        let limiter = RateLimiter {
+            limits: Arc::new(limits),
+            windows: Arc::new(Mutex::new(HashMap::new())),
+            metrics: Arc::new(metrics),
+        };
+
+        let limiter_clone = limiter.clone();
+        tokio::spawn(async move {
+            let mut interval = interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                limiter_clone.purge_expired(Instant::now());
+            }
+        });
+
+        limiter
+    }
+
+    /// Counts a request to `operation` against each of the client's keys. Nothing is
+    /// counted when the request is refused, so a client hammering a limit doesn't push
+    /// its other scopes over as well.
+    pub fn check(
+        &self,
+        operation: &str,
+        clients: &[(RateLimitScope, String)],
+    ) -> Result<(), RateLimited> {
+        let result = self.check_at(operation, clients, Instant::now());
+        if let Err(limited) = &result {
+            self.metrics.record_limited(operation, limited.scope);
+        }
+        result
+    }
+
+    fn check_at(
+        &self,
+        operation: &str,
+        clients: &[(RateLimitScope, String)],
+        now: Instant,
+    ) -> Result<(), RateLimited> {
+        let Some(budget) = self.limits.budget(operation) else {
+            return Ok(());
+        };
+        let limited: Vec<(WindowKey, u32)> = clients
+            .iter()
+            .filter_map(|(scope, client)| {
+                let limit = match scope {
+                    RateLimitScope::Ip => budget.per_ip,
+                    RateLimitScope::Session => budget.per_session,
+                    RateLimitScope::User => budget.per_user,
+                }?;
+                let key = WindowKey {
+                    operation: operation.to_string(),
+                    scope: *scope,
+                    client: client.clone(),
+                };
+                Some((key, limit))
+            })
+            .collect();
+
+        let mut windows = self.windows.lock().unwrap();
+        let mut refused: Option<RateLimited> = None;
+        for (key, limit) in &limited {
+            if let Some(window) = windows.get(key) {
+                let elapsed = now.saturating_duration_since(window.started_at);
+                if elapsed < window.length && window.count >= *limit {
+                    let retry_after = window.length - elapsed;
+                    if refused.is_none_or(|r| retry_after > r.retry_after) {
+                        refused = Some(RateLimited {
+                            scope: key.scope,
+                            retry_after,
+                        });
+                    }
+                }
+            }
+        }
+        if let Some(refused) = refused {
+            return Err(refused);
+        }
+
+        for (key, _) in limited {
+            let window = windows.entry(key).or_insert(Window {
+                started_at: now,
+                length: budget.window,
+                count: 0,
+            });
+            if now.saturating_duration_since(window.started_at) >= window.length {
+                window.started_at = now;
+                window.length = budget.window;
+                window.count = 0;
+            }
+            window.count += 1;
+        }
+        Ok(())
+    }
+
+    fn purge_expired(&self, now: Instant) -> usize {
+        let mut windows = self.windows.lock().unwrap();
+        let before = windows.len();
+        windows
+            .retain(|_, window| now.saturating_duration_since(window.started_at) < window.length);
+        before - windows.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::RateLimitBudget;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(RateLimits {
+            enabled: true,
+            budgets: [(
+                "vote_on_statement".to_string(),
+                RateLimitBudget::new(60, 3, 2, 2),
+            )]
+            .into_iter()
+            .collect(),
+        })
+    }
+
+    fn client(ip: &str, session: &str) -> Vec<(RateLimitScope, String)> {
+        vec![
+            (RateLimitScope::Ip, ip.to_string()),
+            (RateLimitScope::Session, session.to_string()),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_rotating_sessions_hits_ip_limit() {
+        let limiter = limiter();
+        let now = Instant::now();
+
+        assert!(limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "a"), now)
+            .is_ok());
+        assert!(limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "a"), now)
+            .is_ok());
+        let limited = limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "a"), now)
+            .unwrap_err();
+        assert_eq!(limited.scope, RateLimitScope::Session);
+
+        // A fresh cookie gets one more request before the address runs out
+        assert!(limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "b"), now)
+            .is_ok());
+        let limited = limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "c"), now)
+            .unwrap_err();
+        assert_eq!(limited.scope, RateLimitScope::Ip);
+        assert_eq!(limited.retry_after, Duration::from_secs(60));
+
+        // Other addresses and operations are unaffected
+        assert!(limiter
+            .check_at("vote_on_statement", &client("10.0.0.2", "d"), now)
+            .is_ok());
+        assert!(limiter
+            .check_at("add_statement", &client("10.0.0.1", "c"), now)
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_windows_reset() {
+        let limiter = limiter();
+        let now = Instant::now();
+        for _ in 0..2 {
+            limiter
+                .check_at("vote_on_statement", &client("10.0.0.1", "a"), now)
+                .unwrap();
+        }
+        let limited = limiter
+            .check_at(
+                "vote_on_statement",
+                &client("10.0.0.1", "a"),
+                now + Duration::from_secs(45),
+            )
+            .unwrap_err();
+        assert_eq!(limited.retry_after, Duration::from_secs(15));
+
+        let later = now + Duration::from_secs(60);
+        assert!(limiter
+            .check_at("vote_on_statement", &client("10.0.0.1", "a"), later)
+            .is_ok());
+        assert_eq!(limiter.purge_expired(later + Duration::from_secs(60)), 2);
+    }
+}
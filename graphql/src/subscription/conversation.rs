@@ -0,0 +1,144 @@
+use async_graphql::{Context, Guard, Result, Subscription, ID};
+use db::{OrganizationRoleType, StatementModerationStatus};
+use futures::{Stream, StreamExt};
+use sqlx::PgPool;
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+
+use crate::{
+    context::ApiContext,
+    events::EventBroker,
+    guard::{OrganizationGuard, StaffOnly},
+    types::StatementResult,
+};
+
+#[derive(Default)]
+pub struct ConversationSubscription;
+
+#[Subscription]
+impl ConversationSubscription {
+    /// Emits statements as they become visible in a conversation, either because they were
+    /// added with a visible moderation status or because a moderator approved them.
+    /// Defaults to accepted and seed statements. Other statuses are limited to staff and
+    /// members of the organization that owns the conversation.
+    async fn statement_added(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+        moderation_statuses: Option<Vec<StatementModerationStatus>>,
+    ) -> Result<impl Stream<Item = StatementResult>> {
+        let conversation_id = Uuid::parse_str(&conversation_id)?;
+        let moderation_statuses = moderation_statuses.unwrap_or(vec![
+            StatementModerationStatus::Accepted,
+            StatementModerationStatus::Seed,
+        ]);
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+
+        let is_public = moderation_statuses.iter().all(|status| {
+            matches!(
+                status,
+                StatementModerationStatus::Accepted | StatementModerationStatus::Seed
+            )
+        });
+        if !is_public && StaffOnly.check(ctx).await.is_err() {
+            let organization_id = organization_id(&db_pool, conversation_id).await?;
+            OrganizationGuard::new(&ID::from(organization_id), &OrganizationRoleType::Member)
+                .check(ctx)
+                .await?;
+        }
+
+        let receiver = ctx.data::<EventBroker>()?.subscribe_statements();
+        let statement_ids = BroadcastStream::new(receiver).filter_map(move |event| {
+            let statement_id = event.ok().and_then(|event| {
+                let is_visible = moderation_statuses.contains(&event.moderation_status);
+                let was_visible = event
+                    .previous_moderation_status
+                    .is_some_and(|status| moderation_statuses.contains(&status));
+                (event.conversation_id == conversation_id && is_visible && !was_visible)
+                    .then_some(event.statement_id)
+            });
+            async move { statement_id }
+        });
+
+        Ok(load_statements(db_pool, statement_ids))
+    }
+
+    /// Emits statements whose moderation status changed. Limited to members of the
+    /// organization that owns the conversation since it includes rejected statements.
+    async fn statement_moderated(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+    ) -> Result<impl Stream<Item = StatementResult>> {
+        let conversation_id = Uuid::parse_str(&conversation_id)?;
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+
+        let organization_id = organization_id(&db_pool, conversation_id).await?;
+        OrganizationGuard::new(&ID::from(organization_id), &OrganizationRoleType::Member)
+            .check(ctx)
+            .await?;
+
+        let receiver = ctx.data::<EventBroker>()?.subscribe_statements();
+        let statement_ids = BroadcastStream::new(receiver).filter_map(move |event| {
+            let statement_id = event
+                .ok()
+                .filter(|event| {
+                    event.conversation_id == conversation_id
+                        && event.previous_moderation_status.is_some()
+                })
+                .map(|event| event.statement_id);
+            async move { statement_id }
+        });
+
+        Ok(load_statements(db_pool, statement_ids))
+    }
+
+    /// Emits a statement with its latest vote tallies each time a vote on it is cast or changed
+    async fn statement_votes_updated(
+        &self,
+        ctx: &Context<'_>,
+        conversation_id: ID,
+    ) -> Result<impl Stream<Item = StatementResult>> {
+        let conversation_id = Uuid::parse_str(&conversation_id)?;
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let receiver = ctx.data::<EventBroker>()?.subscribe_statement_votes();
+
+        let statement_ids = BroadcastStream::new(receiver).filter_map(move |event| {
+            let statement_id = event
+                .ok()
+                .filter(|event| event.conversation_id == conversation_id)
+                .map(|event| event.statement_id);
+            async move { statement_id }
+        });
+
+        Ok(load_statements(db_pool, statement_ids))
+    }
+}
+
+async fn organization_id(db_pool: &PgPool, conversation_id: Uuid) -> Result<Uuid> {
+    let record = sqlx::query!(
+        "SELECT organization_id FROM conversation WHERE id = $1",
+        conversation_id
+    )
+    .fetch_one(db_pool)
+    .await?;
+    Ok(record.organization_id)
+}
+
+fn load_statements(
+    db_pool: PgPool,
+    statement_ids: impl Stream<Item = Uuid>,
+) -> impl Stream<Item = StatementResult> {
+    statement_ids.filter_map(move |statement_id| {
+        let db_pool = db_pool.clone();
+        async move {
+            match StatementResult::find_by_id(&db_pool, statement_id).await {
+                Ok(statement) => Some(statement),
+                Err(e) => {
+                    tracing::warn!("Failed to load statement {}: {:?}", statement_id, e);
+                    None
+                }
+            }
+        }
+    })
+}
@@ -1,5 +1,9 @@
+mod conversation;
 mod health;
+mod race;
 #[allow(clippy::module_inception)]
 mod subscription;
+pub use conversation::*;
 pub use health::*;
+pub use race::*;
 pub use subscription::*;
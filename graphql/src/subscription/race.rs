@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+
+use async_graphql::{Context, Result, Subscription, ID};
+use db::Race;
+use futures::{Stream, StreamExt};
+use tokio_stream::wrappers::BroadcastStream;
+use uuid::Uuid;
+
+use crate::{context::ApiContext, events::EventBroker, types::RaceResult};
+
+#[derive(Default)]
+pub struct RaceSubscription;
+
+#[Subscription]
+impl RaceSubscription {
+    /// Emits a race each time its results change, e.g. when election night results are merged.
+    /// Subscribe to specific races, every race in an election, or both.
+    async fn race_results_updated(
+        &self,
+        ctx: &Context<'_>,
+        race_ids: Option<Vec<ID>>,
+        election_id: Option<ID>,
+    ) -> Result<impl Stream<Item = RaceResult>> {
+        if race_ids.is_none() && election_id.is_none() {
+            return Err("Either raceIds or electionId must be provided".into());
+        }
+
+        let race_ids = race_ids
+            .unwrap_or_default()
+            .iter()
+            .map(|id| Uuid::parse_str(id))
+            .collect::<Result<HashSet<Uuid>, _>>()?;
+        let election_id = election_id.map(|id| Uuid::parse_str(&id)).transpose()?;
+
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let receiver = ctx.data::<EventBroker>()?.subscribe_race_results();
+
+        tracing::info!(
+            "Starting race results subscription for {} races, election {:?}",
+            race_ids.len(),
+            election_id
+        );
+
+        Ok(BroadcastStream::new(receiver)
+            .filter_map(move |event| {
+                let is_match = match &event {
+                    Ok(event) => {
+                        race_ids.contains(&event.race_id)
+                            || (election_id.is_some() && event.election_id == election_id)
+                    }
+                    // Receiver fell behind and missed events, nothing to match on
+                    Err(_) => false,
+                };
+                async move { event.ok().filter(|_| is_match).map(|e| e.race_id) }
+            })
+            .filter_map(move |race_id| {
+                let db_pool = db_pool.clone();
+                async move {
+                    match Race::find_by_id(&db_pool, race_id).await {
+                        Ok(race) => Some(RaceResult::from(race)),
+                        Err(e) => {
+                            tracing::warn!("Failed to load updated race {}: {}", race_id, e);
+                            None
+                        }
+                    }
+                }
+            }))
+    }
+}
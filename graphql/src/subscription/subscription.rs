@@ -1,6 +1,10 @@
 use async_graphql::MergedSubscription;
 
-use super::HealthSubscription;
+use super::{ConversationSubscription, HealthSubscription, RaceSubscription};
 
 #[derive(MergedSubscription, Default)]
-pub struct Subscription(HealthSubscription);
+pub struct Subscription(
+    HealthSubscription,
+    ConversationSubscription,
+    RaceSubscription,
+);
@@ -0,0 +1,166 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::OrganizationRoleType;
+    use serde_json::json;
+
+    use crate::tests::harness::TestHarness;
+
+    #[tokio::test]
+    async fn test_mutations_are_audited_per_organization() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let org_a = harness.create_organization("Org A").await?;
+        let org_b = harness.create_organization("Org B").await?;
+        let member_a = harness.create_user("member-a@example.com", None).await?;
+        let admin_a = harness.create_user("admin-a@example.com", None).await?;
+        let admin_b = harness.create_user("admin-b@example.com", None).await?;
+        harness
+            .add_organization_member(org_a, member_a, OrganizationRoleType::Member)
+            .await?;
+        harness
+            .add_organization_member(org_a, admin_a, OrganizationRoleType::Admin)
+            .await?;
+        harness
+            .add_organization_member(org_b, admin_b, OrganizationRoleType::Admin)
+            .await?;
+
+        let upsert_embed = r#"
+            mutation($input: UpsertEmbedInput!) {
+                upsertEmbed(input: $input) { embedId: id }
+            }
+        "#;
+        let created = harness
+            .execute_query::<serde_json::Value>(
+                upsert_embed,
+                Some(Variables::from_json(json!({ "input": {
+                    "organizationId": org_a,
+                    "embedType": "POLL",
+                    "name": "Embed",
+                    "attributes": {}
+                } }))),
+                Some(member_a),
+                None,
+            )
+            .await?;
+        let embed_id = created["upsertEmbed"]["embedId"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        harness
+            .execute_query::<serde_json::Value>(
+                upsert_embed,
+                Some(Variables::from_json(json!({ "input": {
+                    "id": embed_id,
+                    "organizationId": org_a,
+                    "name": "Renamed"
+                } }))),
+                Some(member_a),
+                None,
+            )
+            .await?;
+
+        let history = r#"
+            query($organizationId: ID!, $entityId: UUID) {
+                organizationAuditEvents(organizationId: $organizationId, entityId: $entityId) {
+                    actorUserId
+                    entityType
+                    operation
+                    mutation
+                    changes
+                }
+            }
+        "#;
+        let variables = json!({ "organizationId": org_a, "entityId": embed_id });
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                history,
+                Some(Variables::from_json(variables.clone())),
+                Some(admin_a),
+                None,
+            )
+            .await?;
+        let events = result["organizationAuditEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["operation"], "UPDATE");
+        assert_eq!(events[0]["entityType"], "EMBED");
+        assert_eq!(events[0]["mutation"], "upsertEmbed");
+        assert_eq!(events[0]["actorUserId"], json!(member_a));
+        assert_eq!(
+            events[0]["changes"]["name"],
+            json!({ "old": "Embed", "new": "Renamed" })
+        );
+        assert_eq!(events[1]["operation"], "CREATE");
+
+        // Members can't read the history and other organizations can't either
+        for user_id in [member_a, admin_b] {
+            let result = harness
+                .execute_query::<serde_json::Value>(
+                    history,
+                    Some(Variables::from_json(variables.clone())),
+                    Some(user_id),
+                    None,
+                )
+                .await;
+            assert!(result.is_err());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutations_without_a_known_id_are_still_audited() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let organization_id = harness.create_organization("Org").await?;
+        let admin = harness.create_user("admin@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, admin, OrganizationRoleType::Admin)
+            .await?;
+
+        // The created conversation's id isn't selected
+        harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($input: CreateConversationInput!) {
+                    createConversation(input: $input) { topic }
+                }
+                "#,
+                Some(Variables::from_json(json!({ "input": {
+                    "topic": "Park hours",
+                    "organizationId": organization_id
+                } }))),
+                Some(admin),
+                None,
+            )
+            .await?;
+
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                query($organizationId: ID!) {
+                    organizationAuditEvents(organizationId: $organizationId) {
+                        actorUserId
+                        entityType
+                        entityId
+                        operation
+                        mutation
+                    }
+                }
+                "#,
+                Some(Variables::from_json(
+                    json!({ "organizationId": organization_id }),
+                )),
+                Some(admin),
+                None,
+            )
+            .await?;
+        let events = result["organizationAuditEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["mutation"], "createConversation");
+        assert_eq!(events[0]["entityType"], "CONVERSATION");
+        assert_eq!(events[0]["entityId"], json!(null));
+        assert_eq!(events[0]["operation"], json!(null));
+        assert_eq!(events[0]["actorUserId"], json!(admin));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 #[cfg(test)]
 
 mod tests {
-    use crate::tests::harness::TestHarness;
+    use crate::{
+        conversation_analysis::analyze_conversation, conversation_export::ConversationExport,
+        tests::harness::TestHarness,
+    };
+    use db::{ConversationAnalysisSnapshot, OrganizationRoleType};
     use rand::seq::SliceRandom;
     use rand::Rng;
 
@@ -27,6 +31,9 @@ mod tests {
             user_ids.push(user_id);
         }
         println!("Created {} users", user_ids.len());
+        harness
+            .add_organization_member(organization_id, user_ids[0], OrganizationRoleType::Member)
+            .await?;
 
         // Create conversation
         let conversation_id = {
@@ -189,6 +196,11 @@ mod tests {
             }
         }
 
+        // The job worker would pick this up once the votes are in
+        analyze_conversation(&harness.pool, conversation_id.parse()?)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+
         // Verify opinion groups
         let opinion_groups_query = r#"
         query GetOpinionGroups($id: ID!) {
@@ -313,4 +325,327 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_opinion_map_groups_stay_stable() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let organization_id = harness.create_organization("Opinion Map Org").await?;
+        let mut user_ids = Vec::new();
+        for i in 0..7 {
+            user_ids.push(
+                harness
+                    .create_user(&format!("voter-{}@example.com", i), None)
+                    .await?,
+            );
+        }
+        harness
+            .add_organization_member(organization_id, user_ids[0], OrganizationRoleType::Member)
+            .await?;
+
+        let response: serde_json::Value = harness
+            .execute_query(
+                r#"
+                mutation($input: CreateConversationInput!) {
+                    createConversation(input: $input) { id }
+                }
+                "#,
+                Some(async_graphql::Variables::from_json(serde_json::json!({
+                    "input": { "topic": "Parks", "organizationId": organization_id }
+                }))),
+                Some(user_ids[0]),
+                None,
+            )
+            .await?;
+        let conversation_id = response["createConversation"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let mut statement_ids = Vec::new();
+        for content in ["More parks", "Longer park hours", "Fewer park rules"] {
+            let response: serde_json::Value = harness
+                .execute_query(
+                    r#"
+                    mutation($input: AddStatementInput!) {
+                        addStatement(input: $input) { id }
+                    }
+                    "#,
+                    Some(async_graphql::Variables::from_json(serde_json::json!({
+                        "input": { "conversationId": conversation_id, "content": content }
+                    }))),
+                    Some(user_ids[0]),
+                    None,
+                )
+                .await?;
+            statement_ids.push(response["addStatement"]["id"].as_str().unwrap().to_string());
+        }
+
+        // Voters 0-2 back every statement except the last, voters 3-5 the opposite
+        let vote = |user_idx: usize, statement_idx: usize| {
+            let supports = (user_idx < 3) != (statement_idx == 2);
+            let vote_type = if supports { "SUPPORT" } else { "OPPOSE" };
+            (
+                user_ids[user_idx],
+                async_graphql::Variables::from_json(serde_json::json!({
+                    "statementId": statement_ids[statement_idx],
+                    "voteType": vote_type,
+                })),
+            )
+        };
+        let vote_mutation = r#"
+            mutation($statementId: ID!, $voteType: ArgumentPosition!) {
+                voteOnStatement(statementId: $statementId, voteType: $voteType) { id }
+            }
+        "#;
+        for user_idx in 0..6 {
+            for statement_idx in 0..3 {
+                // Voter 5 skips a statement, which shouldn't read as neutral
+                if user_idx == 5 && statement_idx == 1 {
+                    continue;
+                }
+                let (user_id, variables) = vote(user_idx, statement_idx);
+                harness
+                    .execute_query::<serde_json::Value>(
+                        vote_mutation,
+                        Some(variables),
+                        Some(user_id),
+                        Some(uuid::Uuid::new_v4()),
+                    )
+                    .await?;
+            }
+        }
+
+        let map_query = r#"
+            query($id: ID!) {
+                conversationById(id: $id) {
+                    analyzedAt
+                    opinionMap {
+                        participants { userId x y groupId }
+                        groups { id size }
+                        explainedVariance
+                    }
+                }
+            }
+        "#;
+        let variables =
+            async_graphql::Variables::from_json(serde_json::json!({ "id": conversation_id }));
+
+        // Nothing to show until the conversation has been analyzed
+        let response: serde_json::Value = harness
+            .execute_query(map_query, Some(variables.clone()), Some(user_ids[0]), None)
+            .await?;
+        assert!(response["conversationById"]["analyzedAt"].is_null());
+        assert_eq!(
+            response["conversationById"]["opinionMap"]["participants"],
+            serde_json::json!([])
+        );
+
+        let conversation_id: uuid::Uuid = conversation_id.parse()?;
+        let pool = &harness.pool;
+        assert!(ConversationAnalysisSnapshot::stale_conversations(pool, 10)
+            .await?
+            .contains(&conversation_id));
+        analyze_conversation(pool, conversation_id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+        assert!(!ConversationAnalysisSnapshot::stale_conversations(pool, 1)
+            .await?
+            .contains(&conversation_id));
+
+        let response: serde_json::Value = harness
+            .execute_query(map_query, Some(variables.clone()), Some(user_ids[0]), None)
+            .await?;
+        assert!(response["conversationById"]["analyzedAt"].is_string());
+        let map = &response["conversationById"]["opinionMap"];
+        let group_of = |map: &serde_json::Value, user_id: uuid::Uuid| {
+            map["participants"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|participant| participant["userId"] == user_id.to_string())
+                .map(|participant| participant["groupId"].clone())
+                .unwrap()
+        };
+        assert_eq!(map["groups"].as_array().unwrap().len(), 2);
+        assert!(map["explainedVariance"][0].as_f64().unwrap() > 0.5);
+        let first_group = group_of(map, user_ids[0]);
+        let second_group = group_of(map, user_ids[3]);
+        assert_ne!(first_group, second_group);
+        for (user_idx, user_id) in user_ids.iter().enumerate().take(6) {
+            let expected = if user_idx < 3 {
+                &first_group
+            } else {
+                &second_group
+            };
+            assert_eq!(&group_of(map, *user_id), expected);
+        }
+
+        // A new voter joins the second camp and nobody's group id changes
+        for statement_idx in 0..3 {
+            let (_, variables) = vote(4, statement_idx);
+            harness
+                .execute_query::<serde_json::Value>(
+                    vote_mutation,
+                    Some(variables),
+                    Some(user_ids[6]),
+                    Some(uuid::Uuid::new_v4()),
+                )
+                .await?;
+        }
+        // Three new votes aren't enough to reanalyze on their own
+        let stale = ConversationAnalysisSnapshot::stale_conversations(pool, 3).await?;
+        assert!(stale.contains(&conversation_id));
+        let stale = ConversationAnalysisSnapshot::stale_conversations(pool, 4).await?;
+        assert!(!stale.contains(&conversation_id));
+        analyze_conversation(pool, conversation_id)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+        let response: serde_json::Value = harness
+            .execute_query(map_query, Some(variables), Some(user_ids[0]), None)
+            .await?;
+        let map = &response["conversationById"]["opinionMap"];
+        assert_eq!(map["participants"].as_array().unwrap().len(), 7);
+        assert_eq!(group_of(map, user_ids[0]), first_group);
+        assert_eq!(group_of(map, user_ids[3]), second_group);
+        assert_eq!(group_of(map, user_ids[6]), second_group);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_is_pseudonymized_in_polis_layout() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let organization_id = harness.create_organization("Export Org").await?;
+        let admin = harness.create_user("admin@example.com", None).await?;
+        let member = harness.create_user("member@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, admin, OrganizationRoleType::Admin)
+            .await?;
+        harness
+            .add_organization_member(organization_id, member, OrganizationRoleType::Member)
+            .await?;
+        let mut voters = Vec::new();
+        for i in 0..3 {
+            voters.push(
+                harness
+                    .create_user(&format!("voter-{}@example.com", i), None)
+                    .await?,
+            );
+        }
+
+        let response: serde_json::Value = harness
+            .execute_query(
+                r#"
+                mutation($input: CreateConversationInput!) {
+                    createConversation(input: $input) { id }
+                }
+                "#,
+                Some(async_graphql::Variables::from_json(serde_json::json!({
+                    "input": { "topic": "Park hours", "organizationId": organization_id }
+                }))),
+                Some(admin),
+                None,
+            )
+            .await?;
+        let conversation_id = response["createConversation"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let mut statement_ids = Vec::new();
+        for content in ["Open the park at dawn", "Close the park at ten"] {
+            let response: serde_json::Value = harness
+                .execute_query(
+                    r#"
+                    mutation($input: AddStatementInput!) {
+                        addStatement(input: $input) { id }
+                    }
+                    "#,
+                    Some(async_graphql::Variables::from_json(serde_json::json!({
+                        "input": {
+                            "conversationId": conversation_id,
+                            "content": content,
+                            "moderationStatus": "ACCEPTED"
+                        }
+                    }))),
+                    Some(admin),
+                    None,
+                )
+                .await?;
+            statement_ids.push(response["addStatement"]["id"].as_str().unwrap().to_string());
+        }
+        for (voter, vote_type) in voters.iter().zip(["SUPPORT", "OPPOSE", "NEUTRAL"]) {
+            for statement_id in &statement_ids {
+                harness
+                    .execute_query::<serde_json::Value>(
+                        r#"
+                        mutation($statementId: ID!, $voteType: ArgumentPosition!) {
+                            voteOnStatement(statementId: $statementId, voteType: $voteType) { id }
+                        }
+                        "#,
+                        Some(async_graphql::Variables::from_json(serde_json::json!({
+                            "statementId": statement_id,
+                            "voteType": vote_type
+                        }))),
+                        Some(*voter),
+                        Some(uuid::Uuid::new_v4()),
+                    )
+                    .await?;
+            }
+        }
+        analyze_conversation(&harness.pool, conversation_id.parse()?)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+
+        let conversation = db::Conversation::find_by_id(&harness.pool, conversation_id.parse()?)
+            .await?
+            .unwrap();
+        let export = ConversationExport::load(&harness.pool, &conversation)
+            .await
+            .map_err(|e| anyhow::anyhow!(e.message))?;
+        assert_eq!(export.comments.len(), 2);
+        assert_eq!(export.comments[0].agrees, 1);
+        assert_eq!(export.comments[0].disagrees, 1);
+        // The admin wrote both statements before anyone voted
+        assert_eq!(export.participants.len(), 4);
+        assert_eq!(export.participants[0].n_comments, 2);
+        assert_eq!(export.participants[0].n_votes, 0);
+        assert!(export.participants[1..]
+            .iter()
+            .all(|participant| participant.group_id.is_some() && participant.n_votes == 2));
+
+        let participants_votes = String::from_utf8(
+            export
+                .participants_votes_csv()
+                .map_err(|e| anyhow::anyhow!(e.message))?,
+        )?;
+        assert_eq!(
+            participants_votes.lines().next(),
+            Some("participant,group-id,n-comments,n-votes,n-agree,n-disagree,0,1")
+        );
+        let json = serde_json::to_string(&export)?;
+        for user_id in voters.iter().chain([&admin]) {
+            assert!(!participants_votes.contains(&user_id.to_string()));
+            assert!(!json.contains(&user_id.to_string()));
+        }
+
+        // Exports are for organization admins
+        let as_member = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($id: ID!) {
+                    exportConversation(conversationId: $id, format: CSV) { url }
+                }
+                "#,
+                Some(async_graphql::Variables::from_json(
+                    serde_json::json!({ "id": conversation_id }),
+                )),
+                Some(member),
+                None,
+            )
+            .await;
+        assert!(as_member.is_err());
+
+        Ok(())
+    }
 }
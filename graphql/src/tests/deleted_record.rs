@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use db::{
+        models::candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
+        DeletableEntityType, DeletedRecord, DeletedRecordFilter, Embed, EmbedType,
+        UpsertEmbedInput,
+    };
+    use serde_json::json;
+
+    use crate::tests::harness::TestHarness;
+
+    #[tokio::test]
+    async fn test_deleted_guides_can_be_restored_until_purged() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let organization_id = harness.create_organization("Org A").await?;
+        let user_id = harness.create_user("member@example.com", None).await?;
+
+        let guide = CandidateGuide::upsert(
+            pool,
+            &UpsertCandidateGuideInput {
+                id: None,
+                name: Some("Guide".to_string()),
+                organization_id: Some(organization_id),
+                user_id: Some(user_id),
+                race_ids: None,
+                submissions_open_at: None,
+                submissions_close_at: None,
+            },
+        )
+        .await?;
+        let embed = Embed::upsert(
+            pool,
+            &UpsertEmbedInput {
+                id: None,
+                organization_id: Some(organization_id),
+                embed_type: Some(EmbedType::CandidateGuide),
+                name: Some("Guide embed".to_string()),
+                description: None,
+                attributes: Some(json!({ "candidateGuideId": guide.id })),
+            },
+            &user_id,
+        )
+        .await?;
+
+        CandidateGuide::delete(pool, guide.id).await?;
+        assert!(CandidateGuide::find_by_id(pool, guide.id).await.is_err());
+        assert!(Embed::find_by_id(pool, embed.id).await.is_err());
+        assert!(CandidateGuide::find_by_organization(pool, organization_id)
+            .await?
+            .is_empty());
+
+        let filter = DeletedRecordFilter {
+            entity_type: None,
+            organization_id: Some(organization_id),
+        };
+        let deleted = DeletedRecord::filter(pool, &filter).await?;
+        assert_eq!(deleted.len(), 2);
+        assert!(deleted.iter().any(|record| record.id == guide.id
+            && record.entity_type == DeletableEntityType::CandidateGuide
+            && record.name.as_deref() == Some("Guide")));
+
+        // Restoring the guide brings back its embed
+        DeletedRecord::restore(pool, DeletableEntityType::CandidateGuide, guide.id).await?;
+        assert!(CandidateGuide::find_by_id(pool, guide.id).await.is_ok());
+        assert!(Embed::find_by_id(pool, embed.id).await.is_ok());
+        assert!(DeletedRecord::filter(pool, &filter).await?.is_empty());
+        assert!(
+            DeletedRecord::restore(pool, DeletableEntityType::CandidateGuide, guide.id)
+                .await
+                .is_err()
+        );
+
+        // Past the retention window it can't be restored and is purged
+        Embed::delete(pool, embed.id).await?;
+        sqlx::query!(
+            "UPDATE embed SET deleted_at = now() - interval '1 year' WHERE id = $1",
+            embed.id
+        )
+        .execute(pool)
+        .await?;
+        assert!(DeletedRecord::filter(pool, &filter).await?.is_empty());
+        assert!(
+            DeletedRecord::restore(pool, DeletableEntityType::Embed, embed.id)
+                .await
+                .is_err()
+        );
+        let purged = DeletedRecord::purge(pool).await?;
+        assert!(purged
+            .iter()
+            .any(|(entity_type, count)| *entity_type == DeletableEntityType::Embed && *count >= 1));
+        let remaining = sqlx::query_scalar!("SELECT COUNT(*) FROM embed WHERE id = $1", embed.id)
+            .fetch_one(pool)
+            .await?;
+        assert_eq!(remaining, Some(0));
+        assert!(CandidateGuide::find_by_id(pool, guide.id).await.is_ok());
+
+        Ok(())
+    }
+}
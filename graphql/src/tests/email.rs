@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::{EmailOutbox, EmailOutboxFilter, EmailStatus};
+    use mailers::{EmailTemplate, FileTransport};
+    use serde_json::json;
+
+    use crate::{
+        email::{deliver_outbox, enqueue_email},
+        tests::harness::TestHarness,
+    };
+
+    #[tokio::test]
+    async fn test_password_reset_is_delivered_through_outbox() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        harness.create_user("reset@example.com", None).await?;
+        let dir = std::env::temp_dir().join(format!("populist-email-{}", uuid::Uuid::new_v4()));
+        let transport = FileTransport::new(&dir);
+
+        harness
+            .execute_query::<serde_json::Value>(
+                r#"mutation { requestPasswordReset(email: "reset@example.com") }"#,
+                None,
+                None,
+                None,
+            )
+            .await?;
+        assert_eq!(deliver_outbox(pool, &transport).await?, 1);
+
+        let messages = transport.messages()?;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].to, "reset@example.com");
+        let reset_token = messages[0]
+            .text
+            .split("token=")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap()
+            .to_string();
+
+        harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($input: ResetPasswordInput!) {
+                    resetPassword(input: $input)
+                }
+                "#,
+                Some(Variables::from_json(json!({ "input": {
+                    "newPassword": "a much stronger password 123!",
+                    "resetToken": reset_token
+                } }))),
+                None,
+                None,
+            )
+            .await?;
+        assert_eq!(deliver_outbox(pool, &transport).await?, 1);
+        let messages = transport.messages()?;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].subject, "Your Populist password was changed");
+
+        // Nothing is sent twice
+        assert_eq!(deliver_outbox(pool, &transport).await?, 0);
+        let sent = EmailOutbox::filter(
+            pool,
+            &EmailOutboxFilter {
+                status: Some(EmailStatus::Sent),
+                ..Default::default()
+            },
+            10,
+        )
+        .await?;
+        assert_eq!(sent.len(), 2);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_emails_are_deduplicated() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let template = EmailTemplate::Invite {
+            invite_url: "https://populist.us/register?inviteToken=abc".to_string(),
+            organization_name: Some("Org A".to_string()),
+            politician_name: None,
+        };
+
+        let first = enqueue_email(
+            pool,
+            "invitee@example.com",
+            template.clone(),
+            Some("invite:abc"),
+        )
+        .await?;
+        let second =
+            enqueue_email(pool, "invitee@example.com", template, Some("invite:abc")).await?;
+        assert!(first.is_some());
+        assert!(second.is_none());
+
+        let queued = EmailOutbox::filter(pool, &EmailOutboxFilter::default(), 10).await?;
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].status, EmailStatus::Pending);
+        assert_eq!(queued[0].template, "invite");
+
+        Ok(())
+    }
+}
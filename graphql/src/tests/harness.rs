@@ -1,14 +1,19 @@
-use std::net::SocketAddr;
+use std::net::IpAddr;
 use std::time::Duration;
 
 use async_graphql::Variables;
 use auth::{create_random_token, create_temporary_username, AccessTokenClaims};
-use db::{AddressInput, CreateUserWithProfileInput};
+use db::{AddressInput, CreateUserWithProfileInput, OrganizationApiKey, OrganizationRoleType};
 use jsonwebtoken::{Header, TokenData};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::{cache::Cache, context::ApiContext, new_schema, SessionData};
+use crate::{
+    audit::AuditExtension,
+    cache::{Cache, MemoryCache},
+    context::ApiContext,
+    new_schema, SessionData,
+};
 
 #[derive(Clone)]
 #[allow(dead_code)]
@@ -79,6 +84,29 @@ impl TestHarness {
         Ok(organization_id)
     }
 
+    /// Gives the user a role in the organization
+    pub async fn add_organization_member(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+        role: OrganizationRoleType,
+    ) -> anyhow::Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO organization_users (organization_id, user_id, role)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, organization_id) DO UPDATE SET role = EXCLUDED.role
+            "#,
+            organization_id,
+            user_id,
+            role as OrganizationRoleType,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Creates a fresh context for GraphQL operations
     fn create_context(&self) -> ApiContext {
         ApiContext::new(self.pool.clone())
@@ -125,9 +153,8 @@ impl TestHarness {
 
         let mut schema = new_schema()
             .data(context)
-            .data(Cache::<String, serde_json::Value>::new(
-                Duration::from_secs(300),
-            ));
+            .data(Cache::new(MemoryCache::new(1024, Duration::from_secs(300))))
+            .extension(AuditExtension);
 
         if let Some(uid) = user_id {
             let claims = AccessTokenClaims {
@@ -135,7 +162,9 @@ impl TestHarness {
                 username: "test_user".to_string(),
                 email: "test@example.com".to_string(),
                 system_role: db::SystemRoleType::User,
-                organizations: vec![],
+                // Mirrors the roles login puts in the access token
+                organizations: db::User::organization_roles(&self.pool, uid).await?,
+                sid: None,
                 exp: usize::MAX,
             };
             schema = schema.data(Some(TokenData {
@@ -145,10 +174,11 @@ impl TestHarness {
         }
 
         if let Some(sid) = session_id {
-            let test_socket_addr = SocketAddr::from(([127, 0, 0, 1], 8080));
+            let test_ip = IpAddr::from([127, 0, 0, 1]);
             schema = schema.data(SessionData {
                 session_id: crate::SessionID(sid.to_string()),
-                ip: test_socket_addr,
+                ip: test_ip,
+                user_agent: None,
             });
         }
 
@@ -161,4 +191,32 @@ impl TestHarness {
 
         Ok(serde_json::from_value(response.data.into_json()?)?)
     }
+
+    /// Runs a query the way the server does for calls made with an organization API key
+    pub async fn execute_query_with_api_key<T: serde::de::DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: Option<Variables>,
+        api_key: OrganizationApiKey,
+    ) -> anyhow::Result<T> {
+        let request = match variables {
+            Some(vars) => async_graphql::Request::new(query).variables(vars),
+            None => async_graphql::Request::new(query),
+        };
+
+        let schema = new_schema()
+            .data(self.create_context())
+            .data(Cache::new(MemoryCache::new(1024, Duration::from_secs(300))))
+            .data(None::<TokenData<AccessTokenClaims>>)
+            .data(api_key)
+            .extension(AuditExtension)
+            .finish();
+        let response = schema.execute(request).await;
+
+        if let Some(error) = response.errors.first() {
+            return Err(anyhow::anyhow!("GraphQL error: {:?}", error));
+        }
+
+        Ok(serde_json::from_value(response.data.into_json()?)?)
+    }
 }
@@ -1,3 +1,11 @@
+mod audit;
 mod auth;
 mod conversation;
+mod deleted_record;
+mod email;
 mod harness;
+mod moderation;
+mod organization_guard;
+mod outreach;
+mod revision;
+mod submission_review;
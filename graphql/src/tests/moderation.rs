@@ -0,0 +1,286 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::OrganizationRoleType;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::tests::harness::TestHarness;
+
+    const ADD_STATEMENT: &str = r#"
+        mutation($input: AddStatementInput!) {
+            addStatement(input: $input) { id moderationStatus }
+        }
+    "#;
+
+    const MODERATION_QUEUE: &str = r#"
+        query($organizationId: ID!, $filter: ModerationQueueFilter) {
+            statementModerationQueue(organizationId: $organizationId, filter: $filter) {
+                id
+                content
+                moderationStatus
+                screeningFlags { reason detail }
+            }
+        }
+    "#;
+
+    async fn add_statement(
+        harness: &TestHarness,
+        user_id: Uuid,
+        input: serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                ADD_STATEMENT,
+                Some(Variables::from_json(json!({ "input": input }))),
+                Some(user_id),
+                None,
+            )
+            .await?;
+        Ok(result["addStatement"].clone())
+    }
+
+    #[tokio::test]
+    async fn test_prescreen_queue_and_bulk_moderation() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let organization_id = harness.create_organization("Moderation Org").await?;
+        let member = harness.create_user("member@example.com", None).await?;
+        let participant = harness.create_user("participant@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, member, OrganizationRoleType::Member)
+            .await?;
+
+        let created = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($input: CreateConversationInput!) {
+                    createConversation(input: $input) { id moderationMode blockedWords }
+                }
+                "#,
+                Some(Variables::from_json(json!({ "input": {
+                    "topic": "Bike lanes on Main Street",
+                    "organizationId": organization_id,
+                    "blockedWords": ["casino"]
+                } }))),
+                Some(member),
+                None,
+            )
+            .await?;
+        assert_eq!(created["createConversation"]["moderationMode"], "PRE");
+        assert_eq!(
+            created["createConversation"]["blockedWords"],
+            json!(["casino"])
+        );
+        let conversation_id = created["createConversation"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let clean = add_statement(
+            &harness,
+            participant,
+            json!({ "conversationId": conversation_id, "content": "Bike lanes make Main Street safer for everyone" }),
+        )
+        .await?;
+        assert_eq!(clean["moderationStatus"], "UNMODERATED");
+
+        let attack = add_statement(
+            &harness,
+            participant,
+            json!({ "conversationId": conversation_id, "content": "Only idiots want more parking" }),
+        )
+        .await?;
+        let duplicate = add_statement(
+            &harness,
+            participant,
+            json!({ "conversationId": conversation_id, "content": "Bike lanes make Main Street safer for everyone!" }),
+        )
+        .await?;
+        // Participants can't accept their own statements
+        let blocked = add_statement(
+            &harness,
+            participant,
+            json!({
+                "conversationId": conversation_id,
+                "content": "Visit my casino after the ride",
+                "moderationStatus": "ACCEPTED"
+            }),
+        )
+        .await?;
+        assert_eq!(blocked["moderationStatus"], "UNMODERATED");
+
+        let flagged = harness
+            .execute_query::<serde_json::Value>(
+                MODERATION_QUEUE,
+                Some(Variables::from_json(json!({
+                    "organizationId": organization_id,
+                    "filter": { "flaggedOnly": true }
+                }))),
+                Some(member),
+                None,
+            )
+            .await?;
+        let flagged = flagged["statementModerationQueue"].as_array().unwrap();
+        let reasons: Vec<(&str, &str)> = flagged
+            .iter()
+            .map(|entry| {
+                (
+                    entry["id"].as_str().unwrap(),
+                    entry["screeningFlags"][0]["reason"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (attack["id"].as_str().unwrap(), "PERSONAL_ATTACK"),
+                (duplicate["id"].as_str().unwrap(), "DUPLICATE"),
+                (blocked["id"].as_str().unwrap(), "BLOCKED_WORD"),
+            ]
+        );
+
+        // The queue is only for the organization's members
+        let queue_as_participant = harness
+            .execute_query::<serde_json::Value>(
+                MODERATION_QUEUE,
+                Some(Variables::from_json(
+                    json!({ "organizationId": organization_id }),
+                )),
+                Some(participant),
+                None,
+            )
+            .await;
+        assert!(queue_as_participant.is_err());
+
+        let flagged_ids: Vec<&str> = reasons.iter().map(|(id, _)| *id).collect();
+        let bulk_moderate = r#"
+            mutation($organizationId: ID!, $statementIds: [ID!]!) {
+                bulkModerateStatements(
+                    organizationId: $organizationId,
+                    statementIds: $statementIds,
+                    moderationStatus: REJECTED
+                ) { id moderationStatus }
+            }
+        "#;
+        let rejected = harness
+            .execute_query::<serde_json::Value>(
+                bulk_moderate,
+                Some(Variables::from_json(json!({
+                    "organizationId": organization_id,
+                    "statementIds": flagged_ids
+                }))),
+                Some(member),
+                None,
+            )
+            .await?;
+        let rejected = rejected["bulkModerateStatements"].as_array().unwrap();
+        assert_eq!(rejected.len(), 3);
+        assert!(rejected
+            .iter()
+            .all(|statement| statement["moderationStatus"] == "REJECTED"));
+
+        let audited: i64 = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM audit_event WHERE mutation = 'bulkModerateStatements'"#
+        )
+        .fetch_one(&harness.pool)
+        .await?;
+        assert_eq!(audited, 3);
+
+        let remaining = harness
+            .execute_query::<serde_json::Value>(
+                MODERATION_QUEUE,
+                Some(Variables::from_json(
+                    json!({ "organizationId": organization_id }),
+                )),
+                Some(member),
+                None,
+            )
+            .await?;
+        let remaining = remaining["statementModerationQueue"].as_array().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0]["id"], clean["id"]);
+        assert_eq!(remaining[0]["screeningFlags"], json!([]));
+
+        // Statements in another organization can't be moderated through this one
+        let other_organization_id = harness.create_organization("Other Org").await?;
+        harness
+            .add_organization_member(other_organization_id, member, OrganizationRoleType::Member)
+            .await?;
+        let cross_organization = harness
+            .execute_query::<serde_json::Value>(
+                bulk_moderate,
+                Some(Variables::from_json(json!({
+                    "organizationId": other_organization_id,
+                    "statementIds": [clean["id"]]
+                }))),
+                Some(member),
+                None,
+            )
+            .await;
+        assert!(cross_organization.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_post_moderation_accepts_unflagged_statements() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let organization_id = harness.create_organization("Post Moderation Org").await?;
+        let member = harness.create_user("member@example.com", None).await?;
+        let participant = harness.create_user("participant@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, member, OrganizationRoleType::Member)
+            .await?;
+
+        let created = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($input: CreateConversationInput!) {
+                    createConversation(input: $input) { id }
+                }
+                "#,
+                Some(Variables::from_json(json!({ "input": {
+                    "topic": "Library hours",
+                    "organizationId": organization_id
+                } }))),
+                Some(member),
+                None,
+            )
+            .await?;
+        let conversation_id = created["createConversation"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let updated = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($id: ID!) {
+                    updateConversation(conversationId: $id, moderationMode: POST) { moderationMode }
+                }
+                "#,
+                Some(Variables::from_json(json!({ "id": conversation_id }))),
+                Some(member),
+                None,
+            )
+            .await?;
+        assert_eq!(updated["updateConversation"]["moderationMode"], "POST");
+
+        let clean = add_statement(
+            &harness,
+            participant,
+            json!({ "conversationId": conversation_id, "content": "The library should open on Sundays" }),
+        )
+        .await?;
+        assert_eq!(clean["moderationStatus"], "ACCEPTED");
+
+        let profane = add_statement(
+            &harness,
+            participant,
+            json!({ "conversationId": conversation_id, "content": "These hours are bullshit" }),
+        )
+        .await?;
+        assert_eq!(profane["moderationStatus"], "UNMODERATED");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::{
+        models::{
+            candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
+            question::UpsertQuestionInput,
+        },
+        ApiKeyScope, CreateOrganizationApiKeyInput, Embed, EmbedType, OrganizationApiKey,
+        OrganizationRoleType, UpsertEmbedInput, UpsertPollInput,
+    };
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{guard::OrganizationResource, tests::harness::TestHarness};
+
+    /// One of each kind of organization content
+    #[derive(Default)]
+    struct Content {
+        embed_id: Uuid,
+        candidate_guide_id: Uuid,
+        question_id: Uuid,
+        question_submission_id: Uuid,
+        poll_id: Uuid,
+        conversation_id: Uuid,
+        statement_id: Uuid,
+    }
+
+    async fn seed_content(
+        harness: &TestHarness,
+        organization_id: Uuid,
+        created_by: Uuid,
+    ) -> anyhow::Result<Content> {
+        let pool = &harness.pool;
+        let embed = Embed::upsert(
+            pool,
+            &UpsertEmbedInput {
+                id: None,
+                organization_id: Some(organization_id),
+                embed_type: Some(EmbedType::Poll),
+                name: Some("Embed".to_string()),
+                description: None,
+                attributes: Some(json!({})),
+            },
+            &created_by,
+        )
+        .await?;
+        let candidate_guide = CandidateGuide::upsert(
+            pool,
+            &UpsertCandidateGuideInput {
+                id: None,
+                name: Some("Guide".to_string()),
+                organization_id: Some(organization_id),
+                user_id: Some(created_by),
+                race_ids: None,
+                submissions_open_at: None,
+                submissions_close_at: None,
+            },
+        )
+        .await?;
+        let question = db::Question::upsert(
+            pool,
+            &UpsertQuestionInput {
+                id: None,
+                name: None,
+                prompt: Some("Why are you running?".to_string()),
+                response_char_limit: None,
+                response_placeholder_text: None,
+                allow_anonymous_responses: None,
+                embed_id: None,
+                candidate_guide_id: None,
+                issue_tag_ids: None,
+                translations: None,
+                should_translate: None,
+                organization_id: Some(organization_id),
+            },
+        )
+        .await?;
+        let question_submission_id = sqlx::query_scalar!(
+            "INSERT INTO question_submission (question_id, response) VALUES ($1, 'To fix the roads') RETURNING id",
+            question.id
+        )
+        .fetch_one(pool)
+        .await?;
+        let poll = db::Poll::upsert(
+            pool,
+            &UpsertPollInput {
+                id: None,
+                prompt: Some("Which issue matters most?".to_string()),
+                name: None,
+                allow_anonymous_responses: None,
+                allow_write_in_responses: None,
+                options: vec![],
+                organization_id: Some(organization_id),
+            },
+        )
+        .await?;
+        let conversation_id = sqlx::query_scalar!(
+            "INSERT INTO conversation (topic, organization_id) VALUES ('Transit', $1) RETURNING id",
+            organization_id
+        )
+        .fetch_one(pool)
+        .await?;
+        let statement_id = sqlx::query_scalar!(
+            "INSERT INTO statement (conversation_id, content) VALUES ($1, 'More buses') RETURNING id",
+            conversation_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Content {
+            embed_id: embed.id,
+            candidate_guide_id: candidate_guide.id,
+            question_id: question.id,
+            question_submission_id,
+            poll_id: poll.id,
+            conversation_id,
+            statement_id,
+        })
+    }
+
+    /// Every guarded mutation, run by a user of `organization_id` against `content`.
+    /// Upserts pass the caller's own organization and copies go to the caller's own
+    /// question in `own`, as an attacker would.
+    fn mutations(
+        content: &Content,
+        own: &Content,
+        organization_id: Uuid,
+    ) -> Vec<(&'static str, OrganizationResource, Uuid, serde_json::Value)> {
+        vec![
+            (
+                "mutation($input: UpsertEmbedInput!) { upsertEmbed(input: $input) { id } }",
+                OrganizationResource::Embed,
+                content.embed_id,
+                json!({ "input": { "id": content.embed_id, "organizationId": organization_id, "name": "Renamed" } }),
+            ),
+            (
+                "mutation($id: UUID!) { deleteEmbed(id: $id) { id } }",
+                OrganizationResource::Embed,
+                content.embed_id,
+                json!({ "id": content.embed_id }),
+            ),
+            (
+                "mutation($input: UpsertCandidateGuideInput!) { upsertCandidateGuide(input: $input) { id } }",
+                OrganizationResource::CandidateGuide,
+                content.candidate_guide_id,
+                json!({ "input": { "id": content.candidate_guide_id, "organizationId": organization_id, "name": "Renamed" } }),
+            ),
+            (
+                "mutation($id: ID!) { openAllCandidateGuideSubmissions(candidateGuideId: $id) }",
+                OrganizationResource::CandidateGuide,
+                content.candidate_guide_id,
+                json!({ "id": content.candidate_guide_id }),
+            ),
+            (
+                "mutation($id: ID!) { deleteCandidateGuide(id: $id) }",
+                OrganizationResource::CandidateGuide,
+                content.candidate_guide_id,
+                json!({ "id": content.candidate_guide_id }),
+            ),
+            (
+                "mutation($input: UpsertQuestionInput!) { upsertQuestion(input: $input) { id } }",
+                OrganizationResource::Question,
+                content.question_id,
+                json!({ "input": { "id": content.question_id, "organizationId": organization_id, "prompt": "Renamed" } }),
+            ),
+            (
+                "mutation($id: ID!) { deleteQuestion(id: $id) { id } }",
+                OrganizationResource::Question,
+                content.question_id,
+                json!({ "id": content.question_id }),
+            ),
+            (
+                "mutation($id: ID!, $target: ID!) { copyQuestionSubmission(questionSubmissionId: $id, targetQuestionId: $target) { id } }",
+                OrganizationResource::QuestionSubmission,
+                content.question_submission_id,
+                json!({ "id": content.question_submission_id, "target": own.question_id }),
+            ),
+            (
+                "mutation($input: UpsertPollInput!) { upsertPoll(input: $input) { id } }",
+                OrganizationResource::Poll,
+                content.poll_id,
+                json!({ "input": { "id": content.poll_id, "organizationId": organization_id, "prompt": "Renamed", "options": [] } }),
+            ),
+            (
+                "mutation($id: ID!) { deletePoll(id: $id) { id } }",
+                OrganizationResource::Poll,
+                content.poll_id,
+                json!({ "id": content.poll_id }),
+            ),
+            (
+                "mutation($id: ID!) { updateConversation(conversationId: $id, topic: \"Renamed\") { id } }",
+                OrganizationResource::Conversation,
+                content.conversation_id,
+                json!({ "id": content.conversation_id }),
+            ),
+            (
+                "mutation($id: ID!) { moderateStatement(statementId: $id, moderationStatus: REJECTED) { id } }",
+                OrganizationResource::Statement,
+                content.statement_id,
+                json!({ "id": content.statement_id }),
+            ),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_members_can_only_edit_their_own_organizations_content() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let org_a = harness.create_organization("Org A").await?;
+        let org_b = harness.create_organization("Org B").await?;
+        let owner_b = harness.create_user("owner-b@example.com", None).await?;
+        harness
+            .add_organization_member(org_b, owner_b, OrganizationRoleType::Owner)
+            .await?;
+
+        let roles = [
+            None,
+            Some(OrganizationRoleType::ReadOnly),
+            Some(OrganizationRoleType::Member),
+            Some(OrganizationRoleType::Admin),
+            Some(OrganizationRoleType::Owner),
+        ];
+        for (i, role) in roles.into_iter().enumerate() {
+            let user_id = harness
+                .create_user(&format!("user-{}@example.com", i), None)
+                .await?;
+            if let Some(role) = role {
+                harness
+                    .add_organization_member(org_a, user_id, role)
+                    .await?;
+            }
+            let can_edit =
+                role.is_some_and(|role| role as i32 >= OrganizationRoleType::Member as i32);
+            let mutation_count = mutations(&Content::default(), &Content::default(), org_a).len();
+
+            for index in 0..mutation_count {
+                // Fresh content for every call, since some of them delete it
+                let own = seed_content(&harness, org_a, owner_b).await?;
+                let other = seed_content(&harness, org_b, owner_b).await?;
+
+                let (query, _, _, variables) = mutations(&own, &own, org_a).remove(index);
+                let result = harness
+                    .execute_query::<serde_json::Value>(
+                        query,
+                        Some(Variables::from_json(variables)),
+                        Some(user_id),
+                        None,
+                    )
+                    .await;
+                assert_eq!(
+                    result.is_ok(),
+                    can_edit,
+                    "{:?} of org A running {} on org A content: {:?}",
+                    role,
+                    query,
+                    result
+                );
+
+                let (query, resource, id, variables) = mutations(&other, &own, org_a).remove(index);
+                let result = harness
+                    .execute_query::<serde_json::Value>(
+                        query,
+                        Some(Variables::from_json(variables)),
+                        Some(user_id),
+                        None,
+                    )
+                    .await;
+                assert!(
+                    result.is_err(),
+                    "{:?} of org A was able to run {} on org B content",
+                    role,
+                    query
+                );
+                assert_eq!(
+                    resource.organization_id(&harness.pool, id).await.unwrap(),
+                    Some(org_b),
+                    "{} changed org B content",
+                    query
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_content_cant_be_created_in_another_organization() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let org_a = harness.create_organization("Org A").await?;
+        let org_b = harness.create_organization("Org B").await?;
+        let member_a = harness.create_user("member-a@example.com", None).await?;
+        harness
+            .add_organization_member(org_a, member_a, OrganizationRoleType::Member)
+            .await?;
+
+        let create_conversation = r#"
+            mutation($input: CreateConversationInput!) {
+                createConversation(input: $input) { id }
+            }
+        "#;
+        for (organization_id, allowed) in [(org_a, true), (org_b, false)] {
+            let variables =
+                json!({ "input": { "topic": "Housing", "organizationId": organization_id } });
+            let result = harness
+                .execute_query::<serde_json::Value>(
+                    create_conversation,
+                    Some(Variables::from_json(variables)),
+                    Some(member_a),
+                    None,
+                )
+                .await;
+            assert_eq!(result.is_ok(), allowed, "{:?}", result);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_keys_can_only_run_embed_mutations() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let org = harness.create_organization("Org A").await?;
+        let owner = harness.create_user("owner@example.com", None).await?;
+        harness
+            .add_organization_member(org, owner, OrganizationRoleType::Owner)
+            .await?;
+
+        // Content embeds are polls, so only the first key can write them
+        for (embed_type, can_write_embeds) in
+            [(EmbedType::Poll, true), (EmbedType::CandidateGuide, false)]
+        {
+            let (api_key, _) = OrganizationApiKey::create(
+                &harness.pool,
+                &CreateOrganizationApiKeyInput {
+                    organization_id: org,
+                    name: "CMS sync".to_string(),
+                    scope: ApiKeyScope::Write,
+                    embed_types: Some(vec![embed_type]),
+                    expires_at: None,
+                },
+                owner,
+            )
+            .await?;
+
+            let mutation_count = mutations(&Content::default(), &Content::default(), org).len();
+            for index in 0..mutation_count {
+                let own = seed_content(&harness, org, owner).await?;
+                let (query, resource, _, variables) = mutations(&own, &own, org).remove(index);
+                let result = harness
+                    .execute_query_with_api_key::<serde_json::Value>(
+                        query,
+                        Some(Variables::from_json(variables)),
+                        api_key.clone(),
+                    )
+                    .await;
+                let allowed = can_write_embeds && resource == OrganizationResource::Embed;
+                assert_eq!(
+                    result.is_ok(),
+                    allowed,
+                    "{:?} write key running {}: {:?}",
+                    embed_type,
+                    query,
+                    result
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
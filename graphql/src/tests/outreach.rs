@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::{
+        models::{
+            candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
+            question::UpsertQuestionInput,
+        },
+        InsertPoliticianInput, Office, OrganizationRoleType, OutreachRecipient, PoliticalScope,
+        Politician, Race, UpsertOfficeInput, UpsertRaceInput,
+    };
+    use mailers::FileTransport;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        email::deliver_outbox,
+        outreach::{intake_form_url, send_outreach_reminders},
+        tests::harness::TestHarness,
+    };
+
+    const SEND_OUTREACH: &str = r#"
+        mutation($candidateGuideId: ID!) {
+            sendCandidateGuideOutreach(candidateGuideId: $candidateGuideId, input: {}) {
+                invitesSent
+            }
+        }
+    "#;
+
+    async fn create_candidate(
+        harness: &TestHarness,
+        race_id: Uuid,
+        first_name: &str,
+    ) -> anyhow::Result<Politician> {
+        let politician = Politician::insert(
+            &harness.pool,
+            &InsertPoliticianInput {
+                first_name: first_name.to_string(),
+                last_name: "Candidate".to_string(),
+                email: Some(format!(" {}@example.com ", first_name.to_lowercase())),
+                ..Default::default()
+            },
+        )
+        .await?;
+        sqlx::query!(
+            "INSERT INTO race_candidates (race_id, candidate_id) VALUES ($1, $2)",
+            race_id,
+            politician.id
+        )
+        .execute(&harness.pool)
+        .await?;
+        Ok(politician)
+    }
+
+    #[tokio::test]
+    async fn test_outreach_invites_reminders_and_response_rates() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let organization_id = harness.create_organization("Outreach Org").await?;
+        let member_id = harness.create_user("member@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, member_id, OrganizationRoleType::Member)
+            .await?;
+        let office = Office::upsert(
+            pool,
+            &UpsertOfficeInput {
+                title: Some("Mayor".to_string()),
+                political_scope: Some(PoliticalScope::Local),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let race = Race::upsert(
+            pool,
+            &UpsertRaceInput {
+                title: Some("Mayor of Springfield".to_string()),
+                office_id: Some(office.id),
+                ..Default::default()
+            },
+        )
+        .await?;
+        let guide = CandidateGuide::upsert(
+            pool,
+            &UpsertCandidateGuideInput {
+                id: None,
+                name: Some("Mayoral Guide".to_string()),
+                organization_id: Some(organization_id),
+                user_id: Some(member_id),
+                race_ids: Some(vec![race.id]),
+                submissions_open_at: None,
+                submissions_close_at: Some(chrono::Utc::now() + chrono::Duration::days(10)),
+            },
+        )
+        .await?;
+        let answering = create_candidate(&harness, race.id, "Ada").await?;
+        create_candidate(&harness, race.id, "Grace").await?;
+        let variables = Variables::from_json(json!({ "candidateGuideId": guide.id }));
+
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                SEND_OUTREACH,
+                Some(variables.clone()),
+                Some(member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(result["sendCandidateGuideOutreach"]["invitesSent"], 2);
+
+        // Candidates are only invited once
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                SEND_OUTREACH,
+                Some(variables.clone()),
+                Some(member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(result["sendCandidateGuideOutreach"]["invitesSent"], 0);
+
+        let dir = std::env::temp_dir().join(format!("populist-outreach-{}", Uuid::new_v4()));
+        let transport = FileTransport::new(&dir);
+        assert_eq!(deliver_outbox(pool, &transport).await?, 2);
+        let invite = transport
+            .messages()?
+            .into_iter()
+            .find(|message| message.to == "ada@example.com")
+            .unwrap();
+        assert_eq!(
+            invite.subject,
+            "Outreach Org candidate questionnaire: Mayor of Springfield"
+        );
+        let tracking_token = invite
+            .text
+            .split("/outreach/")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .unwrap()
+            .to_string();
+
+        OutreachRecipient::record_open(pool, &tracking_token).await?;
+        let recipient = OutreachRecipient::record_click(pool, &tracking_token)
+            .await?
+            .unwrap();
+        assert_eq!(recipient.politician_id, answering.id);
+        assert!(intake_form_url(&recipient).ends_with(&format!(
+            "token={}",
+            recipient.intake_token.clone().unwrap()
+        )));
+
+        let question = db::Question::upsert(
+            pool,
+            &UpsertQuestionInput {
+                id: None,
+                name: None,
+                prompt: Some("Why are you running?".to_string()),
+                response_char_limit: None,
+                response_placeholder_text: None,
+                allow_anonymous_responses: None,
+                embed_id: None,
+                candidate_guide_id: Some(guide.id),
+                issue_tag_ids: None,
+                translations: None,
+                should_translate: None,
+                organization_id: Some(organization_id),
+            },
+        )
+        .await?;
+        sqlx::query!(
+            "INSERT INTO question_submission (question_id, candidate_id, response) VALUES ($1, $2, 'To fix the roads')",
+            question.id,
+            answering.id
+        )
+        .execute(pool)
+        .await?;
+
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                query($candidateGuideId: ID!) {
+                    candidateGuideOutreach(candidateGuideId: $candidateGuideId) {
+                        raceStats { recipients delivered opened clicked submitted responseRate }
+                    }
+                }
+                "#,
+                Some(variables),
+                Some(member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(
+            result["candidateGuideOutreach"]["raceStats"][0],
+            json!({
+                "recipients": 2,
+                "delivered": 2,
+                "opened": 1,
+                "clicked": 1,
+                "submitted": 1,
+                "responseRate": 0.5
+            })
+        );
+
+        // Both reminders come due at once a day before the deadline, and only the
+        // candidate who hasn't answered gets one
+        sqlx::query!(
+            "UPDATE candidate_guide_outreach SET deadline = now() + interval '1 day' WHERE candidate_guide_id = $1",
+            guide.id
+        )
+        .execute(pool)
+        .await?;
+        assert_eq!(send_outreach_reminders(pool).await?, 1);
+        assert_eq!(send_outreach_reminders(pool).await?, 0);
+        assert_eq!(deliver_outbox(pool, &transport).await?, 1);
+        let messages = transport.messages()?;
+        let reminder = messages.last().unwrap();
+        assert_eq!(reminder.to, "grace@example.com");
+        assert!(reminder.subject.starts_with("Reminder: Outreach Org"));
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::{
+        models::{
+            candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
+            question::UpsertQuestionInput,
+        },
+        InsertPoliticianInput, OrganizationRoleType, Politician, QuestionSubmission, Revision,
+        RevisionEntityType, SubmissionStatus, UpdatePoliticianInput, UpsertQuestionSubmissionInput,
+    };
+    use serde_json::json;
+
+    use crate::tests::harness::TestHarness;
+
+    #[tokio::test]
+    async fn test_submission_revisions_can_be_compared_and_reverted() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let organization_id = harness.create_organization("Revision Org").await?;
+        let member_id = harness.create_user("editor@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, member_id, OrganizationRoleType::Member)
+            .await?;
+        let outsider_id = harness.create_user("outsider@example.com", None).await?;
+        let guide = CandidateGuide::upsert(
+            pool,
+            &UpsertCandidateGuideInput {
+                id: None,
+                name: Some("Guide".to_string()),
+                organization_id: Some(organization_id),
+                user_id: Some(member_id),
+                race_ids: None,
+                submissions_open_at: None,
+                submissions_close_at: None,
+            },
+        )
+        .await?;
+        let question = db::Question::upsert(
+            pool,
+            &UpsertQuestionInput {
+                id: None,
+                name: None,
+                prompt: Some("Why are you running?".to_string()),
+                response_char_limit: None,
+                response_placeholder_text: None,
+                allow_anonymous_responses: None,
+                embed_id: None,
+                candidate_guide_id: Some(guide.id),
+                issue_tag_ids: None,
+                translations: None,
+                should_translate: None,
+                organization_id: Some(organization_id),
+            },
+        )
+        .await?;
+        let candidate = Politician::insert(
+            pool,
+            &InsertPoliticianInput {
+                first_name: "Ada".to_string(),
+                last_name: "Candidate".to_string(),
+                biography: Some("Engineer".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let answer = |id: Option<uuid::Uuid>, response: &str| UpsertQuestionSubmissionInput {
+            id,
+            question_id: question.id,
+            candidate_id: Some(candidate.id),
+            respondent_id: None,
+            response: response.to_string(),
+            editorial: None,
+            sentiment: None,
+            translations: None,
+            should_translate: None,
+            copied_from_id: None,
+            status: None,
+        };
+        let submission = QuestionSubmission::upsert(pool, &answer(None, "Roads")).await?;
+        QuestionSubmission::upsert(pool, &answer(Some(submission.id), "Roads and bridges")).await?;
+        QuestionSubmission::review(
+            pool,
+            submission.id,
+            Some(SubmissionStatus::InReview),
+            Some("Trimmed".to_string()),
+            Some(member_id),
+        )
+        .await?;
+        // Status changes alone aren't a new revision
+        QuestionSubmission::review(
+            pool,
+            submission.id,
+            Some(SubmissionStatus::Approved),
+            None,
+            Some(member_id),
+        )
+        .await?;
+
+        let query = r#"
+            query($id: ID!) {
+                questionById(id: $id) {
+                    submissions {
+                        revisions { revisionNumber data }
+                        revisionDiff(fromRevision: 1, toRevision: 3)
+                    }
+                }
+            }
+        "#;
+        let variables = Variables::from_json(json!({ "id": question.id }));
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                query,
+                Some(variables.clone()),
+                Some(member_id),
+                None,
+            )
+            .await?;
+        let submission_result = &result["questionById"]["submissions"][0];
+        let revisions = submission_result["revisions"].as_array().unwrap();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!(revisions[0]["revisionNumber"], 3);
+        assert_eq!(revisions[2]["data"]["response"], "Roads");
+        assert_eq!(
+            submission_result["revisionDiff"],
+            json!({
+                "response": { "old": "Roads", "new": "Roads and bridges" },
+                "editorial": { "old": null, "new": "Trimmed" },
+            })
+        );
+
+        // Other organizations can't read the history
+        assert!(harness
+            .execute_query::<serde_json::Value>(query, Some(variables), Some(outsider_id), None)
+            .await
+            .is_err());
+
+        Revision::revert(
+            pool,
+            RevisionEntityType::QuestionSubmission,
+            submission.id,
+            1,
+        )
+        .await?;
+        let reverted = QuestionSubmission::find_by_id(pool, submission.id).await?;
+        assert_eq!(reverted.response, "Roads");
+        assert_eq!(reverted.editorial, None);
+        // Reverting keeps the review status
+        assert_eq!(reverted.status, SubmissionStatus::Approved);
+        let revisions =
+            Revision::list(pool, RevisionEntityType::QuestionSubmission, submission.id).await?;
+        assert_eq!(revisions.len(), 4);
+        assert_eq!(revisions[0].data, revisions[3].data);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_politician_profile_revisions() -> anyhow::Result<()> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let politician = Politician::insert(
+            pool,
+            &InsertPoliticianInput {
+                first_name: "Grace".to_string(),
+                last_name: "Candidate".to_string(),
+                biography: Some("Teacher".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Politician::update(
+            pool,
+            &UpdatePoliticianInput {
+                id: politician.id,
+                biography: Some("Teacher and school board member".to_string()),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let diff =
+            Revision::diff(pool, RevisionEntityType::Politician, politician.id, 1, 2).await?;
+        assert_eq!(
+            diff,
+            json!({ "biography": { "old": "Teacher", "new": "Teacher and school board member" } })
+        );
+
+        Revision::revert(pool, RevisionEntityType::Politician, politician.id, 1).await?;
+        let reverted = Politician::find_by_id(pool, politician.id).await?;
+        assert_eq!(reverted.biography.as_deref(), Some("Teacher"));
+        assert_eq!(
+            Revision::list(pool, RevisionEntityType::Politician, politician.id)
+                .await?
+                .len(),
+            3
+        );
+        assert!(
+            Revision::revert(pool, RevisionEntityType::Politician, politician.id, 9)
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+}
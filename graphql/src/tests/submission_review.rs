@@ -0,0 +1,383 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Variables;
+    use db::{
+        models::{
+            candidate_guide::{CandidateGuide, UpsertCandidateGuideInput},
+            question::UpsertQuestionInput,
+        },
+        InsertPoliticianInput, OrganizationRoleType, Politician,
+    };
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::tests::harness::TestHarness;
+
+    const UPSERT_SUBMISSION: &str = r#"
+        mutation($input: UpsertQuestionSubmissionInput!, $intakeToken: String) {
+            upsertQuestionSubmission(questionSubmissionInput: $input, intakeToken: $intakeToken) {
+                id
+                status
+            }
+        }
+    "#;
+
+    const REVIEW_SUBMISSION: &str = r#"
+        mutation($id: ID!, $input: ReviewQuestionSubmissionInput!) {
+            reviewQuestionSubmission(questionSubmissionId: $id, input: $input) {
+                status
+                editorial
+            }
+        }
+    "#;
+
+    struct Setup {
+        harness: TestHarness,
+        member_id: Uuid,
+        guide_id: Uuid,
+        question_id: Uuid,
+        candidate: Politician,
+    }
+
+    async fn setup() -> anyhow::Result<Setup> {
+        let harness = TestHarness::new().await?;
+        let pool = &harness.pool;
+        let organization_id = harness.create_organization("Review Org").await?;
+        let member_id = harness.create_user("editor@example.com", None).await?;
+        harness
+            .add_organization_member(organization_id, member_id, OrganizationRoleType::Member)
+            .await?;
+        let guide = CandidateGuide::upsert(
+            pool,
+            &UpsertCandidateGuideInput {
+                id: None,
+                name: Some("Review Guide".to_string()),
+                organization_id: Some(organization_id),
+                user_id: Some(member_id),
+                race_ids: None,
+                submissions_open_at: None,
+                submissions_close_at: Some(chrono::Utc::now() + chrono::Duration::days(10)),
+            },
+        )
+        .await?;
+        let question = db::Question::upsert(
+            pool,
+            &UpsertQuestionInput {
+                id: None,
+                name: None,
+                prompt: Some("Why are you running?".to_string()),
+                response_char_limit: None,
+                response_placeholder_text: None,
+                allow_anonymous_responses: None,
+                embed_id: None,
+                candidate_guide_id: Some(guide.id),
+                issue_tag_ids: None,
+                translations: None,
+                should_translate: None,
+                organization_id: Some(organization_id),
+            },
+        )
+        .await?;
+        let candidate = Politician::insert(
+            pool,
+            &InsertPoliticianInput {
+                first_name: "Ada".to_string(),
+                last_name: "Candidate".to_string(),
+                ..Default::default()
+            },
+        )
+        .await?;
+        sqlx::query!(
+            "UPDATE politician SET intake_token = 'candidate-token' WHERE id = $1",
+            candidate.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(Setup {
+            harness,
+            member_id,
+            guide_id: guide.id,
+            question_id: question.id,
+            candidate,
+        })
+    }
+
+    fn submission_variables(
+        setup: &Setup,
+        id: Option<&str>,
+        intake_token: &str,
+        response: &str,
+    ) -> Variables {
+        Variables::from_json(json!({
+            "input": {
+                "id": id,
+                "questionId": setup.question_id,
+                "candidateId": setup.candidate.id,
+                "response": response,
+            },
+            "intakeToken": intake_token,
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_submissions_move_through_review() -> anyhow::Result<()> {
+        let setup = setup().await?;
+        let harness = &setup.harness;
+
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(submission_variables(
+                    &setup,
+                    None,
+                    "candidate-token",
+                    "To fix the roads",
+                )),
+                None,
+                None,
+            )
+            .await?;
+        assert_eq!(result["upsertQuestionSubmission"]["status"], "SUBMITTED");
+        let id = result["upsertQuestionSubmission"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        // Someone else's token can't write the candidate's answers
+        let outsider_id = harness.create_user("outsider@example.com", None).await?;
+        assert!(harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(submission_variables(
+                    &setup,
+                    Some(&id),
+                    "wrong-token",
+                    "Something else"
+                )),
+                Some(outsider_id),
+                None,
+            )
+            .await
+            .is_err());
+
+        let review = |status: &str, editorial: Option<&str>| {
+            Variables::from_json(json!({
+                "id": id,
+                "input": { "status": status, "editorial": editorial },
+            }))
+        };
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                REVIEW_SUBMISSION,
+                Some(review("IN_REVIEW", Some("Trim to 100 words"))),
+                Some(setup.member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(result["reviewQuestionSubmission"]["status"], "IN_REVIEW");
+
+        // The candidate can't change an answer that's being reviewed
+        let locked = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(submission_variables(
+                    &setup,
+                    Some(&id),
+                    "candidate-token",
+                    "To fix the roads and bridges",
+                )),
+                None,
+                None,
+            )
+            .await;
+        assert!(locked.unwrap_err().to_string().contains("being reviewed"));
+
+        // Answers have to be approved before they're published
+        assert!(harness
+            .execute_query::<serde_json::Value>(
+                REVIEW_SUBMISSION,
+                Some(review("PUBLISHED", None)),
+                Some(setup.member_id),
+                None,
+            )
+            .await
+            .is_err());
+        harness
+            .execute_query::<serde_json::Value>(
+                REVIEW_SUBMISSION,
+                Some(review("APPROVED", Some("Trimmed to 100 words"))),
+                Some(setup.member_id),
+                None,
+            )
+            .await?;
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                mutation($candidateGuideId: ID!) {
+                    publishApprovedCandidateGuideSubmissions(candidateGuideId: $candidateGuideId)
+                }
+                "#,
+                Some(Variables::from_json(
+                    json!({ "candidateGuideId": setup.guide_id }),
+                )),
+                Some(setup.member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(result["publishApprovedCandidateGuideSubmissions"], 1);
+
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                r#"
+                query($id: ID!) {
+                    questionSubmissionReviews(questionSubmissionId: $id) {
+                        fromStatus
+                        toStatus
+                        editorial
+                        reviewerId
+                    }
+                }
+                "#,
+                Some(Variables::from_json(json!({ "id": id }))),
+                Some(setup.member_id),
+                None,
+            )
+            .await?;
+        let reviews = result["questionSubmissionReviews"].as_array().unwrap();
+        assert_eq!(reviews.len(), 3);
+        assert_eq!(reviews[0]["editorial"], "Trim to 100 words");
+        assert_eq!(reviews[1]["toStatus"], "APPROVED");
+        assert_eq!(reviews[1]["editorial"], "Trimmed to 100 words");
+        assert_eq!(reviews[2]["toStatus"], "PUBLISHED");
+        assert_eq!(reviews[0]["reviewerId"], setup.member_id.to_string());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_intake_token_is_refused_after_close() -> anyhow::Result<()> {
+        let setup = setup().await?;
+        let harness = &setup.harness;
+        sqlx::query!(
+            "UPDATE candidate_guide SET submissions_close_at = now() - interval '1 minute' WHERE id = $1",
+            setup.guide_id
+        )
+        .execute(&harness.pool)
+        .await?;
+
+        let closed = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(submission_variables(
+                    &setup,
+                    None,
+                    "candidate-token",
+                    "Too late",
+                )),
+                None,
+                None,
+            )
+            .await;
+        assert!(closed.unwrap_err().to_string().contains("closed"));
+
+        // Editors can still fix up answers after the guide closes
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(Variables::from_json(json!({
+                    "input": {
+                        "questionId": setup.question_id,
+                        "candidateId": setup.candidate.id,
+                        "response": "Entered by the editor",
+                    },
+                }))),
+                Some(setup.member_id),
+                None,
+            )
+            .await?;
+        assert_eq!(result["upsertQuestionSubmission"]["status"], "SUBMITTED");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_answers_without_candidate_cant_overwrite_candidate_answers() -> anyhow::Result<()>
+    {
+        let setup = setup().await?;
+        let harness = &setup.harness;
+        let result = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(submission_variables(
+                    &setup,
+                    None,
+                    "candidate-token",
+                    "To fix the roads",
+                )),
+                None,
+                None,
+            )
+            .await?;
+        let id = result["upsertQuestionSubmission"]["id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        // Leaving out `candidateId` doesn't skip the token check on someone else's answer
+        let outsider_id = harness.create_user("outsider@example.com", None).await?;
+        for input in [
+            json!({
+                "id": id,
+                "questionId": setup.question_id,
+                "response": "Rewritten",
+            }),
+            json!({
+                "id": id,
+                "questionId": setup.question_id,
+                "response": "Rewritten and published",
+                "editorial": "Looks good",
+                "status": "PUBLISHED",
+            }),
+        ] {
+            let rewrite = harness
+                .execute_query::<serde_json::Value>(
+                    UPSERT_SUBMISSION,
+                    Some(Variables::from_json(json!({ "input": input }))),
+                    Some(outsider_id),
+                    None,
+                )
+                .await;
+            assert!(rewrite.is_err());
+        }
+
+        // New answers that aren't from a candidate can't be published by their author either
+        let published = harness
+            .execute_query::<serde_json::Value>(
+                UPSERT_SUBMISSION,
+                Some(Variables::from_json(json!({
+                    "input": {
+                        "questionId": setup.question_id,
+                        "response": "Published without review",
+                        "status": "PUBLISHED",
+                    },
+                }))),
+                Some(outsider_id),
+                None,
+            )
+            .await;
+        assert!(published.is_err());
+
+        let answer = sqlx::query!(
+            r#"SELECT response, editorial, status::text AS "status!" FROM question_submission WHERE id = $1"#,
+            Uuid::parse_str(&id)?
+        )
+        .fetch_one(&harness.pool)
+        .await?;
+        assert_eq!(answer.response, "To fix the roads");
+        assert_eq!(answer.editorial, None);
+        assert_eq!(answer.status, "submitted");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use async_graphql::{SimpleObject, ID};
+use db::{AuditEntityType, AuditEvent, AuditOperation, DateTime};
+use serde_json::Value as JSON;
+
+use crate::is_admin;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct AuditEventResult {
+    id: ID,
+    actor_user_id: Option<ID>,
+    actor_api_key_id: Option<ID>,
+    organization_id: Option<ID>,
+    /// Missing for mutations without a single target record
+    entity_type: Option<AuditEntityType>,
+    entity_id: Option<ID>,
+    /// Missing when the mutation didn't change the record's columns
+    operation: Option<AuditOperation>,
+    mutation: String,
+    /// Changed columns as `{"column": {"old": ..., "new": ...}}`
+    changes: JSON,
+    created_at: DateTime,
+}
+
+impl From<AuditEvent> for AuditEventResult {
+    fn from(e: AuditEvent) -> Self {
+        Self {
+            id: e.id.into(),
+            actor_user_id: e.actor_user_id.map(ID::from),
+            actor_api_key_id: e.actor_api_key_id.map(ID::from),
+            organization_id: e.organization_id.map(ID::from),
+            entity_type: e.entity_type,
+            entity_id: e.entity_id.map(ID::from),
+            operation: e.operation,
+            mutation: e.mutation,
+            changes: e.changes,
+            created_at: e.created_at,
+        }
+    }
+}
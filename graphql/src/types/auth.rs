@@ -1,7 +1,7 @@
 use crate::{context::ApiContext, is_admin, Error};
 use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
 use auth::AccessTokenClaims;
-use db::{OrganizationRole, SystemRoleType, User, UserWithProfile};
+use db::{DateTime, OrganizationRole, SystemRoleType, User, UserSession, UserWithProfile};
 use jsonwebtoken::TokenData;
 
 use super::UserResult;
@@ -35,6 +35,32 @@ pub struct LoginResult {
     pub user_id: ID,
 }
 
+/// A device the user is signed in on
+#[derive(SimpleObject)]
+#[graphql(visible = "is_admin")]
+pub struct UserSessionResult {
+    pub id: ID,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime,
+    pub last_used_at: DateTime,
+    /// Whether this is the session making the request
+    pub is_current: bool,
+}
+
+impl UserSessionResult {
+    pub fn new(session: UserSession, current_session_id: Option<uuid::Uuid>) -> Self {
+        Self {
+            id: session.id.into(),
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+            is_current: current_session_id == Some(session.id),
+        }
+    }
+}
+
 #[ComplexObject]
 impl AuthTokenResult {
     async fn user_profile(&self, ctx: &Context<'_>) -> Result<UserResult> {
@@ -13,6 +13,8 @@ pub struct CandidateGuideResult {
     name: Option<String>,
     submissions_open_at: Option<chrono::DateTime<chrono::Utc>>,
     submissions_close_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether candidates can still submit and edit their answers
+    accepting_submissions: bool,
     created_at: chrono::DateTime<chrono::Utc>,
     updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -35,6 +37,7 @@ impl CandidateGuideResult {
             FROM embed
             WHERE embed_type = 'candidate_guide' 
             AND attributes->>'candidateGuideId' = $1
+            AND deleted_at IS NULL
         "#,
             self.id.as_str()
         )
@@ -62,6 +65,7 @@ impl CandidateGuideResult {
                 FROM embed
             WHERE embed_type = 'candidate_guide' 
             AND attributes->>'candidateGuideId' = $1
+            AND deleted_at IS NULL
         "#,
             self.id.as_str()
         )
@@ -141,12 +145,14 @@ impl CandidateGuideResult {
 
 impl From<CandidateGuide> for CandidateGuideResult {
     fn from(c: CandidateGuide) -> Self {
+        let accepting_submissions = c.is_accepting_submissions(chrono::Utc::now());
         Self {
             id: ID::from(c.id),
             organization_id: ID::from(c.organization_id),
             name: c.name,
             submissions_open_at: c.submissions_open_at,
             submissions_close_at: c.submissions_close_at,
+            accepting_submissions,
             created_at: c.created_at,
             updated_at: c.updated_at,
         }
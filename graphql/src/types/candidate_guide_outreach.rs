@@ -0,0 +1,117 @@
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use db::{CandidateGuideOutreach, DateTime, OutreachRaceStats, OutreachRecipient};
+
+use crate::context::ApiContext;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(complex)]
+pub struct CandidateGuideOutreachResult {
+    id: ID,
+    candidate_guide_id: ID,
+    deadline: DateTime,
+    /// Days before the deadline that candidates who haven't responded are reminded
+    reminder_days: Vec<i32>,
+    created_at: DateTime,
+    updated_at: DateTime,
+    #[graphql(skip)]
+    outreach: CandidateGuideOutreach,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct OutreachRecipientResult {
+    id: ID,
+    race_id: ID,
+    race_title: String,
+    politician_id: ID,
+    candidate_name: String,
+    email: String,
+    reminders_sent: i32,
+    last_reminded_at: Option<DateTime>,
+    delivered_at: Option<DateTime>,
+    opened_at: Option<DateTime>,
+    clicked_at: Option<DateTime>,
+    submitted_at: Option<DateTime>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct OutreachRaceStatsResult {
+    race_id: ID,
+    race_title: String,
+    recipients: i64,
+    delivered: i64,
+    opened: i64,
+    clicked: i64,
+    submitted: i64,
+    /// Share of emailed candidates who submitted answers, from 0 to 1
+    response_rate: f64,
+}
+
+#[ComplexObject]
+impl CandidateGuideOutreachResult {
+    async fn recipients(&self, ctx: &Context<'_>) -> Result<Vec<OutreachRecipientResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let recipients = self.outreach.recipients(&db_pool).await?;
+        Ok(recipients
+            .into_iter()
+            .map(OutreachRecipientResult::from)
+            .collect())
+    }
+
+    /// Delivery, engagement and response counts for each race
+    async fn race_stats(&self, ctx: &Context<'_>) -> Result<Vec<OutreachRaceStatsResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let stats = self.outreach.stats_by_race(&db_pool).await?;
+        Ok(stats
+            .into_iter()
+            .map(OutreachRaceStatsResult::from)
+            .collect())
+    }
+}
+
+impl From<CandidateGuideOutreach> for CandidateGuideOutreachResult {
+    fn from(o: CandidateGuideOutreach) -> Self {
+        Self {
+            id: o.id.into(),
+            candidate_guide_id: o.candidate_guide_id.into(),
+            deadline: o.deadline,
+            reminder_days: o.reminder_days.clone(),
+            created_at: o.created_at,
+            updated_at: o.updated_at,
+            outreach: o,
+        }
+    }
+}
+
+impl From<OutreachRecipient> for OutreachRecipientResult {
+    fn from(r: OutreachRecipient) -> Self {
+        Self {
+            id: r.id.into(),
+            race_id: r.race_id.into(),
+            race_title: r.race_title,
+            politician_id: r.politician_id.into(),
+            candidate_name: r.candidate_name,
+            email: r.email,
+            reminders_sent: r.reminders_sent,
+            last_reminded_at: r.last_reminded_at,
+            delivered_at: r.delivered_at,
+            opened_at: r.opened_at,
+            clicked_at: r.clicked_at,
+            submitted_at: r.submitted_at,
+        }
+    }
+}
+
+impl From<OutreachRaceStats> for OutreachRaceStatsResult {
+    fn from(s: OutreachRaceStats) -> Self {
+        Self {
+            race_id: s.race_id.into(),
+            response_rate: s.response_rate(),
+            race_title: s.race_title,
+            recipients: s.recipients,
+            delivered: s.delivered,
+            opened: s.opened,
+            clicked: s.clicked,
+            submitted: s.submitted,
+        }
+    }
+}
@@ -1,26 +1,24 @@
-use std::collections::{HashMap, HashSet};
-
-use async_graphql::{ComplexObject, Context, Error, Result, SimpleObject, ID};
-use async_openai::{
-    types::{ChatCompletionRequestMessage, CreateChatCompletionRequestArgs, Role},
-    Client,
-};
+use async_graphql::{ComplexObject, Context, Error, Guard, Result, SimpleObject, ID};
 use auth::AccessTokenClaims;
 use chrono::{DateTime, Utc};
 use db::{
-    models::conversation::{Conversation, StatementView, StatementVote},
-    ArgumentPosition, EmbedType, StatementModerationStatus, UserWithProfile,
+    models::conversation::Conversation, ArgumentPosition, ConversationAnalysisSnapshot,
+    ConversationModerationMode, EmbedType, ModerationQueueEntry, OrganizationRoleType,
+    ScreeningFlag, StatementModerationStatus, UserWithProfile,
 };
 use jsonwebtoken::TokenData;
 
-use ndarray::{Array2, ArrayView1, Axis};
-
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::{cache::Cache, context::ApiContext, SessionData};
+use crate::{
+    cache::{conversation_tag, Cache},
+    context::ApiContext,
+    conversation_export::ConversationExportFormat,
+    guard::OrganizationGuard,
+    is_admin, SessionData,
+};
 
 use super::{EmbedResult, UserResult};
 
@@ -43,13 +41,21 @@ pub struct ConversationResult {
     pub id: ID,
     topic: String,
     description: Option<String>,
+    /// Whether new statements wait for a moderator before they're shown
+    moderation_mode: ConversationModerationMode,
+    /// Whether new statements are also screened by an LLM
+    llm_screening: bool,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
+    #[graphql(skip)]
+    organization_id: Uuid,
+    #[graphql(skip)]
+    blocked_words: Vec<String>,
 }
 
 #[derive(SimpleObject)]
 #[graphql(complex)]
-struct StatementResult {
+pub struct StatementResult {
     id: ID,
     conversation_id: ID,
     author_id: Option<ID>,
@@ -62,6 +68,47 @@ struct StatementResult {
     moderation_status: StatementModerationStatus,
 }
 
+/// A statement waiting in, or already through, an organization's moderation queue
+#[derive(SimpleObject)]
+pub struct ModerationQueueEntryResult {
+    id: ID,
+    conversation_id: ID,
+    conversation_topic: String,
+    author_id: Option<ID>,
+    content: String,
+    moderation_status: StatementModerationStatus,
+    /// Why the pre-screen held the statement, empty when nothing matched
+    screening_flags: Vec<ScreeningFlag>,
+    moderated_by: Option<ID>,
+    moderated_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+impl From<ModerationQueueEntry> for ModerationQueueEntryResult {
+    fn from(entry: ModerationQueueEntry) -> Self {
+        Self {
+            screening_flags: entry.flags(),
+            id: ID::from(entry.id),
+            conversation_id: ID::from(entry.conversation_id),
+            conversation_topic: entry.conversation_topic,
+            author_id: entry.author_id.map(ID::from),
+            content: entry.content,
+            moderation_status: entry.moderation_status,
+            moderated_by: entry.moderated_by.map(ID::from),
+            moderated_at: entry.moderated_at,
+            created_at: entry.created_at,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct ConversationExportResult {
+    /// Signed link to download the export
+    pub url: String,
+    pub format: ConversationExportFormat,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(SimpleObject)]
 struct ConversationStats {
     total_participants: i64,
@@ -90,59 +137,128 @@ struct VoteDistributionBucket {
     percentage: f64,        // What percentage of total participants this represents
 }
 
+impl ConversationResult {
+    async fn analysis_snapshot(
+        &self,
+        ctx: &Context<'_>,
+    ) -> Result<Option<ConversationAnalysisSnapshot>> {
+        let conversation_id = Uuid::parse_str(&self.id)?;
+        let cache_key = format!("conversation:{}:analysis_snapshot", conversation_id);
+        let cache = ctx.data::<Cache>()?;
+        if let Some(cached) = cache.get(&cache_key).await {
+            if let Ok(snapshot) = serde_json::from_value(cached) {
+                return Ok(Some(snapshot));
+            }
+        }
+
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let snapshot = ConversationAnalysisSnapshot::latest(&db_pool, conversation_id).await?;
+        // Tagged so it's dropped as soon as a newer snapshot is stored
+        if let Some(snapshot) = &snapshot {
+            cache
+                .set(
+                    &cache_key,
+                    serde_json::to_value(snapshot)?,
+                    &[conversation_tag(conversation_id)],
+                )
+                .await;
+        }
+        Ok(snapshot)
+    }
+}
+
 impl From<Conversation> for ConversationResult {
     fn from(conversation: Conversation) -> Self {
         Self {
             id: ID(conversation.id.to_string()),
             topic: conversation.topic,
             description: conversation.description,
+            moderation_mode: conversation.moderation_mode,
+            llm_screening: conversation.llm_screening,
             created_at: conversation.created_at,
             updated_at: conversation.updated_at,
+            organization_id: conversation.organization_id,
+            blocked_words: conversation.blocked_words,
         }
     }
 }
 
 #[derive(SimpleObject, Clone, Serialize, Deserialize)]
-struct OpinionScore {
-    id: String,
-    content: String,
-    score: f64,
-    total_votes: i32,
-    support_votes: i32,
-    oppose_votes: i32,
-    neutral_votes: i32,
-    total_views: i32,
-    non_voting_views: i32,
+pub struct OpinionScore {
+    pub id: String,
+    pub content: String,
+    pub score: f64,
+    pub total_votes: i32,
+    pub support_votes: i32,
+    pub oppose_votes: i32,
+    pub neutral_votes: i32,
+    pub total_views: i32,
+    pub non_voting_views: i32,
 }
 
 #[derive(SimpleObject, Serialize, Deserialize)]
-struct OpinionAnalysis {
-    overview: Option<String>,
-    consensus_opinions: Vec<OpinionScore>,
-    divisive_opinions: Vec<OpinionScore>,
+pub struct OpinionAnalysis {
+    pub overview: Option<String>,
+    pub consensus_opinions: Vec<OpinionScore>,
+    pub divisive_opinions: Vec<OpinionScore>,
 }
 
 #[derive(SimpleObject, Debug, Serialize, Deserialize)]
-struct OpinionGroup {
-    id: ID,
-    users: Vec<ID>, // Using String to represent UUIDs
-    characteristic_votes: Vec<CharacteristicVote>,
-    summary: String,
+pub struct OpinionGroup {
+    pub id: ID,
+    pub users: Vec<ID>, // Using String to represent UUIDs
+    pub characteristic_votes: Vec<CharacteristicVote>,
+    pub summary: String,
+}
+
+/// Participants placed on a 2D map of how they voted, with the group each belongs to
+#[derive(SimpleObject, Debug, Serialize, Deserialize)]
+pub struct OpinionMapResult {
+    pub participants: Vec<OpinionMapParticipant>,
+    pub groups: Vec<OpinionMapGroup>,
+    /// Share of the vote variance captured by the x and y axes
+    pub explained_variance: Vec<f64>,
+}
+
+#[derive(SimpleObject, Debug, Serialize, Deserialize)]
+pub struct OpinionMapParticipant {
+    pub user_id: Option<ID>,
+    pub session_id: Option<ID>,
+    pub x: f64,
+    pub y: f64,
+    pub group_id: ID,
+}
+
+#[derive(SimpleObject, Debug, Serialize, Deserialize)]
+pub struct OpinionMapGroup {
+    pub id: ID,
+    /// Centroid of the group on the map
+    pub x: f64,
+    pub y: f64,
+    pub size: i32,
 }
 
 #[derive(SimpleObject, Debug, Serialize, Deserialize)]
 #[graphql(complex)]
-struct CharacteristicVote {
-    statement_id: ID,
-    mean_sentiment: f64,
-    consensus_level: f64,
-    significance_level: f64,
+pub struct CharacteristicVote {
+    pub statement_id: ID,
+    pub mean_sentiment: f64,
+    pub consensus_level: f64,
+    pub significance_level: f64,
 }
 
 #[ComplexObject]
 impl CharacteristicVote {
     async fn statement(&self, ctx: &Context<'_>) -> async_graphql::Result<StatementResult> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        StatementResult::find_by_id(&db_pool, Uuid::parse_str(&self.statement_id.to_string())?)
+            .await
+    }
+}
+
+impl StatementResult {
+    /// Loads a single statement along with its current vote tallies
+    pub async fn find_by_id(db_pool: &PgPool, id: Uuid) -> async_graphql::Result<Self> {
         let statement = sqlx::query!(
             r#"
             SELECT 
@@ -161,9 +277,9 @@ impl CharacteristicVote {
             WHERE s.id = $1
             GROUP BY s.id
             "#,
-            Uuid::parse_str(&self.statement_id.to_string())?
+            id
         )
-        .fetch_one(&db_pool)
+        .fetch_one(db_pool)
         .await?;
 
         Ok(StatementResult {
@@ -183,6 +299,19 @@ impl CharacteristicVote {
 
 #[ComplexObject]
 impl ConversationResult {
+    /// Words that hold a statement for review on top of the built in lists. Only members of
+    /// the conversation's organization can see them.
+    #[graphql(visible = "is_admin")]
+    async fn blocked_words(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        OrganizationGuard::new(
+            &ID::from(self.organization_id),
+            &OrganizationRoleType::Member,
+        )
+        .check(ctx)
+        .await?;
+        Ok(self.blocked_words.clone())
+    }
+
     async fn statements(
         &self,
         ctx: &Context<'_>,
@@ -627,213 +756,60 @@ impl ConversationResult {
             .collect())
     }
 
-    async fn opinion_analysis(&self, ctx: &Context<'_>, limit: i32) -> Result<OpinionAnalysis> {
-        let cache_key = format!("conversation:{}:opinion_analysis", *self.id);
-        let cache = ctx.data::<Cache<String, serde_json::Value>>().unwrap();
-        if let Some(cached) = cache.get(&cache_key) {
-            let data: OpinionAnalysis = serde_json::from_value(cached.clone())
-                .map_err(|e| async_graphql::Error::new(e.to_string()))?;
-            return Ok(data);
-        }
-
-        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-        let statements_with_votes = fetch_statements_with_votes(&db_pool, &self.id).await?;
-
-        // Process statements once to get both consensus and divisive opinions
-        let mut consensus_statements: Vec<(StatementWithMeta, f64)> = Vec::new();
-        let mut divisive_statements: Vec<(StatementWithMeta, f64)> = Vec::new();
-
-        for statement in statements_with_votes
-            .into_iter()
-            .filter(|s| !s.votes.is_empty())
-        {
-            let vote_counts = count_votes(&statement.votes);
-            let total_votes = statement.votes.len() as f64;
-
-            // Only add to divisive if there's actually a mix of votes
-            let has_vote_variety = vote_counts.values().filter(|&&count| count > 0).count() > 1;
-
-            let consensus_score = calculate_consensus_score(&vote_counts, total_votes);
-            consensus_statements.push((statement.clone(), consensus_score));
-
-            if has_vote_variety {
-                let divisiveness_score = calculate_divisiveness_score(&vote_counts, total_votes);
-                divisive_statements.push((statement, divisiveness_score));
-            }
-        }
-
-        // Sort and truncate both lists
-        consensus_statements.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-        divisive_statements.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-        let consensus_opinions: Vec<OpinionScore> = consensus_statements
-            .into_iter()
-            .take(limit as usize)
-            .map(|(statement, score)| {
-                let counts = count_votes(&statement.votes);
-                // Get unique voting sessions
-                let voting_sessions: HashSet<_> = statement
-                    .votes
-                    .iter()
-                    .filter_map(|v| v.session_id.as_ref())
-                    .collect();
-
-                // Get unique viewing sessions
-                let viewing_sessions: HashSet<_> =
-                    statement.views.iter().map(|v| &v.session_id).collect();
-
-                // Calculate non-voting views
-                let non_voting_views = viewing_sessions.difference(&voting_sessions).count();
-                OpinionScore {
-                    id: statement.id.to_string(),
-                    content: statement.content,
-                    score,
-                    total_votes: statement.votes.len() as i32,
-                    support_votes: counts.get(&ArgumentPosition::Support).copied().unwrap_or(0),
-                    oppose_votes: counts.get(&ArgumentPosition::Oppose).copied().unwrap_or(0),
-                    neutral_votes: counts.get(&ArgumentPosition::Neutral).copied().unwrap_or(0),
-                    total_views: viewing_sessions.len() as i32,
-                    non_voting_views: non_voting_views as i32,
-                }
-            })
-            .collect();
-
-        let divisive_opinions: Vec<OpinionScore> = divisive_statements
-            .into_iter()
-            .take(limit as usize)
-            .map(|(statement, score)| {
-                let counts = count_votes(&statement.votes);
-                // Get unique voting sessions
-                let voting_sessions: HashSet<_> = statement
-                    .votes
-                    .iter()
-                    .filter_map(|v| v.session_id.as_ref())
-                    .collect();
-
-                // Get unique viewing sessions
-                let viewing_sessions: HashSet<_> =
-                    statement.views.iter().map(|v| &v.session_id).collect();
-
-                // Calculate non-voting views
-                let non_voting_views = viewing_sessions.difference(&voting_sessions).count();
-                OpinionScore {
-                    id: statement.id.to_string(),
-                    content: statement.content,
-                    score,
-                    total_votes: statement.votes.len() as i32,
-                    support_votes: counts.get(&ArgumentPosition::Support).copied().unwrap_or(0),
-                    oppose_votes: counts.get(&ArgumentPosition::Oppose).copied().unwrap_or(0),
-                    neutral_votes: counts.get(&ArgumentPosition::Neutral).copied().unwrap_or(0),
-                    total_views: viewing_sessions.len() as i32,
-                    non_voting_views: non_voting_views as i32,
-                }
-            })
-            .collect();
-
-        let overview =
-            match generate_opinion_summary(consensus_opinions.clone(), divisive_opinions.clone())
-                .await
-            {
-                Ok(summary) => Some(summary),
-                Err(_) => None,
-            };
+    /// When the opinion analysis, groups and map were last computed. They're recomputed in
+    /// the background as votes come in, and are empty until the first analysis.
+    async fn analyzed_at(&self, ctx: &Context<'_>) -> Result<Option<DateTime<Utc>>> {
+        Ok(self.analysis_snapshot(ctx).await?.map(|s| s.created_at))
+    }
 
-        let analysis = OpinionAnalysis {
-            overview,
-            consensus_opinions,
-            divisive_opinions,
+    async fn opinion_analysis(&self, ctx: &Context<'_>, limit: i32) -> Result<OpinionAnalysis> {
+        let mut analysis: OpinionAnalysis = match self.analysis_snapshot(ctx).await? {
+            Some(snapshot) => serde_json::from_value(snapshot.opinion_analysis)?,
+            None => OpinionAnalysis {
+                overview: None,
+                consensus_opinions: vec![],
+                divisive_opinions: vec![],
+            },
         };
-
-        cache.set(cache_key, serde_json::to_value(&analysis)?);
-
+        analysis.consensus_opinions.truncate(limit.max(0) as usize);
+        analysis.divisive_opinions.truncate(limit.max(0) as usize);
         Ok(analysis)
     }
 
     async fn opinion_groups(
         &self,
         ctx: &Context<'_>,
-        num_groups: Option<usize>,
-    ) -> Result<Vec<OpinionGroup>, Error> {
-        let cache_key = format!(
-            "conversation:{}:opinion_groups:num_groups:{}",
-            *self.id,
-            num_groups.unwrap_or(0)
-        );
-        let cache = ctx.data::<Cache<String, serde_json::Value>>().unwrap();
-        if let Some(cached) = cache.get(&cache_key) {
-            let data: Vec<OpinionGroup> = serde_json::from_value(cached.clone())
-                .map_err(|e| async_graphql::Error::new(e.to_string()))?;
-            return Ok(data);
-        }
-
-        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-
-        // Fetch all votes
-        let votes = sqlx::query_as!(
-            StatementVote,
-            r#"
-            SELECT v.id, statement_id, s.content, user_id, session_id, vote_type AS "vote_type: ArgumentPosition", v.created_at, v.updated_at
-            FROM statement_vote v
-            JOIN statement s ON v.statement_id = s.id
-            WHERE s.conversation_id = $1
-            "#,
-            uuid::Uuid::parse_str(&self.id)?
-        )
-        .fetch_all(&db_pool)
-        .await?;
-
-        // Convert votes to numerical matrix
-        let (matrix, voter_ids, statement_ids) = prepare_voting_matrix(&votes);
-
-        // Determine optimal number of clusters
-        let optimal_k = num_groups.unwrap_or(calculate_optimal_clusters(&matrix));
-
-        // Perform k-means clustering with optimal k
-        let groups = kmeans(&matrix, optimal_k, 100);
-
-        println!("K-means output groups:");
-        for (i, group) in groups.iter().enumerate() {
-            println!("Group {}: {} members", i, group.len());
+        #[graphql(
+            name = "numGroups",
+            desc = "Ignored, the number of groups is picked when the analysis runs"
+        )]
+        _num_groups: Option<usize>,
+    ) -> Result<Vec<OpinionGroup>> {
+        match self.analysis_snapshot(ctx).await? {
+            Some(snapshot) => Ok(serde_json::from_value(snapshot.opinion_groups)?),
+            None => Ok(vec![]),
         }
+    }
 
-        // Analyze groups
-        let mut opinion_groups = Vec::new();
-        for (group_id, group_indices) in groups.iter().enumerate() {
-            // Skip empty groups
-            if group_indices.is_empty() {
-                continue;
-            }
-
-            let users: Vec<ID> = group_indices
-                .iter()
-                .map(|&idx| match &voter_ids[idx] {
-                    VoterId::User(uuid) => uuid.into(),
-                    VoterId::Session(session) => ID::from(session.to_string()),
-                })
-                .collect();
-
-            let characteristic_votes: Vec<CharacteristicVote> =
-                analyze_group_votes(&matrix, group_indices, &statement_ids);
-
-            let summary = match generate_group_summary(&db_pool, &characteristic_votes).await {
-                Ok(summary) => summary,
-                Err(e) => {
-                    tracing::debug!("Error generating group summary: {:?}", e);
-                    "Group summary unavailable.".to_string()
-                }
-            };
-
-            opinion_groups.push(OpinionGroup {
-                id: ID::from(group_id.to_string()),
-                users,
-                characteristic_votes,
-                summary: summary.to_string(),
-            });
+    /// Each participant's position on the opinion map and their group. Group ids are the
+    /// same ones `opinionGroups` returns and stay stable as new votes come in.
+    async fn opinion_map(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(
+            name = "numGroups",
+            desc = "Ignored, the number of groups is picked when the analysis runs"
+        )]
+        _num_groups: Option<usize>,
+    ) -> Result<OpinionMapResult> {
+        match self.analysis_snapshot(ctx).await? {
+            Some(snapshot) => Ok(serde_json::from_value(snapshot.opinion_map)?),
+            None => Ok(OpinionMapResult {
+                participants: vec![],
+                groups: vec![],
+                explained_variance: vec![],
+            }),
         }
-
-        cache.set(cache_key, serde_json::to_value(&opinion_groups)?);
-
-        Ok(opinion_groups)
     }
 
     async fn embed(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<EmbedResult>> {
@@ -855,6 +831,7 @@ impl ConversationResult {
             FROM embed
             WHERE embed_type = 'conversation' 
             AND attributes->>'conversationId' = $1
+            AND deleted_at IS NULL
             LIMIT 1"#,
             self.id.to_string()
         )
@@ -961,661 +938,3 @@ impl StatementResult {
         Ok((total_votes as f64 / total_participants as f64) * 100.0)
     }
 }
-
-fn analyze_group_votes(
-    matrix: &Array2<f64>,
-    group_user_indices: &[usize],
-    statement_ids: &[Uuid],
-) -> Vec<CharacteristicVote> {
-    let mut candidates = Vec::new();
-
-    // First pass: collect all potential characteristic votes with their metrics
-    for (stmt_idx, &stmt_id) in statement_ids.iter().enumerate() {
-        let votes = matrix.column(stmt_idx);
-
-        let group_votes: Vec<f64> = group_user_indices
-            .iter()
-            .map(|&user_idx| votes[user_idx])
-            .filter(|&vote| vote != 0.0)
-            .collect();
-
-        if !group_votes.is_empty() {
-            let mean = group_votes.iter().sum::<f64>() / group_votes.len() as f64;
-
-            let variance = if group_votes.len() > 1 {
-                group_votes.iter().map(|&x| (x - mean).powi(2)).sum::<f64>()
-                    / (group_votes.len() - 1) as f64
-            } else {
-                0.0
-            };
-            let std_dev = variance.sqrt();
-            let mut consensus = 1.0 - (std_dev / 1.0).min(1.0);
-            let significance = group_votes.len() as f64 / group_user_indices.len() as f64;
-
-            // Adjust consensus score for high-consensus, low-participation cases
-            if consensus > 0.7 && significance <= 0.5 {
-                consensus *= significance / 0.5;
-            }
-
-            candidates.push((stmt_id, mean, consensus, significance));
-        }
-    }
-
-    // Sort candidates by combined score (consensus * significance)
-    candidates.sort_by(|a, b| {
-        let score_a = a.2 * a.3; // consensus * significance
-        let score_b = b.2 * b.3;
-        score_b
-            .partial_cmp(&score_a)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    // Take top 10 votes or adjust thresholds to get at least some characteristic votes
-    let mut characteristic_votes = Vec::new();
-    let initial_consensus_threshold = 0.3;
-    let initial_significance_threshold = 0.5;
-    let mut consensus_threshold = initial_consensus_threshold;
-    let mut significance_threshold = initial_significance_threshold;
-
-    while characteristic_votes.is_empty()
-        && consensus_threshold > 0.0
-        && significance_threshold > 0.0
-    {
-        characteristic_votes = candidates
-            .iter()
-            .filter(|&(_, _, consensus, significance)| {
-                *consensus >= consensus_threshold && *significance >= significance_threshold
-            })
-            .take(10)
-            .map(
-                |&(stmt_id, mean, consensus, significance)| CharacteristicVote {
-                    statement_id: ID::from(stmt_id),
-                    mean_sentiment: mean,
-                    consensus_level: consensus,
-                    significance_level: significance,
-                },
-            )
-            .collect();
-
-        // Reduce thresholds if no votes meet criteria
-        if characteristic_votes.is_empty() {
-            consensus_threshold *= 0.8;
-            significance_threshold *= 0.8;
-        }
-    }
-
-    // If still no votes meet thresholds, take top 3 votes regardless of thresholds
-    if characteristic_votes.is_empty() {
-        characteristic_votes = candidates
-            .iter()
-            .take(3)
-            .map(
-                |&(stmt_id, mean, consensus, significance)| CharacteristicVote {
-                    statement_id: ID::from(stmt_id),
-                    mean_sentiment: mean,
-                    consensus_level: consensus,
-                    significance_level: significance,
-                },
-            )
-            .collect();
-    }
-
-    characteristic_votes
-}
-
-#[derive(Hash, Eq, PartialEq, Clone, Ord, PartialOrd, Debug)]
-enum VoterId {
-    User(Uuid),
-    Session(Uuid),
-}
-
-// Helper function to prepare the voting matrix
-fn prepare_voting_matrix(votes: &[StatementVote]) -> (Array2<f64>, Vec<VoterId>, Vec<Uuid>) {
-    let mut unique_voters = std::collections::HashSet::new();
-    let mut unique_statements = std::collections::HashSet::new();
-
-    // Collect unique voters and statements
-    for vote in votes {
-        if let Some(user_id) = &vote.user_id {
-            unique_voters.insert(VoterId::User(*user_id));
-        } else if let Some(session_id) = &vote.session_id {
-            unique_voters.insert(VoterId::Session(*session_id));
-        }
-        unique_statements.insert(vote.statement_id);
-    }
-
-    let mut voter_ids: Vec<VoterId> = unique_voters.into_iter().collect();
-    let mut statement_ids: Vec<Uuid> = unique_statements.into_iter().collect();
-    voter_ids.sort();
-    statement_ids.sort();
-
-    // Create vote matrix
-    let mut matrix = Array2::zeros((voter_ids.len(), statement_ids.len()));
-
-    for vote in votes {
-        let voter_idx = voter_ids
-            .iter()
-            .position(|v| match (v, &vote.user_id, &vote.session_id) {
-                (VoterId::User(id), Some(user_id), _) => id == user_id,
-                (VoterId::Session(id), _, Some(session_id)) => id == session_id,
-                _ => false,
-            })
-            .unwrap();
-        let statement_idx = statement_ids
-            .iter()
-            .position(|&id| id == vote.statement_id)
-            .unwrap();
-        let value = vote.vote_type.as_f64();
-        matrix[[voter_idx, statement_idx]] = value;
-    }
-
-    (matrix, voter_ids, statement_ids)
-}
-
-fn calculate_optimal_clusters(data: &Array2<f64>) -> usize {
-    const MIN_CLUSTERS: usize = 2;
-    const MAX_CLUSTERS: usize = 5;
-    let n_samples = data.shape()[0];
-
-    // Early return for small datasets
-    if n_samples < MAX_CLUSTERS * 2 {
-        return MIN_CLUSTERS;
-    }
-
-    let mut best_k = MIN_CLUSTERS;
-    let mut best_score = f64::NEG_INFINITY;
-
-    // Use silhouette score to find optimal k
-    for k in MIN_CLUSTERS..=MAX_CLUSTERS {
-        let groups = kmeans(data, k, 100);
-
-        // Calculate average cluster size and variance
-        let avg_size = n_samples as f64 / k as f64;
-        let size_variance = groups
-            .iter()
-            .map(|g| (g.len() as f64 - avg_size).powi(2))
-            .sum::<f64>()
-            / k as f64;
-
-        // Calculate inter-cluster distance
-        let score = calculate_cluster_quality(data, &groups, size_variance);
-
-        if score > best_score {
-            best_score = score;
-            best_k = k;
-        }
-    }
-
-    best_k
-}
-
-fn kmeans(data: &Array2<f64>, k: usize, max_iters: usize) -> Vec<Vec<usize>> {
-    let n_samples = data.shape()[0];
-    let n_features = data.shape()[1];
-    let min_cluster_size = n_samples / (k * 2);
-    let mut rng = rand::thread_rng();
-
-    // Initialize centroids using k-means++ method
-    let mut centroids = Array2::zeros((k, n_features));
-    let mut chosen_indices = Vec::with_capacity(k);
-
-    // Choose first centroid randomly
-    let first_idx = rng.gen_range(0..n_samples);
-    centroids.row_mut(0).assign(&data.row(first_idx));
-    chosen_indices.push(first_idx);
-
-    // Choose remaining centroids
-    for i in 1..k {
-        let mut distances = vec![f64::INFINITY; n_samples];
-
-        // Calculate distances to existing centroids
-        for (sample_idx, distance) in distances.iter_mut().enumerate() {
-            for &centroid_idx in &chosen_indices {
-                let dist = squared_distance(&data.row(sample_idx), &data.row(centroid_idx));
-                *distance = distance.min(dist);
-            }
-        }
-
-        // Choose next centroid with probability proportional to distance
-        let total_dist: f64 = distances.iter().sum();
-        let mut cumsum = 0.0;
-        let threshold = rng.gen::<f64>() * total_dist;
-
-        let next_idx = distances
-            .iter()
-            .enumerate()
-            .find(|(_, &dist)| {
-                cumsum += dist;
-                cumsum >= threshold
-            })
-            .map(|(idx, _)| idx)
-            .unwrap_or_else(|| rng.gen_range(0..n_samples));
-
-        centroids.row_mut(i).assign(&data.row(next_idx));
-        chosen_indices.push(next_idx);
-    }
-
-    let mut groups = vec![Vec::new(); k];
-    let mut converged = false;
-    let mut iterations = 0;
-
-    while !converged && iterations < max_iters {
-        // Clear previous groups
-        groups.iter_mut().for_each(|g| g.clear());
-
-        // Assign points to nearest centroid
-        for i in 0..n_samples {
-            let mut min_dist = f64::INFINITY;
-            let mut closest_cluster = 0;
-
-            for j in 0..k {
-                let dist = squared_distance(&data.row(i), &centroids.row(j));
-                if dist < min_dist {
-                    min_dist = dist;
-                    closest_cluster = j;
-                }
-            }
-
-            groups[closest_cluster].push(i);
-        }
-
-        // Rebalance clusters if needed
-        rebalance_clusters(&mut groups, min_cluster_size);
-
-        // Update centroids and check convergence
-        let mut max_centroid_shift: f64 = 0.0;
-        for (i, group) in groups.iter().enumerate() {
-            if !group.is_empty() {
-                let new_centroid = calculate_centroid(data, group);
-                let shift = squared_distance(&centroids.row(i), &new_centroid.row(0));
-                max_centroid_shift = max_centroid_shift.max(shift);
-                centroids.row_mut(i).assign(&new_centroid.row(0));
-            }
-        }
-
-        converged = max_centroid_shift < 1e-4;
-        iterations += 1;
-    }
-
-    groups
-}
-
-fn squared_distance(a: &ArrayView1<f64>, b: &ArrayView1<f64>) -> f64 {
-    a.iter().zip(b.iter()).map(|(x, y)| (x - y).powi(2)).sum()
-}
-
-fn calculate_centroid(data: &Array2<f64>, indices: &[usize]) -> Array2<f64> {
-    let sum = indices
-        .iter()
-        .fold(Array2::zeros((1, data.shape()[1])), |acc, &idx| {
-            acc + data.row(idx).insert_axis(Axis(0))
-        });
-    sum / indices.len() as f64
-}
-
-fn rebalance_clusters(groups: &mut [Vec<usize>], min_size: usize) {
-    let mut large_clusters: Vec<usize> = groups
-        .iter()
-        .enumerate()
-        .filter(|(_, g)| g.len() > min_size * 2)
-        .map(|(i, _)| i)
-        .collect();
-
-    let mut small_clusters: Vec<usize> = groups
-        .iter()
-        .enumerate()
-        .filter(|(_, g)| g.len() < min_size)
-        .map(|(i, _)| i)
-        .collect();
-
-    while !large_clusters.is_empty() && !small_clusters.is_empty() {
-        let from = large_clusters[0];
-        let to = small_clusters[0];
-
-        let n_transfer = (groups[from].len() - min_size).min(min_size - groups[to].len());
-        let transfer_elements: Vec<_> = groups[from].drain(0..n_transfer).collect();
-        groups[to].extend(transfer_elements);
-
-        if groups[from].len() <= min_size * 2 {
-            large_clusters.remove(0);
-        }
-        if groups[to].len() >= min_size {
-            small_clusters.remove(0);
-        }
-    }
-}
-
-fn calculate_cluster_quality(data: &Array2<f64>, groups: &[Vec<usize>], size_variance: f64) -> f64 {
-    let mut total_score = 0.0;
-    let size_penalty = 1.0 / (1.0 + size_variance.sqrt());
-
-    for (i, group1) in groups.iter().enumerate() {
-        if group1.is_empty() {
-            continue;
-        }
-
-        let mut min_inter_dist = f64::INFINITY;
-
-        for (j, group2) in groups.iter().enumerate() {
-            if i == j || group2.is_empty() {
-                continue;
-            }
-
-            let dist = calculate_group_distance(data, group1, group2);
-            min_inter_dist = min_inter_dist.min(dist);
-        }
-
-        let intra_dist = calculate_group_cohesion(data, group1);
-        if intra_dist == 0.0 {
-            continue;
-        }
-
-        total_score += (min_inter_dist / intra_dist) * size_penalty;
-    }
-
-    total_score / groups.len() as f64
-}
-
-fn calculate_group_distance(data: &Array2<f64>, group1: &[usize], group2: &[usize]) -> f64 {
-    let mut total_dist = 0.0;
-    let mut count = 0;
-
-    for &i in group1 {
-        for &j in group2 {
-            total_dist += squared_distance(&data.row(i), &data.row(j));
-            count += 1;
-        }
-    }
-
-    if count > 0 {
-        total_dist / count as f64
-    } else {
-        f64::INFINITY
-    }
-}
-
-fn calculate_group_cohesion(data: &Array2<f64>, group: &[usize]) -> f64 {
-    if group.len() <= 1 {
-        return 1.0;
-    }
-
-    let mut total_dist = 0.0;
-    let mut count = 0;
-
-    for (i, &idx1) in group.iter().enumerate() {
-        for &idx2 in group.iter().skip(i + 1) {
-            total_dist += squared_distance(&data.row(idx1), &data.row(idx2));
-            count += 1;
-        }
-    }
-
-    if count > 0 {
-        total_dist / count as f64
-    } else {
-        f64::INFINITY
-    }
-}
-
-#[derive(Clone)]
-struct StatementWithMeta {
-    id: Uuid,
-    content: String,
-    votes: Vec<StatementVote>,
-    views: Vec<StatementView>,
-}
-
-async fn fetch_statements_with_votes(
-    db_pool: &PgPool,
-    conversation_id: &str,
-) -> Result<Vec<StatementWithMeta>> {
-    let votes = sqlx::query_as!(
-        StatementVote,
-        r#"
-        SELECT v.id, statement_id, s.content, user_id, session_id, vote_type AS "vote_type: ArgumentPosition", v.created_at, v.updated_at
-        FROM statement_vote v
-        JOIN statement s ON v.statement_id = s.id
-        WHERE s.conversation_id = $1
-        "#,
-        Uuid::parse_str(conversation_id)?
-    )
-    .fetch_all(db_pool)
-    .await?;
-
-    let views = sqlx::query_as!(
-        StatementView,
-        r#"
-        SELECT sv.id, statement_id, session_id, user_id, sv.created_at, sv.updated_at
-        FROM statement_view sv
-        JOIN statement s ON sv.statement_id = s.id
-        WHERE s.conversation_id = $1
-        "#,
-        Uuid::parse_str(conversation_id)?
-    )
-    .fetch_all(db_pool)
-    .await?;
-
-    let mut statements_map: HashMap<Uuid, StatementWithMeta> = HashMap::new();
-
-    // Process votes first to establish content
-    for vote in votes {
-        statements_map
-            .entry(vote.statement_id)
-            .or_insert_with(|| StatementWithMeta {
-                id: vote.statement_id,
-                content: vote.content.clone(),
-                votes: Vec::new(),
-                views: Vec::new(),
-            })
-            .votes
-            .push(vote);
-    }
-
-    // Process views, skipping statements that don't exist
-    for view in views {
-        if let Some(statement) = statements_map.get_mut(&view.statement_id) {
-            statement.views.push(view);
-        }
-    }
-
-    Ok(statements_map.into_values().collect())
-}
-
-fn count_votes(votes: &[StatementVote]) -> HashMap<ArgumentPosition, i32> {
-    let mut counts = HashMap::new();
-    for vote in votes {
-        *counts.entry(vote.vote_type).or_insert(0) += 1;
-    }
-    counts
-}
-
-fn calculate_consensus_score(
-    vote_counts: &HashMap<ArgumentPosition, i32>,
-    total_votes: f64,
-) -> f64 {
-    let support = *vote_counts.get(&ArgumentPosition::Support).unwrap_or(&0) as f64;
-    let oppose = *vote_counts.get(&ArgumentPosition::Oppose).unwrap_or(&0) as f64;
-    let neutral = *vote_counts.get(&ArgumentPosition::Neutral).unwrap_or(&0) as f64;
-
-    let max_votes = support.max(oppose).max(neutral);
-    let max_vote_ratio = max_votes / total_votes;
-
-    // Penalize statements with few votes
-    let vote_volume_factor = (total_votes / 10.0).min(1.0);
-
-    max_vote_ratio * vote_volume_factor
-}
-
-fn calculate_divisiveness_score(
-    vote_counts: &HashMap<ArgumentPosition, i32>,
-    total_votes: f64,
-) -> f64 {
-    let support = vote_counts
-        .get(&ArgumentPosition::Support)
-        .copied()
-        .unwrap_or(0) as f64;
-    let oppose = vote_counts
-        .get(&ArgumentPosition::Oppose)
-        .copied()
-        .unwrap_or(0) as f64;
-
-    // Ignore neutral votes for divisiveness calculation
-    let active_votes = support + oppose;
-    if active_votes == 0.0 {
-        return 0.0;
-    }
-
-    // Calculate the proportion of support vs oppose among non-neutral votes
-    let support_ratio = support / active_votes;
-
-    // Score is highest when support_ratio is close to 0.5 (perfect split)
-    // and lowest when it's close to 0.0 or 1.0 (consensus)
-    let balance_score = 1.0 - (support_ratio - 0.5).abs() * 2.0;
-
-    // Consider total engagement (non-neutral votes) as a factor
-    let engagement_ratio = active_votes / total_votes;
-
-    // Penalize low vote counts
-    let vote_volume_factor = (total_votes / 10.0).min(1.0);
-
-    // Combine factors with more weight on the balance score
-    balance_score * engagement_ratio * vote_volume_factor
-}
-
-async fn generate_group_summary(
-    db_pool: &PgPool,
-    characteristic_votes: &[CharacteristicVote],
-) -> Result<String, Error> {
-    // Fetch statement contents for context
-    let mut statement_details = Vec::new();
-    for vote in characteristic_votes {
-        let statement = sqlx::query!(
-            r#"
-            SELECT content
-            FROM statement
-            WHERE id = $1
-            "#,
-            Uuid::parse_str(&vote.statement_id)?
-        )
-        .fetch_one(db_pool)
-        .await?;
-
-        statement_details.push((
-            statement.content,
-            vote.mean_sentiment,
-            vote.consensus_level,
-            vote.significance_level,
-        ));
-    }
-
-    let prompt = format!(
-        "You are analyzing a group of users in a discussion. Here are their most characteristic voting patterns:\n\n{}{}",
-        statement_details.iter()
-            .map(|(content, sentiment, consensus, significance)| {
-                format!(
-                    "Statement: '{}'\nSentiment: {:.2} (-1 to +1)\nConsensus: {:.2}\nParticipation: {:.2}\n",
-                    content, sentiment, consensus, significance
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n"),
-        "\nBased on these voting patterns, write a 2-3 sentence summary describing this group's positions and characteristics. Focus on the statement content and the most strongly held views and areas of agreement."
-    );
-
-    dotenv::dotenv().ok();
-    let client = Client::new();
-
-    let response = client
-        .chat()
-        .create(
-            CreateChatCompletionRequestArgs::default()
-                .model("gpt-4")
-                .messages([ChatCompletionRequestMessage {
-                    role: Role::User,
-                    content: prompt,
-                    name: None,
-                }])
-                .temperature(0.7)
-                .max_tokens(40_u16)
-                .build()?,
-        )
-        .await;
-
-    // Handle API error
-    match response {
-        Ok(response) => {
-            // Extract and return the generated summary
-            let summary = response.choices[0].message.content.clone();
-
-            Ok(summary)
-        }
-        Err(err) => Err(Error::new(format!("OpenAI API error: {:?}", err)))?,
-    }
-}
-
-async fn generate_opinion_summary(
-    consensus_opinions: Vec<OpinionScore>,
-    divisive_opinions: Vec<OpinionScore>,
-) -> Result<String, Error> {
-    if consensus_opinions.is_empty() && divisive_opinions.is_empty() {
-        return Ok("No opinions to summarize.".to_string());
-    }
-
-    let mut opinion_details = Vec::new();
-
-    // Format consensus opinions
-    for opinion in consensus_opinions {
-        opinion_details.push(format!(
-            "Consensus Opinion: '{}'\nSupport: {}\nOppose: {}\nNeutral: {}\nTotal Votes: {}\n",
-            opinion.content,
-            opinion.support_votes,
-            opinion.oppose_votes,
-            opinion.neutral_votes,
-            opinion.total_votes
-        ));
-    }
-
-    // Format divisive opinions
-    for opinion in divisive_opinions {
-        opinion_details.push(format!(
-            "Divisive Opinion: '{}'\nSupport: {}\nOppose: {}\nNeutral: {}\nTotal Votes: {}\n",
-            opinion.content,
-            opinion.support_votes,
-            opinion.oppose_votes,
-            opinion.neutral_votes,
-            opinion.total_votes
-        ));
-    }
-
-    let prompt = format!(
-        "You are analyzing voting patterns on various opinions. Here are the most notable consensus and divisive opinions:\n\n{}{}",
-        opinion_details.join("\n"),
-        "\nWrite a 2-3 sentence summary describing the overall patterns in these opinions. Focus on what unites and divides the community, without directly quoting the statements. Highlight any particularly strong consensus or notable divisions."
-    );
-
-    dotenv::dotenv().ok();
-    let client = Client::new();
-
-    let response = client
-        .chat()
-        .create(
-            CreateChatCompletionRequestArgs::default()
-                .model("gpt-3.5-turbo-0125")
-                .messages([ChatCompletionRequestMessage {
-                    role: Role::User,
-                    content: prompt,
-                    name: None,
-                }])
-                .temperature(0.7)
-                .max_tokens(320_u16)
-                .build()?,
-        )
-        .await;
-
-    match response {
-        Ok(response) => Ok(response.choices[0].message.content.clone()),
-        Err(err) => {
-            tracing::debug!("OpenAI API error: {:?}", err);
-            Err(Error::new(format!("OpenAI API error: {:?}", err)))
-        }
-    }
-}
@@ -0,0 +1,28 @@
+use async_graphql::{SimpleObject, ID};
+use db::{DateTime, DeletableEntityType, DeletedRecord};
+
+use crate::is_admin;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct DeletedRecordResult {
+    entity_type: DeletableEntityType,
+    id: ID,
+    name: Option<String>,
+    organization_id: Option<ID>,
+    deleted_at: DateTime,
+    purge_at: DateTime,
+}
+
+impl From<DeletedRecord> for DeletedRecordResult {
+    fn from(r: DeletedRecord) -> Self {
+        Self {
+            entity_type: r.entity_type,
+            id: r.id.into(),
+            name: r.name,
+            organization_id: r.organization_id.map(ID::from),
+            deleted_at: r.deleted_at,
+            purge_at: r.purge_at,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{BallotMeasureResult, RaceResult};
+use super::{BallotMeasureResult, RaceResult, TurnoutResult};
 use crate::{
     context::ApiContext,
     relay::{Base64Cursor, ConnectionFields, ConnectionResult},
@@ -14,9 +14,9 @@ use db::{
     filters::tx::apply_tx_filters,
     models::{
         ballot_measure::BallotMeasure,
-        enums::{BallotMeasureStatus, RaceType, State, VoteType},
+        enums::{BallotMeasureStatus, RaceStatus, RaceType, State, VoteType},
     },
-    Address, AddressInput, Election, ElectionScope, Race,
+    Address, AddressInput, Election, ElectionScope, Race, Turnout,
 };
 use geocodio::GeocodioProxy;
 use jsonwebtoken::TokenData;
@@ -297,11 +297,13 @@ async fn get_races_by_address_id(
             r.election_id,
             r.is_special_election,
             r.num_elect,
+            r.status,
+            r.status_updated_at,
             r.created_at,
             r.updated_at
         FROM race r
         JOIN office o ON office_id = o.id
-        WHERE r.election_id =
+        WHERE r.deleted_at IS NULL AND r.election_id =
         "#,
     );
 
@@ -379,6 +381,26 @@ async fn get_races_by_address_id(
 
 #[ComplexObject]
 impl ElectionResult {
+    /// Registered voters and ballots cast across precincts that report turnout
+    async fn turnout(&self, ctx: &Context<'_>) -> Result<Option<TurnoutResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let turnout = Turnout::for_election(&db_pool, uuid::Uuid::parse_str(&self.id)?).await?;
+        Ok(turnout
+            .into_iter()
+            .find(|t| t.county.is_none())
+            .map(TurnoutResult::from))
+    }
+
+    async fn turnout_by_county(&self, ctx: &Context<'_>) -> Result<Vec<TurnoutResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let turnout = Turnout::for_election(&db_pool, uuid::Uuid::parse_str(&self.id)?).await?;
+        Ok(turnout
+            .into_iter()
+            .filter(|t| t.county.is_some())
+            .map(TurnoutResult::from)
+            .collect())
+    }
+
     async fn races(
         &self,
         ctx: &Context<'_>,
@@ -431,6 +453,7 @@ impl ElectionResult {
         SELECT COUNT(*)
         FROM race
         WHERE election_id = $1
+          AND deleted_at IS NULL
           AND ($2::state IS NULL OR state = $2)
           AND ($3::TEXT IS NULL OR LOWER(title) LIKE $3)
         "#,
@@ -466,11 +489,14 @@ impl ElectionResult {
             r.election_id,
             r.is_special_election,
             r.num_elect,
+            r.status AS "status:RaceStatus",
+            r.status_updated_at,
             r.created_at,
             r.updated_at
         FROM race r
         JOIN office o ON o.id = r.office_id
         WHERE r.election_id = $1
+          AND r.deleted_at IS NULL
           AND ($2::state IS NULL OR r.state = $2)
           AND ($3::TEXT IS NULL OR LOWER(r.title) LIKE $3)
         ORDER BY o.priority ASC NULLS LAST, (regexp_match(o.district, '^[0-9]+'))[1]::int ASC NULLS LAST, COALESCE(o.district, '') ASC, (regexp_match(o.seat, '^[0-9]+'))[1]::int ASC NULLS LAST, COALESCE(o.seat, '') ASC, r.title DESC, r.id ASC
@@ -578,6 +604,8 @@ impl ElectionResult {
                 election_id,
                 is_special_election,
                 num_elect,
+                r.status AS "status:RaceStatus",
+                r.status_updated_at,
                 r.created_at,
                 r.updated_at,
                 o.priority,
@@ -625,6 +653,8 @@ impl ElectionResult {
                 election_id: r.election_id,
                 is_special_election: r.is_special_election,
                 num_elect: r.num_elect,
+                status: r.status,
+                status_updated_at: r.status_updated_at,
                 created_at: r.created_at,
                 updated_at: r.updated_at,
             })
@@ -0,0 +1,41 @@
+use async_graphql::{SimpleObject, ID};
+use db::{DateTime, EmailOutbox, EmailStatus};
+
+use crate::is_admin;
+
+/// An email in the outbox. Bodies are left out since they carry invite and reset links.
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct EmailResult {
+    id: ID,
+    to_address: String,
+    template: String,
+    subject: String,
+    dedup_key: Option<String>,
+    status: EmailStatus,
+    attempts: i32,
+    max_attempts: i32,
+    send_at: DateTime,
+    last_error: Option<String>,
+    sent_at: Option<DateTime>,
+    created_at: DateTime,
+}
+
+impl From<EmailOutbox> for EmailResult {
+    fn from(e: EmailOutbox) -> Self {
+        Self {
+            id: e.id.into(),
+            to_address: e.to_address,
+            template: e.template,
+            subject: e.subject,
+            dedup_key: e.dedup_key,
+            status: e.status,
+            attempts: e.attempts,
+            max_attempts: e.max_attempts,
+            send_at: e.send_at,
+            last_error: e.last_error,
+            sent_at: e.sent_at,
+            created_at: e.created_at,
+        }
+    }
+}
@@ -312,16 +312,8 @@ impl EmbedResult {
         if let Some(conversation_id) = conversation_id {
             let conversation_id = uuid::Uuid::parse_str(conversation_id)?;
             let db_pool = ctx.data::<ApiContext>()?.pool.clone();
-            let record = sqlx::query_as!(
-                db::Conversation,
-                r#"
-                SELECT * FROM conversation WHERE id = $1
-                "#,
-                conversation_id
-            )
-            .fetch_one(&db_pool)
-            .await?;
-            Ok(Some(record.into()))
+            let record = db::Conversation::find_by_id(&db_pool, conversation_id).await?;
+            Ok(record.map(|record| record.into()))
         } else {
             Ok(None)
         }
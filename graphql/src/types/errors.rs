@@ -9,6 +9,9 @@ pub enum Error {
     #[error(transparent)]
     CredentialsError(#[from] CredentialsError),
 
+    #[error("{0} is not a valid S3 region")]
+    S3RegionError(String),
+
     #[error(transparent)]
     DatabaseError(#[from] db::Error),
 
@@ -27,6 +30,9 @@ pub enum Error {
     #[error(transparent)]
     GeocodioError(#[from] geocodio::Error),
 
+    #[error(transparent)]
+    EmailError(#[from] mailers::Error),
+
     #[error("A user already exists with this email")]
     UserExistsError,
 
@@ -68,6 +74,12 @@ pub enum Error {
 
     #[error("Please provide a valid voting address")]
     BadAddress,
+
+    #[error("Too many requests, please try again in {retry_after_seconds} seconds")]
+    RateLimited {
+        operation: String,
+        retry_after_seconds: u64,
+    },
 }
 
 impl ErrorExtensions for Error {
@@ -78,6 +90,14 @@ impl ErrorExtensions for Error {
                 e.set("field", field.as_str());
                 e.set("message", message.as_str());
             }
+            Error::RateLimited {
+                operation,
+                retry_after_seconds,
+            } => {
+                e.set("code", "RATE_LIMITED");
+                e.set("operation", operation.as_str());
+                e.set("retryAfter", *retry_after_seconds);
+            }
             _error => {
                 e.set("code", "INTERNAL_SERVER_ERROR");
             }
@@ -0,0 +1,82 @@
+use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use db::{DateTime, Job, JobRun, JobStatus};
+use serde_json::Value as JSON;
+
+use crate::{context::ApiContext, is_admin};
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(complex, visible = "is_admin")]
+pub struct JobResult {
+    id: ID,
+    name: String,
+    payload: JSON,
+    schedule: Option<String>,
+    status: JobStatus,
+    attempts: i32,
+    max_attempts: i32,
+    run_at: DateTime,
+    locked_at: Option<DateTime>,
+    last_error: Option<String>,
+    created_at: DateTime,
+    updated_at: DateTime,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct JobRunResult {
+    id: ID,
+    job_id: ID,
+    attempt: i32,
+    status: JobStatus,
+    error: Option<String>,
+    started_at: DateTime,
+    finished_at: Option<DateTime>,
+}
+
+#[ComplexObject]
+impl JobResult {
+    /// Most recent runs of this job, newest first
+    async fn runs(&self, ctx: &Context<'_>, limit: Option<i64>) -> Result<Vec<JobRunResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let runs = JobRun::find_by_job_id(
+            &db_pool,
+            uuid::Uuid::parse_str(&self.id)?,
+            limit.unwrap_or(20),
+        )
+        .await?;
+        Ok(runs.into_iter().map(JobRunResult::from).collect())
+    }
+}
+
+impl From<Job> for JobResult {
+    fn from(j: Job) -> Self {
+        Self {
+            id: j.id.into(),
+            name: j.name,
+            payload: j.payload,
+            schedule: j.schedule,
+            status: j.status,
+            attempts: j.attempts,
+            max_attempts: j.max_attempts,
+            run_at: j.run_at,
+            locked_at: j.locked_at,
+            last_error: j.last_error,
+            created_at: j.created_at,
+            updated_at: j.updated_at,
+        }
+    }
+}
+
+impl From<JobRun> for JobRunResult {
+    fn from(r: JobRun) -> Self {
+        Self {
+            id: r.id.into(),
+            job_id: r.job_id.into(),
+            attempt: r.attempt,
+            status: r.status,
+            error: r.error,
+            started_at: r.started_at,
+            finished_at: r.finished_at,
+        }
+    }
+}
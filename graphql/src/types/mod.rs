@@ -1,49 +1,75 @@
 mod address;
 mod argument;
+mod audit_event;
 mod auth;
 mod ballot_measure;
 mod bill;
 mod candidate_guide;
+mod candidate_guide_outreach;
 mod committee;
 mod conversation;
+mod deleted_record;
 mod election;
+mod email;
 mod embed;
 mod errors;
 mod health;
 mod issue_tag;
+mod job;
 mod office;
 mod organization;
+mod organization_api_key;
 mod organization_politician_note;
 mod party;
 mod politician;
 mod poll;
+mod precinct;
 mod question;
 mod race;
+mod results_source;
+mod revision;
+mod scrape_run;
 mod upload;
 mod user;
 mod votesmart;
 mod voting_guide;
 
-pub use self::auth::{AuthTokenResult, CreateUserResult, LoginResult};
+pub use self::auth::{AuthTokenResult, CreateUserResult, LoginResult, UserSessionResult};
 pub use address::{AddressExtendedMNResult, AddressResult};
 pub use argument::ArgumentResult;
+pub use audit_event::AuditEventResult;
 pub use ballot_measure::BallotMeasureResult;
 pub use bill::BillResult;
 pub use candidate_guide::*;
+pub use candidate_guide_outreach::*;
 pub use committee::CommitteeResult;
-pub use conversation::ConversationResult;
+pub use conversation::{
+    CharacteristicVote, ConversationExportResult, ConversationResult, ModerationQueueEntryResult,
+    OpinionAnalysis, OpinionGroup, OpinionMapGroup, OpinionMapParticipant, OpinionMapResult,
+    OpinionScore, StatementResult,
+};
+pub use deleted_record::DeletedRecordResult;
 pub use election::ElectionResult;
+pub use email::EmailResult;
 pub use embed::*;
 pub use errors::Error;
 pub use health::Heartbeat;
 pub use issue_tag::IssueTagResult;
+pub use job::{JobResult, JobRunResult};
 pub use office::OfficeResult;
 pub use organization::OrganizationResult;
+pub use organization_api_key::{CreatedOrganizationApiKeyResult, OrganizationApiKeyResult};
 pub use party::*;
 pub use politician::PoliticianResult;
 pub use poll::*;
+pub use precinct::{
+    CandidateVotesResult, CountyResultsResult, PrecinctResultsResult, TurnoutResult,
+};
 pub use question::*;
-pub use race::RaceResult;
+pub use race::{RaceCallResult, RaceProjectionResult, RaceResult, RankedChoiceRoundResult};
+pub use results_source::ResultsSourceResult;
+pub use revision::RevisionResult;
+pub use scrape_run::ScrapeRunResult;
 pub use upload::FileInfo;
 pub use user::UserResult;
 pub use voting_guide::{
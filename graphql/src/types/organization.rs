@@ -1,8 +1,11 @@
 use crate::{context::ApiContext, guard::OrganizationGuard, is_admin};
 
-use super::{organization_politician_note::OrganizationPoliticianNoteResult, IssueTagResult};
+use super::{
+    organization_politician_note::OrganizationPoliticianNoteResult, IssueTagResult,
+    OrganizationApiKeyResult,
+};
 use async_graphql::*;
-use db::{Organization, OrganizationPoliticianNote, OrganizationRoleType};
+use db::{Organization, OrganizationApiKey, OrganizationPoliticianNote, OrganizationRoleType};
 use serde::{Deserialize, Serialize};
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, SimpleObject)]
@@ -160,6 +163,22 @@ impl OrganizationResult {
             .collect();
         Ok(results)
     }
+
+    /// Server-to-server API keys, including revoked ones
+    #[graphql(
+        guard = "OrganizationGuard::new(&self.id, &OrganizationRoleType::Admin)",
+        visible = "is_admin"
+    )]
+    async fn api_keys(&self, ctx: &Context<'_>) -> FieldResult<Vec<OrganizationApiKeyResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let api_keys =
+            OrganizationApiKey::find_by_organization_id(&db_pool, uuid::Uuid::parse_str(&self.id)?)
+                .await?;
+        Ok(api_keys
+            .into_iter()
+            .map(OrganizationApiKeyResult::from)
+            .collect())
+    }
 }
 
 impl From<Organization> for OrganizationResult {
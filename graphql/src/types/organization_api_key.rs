@@ -0,0 +1,56 @@
+use async_graphql::{SimpleObject, ID};
+use db::{ApiKeyScope, DateTime, EmbedType, OrganizationApiKey};
+
+use crate::is_admin;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct OrganizationApiKeyResult {
+    id: ID,
+    organization_id: ID,
+    name: String,
+    /// First characters of the key, to recognize it by
+    prefix: String,
+    scope: ApiKeyScope,
+    embed_types: Vec<EmbedType>,
+    expires_at: Option<DateTime>,
+    last_used_at: Option<DateTime>,
+    created_by: Option<ID>,
+    created_at: DateTime,
+    revoked_at: Option<DateTime>,
+}
+
+/// A newly created or rotated key. The key itself is only ever returned here.
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct CreatedOrganizationApiKeyResult {
+    api_key: OrganizationApiKeyResult,
+    key: String,
+}
+
+impl From<OrganizationApiKey> for OrganizationApiKeyResult {
+    fn from(k: OrganizationApiKey) -> Self {
+        Self {
+            id: k.id.into(),
+            organization_id: k.organization_id.into(),
+            name: k.name,
+            prefix: k.prefix,
+            scope: k.scope,
+            embed_types: k.embed_types,
+            expires_at: k.expires_at,
+            last_used_at: k.last_used_at,
+            created_by: k.created_by.map(ID::from),
+            created_at: k.created_at,
+            revoked_at: k.revoked_at,
+        }
+    }
+}
+
+impl From<(OrganizationApiKey, String)> for CreatedOrganizationApiKeyResult {
+    fn from((api_key, key): (OrganizationApiKey, String)) -> Self {
+        Self {
+            api_key: api_key.into(),
+            key,
+        }
+    }
+}
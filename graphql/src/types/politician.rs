@@ -1,15 +1,15 @@
 use super::{
     party::PoliticalParty, votesmart::VsRating, BillResult, IssueTagResult, OfficeResult,
-    OrganizationResult, RaceResult,
+    OrganizationResult, RaceResult, RevisionResult,
 };
-use crate::{context::ApiContext, relay};
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, relay};
 use async_graphql::{ComplexObject, Context, Enum, Result, SimpleObject, ID};
 use db::{
     models::{
         enums::{BillStatus, PoliticalScope, State},
         politician::Politician,
     },
-    Bill, Chamber,
+    Bill, Chamber, Revision, RevisionEntityType,
 };
 use open_secrets::OpenSecretsProxy;
 use serde::{Deserialize, Serialize};
@@ -590,6 +590,39 @@ impl PoliticianResult {
             Ok(None)
         }
     }
+
+    /// Profile edits, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn revisions(&self, ctx: &Context<'_>) -> Result<Vec<RevisionResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let revisions = Revision::list(
+            &db_pool,
+            RevisionEntityType::Politician,
+            uuid::Uuid::parse_str(&self.id)?,
+        )
+        .await?;
+        Ok(revisions.into_iter().map(RevisionResult::from).collect())
+    }
+
+    /// Changed fields between two revisions as `{"field": {"old": ..., "new": ...}}`
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn revision_diff(
+        &self,
+        ctx: &Context<'_>,
+        from_revision: i32,
+        to_revision: i32,
+    ) -> Result<serde_json::Value> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let diff = Revision::diff(
+            &db_pool,
+            RevisionEntityType::Politician,
+            uuid::Uuid::parse_str(&self.id)?,
+            from_revision,
+            to_revision,
+        )
+        .await?;
+        Ok(diff)
+    }
 }
 
 impl From<Politician> for PoliticianResult {
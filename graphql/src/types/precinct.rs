@@ -0,0 +1,138 @@
+use async_graphql::{SimpleObject, ID};
+use db::{CountyCandidateVotes, PrecinctCandidateVotes, Turnout};
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct CandidateVotesResult {
+    candidate_id: ID,
+    votes: i64,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct PrecinctResultsResult {
+    precinct_id: ID,
+    code: String,
+    name: String,
+    county: Option<String>,
+    votes_by_candidate: Vec<CandidateVotesResult>,
+    total_votes: Option<i32>,
+    has_reported: Option<bool>,
+    turnout: Option<TurnoutResult>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct CountyResultsResult {
+    county: String,
+    votes_by_candidate: Vec<CandidateVotesResult>,
+    total_votes: Option<i64>,
+    num_precincts_reporting: Option<i64>,
+    total_precincts: Option<i64>,
+    turnout: Option<TurnoutResult>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct TurnoutResult {
+    /// `null` for totals across the whole election
+    county: Option<String>,
+    /// Includes voters who registered on election day
+    registered_voters: Option<i64>,
+    ballots_cast: Option<i64>,
+    turnout_percentage: Option<f64>,
+    num_precincts_reporting: i64,
+    total_precincts: i64,
+}
+
+/// Rounded to one decimal place, like `precinctReportingPercentage`
+fn round_percentage(percentage: f64) -> f64 {
+    (percentage * 10.0).round() / 10.0
+}
+
+impl From<Turnout> for TurnoutResult {
+    fn from(t: Turnout) -> Self {
+        Self {
+            turnout_percentage: t.percentage().map(round_percentage),
+            county: t.county,
+            registered_voters: t.registered_voters,
+            ballots_cast: t.ballots_cast,
+            num_precincts_reporting: t.precincts_reporting,
+            total_precincts: t.total_precincts,
+        }
+    }
+}
+
+impl PrecinctResultsResult {
+    /// Groups per candidate rows, which are ordered by precinct, into one result per precinct
+    pub fn from_rows(rows: Vec<PrecinctCandidateVotes>) -> Vec<Self> {
+        let mut results: Vec<Self> = vec![];
+        for row in rows {
+            let votes = CandidateVotesResult {
+                candidate_id: ID::from(row.candidate_id),
+                votes: row.votes as i64,
+            };
+            match results.last_mut() {
+                Some(last) if last.precinct_id == ID::from(row.precinct_id) => {
+                    last.votes_by_candidate.push(votes)
+                }
+                _ => {
+                    let turnout = match (row.registered_voters, row.ballots_cast) {
+                        (None, None) => None,
+                        (registered_voters, ballots_cast) => Some(TurnoutResult::from(Turnout {
+                            county: row.county.clone(),
+                            registered_voters: registered_voters.map(i64::from),
+                            ballots_cast: ballots_cast.map(i64::from),
+                            precincts_reporting: row.has_reported.unwrap_or(false) as i64,
+                            total_precincts: 1,
+                        })),
+                    };
+                    results.push(Self {
+                        precinct_id: ID::from(row.precinct_id),
+                        code: row.precinct_code,
+                        name: row.precinct_name,
+                        county: row.county,
+                        votes_by_candidate: vec![votes],
+                        total_votes: row.total_votes,
+                        has_reported: row.has_reported,
+                        turnout,
+                    })
+                }
+            }
+        }
+        results
+    }
+}
+
+impl CountyResultsResult {
+    /// Groups per candidate rows, which are ordered by county, into one result per county
+    pub fn from_rows(rows: Vec<CountyCandidateVotes>) -> Vec<Self> {
+        let mut results: Vec<Self> = vec![];
+        for row in rows {
+            let votes = CandidateVotesResult {
+                candidate_id: ID::from(row.candidate_id),
+                votes: row.votes,
+            };
+            match results.last_mut() {
+                Some(last) if last.county == row.county => last.votes_by_candidate.push(votes),
+                _ => {
+                    let turnout = match (row.registered_voters, row.ballots_cast) {
+                        (None, None) => None,
+                        (registered_voters, ballots_cast) => Some(TurnoutResult::from(Turnout {
+                            county: Some(row.county.clone()),
+                            registered_voters,
+                            ballots_cast,
+                            precincts_reporting: row.precincts_reporting.unwrap_or(0),
+                            total_precincts: row.total_precincts.unwrap_or(0),
+                        })),
+                    };
+                    results.push(Self {
+                        county: row.county,
+                        votes_by_candidate: vec![votes],
+                        total_votes: row.total_votes,
+                        num_precincts_reporting: row.precincts_reporting,
+                        total_precincts: row.total_precincts,
+                        turnout,
+                    })
+                }
+            }
+        }
+        results
+    }
+}
@@ -1,11 +1,17 @@
-use crate::context::ApiContext;
-use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
+use crate::{
+    context::ApiContext,
+    guard::{OrganizationGuard, OrganizationResource, StaffOnly},
+};
+use async_graphql::{ComplexObject, Context, Guard, Result, SimpleObject, ID};
 use db::{
-    loaders::politician::PoliticianId, DateTime, Embed, EmbedType, IssueTag, Question,
-    QuestionSubmission, Respondent, Sentiment,
+    loaders::politician::PoliticianId, DateTime, Embed, EmbedType, IssueTag, OrganizationRoleType,
+    Question, QuestionSubmission, QuestionSubmissionReview, Respondent, Revision,
+    RevisionEntityType, Sentiment, SubmissionStatus,
 };
 
-use super::{EmbedResult, IssueTagResult, PoliticianResult, SubmissionCountByDateResult};
+use super::{
+    EmbedResult, IssueTagResult, PoliticianResult, RevisionResult, SubmissionCountByDateResult,
+};
 
 #[derive(SimpleObject, Debug, Clone)]
 #[graphql(complex)]
@@ -31,10 +37,23 @@ pub struct QuestionSubmissionResult {
     editorial: Option<String>,
     translations: Option<serde_json::Value>,
     sentiment: Option<Sentiment>,
+    status: SubmissionStatus,
     created_at: DateTime,
     updated_at: DateTime,
 }
 
+#[derive(SimpleObject, Debug, Clone)]
+pub struct QuestionSubmissionReviewResult {
+    id: ID,
+    question_submission_id: ID,
+    from_status: SubmissionStatus,
+    to_status: SubmissionStatus,
+    /// The editorial note after this review
+    editorial: Option<String>,
+    reviewer_id: Option<ID>,
+    created_at: DateTime,
+}
+
 #[derive(SimpleObject, Debug, Clone)]
 pub struct ExternalUserResult {
     name: String,
@@ -93,6 +112,7 @@ impl QuestionResult {
                   translations,
                   sentiment AS "sentiment: Sentiment",
                   copied_from_id,
+                  status AS "status: SubmissionStatus",
                   created_at,
                   updated_at
                 FROM question_submission
@@ -125,6 +145,7 @@ impl QuestionResult {
                   qs.translations,
                   qs.sentiment AS "sentiment: Sentiment",
                   qs.copied_from_id,
+                  qs.status AS "status: SubmissionStatus",
                   qs.created_at,
                   qs.updated_at
                 FROM question_submission qs
@@ -132,6 +153,7 @@ impl QuestionResult {
                 ON qs.candidate_id = rc.candidate_id
                 WHERE qs.question_id = $1
                 AND rc.race_id = $2
+                AND qs.status = 'published'
             "#,
             uuid::Uuid::parse_str(self.id.as_str()).unwrap(),
             uuid::Uuid::parse_str(race_id.as_str()).unwrap(),
@@ -161,6 +183,7 @@ impl QuestionResult {
                   qs.translations,
                   qs.sentiment AS "sentiment: Sentiment",
                   qs.copied_from_id,
+                  qs.status AS "status: SubmissionStatus",
                   qs.created_at,
                   qs.updated_at
                 FROM question_submission qs
@@ -333,6 +356,55 @@ impl QuestionSubmissionResult {
 
         Ok(embed.map(|e| e.into()))
     }
+
+    /// Earlier versions of the answer and its editorial note, newest first. Only staff and
+    /// the question's organization can see them.
+    async fn revisions(&self, ctx: &Context<'_>) -> Result<Vec<RevisionResult>> {
+        self.check_can_view_revisions(ctx).await?;
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let revisions = Revision::list(
+            &db_pool,
+            RevisionEntityType::QuestionSubmission,
+            uuid::Uuid::parse_str(&self.id)?,
+        )
+        .await?;
+        Ok(revisions.into_iter().map(RevisionResult::from).collect())
+    }
+
+    /// Changed fields between two revisions as `{"field": {"old": ..., "new": ...}}`
+    async fn revision_diff(
+        &self,
+        ctx: &Context<'_>,
+        from_revision: i32,
+        to_revision: i32,
+    ) -> Result<serde_json::Value> {
+        self.check_can_view_revisions(ctx).await?;
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let diff = Revision::diff(
+            &db_pool,
+            RevisionEntityType::QuestionSubmission,
+            uuid::Uuid::parse_str(&self.id)?,
+            from_revision,
+            to_revision,
+        )
+        .await?;
+        Ok(diff)
+    }
+}
+
+impl QuestionSubmissionResult {
+    async fn check_can_view_revisions(&self, ctx: &Context<'_>) -> Result<()> {
+        if StaffOnly.check(ctx).await.is_ok() {
+            return Ok(());
+        }
+        OrganizationGuard::owner_of(
+            OrganizationResource::QuestionSubmission,
+            &self.id,
+            &OrganizationRoleType::Member,
+        )
+        .check(ctx)
+        .await
+    }
 }
 
 impl From<Question> for QuestionResult {
@@ -361,12 +433,27 @@ impl From<QuestionSubmission> for QuestionSubmissionResult {
             editorial: q.editorial,
             translations: q.translations,
             sentiment: q.sentiment,
+            status: q.status,
             created_at: q.created_at,
             updated_at: q.updated_at,
         }
     }
 }
 
+impl From<QuestionSubmissionReview> for QuestionSubmissionReviewResult {
+    fn from(r: QuestionSubmissionReview) -> Self {
+        Self {
+            id: r.id.into(),
+            question_submission_id: r.question_submission_id.into(),
+            from_status: r.from_status,
+            to_status: r.to_status,
+            editorial: r.editorial,
+            reviewer_id: r.reviewer_id.map(|id| id.into()),
+            created_at: r.created_at,
+        }
+    }
+}
+
 impl From<Respondent> for RespondentResult {
     fn from(r: Respondent) -> Self {
         Self {
@@ -1,17 +1,21 @@
-use crate::{context::ApiContext, types::OfficeResult};
+use crate::{context::ApiContext, guard::StaffOnly, is_admin, types::OfficeResult};
 use async_graphql::{ComplexObject, Context, Result, SimpleObject, ID};
 use db::{
     loaders::politician::PoliticianId,
     models::{
-        enums::{RaceType, State, VoteType},
+        enums::{RaceStatus, RaceType, State, VoteType},
         politician::Politician,
         race::Race,
     },
-    Election, Embed, EmbedType,
+    Election, Embed, EmbedType, Precinct, Projection, ProjectionOutcome, RaceCall,
+    RankedChoiceRound, RankedChoiceRoundTally,
 };
 use sqlx::QueryBuilder;
 
-use super::{ElectionResult, EmbedResult, PoliticalParty, PoliticianResult};
+use super::{
+    CountyResultsResult, ElectionResult, EmbedResult, PoliticalParty, PoliticianResult,
+    PrecinctResultsResult,
+};
 
 #[derive(SimpleObject, Debug, Clone)]
 #[graphql(complex)]
@@ -31,6 +35,8 @@ pub struct RaceResult {
     pub election_id: Option<ID>,
     pub is_special_election: bool,
     pub num_elect: Option<i32>,
+    pub status: RaceStatus,
+    pub status_updated_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub struct RaceCandidate {
@@ -49,13 +55,135 @@ pub struct RaceCandidateResult {
 }
 
 #[derive(SimpleObject, Debug, Clone)]
+#[graphql(complex)]
 pub struct RaceResultsResult {
+    #[graphql(skip)]
+    race_id: uuid::Uuid,
     votes_by_candidate: Vec<RaceCandidateResult>,
     total_votes: Option<i32>,
     num_precincts_reporting: Option<i32>,
     total_precincts: Option<i32>,
     precinct_reporting_percentage: Option<f64>,
     winners: Option<Vec<PoliticianResult>>,
+    /// Tabulation rounds of a ranked choice race, empty until its ballots are tabulated
+    rounds: Option<Vec<RankedChoiceRoundResult>>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RankedChoiceRoundResult {
+    round: i32,
+    quota: f64,
+    exhausted_votes: f64,
+    /// Votes for write-in candidates, which are counted together and eliminated like a
+    /// candidate
+    write_in_votes: f64,
+    elected_ids: Vec<ID>,
+    eliminated_ids: Vec<ID>,
+    tallies: Vec<RankedChoiceTallyResult>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RankedChoiceTallyResult {
+    candidate_id: ID,
+    votes: f64,
+    /// Votes gained or lost since the previous round
+    transferred: f64,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RaceCallResult {
+    id: ID,
+    race_id: ID,
+    status: RaceStatus,
+    /// Winners, or the candidates advancing to a runoff
+    winner_ids: Vec<ID>,
+    is_runoff: bool,
+    tied_candidate_ids: Vec<ID>,
+    note: Option<String>,
+    /// `null` when projected automatically from results
+    created_by: Option<ID>,
+    created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RaceProjectionResult {
+    outcome: ProjectionOutcome,
+    winner_ids: Vec<ID>,
+    tied_candidate_ids: Vec<ID>,
+}
+
+impl From<RaceCall> for RaceCallResult {
+    fn from(c: RaceCall) -> Self {
+        Self {
+            id: ID::from(c.id),
+            race_id: ID::from(c.race_id),
+            status: c.status,
+            winner_ids: c.winner_ids.into_iter().map(ID::from).collect(),
+            is_runoff: c.is_runoff,
+            tied_candidate_ids: c.tied_candidate_ids.into_iter().map(ID::from).collect(),
+            note: c.note,
+            created_by: c.created_by.map(ID::from),
+            created_at: c.created_at,
+        }
+    }
+}
+
+impl From<Projection> for RaceProjectionResult {
+    fn from(p: Projection) -> Self {
+        Self {
+            outcome: p.outcome,
+            winner_ids: p.winner_ids.into_iter().map(ID::from).collect(),
+            tied_candidate_ids: p.tied_candidate_ids.into_iter().map(ID::from).collect(),
+        }
+    }
+}
+
+impl RankedChoiceRoundResult {
+    pub fn from_rounds(
+        rounds: Vec<RankedChoiceRound>,
+        tallies: Vec<RankedChoiceRoundTally>,
+    ) -> Vec<Self> {
+        rounds
+            .into_iter()
+            .map(|round| Self {
+                round: round.round,
+                quota: round.quota,
+                exhausted_votes: round.exhausted_votes,
+                write_in_votes: round.write_in_votes,
+                elected_ids: round.elected_ids.into_iter().map(ID::from).collect(),
+                eliminated_ids: round.eliminated_ids.into_iter().map(ID::from).collect(),
+                tallies: tallies
+                    .iter()
+                    .filter(|t| t.round == round.round)
+                    .map(|t| RankedChoiceTallyResult {
+                        candidate_id: ID::from(t.candidate_id),
+                        votes: t.votes,
+                        transferred: t.transferred,
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+#[ComplexObject]
+impl RaceResultsResult {
+    /// Results for each precinct, for states that publish precinct level results
+    async fn by_precinct(
+        &self,
+        ctx: &Context<'_>,
+        county: Option<String>,
+    ) -> Result<Vec<PrecinctResultsResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let rows = Precinct::results_by_precinct(&db_pool, self.race_id, county).await?;
+        Ok(PrecinctResultsResult::from_rows(rows))
+    }
+
+    async fn by_county(&self, ctx: &Context<'_>) -> Result<Vec<CountyResultsResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let rows = Precinct::results_by_county(&db_pool, self.race_id).await?;
+        Ok(CountyResultsResult::from_rows(rows))
+    }
 }
 
 #[ComplexObject]
@@ -261,16 +389,46 @@ impl RaceResult {
             false => None,
         };
 
+        let rounds = match ctx.look_ahead().field("rounds").exists()
+            && self.vote_type == VoteType::RankedChoice
+        {
+            true => {
+                let race_id = uuid::Uuid::parse_str(&self.id).unwrap();
+                let rounds = RankedChoiceRound::find_by_race_id(&db_pool, race_id).await?;
+                let tallies = RankedChoiceRoundTally::find_by_race_id(&db_pool, race_id).await?;
+                Some(RankedChoiceRoundResult::from_rounds(rounds, tallies))
+            }
+            false => None,
+        };
+
         Ok(RaceResultsResult {
+            race_id: uuid::Uuid::parse_str(&self.id).unwrap(),
             votes_by_candidate: race_candidate_results,
             total_votes: race_results.total_votes,
             num_precincts_reporting: race_results.num_precincts_reporting,
             total_precincts: race_results.total_precincts,
             precinct_reporting_percentage: race_results.precinct_reporting_percentage,
             winners,
+            rounds,
         })
     }
 
+    /// Status changes of the race, newest first
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn calls(&self, ctx: &Context<'_>) -> Result<Vec<RaceCallResult>> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let calls = RaceCall::find_by_race_id(&db_pool, uuid::Uuid::parse_str(&self.id)?).await?;
+        Ok(calls.into_iter().map(RaceCallResult::from).collect())
+    }
+
+    /// Winners computed from the race's current results, without saving them
+    #[graphql(guard = "StaffOnly", visible = "is_admin")]
+    async fn projection(&self, ctx: &Context<'_>) -> Result<RaceProjectionResult> {
+        let db_pool = ctx.data::<ApiContext>()?.pool.clone();
+        let projection = RaceCall::projection(&db_pool, uuid::Uuid::parse_str(&self.id)?).await?;
+        Ok(projection.into())
+    }
+
     async fn election_date(&self, ctx: &Context<'_>) -> Result<Option<chrono::NaiveDate>> {
         let db_pool = ctx.data::<ApiContext>()?.pool.clone();
         let record = sqlx::query!(
@@ -320,6 +478,7 @@ impl RaceResult {
             FROM embed
             WHERE
                 attributes->>'raceId' = $1
+                AND deleted_at IS NULL
         "#,
             self.id.to_string()
         )
@@ -382,6 +541,8 @@ impl From<Race> for RaceResult {
             election_id: r.election_id.map(ID::from),
             is_special_election: r.is_special_election,
             num_elect: r.num_elect,
+            status: r.status,
+            status_updated_at: r.status_updated_at,
         }
     }
 }
@@ -0,0 +1,48 @@
+use async_graphql::{SimpleObject, ID};
+use db::{DateTime, ResultsSource, ResultsSourceFormat, State};
+use serde_json::Value as JSON;
+
+use crate::is_admin;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct ResultsSourceResult {
+    id: ID,
+    state: State,
+    election_id: ID,
+    name: String,
+    url: String,
+    format: ResultsSourceFormat,
+    table_name: String,
+    options: JSON,
+    poll_interval_seconds: i32,
+    active_from: DateTime,
+    active_until: DateTime,
+    is_enabled: bool,
+    last_polled_at: Option<DateTime>,
+    created_at: DateTime,
+    updated_at: DateTime,
+}
+
+impl From<ResultsSource> for ResultsSourceResult {
+    fn from(s: ResultsSource) -> Self {
+        let table_name = s.staging_table();
+        Self {
+            id: s.id.into(),
+            state: s.state,
+            election_id: s.election_id.into(),
+            name: s.name,
+            url: s.url,
+            format: s.format,
+            table_name,
+            options: s.options,
+            poll_interval_seconds: s.poll_interval_seconds,
+            active_from: s.active_from,
+            active_until: s.active_until,
+            is_enabled: s.is_enabled,
+            last_polled_at: s.last_polled_at,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use async_graphql::{SimpleObject, ID};
+use db::{DateTime, Revision};
+
+/// A record's tracked columns as they were after a change
+#[derive(SimpleObject, Debug, Clone)]
+pub struct RevisionResult {
+    id: ID,
+    revision_number: i32,
+    data: serde_json::Value,
+    created_at: DateTime,
+}
+
+impl From<Revision> for RevisionResult {
+    fn from(r: Revision) -> Self {
+        Self {
+            id: r.id.into(),
+            revision_number: r.revision_number,
+            data: r.data,
+            created_at: r.created_at,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use async_graphql::{SimpleObject, ID};
+use db::{DateTime, JobStatus, ScrapeRun, ScrapeStage};
+use serde_json::Value as JSON;
+
+use crate::is_admin;
+
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(visible = "is_admin")]
+pub struct ScrapeRunResult {
+    id: ID,
+    source_id: String,
+    stage: ScrapeStage,
+    dry_run: bool,
+    options: JSON,
+    status: JobStatus,
+    counts: JSON,
+    error: Option<String>,
+    started_at: DateTime,
+    finished_at: Option<DateTime>,
+    duration_ms: Option<i64>,
+}
+
+impl From<ScrapeRun> for ScrapeRunResult {
+    fn from(r: ScrapeRun) -> Self {
+        Self {
+            id: r.id.into(),
+            source_id: r.source_id,
+            stage: r.stage,
+            dry_run: r.dry_run,
+            options: r.options,
+            status: r.status,
+            counts: r.counts,
+            error: r.error,
+            started_at: r.started_at,
+            finished_at: r.finished_at,
+            duration_ms: r.duration_ms,
+        }
+    }
+}
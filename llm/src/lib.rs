@@ -0,0 +1,542 @@
+mod fixture;
+mod providers;
+pub mod schema;
+
+use std::{
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use config::LlmConfig;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::{info, warn};
+
+pub use fixture::FixtureProvider;
+pub use providers::{provider_from_config, AnthropicProvider, OpenAiProvider};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("LLM provider is misconfigured: {0}")]
+    Config(String),
+    #[error("LLM request failed: {0}")]
+    Request(String),
+    #[error("LLM provider responded with {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("LLM request timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("LLM returned invalid output: {0}")]
+    InvalidOutput(String),
+}
+
+impl Error {
+    /// Rate limits, server errors, timeouts and malformed output are worth another attempt
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Status { status, .. } => *status == 429 || *status >= 500,
+            Error::Request(_) | Error::Timeout(_) | Error::InvalidOutput(_) => true,
+            Error::Config(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::System => "system",
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+}
+
+impl Message {
+    pub fn system(content: impl Into<String>) -> Self {
+        Message {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Message {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+}
+
+/// A single call to a provider, with the task's settings already resolved
+#[derive(Debug, Clone)]
+pub struct CompletionRequest {
+    pub task: String,
+    pub model: String,
+    pub messages: Vec<Message>,
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+    /// Set when the response must be JSON matching this schema
+    pub json_schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub content: String,
+    /// The model that answered, which may be more specific than the one requested
+    pub model: String,
+    pub usage: Usage,
+}
+
+/// Sends a completion to a model. Implementations should make a single attempt and leave
+/// retries and timeouts to [`LlmClient`].
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Used for tasks when neither the task nor `LLM_MODEL` names a model
+    fn default_model(&self) -> &str;
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, Error>;
+}
+
+/// Reports token usage and failed calls, e.g. to Prometheus
+pub trait LlmMetrics: Send + Sync {
+    fn record_usage(&self, provider: &str, model: &str, task: &str, usage: &Usage);
+    fn record_failure(&self, provider: &str, task: &str);
+}
+
+struct NoopMetrics;
+
+impl LlmMetrics for NoopMetrics {
+    fn record_usage(&self, _provider: &str, _model: &str, _task: &str, _usage: &Usage) {}
+    fn record_failure(&self, _provider: &str, _task: &str) {}
+}
+
+/// Calls a provider with the settings configured for each task, retrying with backoff and
+/// validating structured output
+#[derive(Clone)]
+pub struct LlmClient {
+    provider: Arc<dyn LlmProvider>,
+    config: LlmConfig,
+    metrics: Arc<dyn LlmMetrics>,
+}
+
+impl LlmClient {
+    const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+    pub fn new(provider: impl LlmProvider + 'static, config: LlmConfig) -> Self {
+        LlmClient {
+            provider: Arc::new(provider),
+            config,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// A client for the provider picked by [`provider_from_config`]
+    pub fn from_env() -> Result<Self, Error> {
+        dotenv::dotenv().ok();
+        let config = LlmConfig::default();
+        let provider = provider_from_config(&config)?;
+        Ok(LlmClient {
+            provider: Arc::from(provider),
+            config,
+            metrics: Arc::new(NoopMetrics),
+        })
+    }
+
+    pub fn with_metrics(mut self, metrics: impl LlmMetrics + 'static) -> Self {
+        self.metrics = Arc::new(metrics);
+        self
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    /// Text response for `task`
+    pub async fn complete(&self, task: &str, messages: Vec<Message>) -> Result<String, Error> {
+        let completion = self.run(self.request(task, messages, None)).await?;
+        Ok(completion.content.trim().to_string())
+    }
+
+    /// Response for `task` as JSON matching `json_schema`, deserialized into `T`. Output
+    /// that doesn't match is retried like any other failure.
+    pub async fn complete_json<T: DeserializeOwned>(
+        &self,
+        task: &str,
+        mut messages: Vec<Message>,
+        json_schema: &Value,
+    ) -> Result<T, Error> {
+        messages.push(Message::system(format!(
+            "Respond only with JSON, without any other text, that matches this JSON schema:\n{}",
+            json_schema
+        )));
+        let completion = self
+            .run(self.request(task, messages, Some(json_schema.clone())))
+            .await?;
+        let value = schema::extract_json(&completion.content).map_err(Error::InvalidOutput)?;
+        serde_json::from_value(value).map_err(|err| Error::InvalidOutput(err.to_string()))
+    }
+
+    fn request(
+        &self,
+        task: &str,
+        messages: Vec<Message>,
+        json_schema: Option<Value>,
+    ) -> CompletionRequest {
+        let settings = self.config.task(task);
+        let model = settings
+            .model
+            .or_else(|| self.config.model.clone())
+            .unwrap_or_else(|| self.provider.default_model().to_string());
+        CompletionRequest {
+            task: task.to_string(),
+            model,
+            messages,
+            temperature: settings.temperature,
+            max_tokens: settings.max_tokens,
+            json_schema,
+        }
+    }
+
+    async fn run(&self, request: CompletionRequest) -> Result<Completion, Error> {
+        let mut backoff = Self::INITIAL_BACKOFF;
+        let mut attempt = 0;
+        loop {
+            match self.attempt(&request).await {
+                Ok(completion) => return Ok(completion),
+                Err(err) if err.is_retryable() && attempt < self.config.max_retries => {
+                    warn!(
+                        "{} call for {} failed, retrying in {:?}: {}",
+                        self.provider.name(),
+                        request.task,
+                        backoff,
+                        err
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(err) => {
+                    self.metrics
+                        .record_failure(self.provider.name(), &request.task);
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    async fn attempt(&self, request: &CompletionRequest) -> Result<Completion, Error> {
+        let completion = tokio::time::timeout(self.config.timeout, self.provider.complete(request))
+            .await
+            .map_err(|_| Error::Timeout(self.config.timeout))??;
+
+        // Tokens are spent whether or not the output turns out to be usable
+        info!(
+            "{} {} used {} prompt and {} completion tokens for {}",
+            self.provider.name(),
+            completion.model,
+            completion.usage.prompt_tokens,
+            completion.usage.completion_tokens,
+            request.task
+        );
+        self.metrics.record_usage(
+            self.provider.name(),
+            &completion.model,
+            &request.task,
+            &completion.usage,
+        );
+
+        if let Some(json_schema) = &request.json_schema {
+            let value = schema::extract_json(&completion.content).map_err(Error::InvalidOutput)?;
+            schema::validate(json_schema, &value).map_err(Error::InvalidOutput)?;
+        }
+        Ok(completion)
+    }
+}
+
+static CLIENT: OnceLock<LlmClient> = OnceLock::new();
+
+/// Sets the client returned by [`client`], e.g. to attach metrics at startup. Returns false
+/// when a client was already in use.
+pub fn init(client: LlmClient) -> bool {
+    CLIENT.set(client).is_ok()
+}
+
+/// The shared client, configured from the environment on first use unless [`init`] was
+/// called
+pub fn client() -> Result<&'static LlmClient, Error> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client);
+    }
+    let client = LlmClient::from_env()?;
+    Ok(CLIENT.get_or_init(|| client))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Mutex,
+        },
+    };
+
+    fn test_config() -> LlmConfig {
+        LlmConfig {
+            provider: Some("fixture".to_string()),
+            model: None,
+            base_url: None,
+            timeout: Duration::from_secs(5),
+            max_retries: 2,
+            tasks: HashMap::from([(
+                "sentiment".to_string(),
+                config::LlmTaskConfig::new(Some(0.0), 40),
+            )]),
+        }
+    }
+
+    /// Fails the first `failures` calls with `error`, then answers with `content`
+    struct FlakyProvider {
+        calls: AtomicU32,
+        failures: u32,
+        error: fn() -> Error,
+        content: String,
+    }
+
+    #[async_trait]
+    impl LlmProvider for FlakyProvider {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        fn default_model(&self) -> &str {
+            "flaky-1"
+        }
+
+        async fn complete(&self, request: &CompletionRequest) -> Result<Completion, Error> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.failures {
+                return Err((self.error)());
+            }
+            Ok(Completion {
+                content: self.content.clone(),
+                model: request.model.clone(),
+                usage: Usage {
+                    prompt_tokens: 10,
+                    completion_tokens: 2,
+                },
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingMetrics {
+        usage: Mutex<Vec<(String, String, Usage)>>,
+        failures: AtomicU32,
+    }
+
+    impl LlmMetrics for Arc<RecordingMetrics> {
+        fn record_usage(&self, _provider: &str, model: &str, task: &str, usage: &Usage) {
+            self.usage
+                .lock()
+                .unwrap()
+                .push((model.to_string(), task.to_string(), *usage));
+        }
+
+        fn record_failure(&self, _provider: &str, _task: &str) {
+            self.failures.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Sentiment {
+        sentiment: String,
+    }
+
+    fn sentiment_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "sentiment": { "type": "string", "enum": ["positive", "negative", "neutral"] }
+            },
+            "required": ["sentiment"]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_fixture_provider_is_deterministic() {
+        let client = LlmClient::new(
+            FixtureProvider::new().with_response("group_summary", "They agree on parks."),
+            test_config(),
+        );
+        let summary = client
+            .complete("group_summary", vec![Message::user("Summarize")])
+            .await
+            .unwrap();
+        assert_eq!(summary, "They agree on parks.");
+        let overview = client
+            .complete("opinion_summary", vec![Message::user("Summarize")])
+            .await
+            .unwrap();
+        assert_eq!(overview, "Fixture response for opinion_summary.");
+
+        let sentiment: Sentiment = client
+            .complete_json(
+                "sentiment",
+                vec![Message::user("Great!")],
+                &sentiment_schema(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(sentiment.sentiment, "positive");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_transient_errors() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let client = LlmClient::new(
+            FlakyProvider {
+                calls: AtomicU32::new(0),
+                failures: 2,
+                error: || Error::Status {
+                    status: 429,
+                    body: "slow down".to_string(),
+                },
+                content: "Hello".to_string(),
+            },
+            test_config(),
+        )
+        .with_metrics(metrics.clone());
+
+        let content = client
+            .complete("sentiment", vec![Message::user("Hi")])
+            .await
+            .unwrap();
+        assert_eq!(content, "Hello");
+        let usage = metrics.usage.lock().unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].0, "flaky-1");
+        assert_eq!(usage[0].1, "sentiment");
+        assert_eq!(metrics.failures.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_on_client_errors() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let provider = FlakyProvider {
+            calls: AtomicU32::new(0),
+            failures: 5,
+            error: || Error::Status {
+                status: 401,
+                body: "bad key".to_string(),
+            },
+            content: "Hello".to_string(),
+        };
+        let client = LlmClient::new(provider, test_config()).with_metrics(metrics.clone());
+
+        let err = client
+            .complete("sentiment", vec![Message::user("Hi")])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Status { status: 401, .. }));
+        assert_eq!(metrics.failures.load(Ordering::SeqCst), 1);
+        assert!(metrics.usage.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_invalid_structured_output_is_retried_then_rejected() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let client = LlmClient::new(
+            FlakyProvider {
+                calls: AtomicU32::new(0),
+                failures: 0,
+                error: || Error::Request("unused".to_string()),
+                content: r#"{"sentiment": "ecstatic"}"#.to_string(),
+            },
+            test_config(),
+        )
+        .with_metrics(metrics.clone());
+
+        let err = client
+            .complete_json::<Sentiment>("sentiment", vec![Message::user("Hi")], &sentiment_schema())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidOutput(_)));
+        // Every attempt still spent tokens
+        assert_eq!(metrics.usage.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_provider_times_out() {
+        struct SlowProvider;
+
+        #[async_trait]
+        impl LlmProvider for SlowProvider {
+            fn name(&self) -> &'static str {
+                "slow"
+            }
+
+            fn default_model(&self) -> &str {
+                "slow-1"
+            }
+
+            async fn complete(&self, _request: &CompletionRequest) -> Result<Completion, Error> {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+                Err(Error::Request("unreachable".to_string()))
+            }
+        }
+
+        let client = LlmClient::new(SlowProvider, test_config());
+        let err = client
+            .complete("sentiment", vec![Message::user("Hi")])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)));
+    }
+
+    #[test]
+    fn test_task_settings_resolve_model() {
+        let mut config = test_config();
+        let client = LlmClient::new(FixtureProvider::new(), config.clone());
+        let request = client.request("sentiment", vec![], None);
+        assert_eq!(request.model, "fixture");
+        assert_eq!(request.temperature, Some(0.0));
+        assert_eq!(request.max_tokens, 40);
+
+        config.model = Some("gpt-4o".to_string());
+        config
+            .tasks
+            .get_mut("sentiment")
+            .unwrap()
+            .model
+            .replace("gpt-4o-mini".to_string());
+        let client = LlmClient::new(FixtureProvider::new(), config);
+        assert_eq!(
+            client.request("sentiment", vec![], None).model,
+            "gpt-4o-mini"
+        );
+        assert_eq!(client.request("bill_summary", vec![], None).model, "gpt-4o");
+    }
+}
@@ -0,0 +1,305 @@
+use async_trait::async_trait;
+use config::LlmConfig;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{Completion, CompletionRequest, Error, FixtureProvider, LlmProvider, Role, Usage};
+
+/// Chat completions against OpenAI, or any server that speaks the same API such as Ollama,
+/// vLLM or llama.cpp
+pub struct OpenAiProvider {
+    http: reqwest::Client,
+    name: &'static str,
+    base_url: String,
+    api_key: Option<String>,
+    default_model: String,
+}
+
+impl OpenAiProvider {
+    pub fn new(api_key: String) -> Self {
+        OpenAiProvider {
+            http: reqwest::Client::new(),
+            name: "openai",
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key: Some(api_key),
+            default_model: "gpt-4o-mini".to_string(),
+        }
+    }
+
+    /// An OpenAI compatible server at `base_url`, e.g. `http://localhost:11434/v1`. Local
+    /// servers rarely check the key, so it's optional.
+    pub fn compatible(base_url: &str, model: String, api_key: Option<String>) -> Self {
+        OpenAiProvider {
+            http: reqwest::Client::new(),
+            name: "local",
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            default_model: model,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct OpenAiResponse {
+    model: Option<String>,
+    choices: Vec<OpenAiChoice>,
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiChoice {
+    message: OpenAiMessage,
+}
+
+#[derive(Deserialize)]
+struct OpenAiMessage {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_model(&self) -> &str {
+        &self.default_model
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, Error> {
+        let messages: Vec<Value> = request
+            .messages
+            .iter()
+            .map(|message| json!({ "role": message.role.as_str(), "content": message.content }))
+            .collect();
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens,
+        });
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if request.json_schema.is_some() {
+            // The schema itself is in the prompt, this only guarantees well formed JSON
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let mut http_request = self
+            .http
+            .post(format!("{}/chat/completions", self.base_url))
+            .json(&body);
+        if let Some(api_key) = &self.api_key {
+            http_request = http_request.bearer_auth(api_key);
+        }
+        let response = send(http_request).await?;
+        let response: OpenAiResponse = response
+            .json()
+            .await
+            .map_err(|err| Error::InvalidOutput(err.to_string()))?;
+
+        let content = response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| Error::InvalidOutput("Response has no content".to_string()))?;
+        let usage = response
+            .usage
+            .map(|usage| Usage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+            })
+            .unwrap_or_default();
+
+        Ok(Completion {
+            content,
+            model: response.model.unwrap_or_else(|| request.model.clone()),
+            usage,
+        })
+    }
+}
+
+/// Anthropic's Messages API
+pub struct AnthropicProvider {
+    http: reqwest::Client,
+    api_key: String,
+}
+
+impl AnthropicProvider {
+    const API_URL: &'static str = "https://api.anthropic.com/v1/messages";
+    const API_VERSION: &'static str = "2023-06-01";
+
+    pub fn new(api_key: String) -> Self {
+        AnthropicProvider {
+            http: reqwest::Client::new(),
+            api_key,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AnthropicResponse {
+    model: Option<String>,
+    content: Vec<AnthropicContent>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicContent {
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn default_model(&self) -> &str {
+        "claude-3-5-haiku-latest"
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<Completion, Error> {
+        // System prompts are a separate field rather than a message here
+        let system = request
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::System)
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let messages: Vec<Value> = request
+            .messages
+            .iter()
+            .filter(|message| message.role != Role::System)
+            .map(|message| json!({ "role": message.role.as_str(), "content": message.content }))
+            .collect();
+        let mut body = json!({
+            "model": request.model,
+            "messages": messages,
+            "max_tokens": request.max_tokens,
+        });
+        if !system.is_empty() {
+            body["system"] = json!(system);
+        }
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature.min(1.0));
+        }
+
+        let response = send(
+            self.http
+                .post(Self::API_URL)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", Self::API_VERSION)
+                .json(&body),
+        )
+        .await?;
+        let response: AnthropicResponse = response
+            .json()
+            .await
+            .map_err(|err| Error::InvalidOutput(err.to_string()))?;
+
+        let content = response
+            .content
+            .into_iter()
+            .filter_map(|content| content.text)
+            .collect::<String>();
+        if content.is_empty() {
+            return Err(Error::InvalidOutput("Response has no content".to_string()));
+        }
+        let usage = response
+            .usage
+            .map(|usage| Usage {
+                prompt_tokens: usage.input_tokens,
+                completion_tokens: usage.output_tokens,
+            })
+            .unwrap_or_default();
+
+        Ok(Completion {
+            content,
+            model: response.model.unwrap_or_else(|| request.model.clone()),
+            usage,
+        })
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, Error> {
+    let response = request
+        .send()
+        .await
+        .map_err(|err| Error::Request(err.to_string()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Status {
+            status: status.as_u16(),
+            body,
+        });
+    }
+    Ok(response)
+}
+
+/// Picks a provider with `LLM_PROVIDER` (`openai`, `anthropic`, `local` or `fixture`).
+/// Without it, calls go to OpenAI when `OPENAI_API_KEY` is set, then Anthropic when
+/// `ANTHROPIC_API_KEY` is, and are answered by fixtures otherwise.
+pub fn provider_from_config(config: &LlmConfig) -> Result<Box<dyn LlmProvider>, Error> {
+    let key = |name: &str| dotenv::var(name).ok().filter(|key| !key.trim().is_empty());
+    let openai_api_key = key("OPENAI_API_KEY");
+    let anthropic_api_key = key("ANTHROPIC_API_KEY");
+    let provider = config.provider.clone().unwrap_or_else(|| {
+        match (&openai_api_key, &anthropic_api_key) {
+            (Some(_), _) => "openai",
+            (None, Some(_)) => "anthropic",
+            (None, None) => {
+                warn!("No LLM API key is set, answering LLM calls with fixtures");
+                "fixture"
+            }
+        }
+        .to_string()
+    });
+
+    match provider.as_str() {
+        "openai" => {
+            let api_key = openai_api_key
+                .ok_or_else(|| Error::Config("OPENAI_API_KEY must be set".to_string()))?;
+            Ok(Box::new(OpenAiProvider::new(api_key)))
+        }
+        "anthropic" => {
+            let api_key = anthropic_api_key
+                .ok_or_else(|| Error::Config("ANTHROPIC_API_KEY must be set".to_string()))?;
+            Ok(Box::new(AnthropicProvider::new(api_key)))
+        }
+        "local" => {
+            let base_url = config.base_url.as_deref().ok_or_else(|| {
+                Error::Config("LLM_BASE_URL must be set for the local provider".to_string())
+            })?;
+            let model = config.model.clone().ok_or_else(|| {
+                Error::Config("LLM_MODEL must be set for the local provider".to_string())
+            })?;
+            Ok(Box::new(OpenAiProvider::compatible(
+                base_url,
+                model,
+                key("LLM_API_KEY"),
+            )))
+        }
+        "fixture" => Ok(Box::new(FixtureProvider::new())),
+        other => Err(Error::Config(format!(
+            "Unknown LLM_PROVIDER {}, expected openai, anthropic, local or fixture",
+            other
+        ))),
+    }
+}
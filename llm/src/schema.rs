@@ -0,0 +1,240 @@
+//! Checks structured output against the subset of JSON Schema we ask models for: `type`,
+//! `enum`, `properties`, `required`, `additionalProperties` and `items`. Other keywords are
+//! ignored.
+
+use serde_json::{Map, Value};
+
+/// Returns a description of the first place `value` doesn't match `schema`
+pub fn validate(schema: &Value, value: &Value) -> Result<(), String> {
+    validate_at(schema, value, "$")
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        // `true` and `{}` accept anything, `false` accepts nothing
+        return match schema {
+            Value::Bool(false) => Err(format!("{} is not allowed", path)),
+            _ => Ok(()),
+        };
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            return Err(format!(
+                "{} should be {} but is {}",
+                path,
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{} should be one of {}",
+                path,
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        validate_object(schema, object, path)?;
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+) -> Result<(), String> {
+    let properties = schema.get("properties").and_then(Value::as_object);
+
+    if let Some(Value::Array(required)) = schema.get("required") {
+        if let Some(missing) = required
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|key| !object.contains_key(*key))
+        {
+            return Err(format!("{} is missing {}", path, missing));
+        }
+    }
+
+    for (key, property) in object {
+        let property_path = format!("{}.{}", path, key);
+        match properties.and_then(|properties| properties.get(key)) {
+            Some(property_schema) => validate_at(property_schema, property, &property_path)?,
+            None => {
+                if let Some(additional) = schema.get("additionalProperties") {
+                    validate_at(additional, property, &property_path)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn has_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// The simplest value that satisfies `schema`, used by the fixture provider
+pub fn example(schema: &Value) -> Value {
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if let Some(first) = allowed.first() {
+            return first.clone();
+        }
+    }
+
+    let expected = match schema.get("type") {
+        Some(Value::String(t)) => t.as_str(),
+        Some(Value::Array(types)) => types.first().and_then(Value::as_str).unwrap_or("null"),
+        _ => "null",
+    };
+    match expected {
+        "object" => {
+            let properties = schema
+                .get("properties")
+                .and_then(Value::as_object)
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .map(|(key, property)| (key.clone(), example(property)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Value::Object(properties)
+        }
+        "array" => Value::Array(vec![]),
+        "string" => Value::String(String::new()),
+        "number" | "integer" => Value::from(0),
+        "boolean" => Value::Bool(false),
+        _ => Value::Null,
+    }
+}
+
+/// Pulls the JSON out of a response, dropping the Markdown code fence some models wrap it in
+pub fn extract_json(content: &str) -> Result<Value, String> {
+    let trimmed = content.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+    serde_json::from_str(unfenced.trim()).map_err(|err| format!("Response is not JSON: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sentiment_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "sentiment": { "type": "string", "enum": ["positive", "negative", "neutral"] },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "confidence": { "type": ["number", "null"] }
+            },
+            "required": ["sentiment"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = sentiment_schema();
+        assert!(validate(&schema, &json!({ "sentiment": "positive" })).is_ok());
+        assert!(validate(
+            &schema,
+            &json!({ "sentiment": "neutral", "tags": ["tax"], "confidence": null })
+        )
+        .is_ok());
+
+        assert_eq!(
+            validate(&schema, &json!({ "tags": [] })).unwrap_err(),
+            "$ is missing sentiment"
+        );
+        assert_eq!(
+            validate(&schema, &json!({ "sentiment": "angry" })).unwrap_err(),
+            r#"$.sentiment should be one of ["positive","negative","neutral"]"#
+        );
+        assert_eq!(
+            validate(
+                &schema,
+                &json!({ "sentiment": "positive", "tags": ["tax", 3] })
+            )
+            .unwrap_err(),
+            "$.tags[1] should be string but is number"
+        );
+        assert_eq!(
+            validate(&schema, &json!({ "sentiment": "positive", "extra": 1 })).unwrap_err(),
+            "$.extra is not allowed"
+        );
+        assert!(validate(&schema, &json!(["positive"])).is_err());
+    }
+
+    #[test]
+    fn test_validate_additional_properties_schema() {
+        let schema = json!({
+            "type": "object",
+            "additionalProperties": { "type": "string" }
+        });
+        assert!(validate(&schema, &json!({ "es": "Hola", "fr": "Bonjour" })).is_ok());
+        assert!(validate(&schema, &json!({ "es": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_example_satisfies_schema() {
+        let schema = sentiment_schema();
+        let value = example(&schema);
+        assert_eq!(value["sentiment"], "positive");
+        assert!(validate(&schema, &value).is_ok());
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(
+            extract_json("```json\n{\"es\": \"Hola\"}\n```").unwrap(),
+            json!({ "es": "Hola" })
+        );
+        assert_eq!(extract_json(" [1, 2] ").unwrap(), json!([1, 2]));
+        assert!(extract_json("Sure! Here you go").is_err());
+    }
+}
@@ -1,145 +1,128 @@
-use sendgrid::v3::{Email, Message, Personalization, Sender};
-use serde_json::json;
+mod templates;
+mod transport;
 
-static POPULIST_FROM_EMAIL: &str = "info@populist.us";
-static WELCOME_EMAIL_TEMPLATE_ID: &str = "d-edaebe0011f441348a0f310c05813cb0";
-static FORGOT_PASSWORD_TEMPLATE_ID: &str = "d-819b5a97194e4b3e99efa5ec2d9c6e6e";
-static PASSWORD_CHANGED_TEMPLATE_ID: &str = "d-a5a79e8740864187aadfdd0bc07bbb97";
-static INVITE_EMAIL_TEMPLATE_ID: &str = "d-4a43724169e54aa7a3a12553f7163808";
+use serde::{Deserialize, Serialize};
 
-pub struct EmailClient {
-    from: Email,
-    sender: Sender,
-}
+pub use templates::{CandidateGuideOutreach, EmailTemplate};
+pub use transport::{
+    transport_from_env, EmailTransport, FileTransport, LogTransport, SendgridTransport,
+    SmtpTransport,
+};
 
-impl Default for EmailClient {
-    fn default() -> Self {
-        let api_key = dotenv::var("SENDGRID_API_KEY").expect("SENDGRID_API_KEY must be set");
-        Self {
-            from: Email::new(POPULIST_FROM_EMAIL).set_name("Populist"),
-            sender: Sender::new(api_key),
-        }
-    }
+pub static POPULIST_FROM_EMAIL: &str = "info@populist.us";
+pub static POPULIST_FROM_NAME: &str = "Populist";
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error("Failed to render email: {0}")]
+    Template(#[from] tera::Error),
+    #[error("Email transport is misconfigured: {0}")]
+    Config(String),
+    #[error("Failed to send email: {0}")]
+    Transport(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
-impl EmailClient {
-    pub fn new(api_key: String) -> Self {
-        Self {
-            from: Email::new(POPULIST_FROM_EMAIL).set_name("Populist"),
-            sender: Sender::new(api_key),
-        }
-    }
+/// A rendered email, ready to hand to a transport
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
 
-    // Send out email with username, and link to confirm & set password
-    pub async fn send_welcome_email(
-        &self,
-        recipient_email: String,
-        account_confirmation_url: String,
-    ) -> Result<u16, sendgrid::SendgridError> {
-        let p = Personalization::new(Email::new(&recipient_email))
-            .add_dynamic_template_data_json(&json!({
-                "account_confirmation_url": &account_confirmation_url
-            }))
-            .unwrap();
-        let mail = Message::new(self.from.clone())
-            .set_template_id(WELCOME_EMAIL_TEMPLATE_ID)
-            .add_personalization(p);
-        let response = self.sender.send(&mail).await;
-        let status = response.unwrap().status();
-        Ok(status.into())
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub async fn send_invite_email(
-        &self,
-        recipient_email: String,
-        invite_url: String,
-        organization_name: Option<String>,
-        politician_name: Option<String>,
-    ) -> Result<u16, sendgrid::SendgridError> {
-        let p = Personalization::new(Email::new(&recipient_email))
-            .add_dynamic_template_data_json(&json!({ "organization_name": &organization_name, "politician_name": &politician_name, "invite_url": &invite_url }))
-            .unwrap();
-        let mail = Message::new(self.from.clone())
-            .set_template_id(INVITE_EMAIL_TEMPLATE_ID)
-            .add_personalization(p);
-        let response = self.sender.send(&mail).await;
-        let status = response.unwrap().status();
-        Ok(status.into())
-    }
+    #[test]
+    fn test_render_reset_password() {
+        let message = EmailTemplate::ResetPassword {
+            reset_password_url: "https://populist.us/auth/reset-password?token=abc&x=1".to_string(),
+        }
+        .render("test@populist.us")
+        .unwrap();
 
-    pub async fn send_reset_password_email(
-        &self,
-        recipient_email: String,
-        reset_password_url: String,
-    ) -> Result<u16, sendgrid::SendgridError> {
-        let p = Personalization::new(Email::new(&recipient_email))
-            .add_dynamic_template_data_json(&json!({ "reset_password_url": &reset_password_url }))
-            .unwrap();
-        let mail = Message::new(self.from.clone())
-            .set_template_id(FORGOT_PASSWORD_TEMPLATE_ID)
-            .add_personalization(p);
-        let response = self.sender.send(&mail).await;
-        let status = response.unwrap().status();
-        Ok(status.into())
+        assert_eq!(message.to, "test@populist.us");
+        assert_eq!(message.subject, "Reset your Populist password");
+        assert!(message
+            .text
+            .contains("https://populist.us/auth/reset-password?token=abc&x=1"));
+        // Links are escaped in HTML, never in the plain text body
+        assert!(message.html.contains("token=abc&amp;x=1"));
     }
 
-    pub async fn send_password_changed_email(
-        &self,
-        recipient_email: String,
-    ) -> Result<u16, sendgrid::SendgridError> {
-        let p = Personalization::new(Email::new(&recipient_email));
-        let mail = Message::new(self.from.clone())
-            .set_template_id(PASSWORD_CHANGED_TEMPLATE_ID)
-            .add_personalization(p);
+    #[test]
+    fn test_render_invite() {
+        let organization_invite = EmailTemplate::Invite {
+            invite_url: "https://populist.us/invite".to_string(),
+            organization_name: Some("League of <Voters>".to_string()),
+            politician_name: None,
+        }
+        .render("test@populist.us")
+        .unwrap();
+        assert_eq!(
+            organization_invite.subject,
+            "You've been invited to join League of <Voters> on Populist"
+        );
+        assert!(organization_invite
+            .html
+            .contains("League of &lt;Voters&gt;"));
 
-        let response = self.sender.send(&mail).await;
-        let status = response.unwrap().status();
-        Ok(status.into())
+        let politician_invite = EmailTemplate::Invite {
+            invite_url: "https://populist.us/invite".to_string(),
+            organization_name: None,
+            politician_name: Some("Ada Lovelace".to_string()),
+        }
+        .render("test@populist.us")
+        .unwrap();
+        assert!(politician_invite
+            .text
+            .contains("manage the Populist profile of Ada Lovelace"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::EmailClient;
-    use dotenv::dotenv;
+    #[test]
+    fn test_render_candidate_guide_invite() {
+        let message = EmailTemplate::CandidateGuideInvite(CandidateGuideOutreach {
+            candidate_name: "Ada Lovelace".to_string(),
+            organization_name: "League of Voters".to_string(),
+            guide_name: None,
+            race_title: "Minneapolis Mayor".to_string(),
+            form_url: "https://api.populist.us/outreach/abc/click".to_string(),
+            open_url: "https://api.populist.us/outreach/abc/open".to_string(),
+            deadline: "October 31, 2026".to_string(),
+        })
+        .render("candidate@example.com")
+        .unwrap();
 
-    #[tokio::test]
-    #[ignore]
-    async fn test_send_welcome_email() {
-        dotenv().ok();
-        let client = EmailClient::default();
-        let result = client
-            .send_welcome_email(
-                "wileymckayconte@gmail.com".to_string(),
-                "https://populist.us/test".to_string(),
-            )
-            .await;
-        assert_eq!(result.unwrap(), 202);
+        assert_eq!(
+            message.subject,
+            "League of Voters candidate questionnaire: Minneapolis Mayor"
+        );
+        assert!(message
+            .text
+            .contains("https://api.populist.us/outreach/abc/click"));
+        // Slashes are escaped in HTML attributes, which browsers decode
+        assert!(message
+            .html
+            .contains("api.populist.us&#x2F;outreach&#x2F;abc&#x2F;open"));
+        // Only the HTML body can track opens
+        assert!(!message.text.contains("/open"));
     }
 
     #[tokio::test]
-    #[ignore]
-    async fn test_send_reset_password_email() {
-        dotenv().ok();
-        let client = EmailClient::default();
-        let result = client
-            .send_reset_password_email(
-                "wiley@populist.us".to_string(),
-                "https://populist.us/test".to_string(),
-            )
-            .await;
+    async fn test_file_transport() {
+        let dir = std::env::temp_dir().join(format!("mailers-{}", uuid::Uuid::new_v4()));
+        let transport = FileTransport::new(&dir);
+        let message = EmailTemplate::PasswordChanged
+            .render("test@populist.us")
+            .unwrap();
 
-        assert_eq!(result.unwrap(), 202);
-    }
+        transport.send(&message).await.unwrap();
+        assert_eq!(transport.messages().unwrap(), vec![message]);
 
-    #[tokio::test]
-    #[ignore]
-    async fn test_password_changed_email() {
-        dotenv().ok();
-        let client = EmailClient::default();
-        let result = client
-            .send_password_changed_email("wiley@populist.us".to_string())
-            .await;
-        assert_eq!(result.unwrap(), 202);
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,166 @@
+use std::sync::LazyLock;
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::{EmailMessage, Error};
+
+/// Templates are compiled into the binary so every environment renders the same mail
+static TEMPLATES: LazyLock<Tera> = LazyLock::new(|| {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(vec![
+        ("base.html", include_str!("../templates/base.html")),
+        ("welcome.html", include_str!("../templates/welcome.html")),
+        ("welcome.txt", include_str!("../templates/welcome.txt")),
+        ("invite.html", include_str!("../templates/invite.html")),
+        ("invite.txt", include_str!("../templates/invite.txt")),
+        (
+            "reset_password.html",
+            include_str!("../templates/reset_password.html"),
+        ),
+        (
+            "reset_password.txt",
+            include_str!("../templates/reset_password.txt"),
+        ),
+        (
+            "password_changed.html",
+            include_str!("../templates/password_changed.html"),
+        ),
+        (
+            "password_changed.txt",
+            include_str!("../templates/password_changed.txt"),
+        ),
+        (
+            "candidate_guide_invite.html",
+            include_str!("../templates/candidate_guide_invite.html"),
+        ),
+        (
+            "candidate_guide_invite.txt",
+            include_str!("../templates/candidate_guide_invite.txt"),
+        ),
+        (
+            "candidate_guide_reminder.html",
+            include_str!("../templates/candidate_guide_reminder.html"),
+        ),
+        (
+            "candidate_guide_reminder.txt",
+            include_str!("../templates/candidate_guide_reminder.txt"),
+        ),
+    ])
+    .expect("Email templates failed to compile");
+    tera
+});
+
+/// What a candidate needs to answer a candidate guide's questionnaire
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateGuideOutreach {
+    pub candidate_name: String,
+    pub organization_name: String,
+    pub guide_name: Option<String>,
+    pub race_title: String,
+    /// The candidate's intake form, linked through the click tracker
+    pub form_url: String,
+    /// Tracking pixel that records the email was opened
+    pub open_url: String,
+    /// Already formatted for display, e.g. `October 31, 2026`
+    pub deadline: String,
+}
+
+/// Every email we send, along with the data its template needs
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    /// Sent on sign up, with a link to confirm the account
+    Welcome {
+        account_confirmation_url: String,
+    },
+    /// Sent when someone is invited to an organization or to manage a politician
+    Invite {
+        invite_url: String,
+        organization_name: Option<String>,
+        politician_name: Option<String>,
+    },
+    ResetPassword {
+        reset_password_url: String,
+    },
+    PasswordChanged,
+    /// Asks a candidate to answer an organization's candidate guide questions
+    CandidateGuideInvite(CandidateGuideOutreach),
+    /// Sent before the deadline to candidates who haven't answered yet
+    CandidateGuideReminder(CandidateGuideOutreach),
+}
+
+impl EmailTemplate {
+    /// Identifies the template in the outbox, and names its files in `templates/`
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmailTemplate::Welcome { .. } => "welcome",
+            EmailTemplate::Invite { .. } => "invite",
+            EmailTemplate::ResetPassword { .. } => "reset_password",
+            EmailTemplate::PasswordChanged => "password_changed",
+            EmailTemplate::CandidateGuideInvite(_) => "candidate_guide_invite",
+            EmailTemplate::CandidateGuideReminder(_) => "candidate_guide_reminder",
+        }
+    }
+
+    fn subject(&self) -> String {
+        match self {
+            EmailTemplate::Welcome { .. } => "Welcome to Populist".to_string(),
+            EmailTemplate::Invite {
+                organization_name: Some(organization_name),
+                ..
+            } => format!(
+                "You've been invited to join {} on Populist",
+                organization_name
+            ),
+            EmailTemplate::Invite { .. } => "You've been invited to Populist".to_string(),
+            EmailTemplate::ResetPassword { .. } => "Reset your Populist password".to_string(),
+            EmailTemplate::PasswordChanged => "Your Populist password was changed".to_string(),
+            EmailTemplate::CandidateGuideInvite(outreach) => format!(
+                "{} candidate questionnaire: {}",
+                outreach.organization_name, outreach.race_title
+            ),
+            EmailTemplate::CandidateGuideReminder(outreach) => format!(
+                "Reminder: {} candidate questionnaire due {}",
+                outreach.organization_name, outreach.deadline
+            ),
+        }
+    }
+
+    fn context(&self) -> Result<Context, Error> {
+        let mut context = Context::new();
+        match self {
+            EmailTemplate::Welcome {
+                account_confirmation_url,
+            } => context.insert("account_confirmation_url", account_confirmation_url),
+            EmailTemplate::Invite {
+                invite_url,
+                organization_name,
+                politician_name,
+            } => {
+                context.insert("invite_url", invite_url);
+                context.insert("organization_name", organization_name);
+                context.insert("politician_name", politician_name);
+            }
+            EmailTemplate::ResetPassword { reset_password_url } => {
+                context.insert("reset_password_url", reset_password_url)
+            }
+            EmailTemplate::PasswordChanged => {}
+            EmailTemplate::CandidateGuideInvite(outreach)
+            | EmailTemplate::CandidateGuideReminder(outreach) => {
+                context = Context::from_serialize(outreach)?
+            }
+        }
+        Ok(context)
+    }
+
+    /// Renders the HTML and plain text bodies of the email for a recipient
+    pub fn render(&self, to: &str) -> Result<EmailMessage, Error> {
+        let context = self.context()?;
+        Ok(EmailMessage {
+            to: to.to_string(),
+            subject: self.subject(),
+            html: TEMPLATES.render(&format!("{}.html", self.name()), &context)?,
+            text: TEMPLATES.render(&format!("{}.txt", self.name()), &context)?,
+        })
+    }
+}
@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use lettre::{
+    message::{Mailbox, MultiPart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+};
+use sendgrid::v3::{Content, Email, Message, Personalization, Sender};
+use tracing::info;
+
+use crate::{EmailMessage, Error, POPULIST_FROM_EMAIL, POPULIST_FROM_NAME};
+
+/// Delivers rendered email. Implementations should return an error for anything the
+/// provider didn't accept so the outbox can retry it.
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), Error>;
+}
+
+pub struct SendgridTransport {
+    sender: Sender,
+}
+
+impl SendgridTransport {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            sender: Sender::new(api_key),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SendgridTransport {
+    async fn send(&self, message: &EmailMessage) -> Result<(), Error> {
+        let mail = Message::new(Email::new(POPULIST_FROM_EMAIL).set_name(POPULIST_FROM_NAME))
+            .set_subject(&message.subject)
+            .add_content(
+                Content::new()
+                    .set_content_type("text/plain")
+                    .set_value(&message.text),
+            )
+            .add_content(
+                Content::new()
+                    .set_content_type("text/html")
+                    .set_value(&message.html),
+            )
+            .add_personalization(Personalization::new(Email::new(&message.to)));
+
+        let response = self
+            .sender
+            .send(&mail)
+            .await
+            .map_err(|err| Error::Transport(err.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Transport(format!(
+                "SendGrid responded with {}: {}",
+                status, body
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SmtpTransport {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    /// Reads `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME` and `SMTP_PASSWORD`. Set
+    /// `SMTP_TLS=none` for local catchers like Mailpit that don't speak TLS.
+    pub fn from_env() -> Result<Self, Error> {
+        let host = dotenv::var("SMTP_HOST")
+            .map_err(|_| Error::Config("SMTP_HOST must be set".to_string()))?;
+        let mut builder = match dotenv::var("SMTP_TLS").as_deref() {
+            Ok("none") => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+                .map_err(|err| Error::Config(err.to_string()))?,
+        };
+        if let Some(port) = dotenv::var("SMTP_PORT").ok().and_then(|p| p.parse().ok()) {
+            builder = builder.port(port);
+        }
+        if let (Ok(username), Ok(password)) =
+            (dotenv::var("SMTP_USERNAME"), dotenv::var("SMTP_PASSWORD"))
+        {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self {
+            mailer: builder.build(),
+        })
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    async fn send(&self, message: &EmailMessage) -> Result<(), Error> {
+        let from = Mailbox::new(
+            Some(POPULIST_FROM_NAME.to_string()),
+            POPULIST_FROM_EMAIL
+                .parse()
+                .map_err(|err: lettre::address::AddressError| Error::Config(err.to_string()))?,
+        );
+        let to: Mailbox = message
+            .to
+            .parse()
+            .map_err(|err: lettre::address::AddressError| Error::Transport(err.to_string()))?;
+        let mail = lettre::Message::builder()
+            .from(from)
+            .to(to)
+            .subject(&message.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                message.text.clone(),
+                message.html.clone(),
+            ))
+            .map_err(|err| Error::Transport(err.to_string()))?;
+
+        self.mailer
+            .send(mail)
+            .await
+            .map_err(|err| Error::Transport(err.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Writes each message to a JSON file instead of sending it, so flows that send email
+/// can be run and inspected in tests and staging
+pub struct FileTransport {
+    dir: PathBuf,
+}
+
+impl FileTransport {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Messages written so far, oldest first
+    pub fn messages(&self) -> Result<Vec<EmailMessage>, Error> {
+        let mut paths = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                let contents = std::fs::read_to_string(path)?;
+                serde_json::from_str(&contents).map_err(|err| Error::Transport(err.to_string()))
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EmailTransport for FileTransport {
+    async fn send(&self, message: &EmailMessage) -> Result<(), Error> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Prefixed with the time so files sort in the order they were sent
+        let file_name = format!(
+            "{}-{}.json",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_micros(),
+            uuid::Uuid::new_v4()
+        );
+        let contents = serde_json::to_string_pretty(message)
+            .map_err(|err| Error::Transport(err.to_string()))?;
+        tokio::fs::write(self.dir.join(file_name), contents).await?;
+
+        Ok(())
+    }
+}
+
+/// Logs each message instead of sending it
+pub struct LogTransport;
+
+#[async_trait]
+impl EmailTransport for LogTransport {
+    async fn send(&self, message: &EmailMessage) -> Result<(), Error> {
+        info!(
+            "Email to {} ({}):\n{}",
+            message.to, message.subject, message.text
+        );
+        Ok(())
+    }
+}
+
+/// Picks a transport with `EMAIL_TRANSPORT` (`sendgrid`, `smtp`, `file` or `log`). Without
+/// it, mail goes through SendGrid when `SENDGRID_API_KEY` is set. Otherwise it's logged,
+/// except in production, where logging would expose password reset and invite links.
+pub fn transport_from_env() -> Result<Box<dyn EmailTransport>, Error> {
+    let sendgrid_api_key = dotenv::var("SENDGRID_API_KEY").ok();
+    let transport = match dotenv::var("EMAIL_TRANSPORT") {
+        Ok(transport) => transport,
+        Err(_) if sendgrid_api_key.is_some() => "sendgrid".to_string(),
+        Err(_) if config::Config::default().environment == config::Environment::Production => {
+            return Err(Error::Config(
+                "EMAIL_TRANSPORT or SENDGRID_API_KEY must be set in production".to_string(),
+            ))
+        }
+        Err(_) => "log".to_string(),
+    };
+
+    match transport.as_str() {
+        "sendgrid" => {
+            let api_key = sendgrid_api_key
+                .ok_or_else(|| Error::Config("SENDGRID_API_KEY must be set".to_string()))?;
+            Ok(Box::new(SendgridTransport::new(api_key)))
+        }
+        "smtp" => Ok(Box::new(SmtpTransport::from_env()?)),
+        "file" => Ok(Box::new(FileTransport::new(
+            dotenv::var("EMAIL_FILE_DIR").unwrap_or_else(|_| "emails".to_string()),
+        ))),
+        "log" => Ok(Box::new(LogTransport)),
+        other => Err(Error::Config(format!(
+            "Unknown EMAIL_TRANSPORT {}, expected sendgrid, smtp, file or log",
+            other
+        ))),
+    }
+}
@@ -0,0 +1,58 @@
+use db::RankedChoiceRound;
+use scrapers::mn::rcv_ballots::import_cast_vote_records;
+
+const USAGE: &str =
+    "Usage: mn_import_rcv_ballots <race-id> <cast-vote-records-path-or-url> [--replace] [--tabulate]";
+
+/// Imports a Minneapolis or St. Paul cast vote record CSV into a ranked choice race,
+/// optionally tabulating the race afterwards
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().collect();
+    let positional: Vec<&String> = args
+        .iter()
+        .skip(1)
+        .filter(|a| !a.starts_with("--"))
+        .collect();
+    let (race_id, source) = match positional.as_slice() {
+        [race_id, source] => (
+            uuid::Uuid::parse_str(race_id).expect(USAGE),
+            source.as_str(),
+        ),
+        _ => {
+            println!("{}", USAGE);
+            return;
+        }
+    };
+    let replace = args.iter().any(|a| a == "--replace");
+    let tabulate = args.iter().any(|a| a == "--tabulate");
+
+    let csv_text = if source.starts_with("http") {
+        reqwest::get(source).await.unwrap().text().await.unwrap()
+    } else {
+        std::fs::read_to_string(source).unwrap()
+    };
+
+    db::init_pool().await.unwrap();
+    let pool = db::pool().await;
+    match import_cast_vote_records(&pool.connection, race_id, &csv_text, replace).await {
+        Ok(total) => println!("Race {} has {} ballots", race_id, total),
+        Err(err) => {
+            println!("Failed to import ballots: {}", err);
+            return;
+        }
+    }
+
+    if tabulate {
+        match RankedChoiceRound::tabulate_race(&pool.connection, race_id).await {
+            Ok(tabulation) => println!(
+                "Tabulated {} rounds, winners: {:?}",
+                tabulation.rounds.len(),
+                tabulation.winner_ids
+            ),
+            Err(err) => println!("Failed to tabulate race: {}", err),
+        }
+    }
+}
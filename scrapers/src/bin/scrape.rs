@@ -0,0 +1,136 @@
+//! Runs registered scrapers, processors and mergers and records every run in `scrape_run`.
+//!
+//! Usage:
+//!   scrape list
+//!   scrape run <source> [--stage scrape|process|merge] [--dry-run] [--local] [--<option> <value>]
+//!   scrape status [source]
+//!
+//! Without `--stage`, every stage of the source runs in order, stopping at the first failure.
+
+use db::{ScrapeRun, ScrapeRunFilter, ScrapeStage};
+use scrapers::registry::{self, RunOptions};
+
+const USAGE: &str = "Usage: scrape list | scrape run <source> [--stage scrape|process|merge] [--dry-run] [--local] [--<option> <value>] | scrape status [source]";
+
+#[tokio::main]
+async fn main() {
+    dotenvy::dotenv().ok();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("list") => {
+            list();
+            Ok(true)
+        }
+        Some("run") => match args.get(1) {
+            Some(source_id) if !source_id.starts_with("--") => run(source_id, &args[2..]).await,
+            _ => Err(USAGE.into()),
+        },
+        Some("status") => status(args.get(1).map(String::as_str)).await.map(|_| true),
+        _ => Err(USAGE.into()),
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn list() {
+    for pipeline in registry::registry() {
+        let stages: Vec<String> = pipeline
+            .stages()
+            .iter()
+            .map(|stage| {
+                if pipeline.dry_run_stages().contains(stage) {
+                    format!("{} (dry run)", stage)
+                } else {
+                    stage.to_string()
+                }
+            })
+            .collect();
+        println!("{}", pipeline.source_id());
+        println!("  {}", pipeline.description());
+        println!("  stages: {}", stages.join(", "));
+        for (option, help) in pipeline.options() {
+            println!("  --{}: {}", option, help);
+        }
+    }
+}
+
+/// Returns whether every stage succeeded
+async fn run(source_id: &str, args: &[String]) -> Result<bool, Box<dyn std::error::Error>> {
+    let pipeline = registry::find(source_id)
+        .ok_or_else(|| format!("Unknown source '{}', see `scrape list`", source_id))?;
+    let (stage, options) = RunOptions::from_args(args)?;
+    let stages: Vec<ScrapeStage> = match stage {
+        Some(stage) => vec![stage],
+        None => pipeline.stages().to_vec(),
+    };
+
+    db::init_pool().await?;
+    let pool = db::pool().await;
+
+    for stage in stages {
+        println!("=== {} {} ===\n", source_id, stage);
+        let run = registry::run_stage(pool, pipeline.as_ref(), stage, &options).await?;
+        print_run(&run);
+        if let Some(error) = run.error {
+            eprintln!("\n✗ {} {} failed: {}", source_id, stage, error);
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+async fn status(source_id: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    db::init_pool().await?;
+    let pool = db::pool().await;
+
+    let runs = match source_id {
+        Some(source_id) => {
+            ScrapeRun::filter(
+                &pool.connection,
+                &ScrapeRunFilter {
+                    source_id: Some(source_id.to_string()),
+                    ..Default::default()
+                },
+                20,
+            )
+            .await?
+        }
+        None => ScrapeRun::latest(&pool.connection).await?,
+    };
+
+    if runs.is_empty() {
+        println!("No runs recorded");
+    }
+    for run in runs {
+        print_run(&run);
+    }
+    Ok(())
+}
+
+fn print_run(run: &ScrapeRun) {
+    println!(
+        "{} {} {}{} at {} ({} ms)",
+        run.source_id,
+        run.stage,
+        run.status,
+        if run.dry_run { " [dry run]" } else { "" },
+        run.started_at,
+        run.duration_ms.unwrap_or_default(),
+    );
+    if let Some(counts) = run.counts.as_object() {
+        for (name, count) in counts {
+            println!("  {}: {}", name, count);
+        }
+    }
+    if let Some(error) = &run.error {
+        println!("  error: {}", error);
+    }
+}
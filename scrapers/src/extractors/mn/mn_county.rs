@@ -0,0 +1,118 @@
+/// County names in MN SoS county ID order, i.e. `MN_COUNTIES[0]` is county ID 1
+pub const MN_COUNTIES: [&str; 87] = [
+    "Aitkin",
+    "Anoka",
+    "Becker",
+    "Beltrami",
+    "Benton",
+    "Big Stone",
+    "Blue Earth",
+    "Brown",
+    "Carlton",
+    "Carver",
+    "Cass",
+    "Chippewa",
+    "Chisago",
+    "Clay",
+    "Clearwater",
+    "Cook",
+    "Cottonwood",
+    "Crow Wing",
+    "Dakota",
+    "Dodge",
+    "Douglas",
+    "Faribault",
+    "Fillmore",
+    "Freeborn",
+    "Goodhue",
+    "Grant",
+    "Hennepin",
+    "Houston",
+    "Hubbard",
+    "Isanti",
+    "Itasca",
+    "Jackson",
+    "Kanabec",
+    "Kandiyohi",
+    "Kittson",
+    "Koochiching",
+    "Lac qui Parle",
+    "Lake",
+    "Lake of the Woods",
+    "Le Sueur",
+    "Lincoln",
+    "Lyon",
+    "McLeod",
+    "Mahnomen",
+    "Marshall",
+    "Martin",
+    "Meeker",
+    "Mille Lacs",
+    "Morrison",
+    "Mower",
+    "Murray",
+    "Nicollet",
+    "Nobles",
+    "Norman",
+    "Olmsted",
+    "Otter Tail",
+    "Pennington",
+    "Pine",
+    "Pipestone",
+    "Polk",
+    "Pope",
+    "Ramsey",
+    "Red Lake",
+    "Redwood",
+    "Renville",
+    "Rice",
+    "Rock",
+    "Roseau",
+    "St. Louis",
+    "Scott",
+    "Sherburne",
+    "Sibley",
+    "Stearns",
+    "Steele",
+    "Stevens",
+    "Swift",
+    "Todd",
+    "Traverse",
+    "Wabasha",
+    "Wadena",
+    "Waseca",
+    "Washington",
+    "Watonwan",
+    "Wilkin",
+    "Winona",
+    "Wright",
+    "Yellow Medicine",
+];
+
+/// Looks up a county name from a MN SoS county ID, which may be zero padded
+pub fn extract_county_name(county_id: &str) -> Option<&'static str> {
+    let index = county_id.trim().parse::<usize>().ok()?;
+    MN_COUNTIES.get(index.checked_sub(1)?).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_county_name() {
+        let tests = vec![
+            ("1", Some("Aitkin")),
+            ("27", Some("Hennepin")),
+            ("062", Some("Ramsey")),
+            ("87", Some("Yellow Medicine")),
+            ("0", None),
+            ("88", None),
+            ("", None),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(extract_county_name(input), expected, "county id {}", input);
+        }
+    }
+}
@@ -1,2 +1,3 @@
+pub mod mn_county;
 pub mod mn_office;
 pub mod mn_race;
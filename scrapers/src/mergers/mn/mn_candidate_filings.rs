@@ -1,5 +1,5 @@
 //! Merges staging data from ingest_staging.stg_mn_* into production tables (office, politician, race, race_candidates).
-//! Run after process_mn_candidate_filings. Resolves by slug for offices/races and by ref_key/slug/email/phone for politicians.
+//! Run after process_mn_candidate_filings, e.g. with `scrape run mn.filings --stage merge`. Resolves by slug for offices/races and by ref_key/slug/email/phone for politicians.
 
 use db::{
     Chamber, DistrictType, ElectionScope, Office, PoliticalScope, Politician, Race, RaceCandidate,
@@ -74,22 +74,20 @@ struct StgRaceCandidate {
     ref_key: Option<String>,
 }
 
-#[tokio::main]
-async fn main() {
-    db::init_pool().await.unwrap();
-    let pool = db::pool().await;
-    let db = &pool.connection;
-
-    println!("=== Merge MN staging → production ===\n");
-
-    if let Err(e) = run_merge(db).await {
-        eprintln!("Merge failed: {}", e);
-        std::process::exit(1);
-    }
-    println!("\n✓ Merge completed successfully.");
+/// Counts after a merge run.
+#[derive(Debug, Default)]
+pub struct FilingsMergeStats {
+    pub offices_existing: usize,
+    pub offices_new: usize,
+    pub politicians_existing: usize,
+    pub politicians_new: usize,
+    pub races: usize,
+    pub race_candidates_inserted: usize,
 }
 
-async fn run_merge(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn merge_mn_candidate_filings(
+    pool: &PgPool,
+) -> Result<FilingsMergeStats, Box<dyn std::error::Error>> {
     // 1. Offices: upsert by slug, build stg_office_id -> prod_office_id
     println!("Merging offices...");
     let stg_offices: Vec<StgOffice> = sqlx::query_as(
@@ -200,7 +198,14 @@ async fn run_merge(pool: &PgPool) -> Result<(), Box<dyn std::error::Error>> {
     }
     println!("  Race_candidates: {} new links", inserted);
 
-    Ok(())
+    Ok(FilingsMergeStats {
+        offices_existing,
+        offices_new,
+        politicians_existing,
+        politicians_new,
+        races: stg_to_prod_race.len(),
+        race_candidates_inserted: inserted,
+    })
 }
 
 fn parse_state(s: Option<&String>) -> Option<State> {
@@ -0,0 +1,182 @@
+//! Merge MN SoS precinct level staging tables into `precinct`, `precinct_result` and
+//! `precinct_turnout`.
+//!
+//! Precinct level results files carry the precinct ID in their `precinct_name` column
+//! (county level files leave it empty), while the precinct reporting statistics file has
+//! separate `precinct_id` and `precinct_name` columns. Precincts are keyed by county ID and
+//! precinct ID so both files resolve to the same row.
+
+use sqlx::PgPool;
+
+use crate::extractors::mn::mn_county::MN_COUNTIES;
+use crate::mn::sos::ref_key_sql;
+
+/// Counts after a merge run.
+#[derive(Debug, Default)]
+pub struct PrecinctMergeStats {
+    pub precincts_created: usize,
+    pub rows_upserted: usize,
+}
+
+/// County ID → name lookup as parallel arrays for `UNNEST($1::text[], $2::text[])`
+fn county_arrays() -> (Vec<String>, Vec<String>) {
+    MN_COUNTIES
+        .iter()
+        .enumerate()
+        .map(|(i, name)| ((i + 1).to_string(), name.to_string()))
+        .unzip()
+}
+
+/// Upsert precinct results for every candidate matched by ref_key. Rows of county level
+/// files are skipped.
+pub async fn merge_mn_precinct_results(
+    pool: &PgPool,
+    table_name: &str,
+    dry_run: bool,
+) -> Result<PrecinctMergeStats, Box<dyn std::error::Error + Send + Sync>> {
+    let (county_codes, county_names) = county_arrays();
+    let mut tx = pool.begin().await?;
+
+    let precincts_created = sqlx::query(&format!(
+        r#"
+        INSERT INTO precinct (state, county_code, county, code, name)
+        SELECT DISTINCT ON (LTRIM(TRIM(s.county_id), '0'), TRIM(s.precinct_name))
+            'MN',
+            LTRIM(TRIM(s.county_id), '0'),
+            c.name,
+            TRIM(s.precinct_name),
+            TRIM(s.precinct_name)
+        FROM {} s
+        LEFT JOIN UNNEST($1::text[], $2::text[]) AS c(code, name) ON c.code = LTRIM(TRIM(s.county_id), '0')
+        WHERE NULLIF(TRIM(s.precinct_name), '') IS NOT NULL
+        ON CONFLICT (state, county_code, code) DO NOTHING
+        "#,
+        table_name
+    ))
+    .bind(&county_codes)
+    .bind(&county_names)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as usize;
+
+    let rows_upserted = sqlx::query(&format!(
+        r#"
+        INSERT INTO precinct_result (race_id, precinct_id, candidate_id, votes, total_votes)
+        SELECT DISTINCT ON (rc.race_id, p.id, rc.candidate_id)
+            rc.race_id,
+            p.id,
+            rc.candidate_id,
+            COALESCE(NULLIF(TRIM(s.votes_for_candidate), '')::integer, 0),
+            NULLIF(TRIM(s.total_number_of_votes_for_office_in_area), '')::integer
+        FROM {} s
+        JOIN race_candidates rc ON rc.ref_key = {}
+        JOIN precinct p ON p.state = 'MN'
+            AND p.county_code = LTRIM(TRIM(s.county_id), '0')
+            AND p.code = TRIM(s.precinct_name)
+        WHERE NULLIF(TRIM(s.precinct_name), '') IS NOT NULL
+        ON CONFLICT (race_id, precinct_id, candidate_id) DO UPDATE SET
+            votes = EXCLUDED.votes,
+            total_votes = EXCLUDED.total_votes
+        WHERE (precinct_result.votes, precinct_result.total_votes)
+            IS DISTINCT FROM (EXCLUDED.votes, EXCLUDED.total_votes)
+        "#,
+        table_name,
+        ref_key_sql("s")
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as usize;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(PrecinctMergeStats {
+        precincts_created,
+        rows_upserted,
+    })
+}
+
+/// Upsert precinct names and registration/turnout counts from a precinct reporting
+/// statistics table for an election.
+pub async fn merge_mn_precinct_stats(
+    pool: &PgPool,
+    table_name: &str,
+    election_id: uuid::Uuid,
+    dry_run: bool,
+) -> Result<PrecinctMergeStats, Box<dyn std::error::Error + Send + Sync>> {
+    let (county_codes, county_names) = county_arrays();
+    let mut tx = pool.begin().await?;
+
+    let precincts_created = sqlx::query(&format!(
+        r#"
+        INSERT INTO precinct (state, county_code, county, code, name)
+        SELECT DISTINCT ON (LTRIM(TRIM(s.county_id), '0'), TRIM(s.precinct_id))
+            'MN',
+            LTRIM(TRIM(s.county_id), '0'),
+            c.name,
+            TRIM(s.precinct_id),
+            COALESCE(NULLIF(TRIM(s.precinct_name), ''), TRIM(s.precinct_id))
+        FROM {} s
+        LEFT JOIN UNNEST($1::text[], $2::text[]) AS c(code, name) ON c.code = LTRIM(TRIM(s.county_id), '0')
+        WHERE NULLIF(TRIM(s.precinct_id), '') IS NOT NULL
+        ON CONFLICT (state, county_code, code) DO UPDATE SET
+            name = EXCLUDED.name,
+            county = COALESCE(precinct.county, EXCLUDED.county)
+        WHERE precinct.name IS DISTINCT FROM EXCLUDED.name OR precinct.county IS NULL
+        "#,
+        table_name
+    ))
+    .bind(&county_codes)
+    .bind(&county_names)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as usize;
+
+    let rows_upserted = sqlx::query(&format!(
+        r#"
+        INSERT INTO precinct_turnout (
+            election_id,
+            precinct_id,
+            registered_voters,
+            election_day_registrations,
+            ballots_cast,
+            has_reported
+        )
+        SELECT DISTINCT ON (p.id)
+            $1,
+            p.id,
+            NULLIF(TRIM(s.number_of_voters_registered), '')::integer,
+            NULLIF(TRIM(s.number_of_voters_that_registered_on_election_day), '')::integer,
+            NULLIF(TRIM(s.total_number_voted), '')::integer,
+            TRIM(s.has_reported_statistics) = '1'
+        FROM {} s
+        JOIN precinct p ON p.state = 'MN'
+            AND p.county_code = LTRIM(TRIM(s.county_id), '0')
+            AND p.code = TRIM(s.precinct_id)
+        ON CONFLICT (election_id, precinct_id) DO UPDATE SET
+            registered_voters = EXCLUDED.registered_voters,
+            election_day_registrations = EXCLUDED.election_day_registrations,
+            ballots_cast = EXCLUDED.ballots_cast,
+            has_reported = EXCLUDED.has_reported
+        "#,
+        table_name
+    ))
+    .bind(election_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected() as usize;
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(PrecinctMergeStats {
+        precincts_created,
+        rows_upserted,
+    })
+}
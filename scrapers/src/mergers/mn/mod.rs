@@ -0,0 +1,2 @@
+pub mod mn_candidate_filings;
+pub mod mn_precinct_results;
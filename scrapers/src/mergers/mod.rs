@@ -1 +1,3 @@
+pub mod mn;
+pub mod politician_web_contacts;
 pub mod tx;
@@ -22,24 +22,10 @@ struct StagingRow {
     thumbnail_image_url: Option<String>,
 }
 
-#[tokio::main]
-async fn main() {
-    db::init_pool().await.unwrap();
-    let pool = db::pool().await;
-    let db = &pool.connection;
-
-    println!("=== Merge politician web contacts (stg_tx_scraped_us_house_candidates) → production ===\n");
-
-    match run_merge(db).await {
-        Ok(n) => println!("\n✓ Merge completed. Updated {} politician(s).", n),
-        Err(e) => {
-            eprintln!("Merge failed: {}", e);
-            std::process::exit(1);
-        }
-    }
-}
-
-async fn run_merge(pool: &PgPool) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+/// Returns the number of politicians updated
+pub async fn merge_politician_web_contacts(
+    pool: &PgPool,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
     let rows: Vec<StagingRow> = sqlx::query_as(
         r#"
         SELECT politician_id, source_url, source_type, campaign_website_url, official_website_url,
@@ -1 +1,2 @@
+pub mod tx_candidate_filings;
 pub mod tx_results;
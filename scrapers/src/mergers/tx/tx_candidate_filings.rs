@@ -98,28 +98,25 @@ enum PoliticianOutcome {
     NewInsert,
 }
 
-#[tokio::main]
-async fn main() {
-    let args: Vec<String> = std::env::args().collect();
-    let overwrite_race_candidates = args.iter().any(|a| a == "--overwrite-rcs");
-
-    db::init_pool().await.unwrap();
-    let pool = db::pool().await;
-    let db = &pool.connection;
-
-    println!("=== Merge TX staging → production ===\n");
-    if overwrite_race_candidates {
-        println!("(--overwrite-rcs: existing race_candidates with same ref_key will be replaced)\n");
-    }
-
-    if let Err(e) = run_merge(db, overwrite_race_candidates).await {
-        eprintln!("Merge failed: {}", e);
-        std::process::exit(1);
-    }
-    println!("\n✓ Merge completed successfully.");
+/// Counts after a merge run.
+#[derive(Debug, Default)]
+pub struct FilingsMergeStats {
+    pub offices_existing: usize,
+    pub offices_new: usize,
+    pub politicians_matched: usize,
+    pub addresses_inserted: usize,
+    pub races: usize,
+    pub race_candidates_inserted: usize,
+    pub race_candidates_skipped: usize,
+    pub race_candidates_overwritten: usize,
 }
 
-async fn run_merge(pool: &PgPool, overwrite_race_candidates: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// Merges the TX staging tables. With `overwrite_race_candidates`, existing race_candidates
+/// with the same ref_key are replaced instead of skipped.
+pub async fn merge_tx_candidate_filings(
+    pool: &PgPool,
+    overwrite_race_candidates: bool,
+) -> Result<FilingsMergeStats, Box<dyn std::error::Error>> {
     // 1. Offices: upsert by slug, build stg_office_id -> prod_office_id
     println!("Merging offices...");
     let stg_offices: Vec<StgOffice> = sqlx::query_as(
@@ -282,7 +279,16 @@ async fn run_merge(pool: &PgPool, overwrite_race_candidates: bool) -> Result<(),
         println!("  Race_candidates skipped (existing ref_key): {}", skipped_ref_key);
     }
 
-    Ok(())
+    Ok(FilingsMergeStats {
+        offices_existing,
+        offices_new,
+        politicians_matched: exact_match_count,
+        addresses_inserted,
+        races: stg_to_prod_race.len(),
+        race_candidates_inserted: inserted,
+        race_candidates_skipped: skipped_ref_key,
+        race_candidates_overwritten: overwritten_ref_key,
+    })
 }
 
 fn parse_state(s: Option<&String>) -> Option<State> {
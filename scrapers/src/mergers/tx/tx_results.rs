@@ -400,3 +400,47 @@ pub async fn merge_stg_tx_results_sos_civix_to_production(
     )
     .await
 }
+
+/// Upsert county totals from a county staging table (stg_tx_results_clarity or stg_tx_results_hart) into county_result.
+/// Match by ref_key; the most recently ingested row per race, county and candidate wins.
+/// Returns the number of county_result rows inserted or changed.
+pub async fn merge_stg_tx_county_results(
+    pool: &PgPool,
+    staging_table: &str,
+    dry_run: bool,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let query = format!(
+        r#"
+INSERT INTO county_result (race_id, county, candidate_id, votes, total_votes, precincts_reporting, total_precincts)
+SELECT DISTINCT ON (rc.race_id, s.county, rc.candidate_id)
+  rc.race_id,
+  s.county,
+  rc.candidate_id,
+  COALESCE(s.votes_for_candidate::integer, 0),
+  s.total_votes::integer,
+  s.precincts_reporting::integer,
+  s.precincts_total::integer
+FROM ingest_staging.{} s
+INNER JOIN race_candidates rc ON rc.ref_key = s.ref_key
+WHERE s.county IS NOT NULL
+ORDER BY rc.race_id, s.county, rc.candidate_id, s.ingested_at DESC, s.id DESC
+ON CONFLICT (race_id, county, candidate_id) DO UPDATE SET
+  votes = EXCLUDED.votes,
+  total_votes = EXCLUDED.total_votes,
+  precincts_reporting = EXCLUDED.precincts_reporting,
+  total_precincts = EXCLUDED.total_precincts
+WHERE (county_result.votes, county_result.total_votes, county_result.precincts_reporting, county_result.total_precincts)
+  IS DISTINCT FROM (EXCLUDED.votes, EXCLUDED.total_votes, EXCLUDED.precincts_reporting, EXCLUDED.total_precincts)
+"#,
+        staging_table
+    );
+
+    let mut tx = pool.begin().await?;
+    let rows = sqlx::query(&query).execute(&mut *tx).await?.rows_affected() as usize;
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+    Ok(rows)
+}
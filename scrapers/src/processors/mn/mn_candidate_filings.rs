@@ -1,6 +1,6 @@
 use crate::extractors;
 use crate::generators;
-use db::{Office, Politician, Race, RaceType, State, VoteType};
+use db::{Office, Politician, Race, RaceStatus, RaceType, State, VoteType};
 use serde_json::Value as JSON;
 use slugify::slugify;
 use sqlx::PgPool;
@@ -787,6 +787,8 @@ fn process_race(
         total_precincts: None,
         is_special_election,
         num_elect,
+        status: RaceStatus::Uncalled,
+        status_updated_at: None,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     })
@@ -6,7 +6,7 @@
 
 use crate::extractors;
 use crate::generators;
-use db::{Office, Politician, Race, RaceType, State, VoteType};
+use db::{Office, Politician, Race, RaceStatus, RaceType, State, VoteType};
 use serde_json::Value as JSON;
 use sqlx::FromRow;
 use sqlx::PgPool;
@@ -693,6 +693,8 @@ fn process_tx_race(
         total_precincts: None,
         is_special_election,
         num_elect,
+        status: RaceStatus::Uncalled,
+        status_updated_at: None,
         created_at: chrono::Utc::now(),
         updated_at: chrono::Utc::now(),
     })
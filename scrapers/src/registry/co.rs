@@ -0,0 +1,39 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use db::{DatabasePool, ScrapeStage};
+
+use super::{Counts, Pipeline, RunOptions};
+use crate::co::sos::co_general_candidates;
+
+pub struct GeneralCandidates;
+
+#[async_trait(?Send)]
+impl Pipeline for GeneralCandidates {
+    fn source_id(&self) -> &'static str {
+        "co.general_candidates"
+    }
+
+    fn description(&self) -> &'static str {
+        "CO SoS general election candidates page"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Scrape]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        _stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        let scraper = co_general_candidates::Scraper::default();
+        if options.local {
+            scraper.run_local(db).await?;
+        } else {
+            scraper.run(db).await?;
+        }
+        Ok(Counts::new())
+    }
+}
@@ -0,0 +1,191 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use db::{
+    DatabasePool, ResultsSource, ResultsSourceFilter, ResultsSourceFormat, ScrapeStage, State,
+};
+
+use super::{counts, webdriver, Counts, Pipeline, RunOptions};
+use crate::{
+    mergers::mn::mn_candidate_filings::merge_mn_candidate_filings,
+    mn::{self, mn_ballot_measures},
+    processors::mn::mn_candidate_filings::process_mn_candidate_filings,
+    results_sources,
+};
+
+pub struct BallotMeasures;
+
+#[async_trait(?Send)]
+impl Pipeline for BallotMeasures {
+    fn source_id(&self) -> &'static str {
+        "mn.ballot_measures"
+    }
+
+    fn description(&self) -> &'static str {
+        "MN SoS statewide ballot questions spreadsheet"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Scrape]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        _stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        let scraper = mn_ballot_measures::Scraper::default();
+        if options.local {
+            scraper.run_local(db).await?;
+        } else {
+            scraper.run(db).await?;
+        }
+        Ok(Counts::new())
+    }
+}
+
+pub struct Filings;
+
+#[async_trait(?Send)]
+impl Pipeline for Filings {
+    fn source_id(&self) -> &'static str {
+        "mn.filings"
+    }
+
+    fn description(&self) -> &'static str {
+        "MN SoS candidate filings, from the filings site to offices, politicians and races"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[
+            ScrapeStage::Scrape,
+            ScrapeStage::Process,
+            ScrapeStage::Merge,
+        ]
+    }
+
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            (
+                "offices",
+                "scrape: local (default) or fed_state_county filings",
+            ),
+            ("primaries", "scrape: primary instead of general filings"),
+            ("webdriver-url", "scrape: chromedriver to use"),
+            (
+                "table",
+                "process: filings table, default mn_candidate_filings_local_2025",
+            ),
+            ("race-type", "process: general (default) or primary"),
+        ]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        match stage {
+            ScrapeStage::Scrape => {
+                let driver = webdriver(options).await?;
+                let primaries = options.flag("primaries");
+                let result = match (options.arg("offices").unwrap_or("local"), primaries) {
+                    ("local", false) => mn::get_mn_sos_candidate_filings_local(&driver).await,
+                    ("local", true) => {
+                        mn::get_mn_sos_candidate_filings_local_primaries(&driver).await
+                    }
+                    ("fed_state_county", false) => {
+                        mn::get_mn_sos_candidate_filings_fed_state_county(&driver).await
+                    }
+                    ("fed_state_county", true) => {
+                        mn::get_mn_sos_candidate_filings_fed_state_county_primaries(&driver).await
+                    }
+                    (offices, _) => Err(format!("Unknown offices '{}'", offices).into()),
+                };
+                driver.quit().await?;
+                result?;
+                Ok(Counts::new())
+            }
+            ScrapeStage::Process => {
+                process_mn_candidate_filings(
+                    &db.connection,
+                    options
+                        .arg("table")
+                        .unwrap_or("mn_candidate_filings_local_2025"),
+                    options.arg("race-type").unwrap_or("general"),
+                )
+                .await?;
+                Ok(Counts::new())
+            }
+            ScrapeStage::Merge => {
+                let stats = merge_mn_candidate_filings(&db.connection).await?;
+                Ok(counts([
+                    ("offices_existing", stats.offices_existing),
+                    ("offices_new", stats.offices_new),
+                    ("politicians_existing", stats.politicians_existing),
+                    ("politicians_new", stats.politicians_new),
+                    ("races", stats.races),
+                    ("race_candidates_inserted", stats.race_candidates_inserted),
+                ]))
+            }
+        }
+    }
+}
+
+pub struct Results;
+
+#[async_trait(?Send)]
+impl Pipeline for Results {
+    fn source_id(&self) -> &'static str {
+        "mn.results"
+    }
+
+    fn description(&self) -> &'static str {
+        "MN SoS results files of every active results source, fetched and merged"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Scrape]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        _stage: ScrapeStage,
+        _options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        let sources = ResultsSource::filter(
+            &db.connection,
+            &ResultsSourceFilter {
+                state: Some(State::MN),
+                active: Some(true),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+        let mut polled = 0;
+        let mut failed = 0;
+        for source in sources
+            .iter()
+            .filter(|source| source.format == ResultsSourceFormat::MnSos)
+        {
+            match results_sources::poll_source(&db.connection, source).await {
+                Ok(_) => polled += 1,
+                Err(err) => {
+                    tracing::warn!("Failed to poll {}: {}", source.name, err);
+                    failed += 1;
+                }
+            }
+        }
+        if polled == 0 && failed > 0 {
+            return Err(format!("All {} MN SoS results sources failed", failed).into());
+        }
+        Ok(counts([
+            ("sources_polled", polled),
+            ("sources_failed", failed),
+        ]))
+    }
+}
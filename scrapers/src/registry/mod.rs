@@ -0,0 +1,226 @@
+//! Registry of every scraper, processor and merger, run through the `scrape` CLI.
+//!
+//! Each source is a `Pipeline` with up to three stages: scrape (fetch source data into raw
+//! or staging tables), process (normalize it into ingest_staging) and merge (write it to
+//! production). Every stage run is recorded in `scrape_run`, as are the server's polls of
+//! results sources, under `results_source`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use async_trait::async_trait;
+use db::{DatabasePool, ScrapeRun, ScrapeStage};
+use thirtyfour::{DesiredCapabilities, WebDriver};
+
+use crate::results_sources::DEFAULT_WEBDRIVER_URL;
+
+mod co;
+mod mn;
+mod tx;
+
+/// Named counts reported by a stage, e.g. rows loaded or races updated
+pub type Counts = BTreeMap<String, i64>;
+
+/// Options for one run of a pipeline stage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunOptions {
+    /// Report what would change without writing to production
+    pub dry_run: bool,
+    /// Read saved files under scrapers/html instead of the live source
+    pub local: bool,
+    /// Pipeline specific `--key value` options. Flags without a value are stored as "true".
+    pub args: BTreeMap<String, String>,
+}
+
+impl RunOptions {
+    /// Parses `--dry-run`, `--local` and pipeline options from command line arguments.
+    /// `--stage` is returned separately.
+    pub fn from_args(args: &[String]) -> Result<(Option<ScrapeStage>, Self), String> {
+        let mut stage = None;
+        let mut options = Self::default();
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            let key = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Unexpected argument '{}'", arg))?;
+            match key {
+                "dry-run" => options.dry_run = true,
+                "local" => options.local = true,
+                _ => {
+                    let value = match args.peek() {
+                        Some(value) if !value.starts_with("--") => args.next().unwrap().clone(),
+                        _ => "true".to_string(),
+                    };
+                    if key == "stage" {
+                        stage = Some(
+                            value
+                                .parse::<ScrapeStage>()
+                                .map_err(|_| format!("Unknown stage '{}'", value))?,
+                        );
+                    } else {
+                        options.args.insert(key.to_string(), value);
+                    }
+                }
+            }
+        }
+        Ok((stage, options))
+    }
+
+    pub fn arg(&self, key: &str) -> Option<&str> {
+        self.args.get(key).map(String::as_str)
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.arg(key) == Some("true")
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "local": self.local,
+            "args": self.args,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+pub trait Pipeline {
+    /// Registry id, `<state>.<source>`
+    fn source_id(&self) -> &'static str;
+    fn description(&self) -> &'static str;
+    /// Stages in the order they run
+    fn stages(&self) -> &'static [ScrapeStage];
+    /// Stages that support `--dry-run`
+    fn dry_run_stages(&self) -> &'static [ScrapeStage] {
+        &[]
+    }
+    /// Options understood by the pipeline and what they do, shown by `scrape list`
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>>;
+}
+
+pub fn registry() -> Vec<Box<dyn Pipeline>> {
+    vec![
+        Box::new(co::GeneralCandidates),
+        Box::new(mn::BallotMeasures),
+        Box::new(mn::Filings),
+        Box::new(mn::Results),
+        Box::new(tx::Filings),
+        Box::new(tx::Results),
+        Box::new(tx::CivixFedRepResults),
+        Box::new(tx::CandidateWebContacts),
+    ]
+}
+
+pub fn find(source_id: &str) -> Option<Box<dyn Pipeline>> {
+    registry().into_iter().find(|p| p.source_id() == source_id)
+}
+
+/// Runs one stage of a pipeline and records the run, whether or not it succeeds. Only
+/// fails when the stage can't be started or the run can't be recorded.
+pub async fn run_stage(
+    db: &DatabasePool,
+    pipeline: &dyn Pipeline,
+    stage: ScrapeStage,
+    options: &RunOptions,
+) -> Result<ScrapeRun, Box<dyn Error>> {
+    if !pipeline.stages().contains(&stage) {
+        return Err(format!("{} has no {} stage", pipeline.source_id(), stage).into());
+    }
+    if options.dry_run && !pipeline.dry_run_stages().contains(&stage) {
+        return Err(format!(
+            "The {} stage of {} does not support --dry-run",
+            stage,
+            pipeline.source_id()
+        )
+        .into());
+    }
+
+    let run = ScrapeRun::start(
+        &db.connection,
+        pipeline.source_id(),
+        stage,
+        options.dry_run,
+        options.to_json(),
+    )
+    .await?;
+    let (counts, error) = match pipeline.run(db, stage, options).await {
+        Ok(counts) => (counts, None),
+        Err(err) => (Counts::new(), Some(err.to_string())),
+    };
+    let run = run
+        .finish(&db.connection, serde_json::to_value(counts)?, error)
+        .await?;
+    Ok(run)
+}
+
+/// Headless Chrome session on the chromedriver at `--webdriver-url`
+pub(crate) async fn webdriver(options: &RunOptions) -> Result<WebDriver, Box<dyn Error>> {
+    let mut caps = DesiredCapabilities::chrome();
+    caps.add_arg("--no-sandbox")?;
+    caps.add_arg("--disable-dev-shm-usage")?;
+    caps.add_arg("--headless=new")?;
+    let url = options
+        .arg("webdriver-url")
+        .unwrap_or(DEFAULT_WEBDRIVER_URL);
+    Ok(WebDriver::new(url, caps).await?)
+}
+
+/// Converts the `Send + Sync` errors returned by most scrapers and mergers
+pub(crate) fn boxed(err: Box<dyn Error + Send + Sync>) -> Box<dyn Error> {
+    err
+}
+
+/// Builds `Counts` from name and count pairs
+pub(crate) fn counts<const N: usize>(pairs: [(&str, usize); N]) -> Counts {
+    pairs
+        .into_iter()
+        .map(|(name, count)| (name.to_string(), count as i64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_source_ids_are_unique() {
+        let mut ids: Vec<&str> = registry().iter().map(|p| p.source_id()).collect();
+        let count = ids.len();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), count);
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let (stage, options) = RunOptions::from_args(&args(&[
+            "--stage",
+            "Process",
+            "--dry-run",
+            "--race-type",
+            "primary",
+            "--overwrite-rcs",
+        ]))
+        .unwrap();
+
+        assert_eq!(stage, Some(ScrapeStage::Process));
+        assert!(options.dry_run);
+        assert!(!options.local);
+        assert_eq!(options.arg("race-type"), Some("primary"));
+        assert!(options.flag("overwrite-rcs"));
+
+        assert!(RunOptions::from_args(&args(&["--stage", "upload"])).is_err());
+        assert!(RunOptions::from_args(&args(&["mn.filings"])).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use db::{DatabasePool, ScrapeStage};
+
+use super::{boxed, counts, Counts, Pipeline, RunOptions};
+use crate::{
+    mergers::{
+        politician_web_contacts::merge_politician_web_contacts,
+        tx::{tx_candidate_filings::merge_tx_candidate_filings, tx_results as merge},
+    },
+    processors::tx::{tx_candidate_filings::process_tx_candidate_filings, tx_results as process},
+    results_sources::DEFAULT_WEBDRIVER_URL,
+    tx::{
+        counties::{tx_clarity_results, tx_hart_results},
+        tx_candidate_web_scrape, tx_civix_fed_rep_results, tx_results,
+    },
+};
+
+pub struct Filings;
+
+#[async_trait(?Send)]
+impl Pipeline for Filings {
+    fn source_id(&self) -> &'static str {
+        "tx.filings"
+    }
+
+    fn description(&self) -> &'static str {
+        "TX SoS candidate filings table to offices, politicians and races"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Process, ScrapeStage::Merge]
+    }
+
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("race-type", "process: primary (default) or general"),
+            (
+                "overwrite-rcs",
+                "merge: replace race_candidates with the same ref_key instead of skipping them",
+            ),
+        ]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        match stage {
+            ScrapeStage::Process => {
+                process_tx_candidate_filings(
+                    &db.connection,
+                    options.arg("race-type").unwrap_or("primary"),
+                )
+                .await?;
+                Ok(Counts::new())
+            }
+            _ => {
+                let stats =
+                    merge_tx_candidate_filings(&db.connection, options.flag("overwrite-rcs"))
+                        .await?;
+                Ok(counts([
+                    ("offices_existing", stats.offices_existing),
+                    ("offices_new", stats.offices_new),
+                    ("politicians_matched", stats.politicians_matched),
+                    ("addresses_inserted", stats.addresses_inserted),
+                    ("races", stats.races),
+                    ("race_candidates_inserted", stats.race_candidates_inserted),
+                    ("race_candidates_skipped", stats.race_candidates_skipped),
+                    (
+                        "race_candidates_overwritten",
+                        stats.race_candidates_overwritten,
+                    ),
+                ]))
+            }
+        }
+    }
+}
+
+/// Result formats handled by `tx.results`, in the order they run
+const RESULT_FORMATS: [&str; 4] = ["sos", "clarity", "hart", "other"];
+
+pub struct Results;
+
+#[async_trait(?Send)]
+impl Pipeline for Results {
+    fn source_id(&self) -> &'static str {
+        "tx.results"
+    }
+
+    fn description(&self) -> &'static str {
+        "TX election results from the SoS SFTP feed and Clarity, Hart and other county files"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[
+            ScrapeStage::Scrape,
+            ScrapeStage::Process,
+            ScrapeStage::Merge,
+        ]
+    }
+
+    fn dry_run_stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Merge]
+    }
+
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            (
+                "format",
+                "sos, clarity, hart or other (merge also takes civix), default all",
+            ),
+            ("clarity-urls", "scrape: CSV of county Clarity results URLs"),
+            (
+                "test-merge",
+                "merge: only U. S. Senator rows, to check matching",
+            ),
+        ]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        let pool = &db.connection;
+        let formats: Vec<&str> = match options.arg("format") {
+            Some(format) => vec![format],
+            None => RESULT_FORMATS.to_vec(),
+        };
+        let mut totals = Counts::new();
+
+        for format in formats {
+            match stage {
+                ScrapeStage::Scrape => match format {
+                    // Downloads only, the XML files are loaded by the process stage
+                    "sos" => {
+                        tx_results::run_sos(pool, true, false)
+                            .await
+                            .map_err(boxed)?;
+                    }
+                    "clarity" => {
+                        let csv_path = options.arg("clarity-urls").map(Into::into);
+                        tx_clarity_results::run(pool, csv_path)
+                            .await
+                            .map_err(boxed)?;
+                    }
+                    "hart" => tx_hart_results::run(pool).await.map_err(boxed)?,
+                    "other" => {
+                        let rows = tx_results::run_other(pool).await.map_err(boxed)?;
+                        *totals.entry("other_rows".into()).or_default() += rows as i64;
+                    }
+                    _ => return Err(format!("Can't scrape {} results", format).into()),
+                },
+                ScrapeStage::Process => match format {
+                    "sos" => {
+                        let (files, rows) = process::process_tx_sos_results(pool, true)
+                            .await
+                            .map_err(boxed)?;
+                        totals.insert("sos_files".into(), files as i64);
+                        totals.insert("sos_rows".into(), rows as i64);
+                    }
+                    // Other formats are loaded into staging as they're scraped
+                    _ => {}
+                },
+                ScrapeStage::Merge => {
+                    let test_merge = options.flag("test-merge");
+                    let stats = match format {
+                        "sos" => merge::merge_stg_tx_results_sos_to_production(
+                            pool,
+                            options.dry_run,
+                            test_merge,
+                        )
+                        .await
+                        .map_err(boxed)?,
+                        "clarity" => merge::merge_stg_tx_results_clarity_to_production(
+                            pool,
+                            options.dry_run,
+                            test_merge,
+                        )
+                        .await
+                        .map_err(boxed)?,
+                        "hart" => merge::merge_stg_tx_results_hart_to_production(
+                            pool,
+                            options.dry_run,
+                            test_merge,
+                        )
+                        .await
+                        .map_err(boxed)?,
+                        "other" => merge::merge_stg_tx_results_other_to_production(
+                            pool,
+                            options.dry_run,
+                            test_merge,
+                        )
+                        .await
+                        .map_err(boxed)?,
+                        "civix" => merge::merge_stg_tx_results_sos_civix_to_production(
+                            pool,
+                            options.dry_run,
+                            test_merge,
+                        )
+                        .await
+                        .map_err(boxed)?,
+                        _ => return Err(format!("Can't merge {} results", format).into()),
+                    };
+                    for (name, count) in counts([
+                        ("staging_rows", stats.staging_rows),
+                        ("matched", stats.matched),
+                        ("unmatched", stats.unmatched),
+                        ("race_candidates_updated", stats.race_candidates_updated),
+                        ("races_updated", stats.races_updated),
+                    ]) {
+                        totals.insert(format!("{}_{}", format, name), count);
+                    }
+                }
+            }
+        }
+        Ok(totals)
+    }
+}
+
+pub struct CivixFedRepResults;
+
+#[async_trait(?Send)]
+impl Pipeline for CivixFedRepResults {
+    fn source_id(&self) -> &'static str {
+        "tx.civix_fed_rep"
+    }
+
+    fn description(&self) -> &'static str {
+        "TX SoS election night results site, federal primary races"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Scrape]
+    }
+
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            ("url", "Civix races page"),
+            ("webdriver-url", "chromedriver to use"),
+        ]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        _stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        let rows = tx_civix_fed_rep_results::run(
+            &db.connection,
+            options
+                .arg("url")
+                .unwrap_or(tx_civix_fed_rep_results::CIVIX_RACES_URL),
+            options
+                .arg("webdriver-url")
+                .unwrap_or(DEFAULT_WEBDRIVER_URL),
+        )
+        .await
+        .map_err(boxed)?;
+        Ok(counts([("rows", rows as usize)]))
+    }
+}
+
+pub struct CandidateWebContacts;
+
+#[async_trait(?Send)]
+impl Pipeline for CandidateWebContacts {
+    fn source_id(&self) -> &'static str {
+        "tx.candidate_web_contacts"
+    }
+
+    fn description(&self) -> &'static str {
+        "Campaign sites, social links and emails of TX U.S. House candidates, merged once validated"
+    }
+
+    fn stages(&self) -> &'static [ScrapeStage] {
+        &[ScrapeStage::Scrape, ScrapeStage::Merge]
+    }
+
+    fn options(&self) -> &'static [(&'static str, &'static str)] {
+        &[("limit", "scrape: only this many candidates")]
+    }
+
+    async fn run(
+        &self,
+        db: &DatabasePool,
+        stage: ScrapeStage,
+        options: &RunOptions,
+    ) -> Result<Counts, Box<dyn Error>> {
+        match stage {
+            ScrapeStage::Scrape => {
+                let limit = options.arg("limit").map(str::parse).transpose()?;
+                tx_candidate_web_scrape::run(&db.connection, limit)
+                    .await
+                    .map_err(boxed)?;
+                Ok(Counts::new())
+            }
+            _ => {
+                let updated = merge_politician_web_contacts(&db.connection)
+                    .await
+                    .map_err(boxed)?;
+                Ok(counts([("politicians_updated", updated as usize)]))
+            }
+        }
+    }
+}
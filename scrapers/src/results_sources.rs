@@ -0,0 +1,125 @@
+//! Polls a single `results_source` row with the loader for its format, then merges the
+//! staged rows into race results, plus precinct or county results where the format has
+//! them, and projects winners for races that are fully reported. Called by the server's
+//! `poll_results_source` job, which records each poll in `scrape_run`.
+
+use std::error::Error;
+
+use db::{RaceCall, ResultsSource, ResultsSourceFormat, ScrapeRun, ScrapeStage};
+use sqlx::PgPool;
+
+use crate::mergers::mn::mn_precinct_results;
+use crate::mergers::tx::tx_results as tx_merge;
+use crate::mn::sos::{fetch_results, ResultsFile, PRECINCT_STATS_FILE_NAME};
+use crate::registry::{counts, Counts};
+use crate::tx::counties::{tx_clarity_results, tx_hart_results};
+use crate::tx::tx_civix_fed_rep_results;
+
+pub(crate) const DEFAULT_WEBDRIVER_URL: &str = "http://localhost:9515";
+
+/// `scrape_run` source id of polls started outside the `scrape` CLI
+pub const RESULTS_SOURCE_RUN_ID: &str = "results_source";
+
+/// Polls a source and records the poll in `scrape_run`, whether or not it succeeds
+pub async fn poll(
+    pool: &PgPool,
+    source: &ResultsSource,
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let run = ScrapeRun::start(
+        pool,
+        RESULTS_SOURCE_RUN_ID,
+        ScrapeStage::Scrape,
+        false,
+        serde_json::json!({
+            "resultsSourceId": source.id,
+            "name": source.name,
+            "state": source.state.to_string(),
+            "format": source.format.to_string(),
+        }),
+    )
+    .await?;
+    let result = poll_source(pool, source).await;
+    let (counts, error) = match &result {
+        Ok(counts) => (counts.clone(), None),
+        Err(err) => (Counts::new(), Some(err.to_string())),
+    };
+    run.finish(pool, serde_json::to_value(counts)?, error)
+        .await?;
+    result.map(|_| ())
+}
+
+/// Polls a source without recording a run, for pipelines that record their own
+pub(crate) async fn poll_source(
+    pool: &PgPool,
+    source: &ResultsSource,
+) -> Result<Counts, Box<dyn Error + Send + Sync>> {
+    let option = |key: &str| source.options.get(key).and_then(|v| v.as_str());
+    let county = || {
+        option("county").ok_or_else(|| format!("{} sources require a county option", source.format))
+    };
+
+    let mut counts = match source.format {
+        ResultsSourceFormat::MnSos => {
+            let table_name = source.staging_table();
+            let file = ResultsFile {
+                name: &source.name,
+                url: &source.url,
+                table_name: &table_name,
+            };
+            fetch_results(&[file]).await.map_err(|e| e.to_string())?;
+            let stats = if source.name == PRECINCT_STATS_FILE_NAME {
+                mn_precinct_results::merge_mn_precinct_stats(
+                    pool,
+                    &table_name,
+                    source.election_id,
+                    false,
+                )
+                .await?
+            } else {
+                mn_precinct_results::merge_mn_precinct_results(pool, &table_name, false).await?
+            };
+            tracing::info!(
+                "Merged {} precinct rows from {} ({} new precincts)",
+                stats.rows_upserted,
+                source.name,
+                stats.precincts_created
+            );
+            counts([
+                ("rows_upserted", stats.rows_upserted),
+                ("precincts_created", stats.precincts_created),
+            ])
+        }
+        ResultsSourceFormat::TxClarity => {
+            let rows =
+                tx_clarity_results::run_source(pool, &source.url, county()?, option("party"))
+                    .await?;
+            tracing::info!("Loaded {} Clarity rows from {}", rows, source.name);
+            tx_merge::merge_stg_tx_results_clarity_to_production(pool, false, false).await?;
+            tx_merge::merge_stg_tx_county_results(pool, "stg_tx_results_clarity", false).await?;
+            counts([("rows_loaded", rows as usize)])
+        }
+        ResultsSourceFormat::TxHart => {
+            let rows =
+                tx_hart_results::run_source(pool, &source.url, county()?, option("party")).await?;
+            tracing::info!("Loaded {} Hart rows from {}", rows, source.name);
+            tx_merge::merge_stg_tx_results_hart_to_production(pool, false, false).await?;
+            tx_merge::merge_stg_tx_county_results(pool, "stg_tx_results_hart", false).await?;
+            counts([("rows_loaded", rows as usize)])
+        }
+        ResultsSourceFormat::TxCivix => {
+            let webdriver_url = option("webdriverUrl").unwrap_or(DEFAULT_WEBDRIVER_URL);
+            let rows = tx_civix_fed_rep_results::run(pool, &source.url, webdriver_url).await?;
+            tracing::info!("Loaded {} Civix rows from {}", rows, source.name);
+            tx_merge::merge_stg_tx_results_sos_civix_to_production(pool, false, false).await?;
+            counts([("rows_loaded", rows as usize)])
+        }
+    };
+
+    let projected = RaceCall::project_election(pool, source.election_id).await?;
+    if projected > 0 {
+        tracing::info!("Updated projected winners of {} races", projected);
+    }
+    counts.insert("races_projected".to_string(), projected as i64);
+
+    Ok(counts)
+}
@@ -21,34 +21,25 @@ const SOURCE_ID: &str = "CO-SOS";
 #[derive(Default)]
 pub struct Scraper {}
 
-impl crate::Scraper for Scraper {
-    fn source_id(&self) -> &'static str {
-        SOURCE_ID
-    }
-
-    async fn run(&self, context: &crate::ScraperContext<'_>) -> Result<(), Box<dyn Error>> {
+impl Scraper {
+    pub async fn run(&self, db: &db::DatabasePool) -> Result<(), Box<dyn Error>> {
         let html = reqwest::get(PAGE_URL).await?.text().await?;
-        Self::scrape_html(html, context).await
+        Self::scrape_html(html, db).await
     }
 
-    async fn run_local(&self, context: &crate::ScraperContext<'_>) -> Result<(), Box<dyn Error>> {
+    pub async fn run_local(&self, db: &db::DatabasePool) -> Result<(), Box<dyn Error>> {
         let html = util::read_local_html(HTML_PATH)?;
-        Self::scrape_html(html, context).await
+        Self::scrape_html(html, db).await
     }
-}
 
-impl Scraper {
-    pub async fn scrape_html(
-        html: String,
-        context: &crate::ScraperContext<'_>,
-    ) -> Result<(), Box<dyn Error>> {
+    pub async fn scrape_html(html: String, db: &db::DatabasePool) -> Result<(), Box<dyn Error>> {
         let data = Self::scrape_page_data(html)?;
         let election_year = Self::parse_election_year(&data.title)?;
         let election_date = GeneralElectionDateGenerator::new(election_year).generate()?;
         let (election_title, election_slug) =
             ElectionTitleGenerator::new(&db::RaceType::General, election_year).generate();
         let election = db::Election::upsert_from_source(
-            &context.db.connection,
+            &db.connection,
             &db::UpsertElectionInput {
                 slug: Some(election_slug),
                 title: Some(election_title),
@@ -60,8 +51,7 @@ impl Scraper {
 
         for entry in data.candidates {
             let office = Self::build_office_input(&entry);
-            let office = match db::Office::upsert_from_source(&context.db.connection, &office).await
-            {
+            let office = match db::Office::upsert_from_source(&db.connection, &office).await {
                 Ok(office) => office,
                 Err(err) => {
                     // TODO - Track/log error
@@ -71,7 +61,7 @@ impl Scraper {
             };
 
             let race = Self::build_race_input(&election, &office);
-            let race = match db::Race::upsert_from_source(&context.db.connection, &race).await {
+            let race = match db::Race::upsert_from_source(&db.connection, &race).await {
                 Ok(race) => race,
                 Err(err) => {
                     // TODO - Track/log error
@@ -82,7 +72,7 @@ impl Scraper {
 
             let party = Self::build_party_input(&entry);
             let party = if let Some(party) = party {
-                match db::Party::upsert_from_source(&context.db.connection, &party).await {
+                match db::Party::upsert_from_source(&db.connection, &party).await {
                     Ok(party) => Some(party),
                     Err(err) => {
                         // TODO - Track/log error
@@ -96,8 +86,7 @@ impl Scraper {
 
             let politician = Self::build_politician_input(&entry, &party);
             let politician =
-                match db::Politician::upsert_from_source(&context.db.connection, &politician).await
-                {
+                match db::Politician::upsert_from_source(&db.connection, &politician).await {
                     Ok(politician) => politician,
                     Err(err) => {
                         // TODO - Track/log error
@@ -108,7 +97,7 @@ impl Scraper {
 
             let race_candidate = Self::build_race_candidate_input(&race, &politician);
             if let Err(err) =
-                db::RaceCandidate::upsert_from_source(&context.db.connection, &race_candidate).await
+                db::RaceCandidate::upsert_from_source(&db.connection, &race_candidate).await
             {
                 // TODO - Track/log error
                 println!("Error upserting RaceCandidate: {err}");
@@ -13,36 +13,31 @@ use std::io::Cursor;
 const FILE_PATH: &str = "mn/sos/ballot_measures.xlsx";
 const PAGE_URL: &str =
     "https://www.sos.mn.gov/media/6162/questions-on-2024-state-general-election-ballot.xlsx";
-const SOURCE_ID: &str = "MN-SOS";
 
 #[derive(Default)]
 pub struct Scraper {}
 
-impl crate::Scraper for Scraper {
-    fn source_id(&self) -> &'static str {
-        SOURCE_ID
-    }
-
+impl Scraper {
     // Run for remote sources (byte array)
-    async fn run(&self, context: &crate::ScraperContext<'_>) -> Result<(), Box<dyn Error>> {
+    pub async fn run(&self, db: &db::DatabasePool) -> Result<(), Box<dyn Error>> {
         // Fetch the XLSX file as bytes from a remote source
         let bytes = reqwest::get(PAGE_URL).await?.bytes().await?.to_vec();
         let xlsx_source = XlsxSource::Bytes(bytes);
 
         // Open and scrape the XLSX file
         let xlsx = xlsx_source.open_xlsx()?;
-        Self::scrape_xlsx(xlsx, context).await?;
+        Self::scrape_xlsx(xlsx, db).await?;
         Ok(())
     }
 
     // Run for local sources (file path)
-    async fn run_local(&self, context: &crate::ScraperContext<'_>) -> Result<(), Box<dyn Error>> {
+    pub async fn run_local(&self, db: &db::DatabasePool) -> Result<(), Box<dyn Error>> {
         let path = get_project_root()?.join("scrapers/html").join(FILE_PATH);
         let xlsx_source = XlsxSource::Path(path);
 
         // Open and scrape the XLSX file
         let xlsx = xlsx_source.open_xlsx()?;
-        Self::scrape_xlsx(xlsx, context).await?;
+        Self::scrape_xlsx(xlsx, db).await?;
         Ok(())
     }
 }
@@ -80,14 +75,14 @@ impl XlsxSource {
 impl Scraper {
     pub async fn scrape_xlsx(
         mut xlsx: Xlsx<impl std::io::Read + std::io::Seek>,
-        context: &crate::ScraperContext<'_>,
+        db: &db::DatabasePool,
     ) -> Result<(), Box<dyn Error>> {
         let election_year = Self::parse_election_year("2024 State General Election")?;
         let election_date = GeneralElectionDateGenerator::new(election_year).generate()?;
         let (election_title, election_slug) =
             ElectionTitleGenerator::new(&db::RaceType::General, election_year).generate();
         let election = db::Election::upsert_from_source(
-            &context.db.connection,
+            &db.connection,
             &db::UpsertElectionInput {
                 slug: Some(election_slug),
                 title: Some(election_title),
@@ -146,7 +141,7 @@ impl Scraper {
                 r#"SELECT countyname, countyfips FROM p6t_state_mn.bdry_votingdistricts WHERE countycode = $1"#,
                 county_id
             )
-            .fetch_optional(&context.db.connection)
+            .fetch_optional(&db.connection)
             .await?;
 
             let (county, county_fips) = match county_data {
@@ -207,10 +202,9 @@ impl Scraper {
                 election_scope: Some(election_scope),
             };
 
-            let _ballot_measure =
-                db::BallotMeasure::upsert_from_source(&context.db.connection, &input)
-                    .await
-                    .expect("Error upserting ballot measure");
+            let _ballot_measure = db::BallotMeasure::upsert_from_source(&db.connection, &input)
+                .await
+                .expect("Error upserting ballot measure");
         }
         Ok(())
     }
@@ -1,6 +1,7 @@
 pub mod mn_ballot_measures;
 pub mod mn_candidate_filings_fed_state_county;
 pub mod mn_candidate_filings_local;
+pub mod rcv_ballots;
 pub mod sos;
 
 pub use mn_candidate_filings_fed_state_county::{
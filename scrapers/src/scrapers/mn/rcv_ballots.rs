@@ -0,0 +1,174 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+use db::{RankedBallotInput, RankedChoiceBallot, WRITE_IN_CANDIDATE_ID};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Rankings that aren't a vote for anyone. An overvote (several candidates marked at the
+/// same rank) ends the ballot, the rest are skipped over.
+const OVERVOTE: &str = "overvote";
+const SKIPPED: [&str; 3] = ["", "undervote", "skipped"];
+
+#[derive(Debug, Default)]
+pub struct CastVoteRecords {
+    pub ballots: Vec<RankedBallotInput>,
+    /// Rankings that didn't match a candidate in the race, e.g. "UWI", by ballot count.
+    /// They're ranked as `WRITE_IN_CANDIDATE_ID` on the ballots.
+    pub unmatched: BTreeMap<String, i32>,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parses a cast vote record export of a ranked choice race, as published by
+/// Minneapolis and St. Paul: one column per choice ("1st Choice", "2nd Choice", ...)
+/// and an optional "Count" column when identical ballots are grouped.
+/// `candidates` pairs each race candidate's id with the names they may appear under.
+pub fn parse_cast_vote_records(
+    csv_text: &str,
+    candidates: &[(Uuid, Vec<String>)],
+) -> Result<CastVoteRecords, Box<dyn Error>> {
+    let names: BTreeMap<String, Uuid> = candidates
+        .iter()
+        .flat_map(|(id, names)| names.iter().map(move |name| (normalize_name(name), *id)))
+        .collect();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(csv_text.as_bytes());
+    let headers = reader.headers()?.clone();
+    let choice_columns: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| header.to_lowercase().contains("choice"))
+        .map(|(i, _)| i)
+        .collect();
+    if choice_columns.is_empty() {
+        return Err("No choice columns found in cast vote records".into());
+    }
+    let count_column = headers
+        .iter()
+        .position(|header| header.trim().eq_ignore_ascii_case("count"));
+
+    let mut records = CastVoteRecords::default();
+    for row in reader.records() {
+        let row = row?;
+        let count = match count_column.and_then(|i| row.get(i)) {
+            Some(count) => count.trim().replace(',', "").parse::<i32>()?,
+            None => 1,
+        };
+
+        let mut rankings = vec![];
+        for value in choice_columns.iter().filter_map(|i| row.get(*i)) {
+            let name = normalize_name(value);
+            if name == OVERVOTE {
+                break;
+            }
+            if SKIPPED.contains(&name.as_str()) {
+                continue;
+            }
+            match names.get(&name) {
+                Some(id) => rankings.push(*id),
+                None => {
+                    rankings.push(WRITE_IN_CANDIDATE_ID);
+                    *records
+                        .unmatched
+                        .entry(value.trim().to_string())
+                        .or_insert(0) += count
+                }
+            }
+        }
+
+        records.ballots.push(RankedBallotInput {
+            rankings,
+            count: Some(count),
+        });
+    }
+
+    Ok(records)
+}
+
+/// Imports a cast vote record export into a race, returning the race's ballot count
+pub async fn import_cast_vote_records(
+    db_pool: &PgPool,
+    race_id: Uuid,
+    csv_text: &str,
+    replace: bool,
+) -> Result<i64, Box<dyn Error>> {
+    let candidates = sqlx::query!(
+        r#"
+        SELECT p.id, p.first_name, p.preferred_name, p.last_name, p.full_name
+        FROM politician p
+        JOIN race_candidates rc ON rc.candidate_id = p.id
+        WHERE rc.race_id = $1
+        "#,
+        race_id
+    )
+    .fetch_all(db_pool)
+    .await?
+    .into_iter()
+    .map(|p| {
+        let mut names = vec![format!("{} {}", p.first_name, p.last_name)];
+        if let Some(preferred_name) = p.preferred_name {
+            names.push(format!("{} {}", preferred_name, p.last_name));
+        }
+        names.push(p.full_name);
+        (p.id, names)
+    })
+    .collect::<Vec<_>>();
+
+    let records = parse_cast_vote_records(csv_text, &candidates)?;
+    for (name, count) in &records.unmatched {
+        tracing::warn!(
+            "Counted {} ranking(s) of '{}' as write-ins, no matching candidate",
+            count,
+            name
+        );
+    }
+
+    let total = RankedChoiceBallot::import(db_pool, race_id, &records.ballots, replace).await?;
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cast_vote_records() {
+        let frey = Uuid::from_u128(1);
+        let fateh = Uuid::from_u128(2);
+        let candidates = vec![
+            (frey, vec!["Jacob Frey".to_string()]),
+            (fateh, vec!["Omar Fateh".to_string()]),
+        ];
+        let csv_text = "Precinct,1st Choice,2nd Choice,3rd Choice,Count\n\
+            MINNEAPOLIS W-1 P-01,Jacob Frey,undervote,Omar Fateh,12\n\
+            MINNEAPOLIS W-1 P-01,OMAR FATEH,overvote,Jacob Frey,3\n\
+            MINNEAPOLIS W-1 P-02,UWI,Jacob Frey,,\"1,204\"\n";
+
+        let records = parse_cast_vote_records(csv_text, &candidates).unwrap();
+        let rankings: Vec<(Vec<Uuid>, Option<i32>)> = records
+            .ballots
+            .into_iter()
+            .map(|b| (b.rankings, b.count))
+            .collect();
+        assert_eq!(
+            rankings,
+            vec![
+                (vec![frey, fateh], Some(12)),
+                (vec![fateh], Some(3)),
+                (vec![WRITE_IN_CANDIDATE_ID, frey], Some(1204)),
+            ]
+        );
+        assert_eq!(records.unmatched.get("UWI"), Some(&1204));
+    }
+}
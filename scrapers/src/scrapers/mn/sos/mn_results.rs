@@ -1,6 +1,5 @@
 use csv::ReaderBuilder;
 use reqwest::Client;
-use std::collections::HashMap;
 use std::error::Error;
 use std::fs::File;
 
@@ -23,6 +22,9 @@ static HEADER_NAMES: [&str; 16] = [
     "Total number of votes for Office in area",
 ];
 
+/// Name of the file of precinct registration and turnout counts, which has its own layout
+pub const PRECINCT_STATS_FILE_NAME: &str = "Precinct Reporting Statistics";
+
 static PRECINCT_STATS_HEADER_NAMES: [&str; 12] = [
     "State",
     "County ID",
@@ -38,49 +40,24 @@ static PRECINCT_STATS_HEADER_NAMES: [&str; 12] = [
     "Total Number Voted",
 ];
 
-pub async fn fetch_results() -> Result<(), Box<dyn Error>> {
-    let mut results_file_paths: HashMap<&str, &str> = HashMap::new();
-
-    // results_file_paths.insert(
-    //     "U.S. Senator Statewide",
-    //     "https://electionresultsfiles.sos.state.mn.us/20241105/ussenate.txt",
-    // );
-    // results_file_paths.insert(
-    //     "U.S. Representative by District",
-    //     "https://electionresultsfiles.sos.state.mn.us/20241105/ushouse.txt",
-    // );
-    // results_file_paths.insert(
-    //     "Supreme Court and Courts of Appeals",
-    //     "https://electionresultsfiles.sos.state.mn.us/20241105/judicial.txt",
-    // );
-    // results_file_paths.insert(
-    //     "State Senator by District",
-    //     "https://electionresultsfiles.sos.mn.gov/20251104/stsenate.txt",
-    // );
-    // results_file_paths.insert(
-    //     "County Races",
-    //     "https://electionresultsfiles.sos.mn.gov/20251104/cntyRaces.txt",
-    // );
-    results_file_paths.insert(
-        "Municipal Races and Questions",
-        "https://electionresultsfiles.sos.mn.gov/20251104/local.txt",
-    );
-    results_file_paths.insert(
-        "School Board Races",
-        "https://electionresultsfiles.sos.mn.gov/20251104/sdrace.txt",
-    );
-    // results_file_paths.insert(
-    //     "State Representative by District",
-    //     "https://electionresultsfiles.sos.mn.gov/20250311/LegislativeByDistrict.txt",
-    // );
-    // results_file_paths.insert(
-    //     "District Court Judges",
-    //     "https://electionresultsfiles.sos.mn.gov/20241105/judicialdst.txt",
-    // );
+/// One results file published by the MN SoS for an election
+pub struct ResultsFile<'a> {
+    /// Name of the file, e.g. "School Board Races". Determines the column layout.
+    pub name: &'a str,
+    pub url: &'a str,
+    /// Staging table the file is loaded into before updating races, which must start with
+    /// `db::MN_STAGING_TABLE_PREFIX` since it's dropped and recreated on every fetch
+    pub table_name: &'a str,
+}
 
+pub async fn fetch_results(files: &[ResultsFile<'_>]) -> Result<(), Box<dyn Error>> {
     let client = Client::new();
     let mut table_names = Vec::new();
-    for (name, url) in results_file_paths {
+    for file in files {
+        let (name, url) = (file.name, file.url);
+        if !file.table_name.starts_with(db::MN_STAGING_TABLE_PREFIX) {
+            return Err(format!("Refusing to load results into {}", file.table_name).into());
+        }
         let response = client
             .get(url)
             .send()
@@ -91,29 +68,19 @@ pub async fn fetch_results() -> Result<(), Box<dyn Error>> {
             .await?;
         let data = convert_text_to_csv(name, &response)?;
         let csv_data_as_string = String::from_utf8(data.clone())?;
-        let table_name = format!(
-            "p6t_state_mn.results_{}_{}",
-            url.split('/')
-                .filter_map(|segment| {
-                    if segment.len() == 8 && segment.chars().all(|c| c.is_numeric()) {
-                        Some(segment)
-                    } else {
-                        None
-                    }
-                })
-                .next()
-                .unwrap_or_else(|| {
-                    tracing::warn!("No valid date segment found in URL: {}", url);
-                    "unknown"
-                }),
-            name.replace(['.', ','], "")
-                .replace(' ', "_")
-                .to_lowercase()
-        );
-        table_names.push(table_name.clone());
+        let table_name = file.table_name.to_string();
+        // Statistics files have no races, they're merged into precincts by the caller
+        if name != PRECINCT_STATS_FILE_NAME {
+            table_names.push(table_name.clone());
+        }
         let copy_query = format!("COPY {} FROM STDIN WITH CSV HEADER;", table_name);
         let pool = db::pool().await;
-        sqlx::query(format!(r#"DROP TABLE IF EXISTS {} CASCADE;"#, table_name).as_str())
+        if let Some((schema, _)) = table_name.split_once('.') {
+            sqlx::query(format!("CREATE SCHEMA IF NOT EXISTS {};", schema).as_str())
+                .execute(&pool.connection)
+                .await?;
+        }
+        sqlx::query(format!(r#"DROP TABLE IF EXISTS {};"#, table_name).as_str())
             .execute(&pool.connection)
             .await?;
 
@@ -129,13 +96,15 @@ pub async fn fetch_results() -> Result<(), Box<dyn Error>> {
         // TODO: Refactor this scraper to fit the Scraper interface with run_local fn and remove below line
         _write_to_csv_file(name, &data)?;
     }
-    update_public_schema_with_results(table_names).await;
+    if !table_names.is_empty() {
+        update_public_schema_with_results(table_names).await?;
+    }
 
     Ok(())
 }
 
 fn get_create_table_query(name: &str, table_name: &str) -> String {
-    if name == "Precinct Reporting Statistics" {
+    if name == PRECINCT_STATS_FILE_NAME {
         return format!(
             "CREATE TABLE {} (
             {}
@@ -171,7 +140,7 @@ fn convert_text_to_csv(name: &str, text: &str) -> Result<Vec<u8>, Box<dyn Error>
         let mut wtr = csv::Writer::from_writer(&mut csv_string);
 
         // Write the headers from the above struct
-        if name == "Precinct Reporting Statistics" {
+        if name == PRECINCT_STATS_FILE_NAME {
             wtr.write_record(PRECINCT_STATS_HEADER_NAMES)?;
         } else {
             wtr.write_record(HEADER_NAMES)?;
@@ -199,7 +168,18 @@ fn convert_text_to_csv(name: &str, text: &str) -> Result<Vec<u8>, Box<dyn Error>
     Ok(csv_string)
 }
 
-async fn update_public_schema_with_results(table_names: Vec<String>) {
+/// SQL expression building the race_candidates ref_key of a results row, for the
+/// staging table or CTE aliased as `alias`
+pub fn ref_key_sql(alias: &str) -> String {
+    // Inline slugify (no DB function / unaccent): lower, strip non-alphanumeric, spaces→hyphens, trim
+    format!(
+        "TRIM(BOTH '-' FROM REGEXP_REPLACE(REGEXP_REPLACE(REGEXP_REPLACE(LOWER(CONCAT('mn-sos-', {alias}.office_name, '-', {alias}.candidate_name)), '[^a-z0-9 -]', '', 'g'), '\\s+', '-', 'g'), '-+', '-', 'g'))"
+    )
+}
+
+async fn update_public_schema_with_results(
+    table_names: Vec<String>,
+) -> Result<(), Box<dyn Error>> {
     let db_pool = db::pool().await;
 
     // Build the source CTE dynamically from the provided table names
@@ -209,11 +189,8 @@ async fn update_public_schema_with_results(table_names: Vec<String>) {
         .collect::<Vec<String>>()
         .join(" UNION ALL ");
 
-    // Inline slugify (no DB function / unaccent): lower, strip non-alphanumeric, spaces→hyphens, trim
-    let ref_key_from_source =
-        "TRIM(BOTH '-' FROM REGEXP_REPLACE(REGEXP_REPLACE(REGEXP_REPLACE(LOWER(CONCAT('mn-sos-', source.office_name, '-', source.candidate_name)), '[^a-z0-9 -]', '', 'g'), '\\s+', '-', 'g'), '-+', '-', 'g'))";
-    let ref_key_from_results =
-        "TRIM(BOTH '-' FROM REGEXP_REPLACE(REGEXP_REPLACE(REGEXP_REPLACE(LOWER(CONCAT('mn-sos-', results.office_name, '-', results.candidate_name)), '[^a-z0-9 -]', '', 'g'), '\\s+', '-', 'g'), '-+', '-', 'g'))";
+    let ref_key_from_source = ref_key_sql("source");
+    let ref_key_from_results = ref_key_sql("results");
 
     let query = format!(
         r#"
@@ -299,17 +276,13 @@ async fn update_public_schema_with_results(table_names: Vec<String>) {
         source_tables, ref_key_from_source, ref_key_from_results
     );
 
-    let result = sqlx::query(&query)
+    sqlx::query(&query)
         .execute(&db_pool.connection)
         .await
-        .map_err(|e| {
-            println!("Error updating public schema with results: {}", e);
-            e
-        });
+        .map_err(|e| format!("Error updating public schema with results: {}", e))?;
 
-    if result.is_ok() {
-        println!("Public schema successfully updated with results");
-    }
+    tracing::info!("Public schema successfully updated with results");
+    Ok(())
 }
 
 fn _write_to_csv_file(name: &str, data: &[u8]) -> Result<(), Box<dyn Error>> {
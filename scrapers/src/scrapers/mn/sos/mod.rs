@@ -1,3 +1,3 @@
 pub mod mn_results;
 
-pub use mn_results::fetch_results;
+pub use mn_results::{fetch_results, ref_key_sql, ResultsFile, PRECINCT_STATS_FILE_NAME};
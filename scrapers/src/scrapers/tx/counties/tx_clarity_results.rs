@@ -30,9 +30,20 @@ pub async fn ensure_clarity_staging_table(
         .execute(pool)
         .await?;
 
+    create_clarity_staging_table(pool).await
+}
+
+/// Create the staging table if missing, keeping rows loaded by earlier runs.
+async fn create_clarity_staging_table(
+    pool: &PgPool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    sqlx::query("CREATE SCHEMA IF NOT EXISTS ingest_staging")
+        .execute(pool)
+        .await?;
+
     sqlx::query(
         r#"
-        CREATE TABLE ingest_staging.stg_tx_results_clarity (
+        CREATE TABLE IF NOT EXISTS ingest_staging.stg_tx_results_clarity (
             id BIGSERIAL PRIMARY KEY,
             office_name TEXT,
             office_key TEXT,
@@ -320,3 +331,56 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Load a single county's Clarity export, as polled by a results source. Downloads into a
+/// per-county directory and replaces only rows previously loaded from that county's CSVs,
+/// so sources for different counties can be polled independently.
+pub async fn run_source(
+    pool: &PgPool,
+    url: &str,
+    county_name: &str,
+    party: Option<&str>,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    create_clarity_staging_table(pool).await?;
+
+    let file_suffix = match party {
+        Some(p) => format!("{}_{}", sanitize_for_filename(county_name), sanitize_for_filename(p)),
+        None => sanitize_for_filename(county_name),
+    };
+    let source_dir = clarity_data_path().join(&file_suffix);
+    if source_dir.exists() {
+        fs::remove_dir_all(&source_dir)?;
+    }
+    fs::create_dir_all(&source_dir)?;
+
+    let zip_path = source_dir.join(zip_filename_for_url_county_party(url, county_name, party));
+    download_to_path(&reqwest::Client::new(), url, &zip_path).await?;
+    unzip_into_dir(
+        &zip_path,
+        &source_dir,
+        if file_suffix.is_empty() { None } else { Some(file_suffix.as_str()) },
+        Some(county_name),
+    )?;
+    fs::remove_file(&zip_path)?;
+
+    let csv_files: Vec<PathBuf> = fs::read_dir(&source_dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().map_or(false, |e| e.eq_ignore_ascii_case("csv")))
+        .collect();
+    let source_files: Vec<String> = csv_files
+        .iter()
+        .filter_map(|p| p.file_name().and_then(|s| s.to_str()).map(String::from))
+        .collect();
+
+    sqlx::query("DELETE FROM ingest_staging.stg_tx_results_clarity WHERE source_file = ANY($1)")
+        .bind(&source_files)
+        .execute(pool)
+        .await?;
+
+    let mut total_rows = 0;
+    for csv_path in csv_files {
+        total_rows += process_csv(pool, &csv_path).await?;
+    }
+    Ok(total_rows)
+}
@@ -9,6 +9,7 @@ use std::path::{Path, PathBuf};
 
 use sqlx::PgPool;
 
+use crate::extractors::politician::title_case;
 use crate::processors::tx::tx_hart_results_pdf_processor;
 use crate::processors::tx::tx_results;
 
@@ -24,9 +25,20 @@ pub async fn ensure_hart_staging_table(
         .execute(pool)
         .await?;
 
+    create_hart_staging_table(pool).await
+}
+
+/// Create the staging table if missing, keeping rows loaded by earlier runs.
+async fn create_hart_staging_table(
+    pool: &PgPool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    sqlx::query("CREATE SCHEMA IF NOT EXISTS ingest_staging")
+        .execute(pool)
+        .await?;
+
     sqlx::query(
         r#"
-        CREATE TABLE ingest_staging.stg_tx_results_hart (
+        CREATE TABLE IF NOT EXISTS ingest_staging.stg_tx_results_hart (
             id BIGSERIAL PRIMARY KEY,
             office_name TEXT,
             office_key TEXT,
@@ -173,3 +185,42 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Load a single county's Hart cumulative results PDF, as polled by a results source.
+/// The PDF is saved as cumulative_{county}_{party}.pdf alongside manually added inputs and
+/// only rows previously loaded from that file are replaced.
+pub async fn run_source(
+    pool: &PgPool,
+    url: &str,
+    county_name: &str,
+    party: Option<&str>,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    create_hart_staging_table(pool).await?;
+
+    let county_slug = county_name.trim().to_lowercase().replace(' ', "_");
+    let base = match party {
+        Some(p) => format!("cumulative_{}_{}", county_slug, p.trim().to_lowercase()),
+        None => format!("cumulative_{}", county_slug),
+    };
+    let pdf_path = hart_input_path().join(format!("{}.pdf", base));
+    let csv_name = format!("{}.csv", base);
+    let csv_path = hart_output_path().join(&csv_name);
+    fs::create_dir_all(hart_output_path())?;
+
+    let bytes = reqwest::get(url).await?.error_for_status()?.bytes().await?;
+    fs::create_dir_all(hart_input_path())?;
+    fs::write(&pdf_path, &bytes)?;
+
+    tx_hart_results_pdf_processor::parse_hart_pdf_to_csv(
+        &pdf_path,
+        Some(&csv_path),
+        &title_case(county_name),
+    )?;
+
+    sqlx::query("DELETE FROM ingest_staging.stg_tx_results_hart WHERE source_file = $1")
+        .bind(&csv_name)
+        .execute(pool)
+        .await?;
+
+    tx_results::process_hart_csv(pool, &csv_path, &csv_name, None).await
+}
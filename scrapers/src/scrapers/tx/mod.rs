@@ -1,3 +1,4 @@
 pub mod tx_candidate_web_scrape;
 pub mod tx_civix_fed_rep_results;
 pub mod counties;
+pub mod tx_results;
@@ -24,7 +24,7 @@ static RE_NORMALIZE_RACE_CLICK: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)clic
 /// Set to true to print debug info to stderr (options count, table row count, etc.).
 const DEBUG_SCRAPE: bool = true;
 
-const CIVIX_RACES_URL: &str = "https://goelect.txelections.civixapps.com/ivis-enr-ui/races";
+pub const CIVIX_RACES_URL: &str = "https://goelect.txelections.civixapps.com/ivis-enr-ui/races";
 const TX_SOS_DATA_DIR: &str = "data/tx/sos";
 const OUTPUT_CSV: &str = "sos-results-fed-rep.csv";
 
@@ -542,6 +542,15 @@ async fn click_tab_by_label(
 pub async fn scrape_civix_one_election(
     driver: &WebDriver,
     is_republican: bool,
+) -> Result<Vec<ResultRow>, Box<dyn std::error::Error + Send + Sync>> {
+    scrape_civix_election(driver, CIVIX_RACES_URL, is_republican).await
+}
+
+/// Same as `scrape_civix_one_election` against a specific Civix races page.
+pub async fn scrape_civix_election(
+    driver: &WebDriver,
+    races_url: &str,
+    is_republican: bool,
 ) -> Result<Vec<ResultRow>, Box<dyn std::error::Error + Send + Sync>> {
     let election_name = if is_republican {
         "Republican"
@@ -550,7 +559,7 @@ pub async fn scrape_civix_one_election(
     };
     debug(&format!("scrape_civix_one_election: {} primary (fresh session)", election_name));
 
-    driver.goto(CIVIX_RACES_URL).await?;
+    driver.goto(races_url).await?;
     driver
         .set_page_load_timeout(Duration::from_secs(60))
         .await?;
@@ -770,3 +779,27 @@ pub async fn write_results_to_db(
     tx.commit().await?;
     Ok(count)
 }
+
+/// Scrape both primaries from `races_url` and replace the staging table, as polled by a
+/// results source. `webdriver_url` is a running chromedriver, e.g. http://localhost:9515.
+pub async fn run(
+    pool: &PgPool,
+    races_url: &str,
+    webdriver_url: &str,
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    let mut caps = DesiredCapabilities::chrome();
+    caps.add_arg("--no-sandbox")?;
+    caps.add_arg("--disable-dev-shm-usage")?;
+    caps.add_arg("--headless=new")?;
+
+    let mut rows = Vec::new();
+    // Each primary needs a fresh driver session so the election modal appears
+    for is_republican in [true, false] {
+        let driver = WebDriver::new(webdriver_url, caps.clone()).await?;
+        let result = scrape_civix_election(&driver, races_url, is_republican).await;
+        driver.quit().await?;
+        rows.extend(result?);
+    }
+
+    write_results_to_db(pool, &rows).await
+}
@@ -1,18 +1,6 @@
-//! Texas election results scraper: SOS, Clarity, Hart, and Other county formats.
-//!
-//! Run one or more format scrapers via flags. With no format flag, runs all.
-//!
-//! Flags:
-//!   --sos       Scrape TX Secretary of State results (SFTP download + process to stg_tx_results_sos).
-//!   --clarity   Scrape Clarity county results (URL list CSV → stg_tx_results_clarity).
-//!   --hart      Scrape Hart/CIRA PDF county results → stg_tx_results_hart.
-//!   --other     Scrape Other county CSVs from data dir → stg_tx_results_other.
-//!
-//! SOS-only flags (when running SOS):
-//!   --download-only   Only download from SFTP; do not run the processor.
-//!   --no-download     Only run the processor; do not download.
-//!
-//! Optional positional arg: path to Clarity URL list CSV (used when running clarity; default from data/tx/counties).
+//! Texas election results downloads: SOS results over SFTP and "Other" county CSVs from
+//! the data dir. Clarity and Hart county results are handled by `tx::counties`. Run
+//! with `scrape run tx.results --stage scrape`.
 //!
 //! Env for SOS: TX_SOS_SFTP_HOST, TX_SOS_SFTP_USER, TX_SOS_SFTP_PASSWORD;
 //! optional TX_SOS_SFTP_PORT (default 22), TX_SOS_SFTP_REMOTE_DIR (default ".").
@@ -69,10 +57,13 @@ async fn download_sos_file(
     Ok(())
 }
 
-async fn run_sos(
+/// Downloads the SOS results XML files and loads them into ingest_staging.stg_tx_results_sos.
+/// Returns the number of rows loaded.
+pub async fn run_sos(
+    pool: &sqlx::PgPool,
     download_only: bool,
     no_download: bool,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
     let do_download = !no_download;
     let do_process = !download_only;
 
@@ -143,60 +134,24 @@ async fn run_sos(
         println!("\nDownloaded {} file(s) to {}", downloaded, local_dir.display());
     }
 
-    if do_process {
-        println!("\n=== Running TX SOS Results Processor ===\n");
-        let pool = db::pool().await;
-        match scrapers::processors::tx::tx_results::process_tx_sos_results(
-            &pool.connection,
-            true,
-        )
-        .await
-        {
-            Ok((files, rows)) => {
-                println!(
-                    "\n✓ Processed {} file(s), {} rows loaded into ingest_staging.stg_tx_results_sos",
-                    files, rows
-                );
-            }
-            Err(e) => {
-                eprintln!("\n✗ Processor error: {}", e);
-                return Err(e.into());
-            }
-        }
+    if !do_process {
+        return Ok(0);
     }
-    Ok(())
-}
-
-async fn run_clarity(
-    csv_path: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("=== TX Clarity County Results ===\n");
-    println!(
-        "Data dir: {}\n",
-        scrapers::tx::counties::tx_clarity_results::clarity_data_path().display()
-    );
-    scrapers::tx::counties::tx_clarity_results::run(&db::pool().await.connection, csv_path)
-        .await
-        .map_err(|e| e.into())
-}
 
-async fn run_hart() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    println!("=== TX Hart County Results ===\n");
-    println!(
-        "Input:  {}",
-        scrapers::tx::counties::tx_hart_results::hart_input_path().display()
-    );
+    println!("\n=== Running TX SOS Results Processor ===\n");
+    let (files, rows) =
+        crate::processors::tx::tx_results::process_tx_sos_results(pool, true).await?;
     println!(
-        "Output: {}\n",
-        scrapers::tx::counties::tx_hart_results::hart_output_path().display()
+        "\n✓ Processed {} file(s), {} rows loaded into ingest_staging.stg_tx_results_sos",
+        files, rows
     );
-    scrapers::tx::counties::tx_hart_results::run(&db::pool().await.connection)
-        .await
-        .map_err(|e| e.into())
+    Ok(rows)
 }
 
-async fn run_other() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    use scrapers::processors::tx::tx_results;
+/// Loads every CSV in the Other county data dir into ingest_staging.stg_tx_results_other.
+/// Returns the number of rows loaded.
+pub async fn run_other(pool: &sqlx::PgPool) -> Result<u64, Box<dyn std::error::Error + Send + Sync>> {
+    use crate::processors::tx::tx_results;
 
     println!("=== TX Other County Results ===\n");
     let data_dir = tx_results::other_data_path();
@@ -208,7 +163,7 @@ async fn run_other() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         return Err("Other data dir missing".into());
     }
 
-    let db = &db::pool().await.connection;
+    let db = pool;
 
     sqlx::query("CREATE SCHEMA IF NOT EXISTS ingest_staging")
         .execute(db)
@@ -282,71 +237,5 @@ async fn run_other() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         "\n✓ Done. {} total rows -> ingest_staging.stg_tx_results_other",
         total_rows
     );
-    Ok(())
-}
-
-#[tokio::main]
-async fn main() {
-    dotenvy::dotenv().ok();
-
-    let args: Vec<String> = std::env::args().collect();
-    let run_sos_flag = args.iter().any(|a| a == "--sos");
-    let run_clarity_flag = args.iter().any(|a| a == "--clarity");
-    let run_hart_flag = args.iter().any(|a| a == "--hart");
-    let run_other_flag = args.iter().any(|a| a == "--other");
-
-    let any_format = run_sos_flag || run_clarity_flag || run_hart_flag || run_other_flag;
-    let do_sos = run_sos_flag || !any_format;
-    let do_clarity = run_clarity_flag || !any_format;
-    let do_hart = run_hart_flag || !any_format;
-    let do_other = run_other_flag || !any_format;
-
-    let download_only = args.iter().any(|a| a == "--download-only");
-    let no_download = args.iter().any(|a| a == "--no-download");
-
-    let clarity_csv_path = args
-        .iter()
-        .skip(1)
-        .find(|a| !a.starts_with("--"))
-        .map(PathBuf::from);
-
-    let need_db = (do_sos && !download_only && !no_download) || do_clarity || do_hart || do_other;
-    if need_db {
-        db::init_pool().await.unwrap();
-    }
-
-    let mut failed = false;
-
-    if do_sos {
-        if let Err(e) = run_sos(download_only, no_download).await {
-            eprintln!("\n✗ SOS error: {}", e);
-            failed = true;
-        }
-    }
-
-    if do_clarity {
-        if let Err(e) = run_clarity(clarity_csv_path.clone()).await {
-            eprintln!("\n✗ Clarity error: {}", e);
-            failed = true;
-        }
-    }
-
-    if do_hart {
-        if let Err(e) = run_hart().await {
-            eprintln!("\n✗ Hart error: {}", e);
-            failed = true;
-        }
-    }
-
-    if do_other {
-        if let Err(e) = run_other().await {
-            eprintln!("\n✗ Other error: {}", e);
-            failed = true;
-        }
-    }
-
-    if failed {
-        std::process::exit(1);
-    }
-    println!("\n✓ Done.");
+    Ok(total_rows)
 }
@@ -0,0 +1,105 @@
+//! Post-primary script: creates runoff races for primaries that need one under the state's
+//! runoff rules, and general election races with the winners of the rest.
+//!
+//! Use `--dry-run` to print the races and candidates that would be added without writing
+//! anything. Running it again after more results come in only adds what's missing.
+
+use clap::Parser;
+use db::{advance_races, AdvanceRacesInput, State};
+use std::error::Error;
+use std::process;
+use std::str::FromStr;
+
+#[derive(Parser)]
+#[command(
+    author,
+    version,
+    about = "Create runoff and general election races from a primary election's results"
+)]
+struct Cli {
+    /// State of the primary races (e.g. TX)
+    #[arg(long)]
+    state: String,
+
+    /// Primary (or primary runoff) election to advance races from
+    #[arg(long)]
+    election_id: uuid::Uuid,
+
+    /// Election for new runoff races. Races needing a runoff are skipped without it.
+    #[arg(long)]
+    runoff_election_id: Option<uuid::Uuid>,
+
+    /// Election for new general election races
+    #[arg(long)]
+    general_election_id: Option<uuid::Uuid>,
+
+    /// Print the changes without making them
+    #[arg(long, short = 'n')]
+    dry_run: bool,
+}
+
+async fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let state = State::from_str(&cli.state).map_err(|_| format!("Unknown state {}", cli.state))?;
+    let input = AdvanceRacesInput {
+        state,
+        election_id: cli.election_id,
+        runoff_election_id: cli.runoff_election_id,
+        general_election_id: cli.general_election_id,
+        dry_run: Some(cli.dry_run),
+    };
+
+    db::init_pool().await?;
+    let pool = db::pool().await;
+    let plan = advance_races(&pool.connection, &input).await?;
+
+    for race in &plan.races {
+        match race.existing_race_id {
+            Some(id) => println!(
+                "~ {:?} race {} ({}): add {} candidates",
+                race.kind,
+                race.slug,
+                id,
+                race.new_candidate_ids.len()
+            ),
+            None => println!(
+                "+ {:?} race {} \"{}\" with {} candidates",
+                race.kind,
+                race.slug,
+                race.title,
+                race.new_candidate_ids.len()
+            ),
+        }
+        for candidate_id in &race.new_candidate_ids {
+            println!("    + candidate {}", candidate_id);
+        }
+    }
+    for update in &plan.winner_updates {
+        println!(
+            "~ primary {}: winners {:?}",
+            update.race_id, update.winner_ids
+        );
+    }
+    for skipped in &plan.skipped {
+        println!("- skipped {}: {}", skipped.slug, skipped.reason);
+    }
+
+    println!("--- Summary ---");
+    println!("  Races added or updated:  {}", plan.races.len());
+    println!("  Primary winners set:     {}", plan.winner_updates.len());
+    println!("  Primaries skipped:       {}", plan.skipped.len());
+    if plan.dry_run {
+        println!("  Dry run, nothing was written.");
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+    let cli = Cli::parse();
+
+    if let Err(e) = run(cli).await {
+        eprintln!("Post-processing failed: {}", e);
+        process::exit(1);
+    }
+}
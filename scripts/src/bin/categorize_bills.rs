@@ -1,10 +1,17 @@
-use async_openai::types::{ChatCompletionRequestMessage, CreateChatCompletionRequestArgs, Role};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
+use llm::Message;
+use serde::Deserialize;
+use serde_json::json;
 use std::error::Error;
 use std::process;
 use std::time::Instant;
 
+#[derive(Deserialize)]
+struct Categorization {
+    tags: Vec<String>,
+}
+
 struct Bill {
     id: Option<uuid::Uuid>,
     title: Option<String>,
@@ -34,7 +41,18 @@ async fn categorize_bills() -> Result<(), Box<dyn Error>> {
         .fetch_all(&pool.connection)
         .await?;
 
-    let client = async_openai::Client::new();
+    let client = llm::client()?;
+    let slugs: Vec<&str> = issue_tags.iter().map(|tag| tag.slug.as_str()).collect();
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "tags": {
+                "type": "array",
+                "items": { "type": "string", "enum": slugs }
+            }
+        },
+        "required": ["tags"]
+    });
 
     let bar = ProgressBar::new(bill_records.len() as u64);
     bar.set_style(
@@ -47,36 +65,17 @@ async fn categorize_bills() -> Result<(), Box<dyn Error>> {
             r#"
                 I have the following categories of bills: {tags}
                 Categorize a bill with the following title into one or more of the categories above: {title}
-                Respond only with the slugified tags, as listed above, comma separated.
+                Respond with the slugified tags, as listed above.
             "#,
-            tags = issue_tags
-                .iter()
-                .map(|tag| tag.slug.clone())
-                .collect::<Vec<_>>()
-                .join(", "),
+            tags = slugs.join(", "),
             title = bill.title.unwrap()
         );
-        let request = CreateChatCompletionRequestArgs::default()
-            .model("gpt-4")
-            .messages([ChatCompletionRequestMessage {
-                role: Role::User,
-                content: prompt,
-                name: None,
-            }])
-            .temperature(0.7)
-            .max_tokens(40_u16)
-            .build()?;
-
-        let response = client.chat().create(request).await?;
-        let suggested_slugs: Vec<&str> = response.choices[0]
-            .message
-            .content
-            .split(",")
-            .map(str::trim)
-            .collect();
+        let response: Categorization = client
+            .complete_json("bill_categorization", vec![Message::user(prompt)], &schema)
+            .await?;
 
-        for slug in suggested_slugs {
-            let issue_tag = issue_tags.iter().find(|tag| tag.slug == slug.trim());
+        for slug in response.tags {
+            let issue_tag = issue_tags.iter().find(|tag| tag.slug == slug);
             if let Some(tag) = issue_tag {
                 sqlx::query!(
                     r#"
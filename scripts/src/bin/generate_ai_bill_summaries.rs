@@ -1,9 +1,6 @@
-use async_openai::types::{
-    ChatCompletionRequestMessageArgs, CreateChatCompletionRequestArgs, Role,
-};
-use async_openai::Client;
 use clap::Parser;
 use colored::*;
+use llm::Message;
 use spinners::{Spinner, Spinners};
 use std::error::Error;
 use std::process;
@@ -108,27 +105,18 @@ async fn generate_ai_summaries(session_id: Uuid) -> Result<(), Box<dyn Error>> {
         };
 
         println!("\n🤖 Generating AI summary for bill {}", bill.id);
-        let messages = vec![
-            ChatCompletionRequestMessageArgs::default()
-                .role(Role::System)
-                .content("You are an expert at analyzing legislative text. Provide clear, concise answers based on the bill content provided.")
-                .build()?,
-            ChatCompletionRequestMessageArgs::default()
-                .role(Role::User)
-                .content(format!(
-                    "Summarize the following bill text clearly and concisely:\n\n{}",
-                    truncated_content
-                ))
-                .build()?,
-        ];
-
-        let request = CreateChatCompletionRequestArgs::default()
-            .model("gpt-3.5-turbo-0125")
-            .messages(messages)
-            .build()?;
-
-        let response = Client::new().chat().create(request).await?;
-        let summary = response.choices.first().unwrap().message.content.clone();
+        let summary = llm::client()?
+            .complete(
+                "bill_summary",
+                vec![
+                    Message::system("You are an expert at analyzing legislative text. Provide clear, concise answers based on the bill content provided."),
+                    Message::user(format!(
+                        "Summarize the following bill text clearly and concisely:\n\n{}",
+                        truncated_content
+                    )),
+                ],
+            )
+            .await?;
 
         println!("\n💾 Saving summary for bill {}", bill.id);
         sqlx::query!(
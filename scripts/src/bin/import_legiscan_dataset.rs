@@ -19,9 +19,9 @@ async fn main() {
     let args = Args::parse();
 
     let params = import_legiscan_dataset::ImportSessionDataParams {
-        session_id: args.session_id,
+        session_id: Some(args.session_id),
         state: args.state,
-        year: args.year,
+        year: Some(args.year),
     };
 
     if let Err(err) = import_legiscan_dataset::run(params).await {
@@ -1,10 +1,7 @@
-use async_openai::{
-    types::{ChatCompletionRequestMessageArgs, CreateChatCompletionRequestArgs, Role},
-    Client,
-};
 use clap::Parser;
 use colored::*;
 use db::BillStatus;
+use llm::{LlmClient, Message};
 use spinners::{Spinner, Spinners};
 use std::error::Error;
 use std::fmt;
@@ -29,7 +26,7 @@ struct BillMeta {
 struct BillContext {
     meta: BillMeta,
     body: String,
-    client: Client,
+    client: &'static LlmClient,
 }
 
 impl fmt::Display for BillMeta {
@@ -53,7 +50,7 @@ impl BillContext {
                     status: bill.status,
                 },
                 body: "".into(),
-                client: Client::new(),
+                client: llm::client()?,
             });
         }
 
@@ -69,35 +66,27 @@ impl BillContext {
                 status: bill.status,
             },
             body: content,
-            client: Client::new(),
+            client: llm::client()?,
         })
     }
 
     async fn ask_question(&self, question: &str) -> Result<String, Box<dyn Error>> {
         let mut sp = Spinner::new(Spinners::Dots5, "Getting response...".into());
 
-        let messages = vec![
-            ChatCompletionRequestMessageArgs::default()
-                .role(Role::System)
-                .content("You are an expert at analyzing legislative text. Provide clear, concise answers based on the bill content provided.")
-                .build()?,
-            ChatCompletionRequestMessageArgs::default()
-                .role(Role::User)
-                .content(format!(
-                    "A bill has the following metadata:\n{}\n
+        let answer = self
+            .client
+            .complete(
+                "bill_question",
+                vec![
+                    Message::system("You are an expert at analyzing legislative text. Provide clear, concise answers based on the bill content provided."),
+                    Message::user(format!(
+                        "A bill has the following metadata:\n{}\n
                     Based on this legislative bill text:\n\n{}\n\nAnswer this question: {}",
-                    self.meta, self.body, question
-                ))
-                .build()?,
-        ];
-
-        let request = CreateChatCompletionRequestArgs::default()
-            .model("gpt-3.5-turbo-0125")
-            .messages(messages)
-            .build()?;
-
-        let response = self.client.chat().create(request).await?;
-        let answer = response.choices.first().unwrap().message.content.clone();
+                        self.meta, self.body, question
+                    )),
+                ],
+            )
+            .await?;
 
         sp.stop();
         Ok(answer)
@@ -1,17 +1,31 @@
+use clap::Parser;
+use server::jobs::update_legiscan_bill_data::{self, UpdateBillDataParams};
 use std::{error::Error, process};
 
-async fn update_legiscan_bill_data() -> Result<(), Box<dyn Error>> {
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Legiscan session ids to check for bill changes
+    #[arg(long = "session-id", required = true)]
+    session_ids: Vec<i32>,
+}
+
+async fn update_legiscan_bill_data(args: Args) -> Result<(), Box<dyn Error>> {
     db::init_pool().await.unwrap();
-    server::jobs::update_legiscan_bill_data::run()
-        .await
-        .map_err(|e| tracing::error!("Failed to update bill data: {}", e))
-        .ok();
+    update_legiscan_bill_data::run(UpdateBillDataParams {
+        session_ids: Some(args.session_ids),
+        state: None,
+    })
+    .await
+    .map_err(|e| tracing::error!("Failed to update bill data: {}", e))
+    .ok();
     Ok(())
 }
 
 #[tokio::main]
 async fn main() {
-    if let Err(err) = update_legiscan_bill_data().await {
+    let args = Args::parse();
+    if let Err(err) = update_legiscan_bill_data(args).await {
         println!("error running example: {}", err);
         process::exit(1);
     }
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use graphql::email::deliver_outbox;
+use mailers::EmailTransport;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// How often to check the outbox for emails that are due
+const TICK_INTERVAL: Duration = Duration::from_secs(5);
+
+// Sends queued emails through the transport picked by `EMAIL_TRANSPORT`
+pub async fn run(db_pool: PgPool) {
+    let transport: Box<dyn EmailTransport> = match mailers::transport_from_env() {
+        Ok(transport) => transport,
+        Err(e) => {
+            error!("Not delivering email: {}", e);
+            return;
+        }
+    };
+
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    loop {
+        interval.tick().await;
+        match deliver_outbox(&db_pool, transport.as_ref()).await {
+            Ok(0) => {}
+            Ok(sent) => info!("Sent {} queued emails", sent),
+            Err(e) => error!("Failed to deliver queued emails: {}", e),
+        }
+    }
+}
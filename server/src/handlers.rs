@@ -1,86 +1,185 @@
-use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::http::{playground_source, GraphQLPlaygroundConfig, ALL_WEBSOCKET_PROTOCOLS};
+use async_graphql::Data;
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use auth::{jwt, AccessTokenClaims};
 use axum::{
-    extract::{ConnectInfo, State},
+    extract::{ConnectInfo, State, WebSocketUpgrade},
     http::HeaderMap,
     response::{self, IntoResponse},
 };
 use graphql::{PopulistSchema, SessionData, SessionID};
 use jsonwebtoken::TokenData;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use tower_cookies::{cookie::SameSite, Cookie, Cookies};
 
-async fn refresh_token_check(cookies: &Cookies) -> Option<TokenData<AccessTokenClaims>> {
-    if let Some(refresh_cookie) = cookies.get("refresh_token") {
-        match jwt::validate_refresh_token(refresh_cookie.value()) {
-            Ok(token_data) => {
-                let db_pool = db::pool().await;
-                let user = db::User::find_by_id(&db_pool.connection, token_data.claims.sub).await;
-
-                if let Ok(user) = user {
-                    // Ensure the refresh token in the cookie matches the one associated with the user in the database
-                    if user.clone().refresh_token.unwrap() != refresh_cookie.value() {
-                        // If not, remove the cookies and return None
-                        cookies.to_owned().remove(Cookie::new("access_token", ""));
-                        cookies.to_owned().remove(Cookie::new("refresh_token", ""));
-                        None
-                    } else {
-                        let config::Config {
-                            root_domain,
-                            same_site,
-                            ..
-                        } = config::Config::default();
-
-                        let same_site = match same_site.as_str() {
-                            "Strict" => SameSite::Strict,
-                            "Lax" => SameSite::Lax,
-                            "None" => SameSite::None,
-                            _ => SameSite::None,
-                        };
-
-                        // If so, create a new access token, set it in the cookie, and return it
-                        let organization_roles =
-                            db::User::organization_roles(&db_pool.connection, user.id)
-                                .await
-                                .unwrap_or(vec![]);
-                        let access_token =
-                            jwt::create_access_token_for_user(user, organization_roles).unwrap();
-                        let mut cookie =
-                            tower_cookies::Cookie::new("access_token", access_token.clone());
-                        cookie.set_expires(
-                            time::OffsetDateTime::now_utc() + time::Duration::minutes(15),
-                        );
-                        cookie.set_domain(root_domain);
-                        cookie.set_same_site(same_site);
-                        cookie.set_http_only(true);
-                        cookies.add(cookie);
-                        Some(jwt::validate_access_token(&access_token).unwrap())
-                    }
-                } else {
-                    cookies.to_owned().remove(Cookie::new("access_token", ""));
-                    cookies.to_owned().remove(Cookie::new("refresh_token", ""));
-                    None
+fn clear_auth_cookies(cookies: &Cookies) {
+    cookies.to_owned().remove(Cookie::new("access_token", ""));
+    cookies.to_owned().remove(Cookie::new("refresh_token", ""));
+}
+
+fn set_auth_cookie(cookies: &Cookies, name: &'static str, value: String, lifetime: time::Duration) {
+    let config::Config {
+        root_domain,
+        same_site,
+        ..
+    } = config::Config::default();
+
+    let same_site = match same_site.as_str() {
+        "Strict" => SameSite::Strict,
+        "Lax" => SameSite::Lax,
+        "None" => SameSite::None,
+        _ => SameSite::None,
+    };
+
+    let mut cookie = tower_cookies::Cookie::new(name, value);
+    cookie.set_expires(time::OffsetDateTime::now_utc() + lifetime);
+    cookie.set_domain(root_domain);
+    cookie.set_same_site(same_site);
+    cookie.set_http_only(true);
+    cookies.add(cookie);
+}
+
+/// Addresses of our own proxies, such as the Heroku router, which connect from the private
+/// network rather than the internet
+fn is_trusted_proxy(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_trusted_proxy(IpAddr::V4(ip)),
+            // Unique local addresses are fc00::/7
+            None => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        },
+    }
+}
+
+/// The address of the client behind any of our proxies. Each proxy appends the address it
+/// received the request from to `X-Forwarded-For`, so the rightmost entry that isn't one of
+/// our proxies is the client. Entries further left are set by the client and can't be
+/// trusted. Requests that don't come through a proxy use the connection's address.
+fn client_ip(peer: SocketAddr, headers: &HeaderMap) -> IpAddr {
+    if !is_trusted_proxy(peer.ip()) {
+        return peer.ip();
+    }
+    let forwarded_for = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(','))
+        .filter_map(|entry| entry.trim().parse::<IpAddr>().ok())
+        .collect::<Vec<_>>();
+    forwarded_for
+        .iter()
+        .rev()
+        .find(|ip| !is_trusted_proxy(**ip))
+        .or(forwarded_for.first())
+        .copied()
+        .unwrap_or(peer.ip())
+}
+
+/// Exchanges the refresh token cookie for a new access token, rotating the refresh token.
+/// A rotated token presented again revokes its session and signs the device out.
+async fn refresh_token_check(
+    cookies: &Cookies,
+    ip: IpAddr,
+    headers: &HeaderMap,
+) -> Option<TokenData<AccessTokenClaims>> {
+    let refresh_cookie = cookies.get("refresh_token")?;
+    let presented = refresh_cookie.value().to_string();
+
+    let Ok(token_data) = jwt::validate_refresh_token(&presented) else {
+        clear_auth_cookies(cookies);
+        return None;
+    };
+    let db_pool = db::pool().await;
+    let Ok(user) = db::User::find_by_id(&db_pool.connection, token_data.claims.sub).await else {
+        clear_auth_cookies(cookies);
+        return None;
+    };
+
+    let session_id = match token_data.claims.sid {
+        Some(session_id) => {
+            let next_token = jwt::create_refresh_token_for_user(user.clone(), session_id).ok()?;
+            let outcome = db::UserSession::refresh(
+                &db_pool.connection,
+                session_id,
+                user.id,
+                &presented,
+                &next_token,
+            )
+            .await;
+            match outcome {
+                Ok(db::RefreshOutcome::Rotated) => {
+                    set_auth_cookie(
+                        cookies,
+                        "refresh_token",
+                        next_token,
+                        time::Duration::days(120),
+                    );
+                }
+                Ok(db::RefreshOutcome::Concurrent) => {}
+                Ok(db::RefreshOutcome::Reused | db::RefreshOutcome::Invalid) | Err(_) => {
+                    clear_auth_cookies(cookies);
+                    return None;
                 }
             }
-            Err(_) => {
-                cookies.to_owned().remove(Cookie::new("access_token", ""));
-                cookies.to_owned().remove(Cookie::new("refresh_token", ""));
-                None
+            session_id
+        }
+        // Issued before sessions existed, exchanged once for a session of its own
+        None => {
+            if user.refresh_token.as_deref() != Some(presented.as_str()) {
+                clear_auth_cookies(cookies);
+                return None;
             }
+            let session_id = uuid::Uuid::new_v4();
+            let next_token = jwt::create_refresh_token_for_user(user.clone(), session_id).ok()?;
+            let user_agent = headers
+                .get("user-agent")
+                .and_then(|header| header.to_str().ok());
+            db::UserSession::create(
+                &db_pool.connection,
+                session_id,
+                user.id,
+                &next_token,
+                user_agent,
+                Some(&ip.to_string()),
+            )
+            .await
+            .ok()?;
+            db::User::update_refresh_token(&db_pool.connection, user.id, None)
+                .await
+                .ok()?;
+            set_auth_cookie(
+                cookies,
+                "refresh_token",
+                next_token,
+                time::Duration::days(120),
+            );
+            session_id
         }
-    } else {
-        None
-    }
+    };
+
+    let organization_roles = db::User::organization_roles(&db_pool.connection, user.id)
+        .await
+        .unwrap_or(vec![]);
+    let access_token =
+        jwt::create_access_token_for_user(user, organization_roles, Some(session_id)).unwrap();
+    set_auth_cookie(
+        cookies,
+        "access_token",
+        access_token.clone(),
+        time::Duration::minutes(15),
+    );
+    Some(jwt::validate_access_token(&access_token).unwrap())
 }
 
 pub async fn graphql_handler(
-    ConnectInfo(ip): ConnectInfo<SocketAddr>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
     State(schema): State<PopulistSchema>,
     headers: HeaderMap,
     cookies: Cookies,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
+    let ip = client_ip(peer, &headers);
     let mut headers = headers.clone();
     headers.insert("Access-Control-Allow-Credentials", "true".parse().unwrap());
 
@@ -89,7 +188,22 @@ pub async fn graphql_handler(
         .and_then(|header| header.to_str().ok())
         .and_then(|header| header.split_whitespace().nth(1));
 
-    let bearer_token_data = if let Some(token) = bearer_token {
+    // Organization API keys are sent as bearer tokens too, they're told apart by their prefix
+    let api_key = match bearer_token {
+        Some(token) if token.starts_with(db::API_KEY_PREFIX) => {
+            let db_pool = db::pool().await;
+            match db::OrganizationApiKey::authenticate(&db_pool.connection, token).await {
+                Ok(api_key) => api_key,
+                Err(err) => {
+                    tracing::error!("Error authenticating API key: {}", err);
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let bearer_token_data = if let Some(token) = bearer_token.filter(|_| api_key.is_none()) {
         let token_data = jwt::validate_access_token(token);
         if let Ok(token_data) = token_data {
             tracing::debug!("{:?}", token_data);
@@ -101,12 +215,14 @@ pub async fn graphql_handler(
         None
     };
 
+    // Calls made with an API key never act as a signed in user
     let cookie_token_data = match cookies.get("access_token") {
+        _ if api_key.is_some() => None,
         Some(access_cookie) => match jwt::validate_access_token(access_cookie.value()) {
             Ok(token_data) => Some(token_data),
-            Err(_) => refresh_token_check(&cookies).await,
+            Err(_) => refresh_token_check(&cookies, ip, &headers).await,
         },
-        None => refresh_token_check(&cookies).await,
+        None => refresh_token_check(&cookies, ip, &headers).await,
     };
 
     // Use the bearer token if it's present, otherwise use the cookie
@@ -131,9 +247,20 @@ pub async fn graphql_handler(
         }
     };
 
-    let session_data = SessionData { session_id, ip };
+    let user_agent = headers
+        .get("user-agent")
+        .and_then(|header| header.to_str().ok())
+        .map(String::from);
+    let session_data = SessionData {
+        session_id,
+        ip,
+        user_agent,
+    };
 
-    let req = req.into_inner();
+    let mut req = req.into_inner();
+    if let Some(api_key) = api_key {
+        req = req.data(api_key);
+    }
 
     schema
         .execute(req.data(token_data).data(session_data))
@@ -141,6 +268,48 @@ pub async fn graphql_handler(
         .into()
 }
 
+pub async fn graphql_ws_handler(
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(schema): State<PopulistSchema>,
+    headers: HeaderMap,
+    cookies: Cookies,
+    protocol: GraphQLProtocol,
+    websocket: WebSocketUpgrade,
+) -> impl IntoResponse {
+    // Cookies are only available on the upgrade request, so resolve them before the socket opens
+    let ip = client_ip(peer, &headers);
+    let cookie_token_data = cookies
+        .get("access_token")
+        .and_then(|access_cookie| jwt::validate_access_token(access_cookie.value()).ok());
+    let session_data = cookies.get("session_id").map(|session_cookie| SessionData {
+        session_id: session_cookie.value().to_string().into(),
+        ip,
+        user_agent: None,
+    });
+
+    websocket
+        .protocols(ALL_WEBSOCKET_PROTOCOLS)
+        .on_upgrade(move |stream| {
+            GraphQLWebSocket::new(stream, schema, protocol)
+                .on_connection_init(move |payload| async move {
+                    // Clients that can't send cookies may pass a bearer token in the connection_init payload
+                    let bearer_token_data = payload
+                        .get("Authorization")
+                        .and_then(|header| header.as_str())
+                        .and_then(|header| header.split_whitespace().nth(1))
+                        .and_then(|token| jwt::validate_access_token(token).ok());
+
+                    let mut data = Data::default();
+                    data.insert(bearer_token_data.or(cookie_token_data));
+                    if let Some(session_data) = session_data {
+                        data.insert(session_data);
+                    }
+                    Ok(data)
+                })
+                .serve()
+        })
+}
+
 pub async fn graphql_playground() -> impl IntoResponse {
     response::Html(playground_source(
         GraphQLPlaygroundConfig::new("/").subscription_endpoint("/ws"),
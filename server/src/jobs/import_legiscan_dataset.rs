@@ -4,34 +4,81 @@ use colored::*;
 use db::models::enums::{BillStatus, PoliticalScope, State};
 use db::{Bill, Chamber, UpsertBillInput};
 use legiscan::GetBillResponse;
+use serde::Deserialize;
 use slugify::slugify;
+use sqlx::PgPool;
 use std::collections::HashMap;
 use std::error::Error;
-use std::process;
 use std::str::FromStr;
 use std::time::Instant;
 
-// New struct for function parameters
+// New struct for function parameters, also used as the job queue payload
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ImportSessionDataParams {
-    pub session_id: i32,
+    /// Legiscan session id, defaults to the state's current regular session
+    pub session_id: Option<i32>,
     pub state: State,
-    pub year: i32,
+    /// Defaults to the year the session started
+    pub year: Option<i32>,
+}
+
+/// The Legiscan id and start year of a session, or of the state's latest regular session
+/// that has started when no id is given
+async fn legiscan_session(
+    db_pool: &PgPool,
+    state: State,
+    session_id: Option<i32>,
+) -> Result<(i32, i32), Box<dyn Error>> {
+    let session = sqlx::query!(
+        r#"
+            SELECT
+                legiscan_session_id AS "legiscan_session_id!",
+                EXTRACT(YEAR FROM start_date)::int AS "year!"
+            FROM session
+            WHERE state = $1
+            AND start_date IS NOT NULL
+            AND (
+                legiscan_session_id = $2
+                OR (
+                    $2::int IS NULL
+                    AND legiscan_session_id IS NOT NULL
+                    AND start_date <= CURRENT_DATE
+                    AND name NOT ILIKE '%special%'
+                )
+            )
+            ORDER BY start_date DESC
+            LIMIT 1
+        "#,
+        state as State,
+        session_id
+    )
+    .fetch_optional(db_pool)
+    .await?
+    .ok_or_else(|| format!("No Legiscan session found for {}", state))?;
+
+    Ok((session.legiscan_session_id, session.year))
 }
 
 pub async fn run(params: ImportSessionDataParams) -> Result<(), Box<dyn Error>> {
+    db::init_pool().await.unwrap();
+    let db_pool = &db::pool().await.connection;
+
+    let (session_id, year) = match (params.session_id, params.year) {
+        (Some(session_id), Some(year)) => (session_id, year),
+        (session_id, _) => legiscan_session(db_pool, params.state, session_id).await?,
+    };
+
     // Fetch dataset from Legiscan
     let legiscan = legiscan::LegiscanProxy::new().unwrap();
     let dataset_list = legiscan
         .get_dataset_list(
             Some(params.state.to_string().as_str()),
-            Some(params.year.to_string().as_str()),
+            Some(year.to_string().as_str()),
         )
         .await
         .unwrap();
 
-    db::init_pool().await.unwrap();
-    let db_pool = &db::pool().await.connection;
-
     let start = Instant::now();
 
     let session = sqlx::query!(
@@ -40,7 +87,7 @@ pub async fn run(params: ImportSessionDataParams) -> Result<(), Box<dyn Error>>
             FROM session
             WHERE legiscan_session_id = $1
         "#,
-        params.session_id
+        session_id
     )
     .fetch_one(db_pool)
     .await;
@@ -55,25 +102,18 @@ pub async fn run(params: ImportSessionDataParams) -> Result<(), Box<dyn Error>>
             populist_session_id = session.id;
             if session.legiscan_dataset_hash == Some(hash.clone()) {
                 println!("\n\n🟢 Dataset already up to date.  No new bills found.\n");
-                process::exit(0);
+                return Ok(());
             } else {
                 println!("\n\n🟡 Dataset has changed.  Updating dataset and importing new bills.\n")
             }
         }
         Err(_) => {
-            println!(
-                "\n\n🔴 Error: Populist session not found: {}\n",
-                params.session_id
-            );
-            process::exit(1);
+            return Err(format!("Populist session not found: {}", session_id).into());
         }
     }
 
     let access_key = dataset_list[0].access_key.clone();
-    let dataset = legiscan
-        .get_dataset(params.session_id, &access_key)
-        .await
-        .unwrap();
+    let dataset = legiscan.get_dataset(session_id, &access_key).await.unwrap();
     let zip = dataset.zip;
     // Decode base64 encoded zip file
     let zip_bytes = general_purpose::STANDARD.decode(zip.as_bytes()).unwrap();
@@ -133,7 +173,7 @@ pub async fn run(params: ImportSessionDataParams) -> Result<(), Box<dyn Error>>
                 "{}{}{}",
                 &bill.state.clone(),
                 &bill.bill_number,
-                format_args!("-{}", params.year) // Using params.year instead of hardcoded year
+                format_args!("-{}", year) // Using the session's year instead of hardcoded year
             )
             .to_string())),
             title: Some(bill.title.clone()),
@@ -1,12 +1,44 @@
 use std::collections::HashMap;
 
+use anyhow::anyhow;
+use db::models::enums::State;
+use serde::Deserialize;
 use tracing::info;
 
-pub async fn run() -> anyhow::Result<()> {
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateBillDataParams {
+    /// Legiscan session ids whose bills should be checked for changes
+    pub session_ids: Option<Vec<i32>>,
+    /// Checks the state's sessions that are in progress when no session ids are given
+    pub state: Option<State>,
+}
+
+pub async fn run(params: UpdateBillDataParams) -> anyhow::Result<()> {
+    let pool = db::pool().await;
+    let session_ids = match (params.session_ids, params.state) {
+        (Some(session_ids), _) => session_ids,
+        (None, Some(state)) => {
+            sqlx::query_scalar!(
+                r#"
+                SELECT legiscan_session_id AS "legiscan_session_id!"
+                FROM session
+                WHERE state = $1
+                AND legiscan_session_id IS NOT NULL
+                AND start_date <= CURRENT_DATE
+                AND (end_date IS NULL OR end_date >= CURRENT_DATE)
+            "#,
+                state as State
+            )
+            .fetch_all(&pool.connection)
+            .await?
+        }
+        (None, None) => return Err(anyhow!("Either sessionIds or state is required")),
+    };
+
     let legiscan = legiscan::LegiscanProxy::new().unwrap();
-    let target_session_ids = vec![2173, 2151];
     let mut masterlist = Vec::new();
-    for session_id in target_session_ids {
+    for session_id in session_ids {
         let session_masterlist = legiscan
             .get_master_list_raw_by_session(session_id)
             .await
@@ -22,7 +54,6 @@ pub async fn run() -> anyhow::Result<()> {
     }
 
     let json = serde_json::to_value(bills_hash_map).unwrap();
-    let pool = db::pool().await;
     let updated_bills = sqlx::query!(
         r#"
                 WITH hash AS (
@@ -79,5 +110,9 @@ pub async fn run() -> anyhow::Result<()> {
 async fn test_update_legiscan_bill_data() {
     let _ = tracing_subscriber::fmt::try_init();
     let _ = db::init_pool().await;
-    let _ = run().await;
+    let _ = run(UpdateBillDataParams {
+        session_ids: None,
+        state: Some(State::MN),
+    })
+    .await;
 }
@@ -2,7 +2,14 @@ use async_graphql::extensions::ApolloTracing;
 use axum::routing::get;
 use axum_server::tls_rustls::RustlsConfig;
 use dotenv::dotenv;
-use graphql::{cache::Cache, context::ApiContext, new_schema};
+use graphql::{
+    audit::AuditExtension,
+    cache::{Cache, MemoryCache, PostgresCache},
+    context::ApiContext,
+    events::EventBroker,
+    new_schema,
+    rate_limit::RateLimiter,
+};
 use metrics::metrics_auth;
 use rustls::crypto::ring::default_provider;
 use rustls::crypto::CryptoProvider;
@@ -12,15 +19,18 @@ use tower_cookies::CookieManagerLayer;
 use tower_http::cors::CorsLayer;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
-mod cron;
+mod email_outbox;
 pub mod jobs;
 pub mod metrics;
+mod outreach_tracking;
 mod postgres;
+mod results_scheduler;
 pub mod slack;
-pub use cron::init_job_schedule;
 pub use jobs::*;
+pub use worker::init_job_worker;
 mod handlers;
-pub use handlers::{graphql_handler, graphql_playground};
+mod worker;
+pub use handlers::{graphql_handler, graphql_playground, graphql_ws_handler};
 
 pub async fn run() {
     dotenv().ok();
@@ -32,6 +42,15 @@ pub async fn run() {
 
     metrics::init_metrics();
 
+    // Every LLM call in this process reports its token usage
+    match llm::LlmClient::from_env() {
+        Ok(client) => {
+            info!("LLM calls go to {}", client.provider_name());
+            llm::init(client.with_metrics(metrics::PrometheusLlmMetrics));
+        }
+        Err(err) => tracing::error!("{}", err),
+    }
+
     db::init_pool().await.unwrap();
     let pool = db::pool().await;
     metrics::update_db_connections("main", pool);
@@ -44,14 +63,14 @@ pub async fn run() {
         }
     });
 
-    // Run cron jobs in separate thread
-    tokio::spawn(cron::init_job_schedule());
-
     // Postgres realtime listeners in separate thread
     let pool_for_listener = pool.clone(); // No need to clone the actual connection pool
+    let broker = EventBroker::default();
+    let broker_for_listener = broker.clone();
 
     tokio::spawn(async move {
-        if let Err(e) = postgres::listener(pool_for_listener.connection).await {
+        if let Err(e) = postgres::listener(pool_for_listener.connection, broker_for_listener).await
+        {
             eprintln!("Error in listener: {}", e);
         }
     });
@@ -62,6 +81,9 @@ pub async fn run() {
         .await
         .unwrap();
 
+    // Deliver queued email, which is only logged until a transport is configured
+    tokio::spawn(email_outbox::run(pool.connection.clone()));
+
     let context = ApiContext::new(pool.clone().connection);
 
     let environment = config::Config::default().environment;
@@ -71,10 +93,41 @@ pub async fn run() {
         Duration::from_secs(60 * 30)
     };
 
+    // Postgres shares cached analysis across instances, memory keeps local development simple
+    let cache_backend = std::env::var("CACHE_BACKEND").unwrap_or_else(|_| {
+        if environment == config::Environment::Production {
+            "postgres".to_string()
+        } else {
+            "memory".to_string()
+        }
+    });
+    let cache = match cache_backend.as_str() {
+        "postgres" => Cache::with_metrics(
+            PostgresCache::new(pool.connection.clone(), cache_duration),
+            metrics::PrometheusCacheMetrics,
+        ),
+        _ => Cache::with_metrics(
+            MemoryCache::new(10_000, cache_duration),
+            metrics::PrometheusCacheMetrics,
+        ),
+    };
+
+    // Run queued and recurring jobs in separate thread, with the cache so reanalyzed
+    // conversations drop their cached snapshots
+    tokio::spawn(worker::init_job_worker(pool.connection.clone(), cache.clone()));
+
+    let rate_limiter = RateLimiter::with_metrics(
+        config::RateLimits::default(),
+        metrics::PrometheusRateLimitMetrics,
+    );
+
     let mut schema_builder = new_schema()
         .data(context)
-        .data(Cache::<String, serde_json::Value>::new(cache_duration))
-        .extension(metrics::PrometheusMetricsExtension);
+        .data(cache)
+        .data(rate_limiter)
+        .data(broker)
+        .extension(metrics::PrometheusMetricsExtension)
+        .extension(AuditExtension);
 
     if environment != config::Environment::Production {
         schema_builder = schema_builder.extension(ApolloTracing);
@@ -87,6 +140,15 @@ pub async fn run() {
 
     let app = axum::Router::new()
         .route("/", get(graphql_playground).post(graphql_handler))
+        .route("/ws", get(graphql_ws_handler))
+        .route(
+            "/outreach/:tracking_token/open",
+            get(outreach_tracking::outreach_open_handler),
+        )
+        .route(
+            "/outreach/:tracking_token/click",
+            get(outreach_tracking::outreach_click_handler),
+        )
         .nest(
             "/metrics",
             axum::Router::new()
@@ -2,7 +2,12 @@ use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, N
 use axum::http::Request;
 use axum::middleware::Next;
 use db::DatabasePool;
+use graphql::{
+    cache::CacheMetrics,
+    rate_limit::{RateLimitMetrics, RateLimitScope},
+};
 use lazy_static::lazy_static;
+use llm::{LlmMetrics, Usage};
 use prometheus::{
     Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Registry, TextEncoder,
 };
@@ -98,6 +103,39 @@ lazy_static! {
         &["method", "path"],
     )
     .expect("metric can be created");
+
+    // Resolver cache
+    pub static ref CACHE_REQUESTS_TOTAL: IntCounterVec = IntCounterVec::new(
+        prometheus::opts!("cache_requests_total", "Total number of cache lookups"),
+        &["backend", "result"],
+    )
+    .expect("metric can be created");
+
+    pub static ref CACHE_EVICTIONS_TOTAL: IntCounterVec = IntCounterVec::new(
+        prometheus::opts!("cache_evictions_total", "Total number of cache entries evicted or expired"),
+        &["backend"],
+    )
+    .expect("metric can be created");
+
+    // Rate limiting
+    pub static ref RATE_LIMITED_TOTAL: IntCounterVec = IntCounterVec::new(
+        prometheus::opts!("graphql_rate_limited_total", "Total number of requests refused by a rate limit"),
+        &["operation", "scope"],
+    )
+    .expect("metric can be created");
+
+    // LLM calls
+    pub static ref LLM_TOKENS_TOTAL: IntCounterVec = IntCounterVec::new(
+        prometheus::opts!("llm_tokens_total", "Total number of tokens used by LLM calls"),
+        &["provider", "model", "task", "kind"],
+    )
+    .expect("metric can be created");
+
+    pub static ref LLM_FAILURES_TOTAL: IntCounterVec = IntCounterVec::new(
+        prometheus::opts!("llm_failures_total", "Total number of LLM calls that failed after retries"),
+        &["provider", "task"],
+    )
+    .expect("metric can be created");
 }
 
 // Initialize metrics (register with registry)
@@ -143,6 +181,82 @@ pub fn init_metrics() {
     REGISTRY
         .register(Box::new(HTTP_REQUEST_DURATION_HISTOGRAM.clone()))
         .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(CACHE_REQUESTS_TOTAL.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(CACHE_EVICTIONS_TOTAL.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(RATE_LIMITED_TOTAL.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(LLM_TOKENS_TOTAL.clone()))
+        .expect("collector can be registered");
+
+    REGISTRY
+        .register(Box::new(LLM_FAILURES_TOTAL.clone()))
+        .expect("collector can be registered");
+}
+
+// Reports cache activity from the GraphQL schema to the registry
+pub struct PrometheusCacheMetrics;
+
+impl CacheMetrics for PrometheusCacheMetrics {
+    fn record_hit(&self, backend: &str) {
+        CACHE_REQUESTS_TOTAL
+            .with_label_values(&[backend, "hit"])
+            .inc();
+    }
+
+    fn record_miss(&self, backend: &str) {
+        CACHE_REQUESTS_TOTAL
+            .with_label_values(&[backend, "miss"])
+            .inc();
+    }
+
+    fn record_evictions(&self, backend: &str, count: usize) {
+        if count > 0 {
+            CACHE_EVICTIONS_TOTAL
+                .with_label_values(&[backend])
+                .inc_by(count as u64);
+        }
+    }
+}
+
+// Reports requests refused by the GraphQL rate limiter to the registry
+pub struct PrometheusRateLimitMetrics;
+
+impl RateLimitMetrics for PrometheusRateLimitMetrics {
+    fn record_limited(&self, operation: &str, scope: RateLimitScope) {
+        RATE_LIMITED_TOTAL
+            .with_label_values(&[operation, &scope.to_string()])
+            .inc();
+    }
+}
+
+// Reports token usage and failures of LLM calls to the registry
+pub struct PrometheusLlmMetrics;
+
+impl LlmMetrics for PrometheusLlmMetrics {
+    fn record_usage(&self, provider: &str, model: &str, task: &str, usage: &Usage) {
+        LLM_TOKENS_TOTAL
+            .with_label_values(&[provider, model, task, "prompt"])
+            .inc_by(usage.prompt_tokens as u64);
+        LLM_TOKENS_TOTAL
+            .with_label_values(&[provider, model, task, "completion"])
+            .inc_by(usage.completion_tokens as u64);
+    }
+
+    fn record_failure(&self, provider: &str, task: &str) {
+        LLM_FAILURES_TOTAL
+            .with_label_values(&[provider, task])
+            .inc();
+    }
 }
 
 // Update database connection metrics
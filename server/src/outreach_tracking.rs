@@ -0,0 +1,49 @@
+use axum::{
+    extract::Path,
+    http::header,
+    response::{IntoResponse, Redirect, Response},
+};
+use db::OutreachRecipient;
+use graphql::outreach::intake_form_url;
+use tracing::error;
+
+/// A transparent 1x1 GIF
+const TRACKING_PIXEL: [u8; 43] = [
+    0x47, 0x49, 0x46, 0x38, 0x39, 0x61, 0x01, 0x00, 0x01, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0xff, 0xff, 0xff, 0x21, 0xf9, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00, 0x2c, 0x00, 0x00, 0x00, 0x00,
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x02, 0x44, 0x01, 0x00, 0x3b,
+];
+
+/// Loaded by candidate guide outreach emails to record that they were opened. Always
+/// returns the pixel so a tracking failure never shows up as a broken image.
+pub async fn outreach_open_handler(Path(tracking_token): Path<String>) -> Response {
+    let db_pool = db::pool().await;
+    if let Err(e) = OutreachRecipient::record_open(&db_pool.connection, &tracking_token).await {
+        error!("Failed to record outreach open: {}", e);
+    }
+
+    (
+        [
+            (header::CONTENT_TYPE, "image/gif"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        &TRACKING_PIXEL[..],
+    )
+        .into_response()
+}
+
+/// Records a click on the form link in a candidate guide outreach email, then sends the
+/// candidate on to their intake form
+pub async fn outreach_click_handler(Path(tracking_token): Path<String>) -> Response {
+    let db_pool = db::pool().await;
+    match OutreachRecipient::record_click(&db_pool.connection, &tracking_token).await {
+        Ok(Some(recipient)) => Redirect::temporary(&intake_form_url(&recipient)).into_response(),
+        Ok(None) => {
+            Redirect::temporary(config::Config::default().web_app_url.as_str()).into_response()
+        }
+        Err(e) => {
+            error!("Failed to record outreach click: {}", e);
+            Redirect::temporary(config::Config::default().web_app_url.as_str()).into_response()
+        }
+    }
+}
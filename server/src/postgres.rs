@@ -1,24 +1,37 @@
 use std::error::Error;
 
+use graphql::events::{self, EventBroker};
 use regex::Regex;
 use sqlx::{postgres::PgListener, PgPool};
 
-pub async fn listener(db_pool: PgPool) -> Result<(), Box<dyn Error>> {
+pub async fn listener(db_pool: PgPool, broker: EventBroker) -> Result<(), Box<dyn Error>> {
     // Create a PgListener
     let mut listener = PgListener::connect_with(&db_pool).await?;
 
-    // Start listening to the new_embed_origin channel
+    // Start listening to the new_embed_origin and realtime subscription channels
     listener.listen("new_embed_origin").await?;
+    listener.listen_all(events::CHANNELS).await?;
 
     // Continuously receive notifications
     loop {
         // Wait for a notification
         let notification = listener.recv().await?;
-        let url = notification.payload();
 
-        // Fetch and update the title for the received URL
-        if let Err(e) = fetch_and_update_title(url, &db_pool).await {
-            eprintln!("Failed to fetch or update the title for {}: {}", url, e);
+        match notification.channel() {
+            "new_embed_origin" => {
+                let url = notification.payload();
+
+                // Fetch and update the title for the received URL
+                if let Err(e) = fetch_and_update_title(url, &db_pool).await {
+                    eprintln!("Failed to fetch or update the title for {}: {}", url, e);
+                }
+            }
+            channel => {
+                // Forward everything else to GraphQL subscribers
+                if let Err(e) = broker.publish(channel, notification.payload()) {
+                    tracing::warn!("Failed to publish {} notification: {}", channel, e);
+                }
+            }
         }
     }
 }
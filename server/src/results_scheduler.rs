@@ -0,0 +1,33 @@
+use std::time::Duration;
+
+use db::ResultsSource;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+/// How often to check for sources whose poll interval has elapsed
+const TICK_INTERVAL: Duration = Duration::from_secs(15);
+
+// Queues a poll for each active results source once its interval has elapsed
+pub async fn run(db_pool: PgPool) {
+    let mut interval = tokio::time::interval(TICK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let sources = match ResultsSource::claim_due(&db_pool).await {
+            Ok(sources) => sources,
+            Err(e) => {
+                error!("Failed to load due results sources: {}", e);
+                continue;
+            }
+        };
+
+        for source in sources {
+            info!("Queueing poll of results source {}", source.name);
+            if let Err(e) = source.enqueue_poll(&db_pool).await {
+                error!(
+                    "Failed to queue poll of results source {}: {}",
+                    source.id, e
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,278 @@
+use std::time::Duration;
+
+use anyhow::anyhow;
+use db::{
+    DeletedRecord, EnqueueJobInput, Job, JobStatus, ResultsSource, POLL_RESULTS_SOURCE_JOB,
+    PRUNE_JOBS_JOB, PURGE_DELETED_RECORDS_JOB, REFRESH_CONVERSATION_ANALYSIS_JOB,
+    SEND_OUTREACH_REMINDERS_JOB,
+};
+use graphql::{
+    cache::Cache, conversation_analysis::refresh_conversation_analyses,
+    outreach::send_outreach_reminders,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::{
+    import_legiscan_dataset::{self, ImportSessionDataParams},
+    results_scheduler,
+    slack::send_slack_notification,
+    update_legiscan_bill_data::{self, UpdateBillDataParams},
+};
+
+/// How often the worker checks the job table when the queue is empty
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often a running job's lock is refreshed, well within `db::STALE_LOCK_MINUTES`
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Workers that only poll results sources, so polls on election night don't wait behind
+/// Legiscan imports or the other recurring jobs
+const RESULTS_WORKERS: usize = 2;
+
+/// Recurring jobs scheduled by the worker itself, with their cron schedules
+const RECURRING_JOBS: [(&str, &str); 4] = [
+    // Daily, at 9:00 UTC
+    (PURGE_DELETED_RECORDS_JOB, "0 0 9 * * *"),
+    // Daily, at 9:30 UTC
+    (PRUNE_JOBS_JOB, "0 30 9 * * *"),
+    // Hourly
+    (SEND_OUTREACH_REMINDERS_JOB, "0 0 * * * *"),
+    // Every minute
+    (REFRESH_CONVERSATION_ANALYSIS_JOB, "0 * * * * *"),
+];
+
+/// Jobs that run too often to report each success to Slack, only their failures
+const QUIET_JOBS: [&str; 3] = [
+    POLL_RESULTS_SOURCE_JOB,
+    SEND_OUTREACH_REMINDERS_JOB,
+    REFRESH_CONVERSATION_ANALYSIS_JOB,
+];
+
+/// Jobs that only run against production, e.g. because they spend Legiscan API quota
+const PRODUCTION_ONLY_JOBS: [&str; 1] = ["import_legiscan_dataset"];
+
+// Polls the job table and runs due jobs, with results polls and conversation analysis on
+// workers of their own
+pub async fn init_job_worker(db_pool: PgPool, cache: Cache) {
+    let environment = config::Config::default().environment;
+
+    let mut excluded_names: Vec<String> = match environment {
+        config::Environment::Production => {
+            info!("Running job worker in production environment");
+            vec![]
+        }
+        config::Environment::Staging => {
+            info!("Running job worker in staging environment");
+            PRODUCTION_ONLY_JOBS.iter().map(|n| n.to_string()).collect()
+        }
+        _ => {
+            warn!(
+                "Not running job worker in non-production environment: {}",
+                environment
+            );
+            return;
+        }
+    };
+
+    // Results sources are polled through the queue, so only schedule them where it runs
+    tokio::spawn(results_scheduler::run(db_pool.clone()));
+
+    // Recurring jobs are unique by name, so this only updates the schedule of existing ones
+    // and leaves running and cancelled jobs as they are
+    for (name, schedule) in RECURRING_JOBS {
+        let job = EnqueueJobInput {
+            name: name.to_string(),
+            schedule: Some(schedule.to_string()),
+            ..Default::default()
+        };
+        if let Err(e) = Job::enqueue(&db_pool, &job).await {
+            error!("Failed to schedule {} job: {}", name, e);
+        }
+    }
+
+    let results_jobs = vec![POLL_RESULTS_SOURCE_JOB.to_string()];
+    for _ in 0..RESULTS_WORKERS {
+        tokio::spawn(run_worker(
+            db_pool.clone(),
+            cache.clone(),
+            Some(results_jobs.clone()),
+            vec![],
+        ));
+    }
+    excluded_names.extend(results_jobs);
+
+    // Analysis is refreshed every minute, so it gets a worker of its own too instead of
+    // waiting behind Legiscan imports and holding them up while it runs
+    let analysis_jobs = vec![REFRESH_CONVERSATION_ANALYSIS_JOB.to_string()];
+    tokio::spawn(run_worker(
+        db_pool.clone(),
+        cache.clone(),
+        Some(analysis_jobs.clone()),
+        vec![],
+    ));
+    excluded_names.extend(analysis_jobs);
+
+    run_worker(db_pool, cache, None, excluded_names).await;
+}
+
+/// Runs due jobs one at a time, limited to `names` when given
+async fn run_worker(
+    db_pool: PgPool,
+    cache: Cache,
+    names: Option<Vec<String>>,
+    excluded_names: Vec<String>,
+) {
+    loop {
+        match Job::claim_next(&db_pool, names.as_deref(), &excluded_names).await {
+            Ok(Some((job, run))) => process_job(&db_pool, &cache, job, run.id).await,
+            Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+            Err(e) => {
+                error!("Failed to claim next job: {}", e);
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn process_job(db_pool: &PgPool, cache: &Cache, job: Job, run_id: uuid::Uuid) {
+    info!(
+        "Running {} job (attempt {}/{})",
+        job.name, job.attempts, job.max_attempts
+    );
+
+    // Run in a separate task so a panicking job is recorded as a failure instead of
+    // taking the worker down with it
+    let name = job.name.clone();
+    let payload = job.payload.clone();
+    let cache = cache.clone();
+    let heartbeat = tokio::spawn(heartbeat(db_pool.clone(), job.clone()));
+    let result = match tokio::spawn(async move { run_job(&name, payload, &cache).await }).await {
+        Ok(result) => result,
+        Err(e) => Err(anyhow!("Job panicked: {}", e)),
+    };
+    heartbeat.abort();
+
+    match result {
+        Ok(()) => {
+            info!("Successfully ran {} job", job.name);
+            if let Err(e) = job.complete(db_pool, run_id).await {
+                error!("Failed to record completion of {} job: {}", job.name, e);
+            }
+            if !QUIET_JOBS.contains(&job.name.as_str()) {
+                notify(
+                    "💾 Job Succeeded:",
+                    &format!("Ran {} successfully.", job.name),
+                )
+                .await;
+            }
+        }
+        Err(err) => {
+            let message = err.to_string();
+            error!("Failed to run {} job: {}", job.name, message);
+            match job.fail(db_pool, run_id, &message).await {
+                Ok(JobStatus::Dead) => {
+                    notify(
+                        "❌ Job Failed:",
+                        &format!(
+                            "{} failed after {} attempts: {}",
+                            job.name, job.attempts, message
+                        ),
+                    )
+                    .await
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to record failure of {} job: {}", job.name, e),
+            }
+        }
+    }
+}
+
+/// Keeps a running job locked for as long as it runs, so jobs that take longer than
+/// `db::STALE_LOCK_MINUTES` aren't claimed again by another worker
+async fn heartbeat(db_pool: PgPool, job: Job) {
+    let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+    // The first tick completes immediately, right after the job was locked
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        match job.heartbeat(&db_pool).await {
+            Ok(true) => {}
+            Ok(false) => {
+                warn!("Lost the lock on {} job {}", job.name, job.id);
+                return;
+            }
+            Err(e) => error!("Failed to refresh the lock on {} job: {}", job.name, e),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PollResultsSourceParams {
+    results_source_id: uuid::Uuid,
+}
+
+/// Dispatches a job to its implementation based on its name
+async fn run_job(name: &str, payload: serde_json::Value, cache: &Cache) -> anyhow::Result<()> {
+    match name {
+        "import_legiscan_dataset" => {
+            let params: ImportSessionDataParams = serde_json::from_value(payload)?;
+            import_legiscan_dataset::run(params)
+                .await
+                .map_err(|e| anyhow!(e.to_string()))
+        }
+        "update_legiscan_bill_data" => {
+            let params: UpdateBillDataParams = serde_json::from_value(payload)?;
+            update_legiscan_bill_data::run(params).await
+        }
+        POLL_RESULTS_SOURCE_JOB => {
+            let params: PollResultsSourceParams = serde_json::from_value(payload)?;
+            let pool = db::pool().await;
+            let source =
+                ResultsSource::find_by_id(&pool.connection, params.results_source_id).await?;
+            scrapers::results_sources::poll(&pool.connection, &source)
+                .await
+                .map_err(|e| anyhow!(e.to_string()))
+        }
+        PURGE_DELETED_RECORDS_JOB => {
+            let pool = db::pool().await;
+            for (entity_type, count) in DeletedRecord::purge(&pool.connection).await? {
+                info!("Purged {} deleted {} records", count, entity_type);
+            }
+            Ok(())
+        }
+        PRUNE_JOBS_JOB => {
+            let pool = db::pool().await;
+            let (jobs, runs) = Job::prune(&pool.connection).await?;
+            info!("Pruned {} finished jobs and {} job runs", jobs, runs);
+            Ok(())
+        }
+        SEND_OUTREACH_REMINDERS_JOB => {
+            let pool = db::pool().await;
+            let sent = send_outreach_reminders(&pool.connection)
+                .await
+                .map_err(|e| anyhow!(e.to_string()))?;
+            info!("Queued {} candidate guide outreach reminders", sent);
+            Ok(())
+        }
+        REFRESH_CONVERSATION_ANALYSIS_JOB => {
+            let pool = db::pool().await;
+            let analyzed = refresh_conversation_analyses(&pool.connection, cache)
+                .await
+                .map_err(|e| anyhow!(e.message))?;
+            if analyzed > 0 {
+                info!("Analyzed {} conversations", analyzed);
+            }
+            Ok(())
+        }
+        _ => Err(anyhow!("No handler registered for job {}", name)),
+    }
+}
+
+async fn notify(title: &str, description: &str) {
+    if let Err(e) = send_slack_notification(title, description, None).await {
+        error!("Failed to send Slack notification: {}", e);
+    }
+}